@@ -7,10 +7,11 @@ use std::{
 };
 
 use clap::Clap;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use solana_sdk::pubkey::{ParsePubkeyError, Pubkey};
 
+use lido::state::FeePayoutMode;
 use lido::token::Lamports;
 use lido::token::StLamports;
 
@@ -283,6 +284,10 @@ cli_opt_struct! {
         #[clap(long, value_name = "int")]
         st_sol_appreciation_share: u32,
 
+        /// Maintainer fee share of the rewards.
+        #[clap(long, value_name = "int")]
+        maintainer_fee_share: u32 => 0,
+
         /// Account who will own the stSOL SPL token account that receives treasury fees.
         #[clap(long, value_name = "address")]
         treasury_account_owner: Pubkey,
@@ -308,6 +313,20 @@ cli_opt_struct! {
         /// Address of the Multisig program.
         #[clap(long)]
         multisig_program_id: Pubkey,
+
+        /// Optional name of the stSOL token. If a name, symbol, or URI is
+        /// passed, also propose to create the token metadata.
+        #[clap(long, value_name = "name")]
+        token_name: String => String::new(),
+
+        /// Optional symbol of the stSOL token, for the token metadata.
+        #[clap(long, value_name = "symbol")]
+        token_symbol: String => String::new(),
+
+        /// Optional URI of a JSON document with further details of the stSOL
+        /// token, for the token metadata.
+        #[clap(long, value_name = "uri")]
+        token_uri: String => String::new(),
     }
 }
 
@@ -327,6 +346,109 @@ cli_opt_struct! {
     }
 }
 
+cli_opt_struct! {
+    ClaimMaintainerFeeOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    ClaimValidatorFeesOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Address of the validator vote account.
+        #[clap(long, value_name = "address")]
+        validator_vote_account: Pubkey,
+
+        /// The stSOL account to receive the fees.
+        #[clap(long, value_name = "address")]
+        recipient: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    CreateExchangeRateOracleOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    ChangeDepositCutoffOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Number of slots at the end of every epoch in which deposits are
+        /// refused. When nonzero, deposits are also refused in a new epoch
+        /// until the exchange rate is updated. Zero disables the cutoff.
+        #[clap(long, value_name = "slots")]
+        deposit_cutoff_slots: u64,
+
+        /// Multisig instance.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    SetTokenMetadataOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Name of the stSOL token.
+        #[clap(long, value_name = "name")]
+        token_name: String,
+
+        /// Symbol of the stSOL token.
+        #[clap(long, value_name = "symbol")]
+        token_symbol: String,
+
+        /// URI of a JSON document with further details of the stSOL token,
+        /// such as the logo.
+        #[clap(long, value_name = "uri")]
+        token_uri: String,
+
+        /// Multisig instance.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
 cli_opt_struct! {
     WithdrawOpts {
          /// Address of the Solido program.
@@ -360,6 +482,16 @@ cli_opt_struct! {
         #[clap(long, value_name = "address")]
         validator_fee_account: Pubkey,
 
+        /// Withdraw authority that the vote account is handed back to when the
+        /// validator is removed.
+        #[clap(long, value_name = "address")]
+        validator_vote_withdrawer: Pubkey,
+
+        /// The vote withdrawer or the validator identity, it has to sign when
+        /// the multisig transaction is executed.
+        #[clap(long, value_name = "address")]
+        validator_authority: Pubkey,
+
         /// Multisig instance.
         #[clap(long, value_name = "address")]
         multisig_address: Pubkey,
@@ -394,6 +526,196 @@ cli_opt_struct! {
     }
 }
 
+cli_opt_struct! {
+    ForceRemoveValidatorOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Address of the validator vote account.
+        #[clap(long, value_name = "address")]
+        validator_vote_account: Pubkey,
+
+        /// Multisig instance.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    MigrateValidatorListOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Multisig instance.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    UpgradeValidatorListOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Multisig instance.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    UpgradeMetricsOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Multisig instance.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    UpgradeValidatorEntriesOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Multisig instance.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    UpgradeMaintainersOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Multisig instance.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    SortAccountMapsOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Multisig instance.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    ChangeFeePayoutModeOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// How validation fees are paid out. With 'credit', fees accrue as
+        /// credit that must be claimed separately. With 'direct', fees are
+        /// minted to the validator's fee account when they are collected.
+        #[clap(long, value_name = "credit/direct")]
+        fee_payout_mode: FeePayoutMode,
+
+        /// Multisig instance.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    UpgradeFeePayoutModeOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Multisig instance.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
 cli_opt_struct! {
     AddRemoveMaintainerOpts {
         /// Address of the Solido program.
@@ -461,6 +783,10 @@ cli_opt_struct! {
         /// `StakeTime::OnlyNearEpochEnd`.
         #[clap(long, value_name = "anytime/only-near-epoch-end")]
         stake_time: StakeTime => StakeTime::OnlyNearEpochEnd,
+
+        /// Build the maintenance transaction and simulate it, but do not send it.
+        #[clap(long, min_values = 0, default_missing_value = "true", value_name = "true/false")]
+        dry_run: bool => false,
     }
 }
 
@@ -645,6 +971,219 @@ impl FromStr for StakeTime {
     }
 }
 
+/// A Solido instance for the maintenance daemon to maintain.
+#[derive(Copy, Clone, Debug)]
+pub struct SolidoInstance {
+    pub solido_program_id: Pubkey,
+    pub solido_address: Pubkey,
+
+    /// When to stake and unstake, if different from the daemon's `--stake-time`.
+    pub stake_time: Option<StakeTime>,
+}
+
+/// Constructs a `SolidoInstance` from a string of the form
+/// `<solido_program_id>:<solido_address>[:<stake_time>]`.
+impl FromStr for SolidoInstance {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<SolidoInstance, String> {
+        let parts: Vec<&str> = s.split(':').collect();
+        let parse_pubkey = |part: &str| {
+            Pubkey::from_str(part).map_err(|err| format!("Invalid address '{}': {}", part, err))
+        };
+        let (solido_program_id, solido_address, stake_time) = match parts[..] {
+            [program_id, address] => (parse_pubkey(program_id)?, parse_pubkey(address)?, None),
+            [program_id, address, stake_time] => (
+                parse_pubkey(program_id)?,
+                parse_pubkey(address)?,
+                Some(StakeTime::from_str(stake_time)?),
+            ),
+            _ => {
+                return Err(format!(
+                    "Invalid Solido instance '{}', expected \
+                    '<solido_program_id>:<solido_address>[:<stake_time>]'.",
+                    s
+                ))
+            }
+        };
+        Ok(SolidoInstance {
+            solido_program_id,
+            solido_address,
+            stake_time,
+        })
+    }
+}
+
+/// Type to represent a vector of `SolidoInstance`.
+#[derive(Clone, Debug, Default)]
+pub struct SolidoInstanceVec(pub Vec<SolidoInstance>);
+
+/// Constructs a `SolidoInstanceVec` from a string by splitting the string by
+/// ',' and constructing a `SolidoInstance` for each of the tokens. The empty
+/// string is the empty vector.
+impl FromStr for SolidoInstanceVec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Ok(SolidoInstanceVec(Vec::new()));
+        }
+        let instances = s
+            .split(',')
+            .map(SolidoInstance::from_str)
+            .collect::<Result<Vec<SolidoInstance>, Self::Err>>()?;
+        Ok(SolidoInstanceVec(instances))
+    }
+}
+
+/// A maintenance operation that the maintenance daemon can perform.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MaintenanceTask {
+    MergeStake,
+    UpdateExchangeRate,
+    UnstakeFromInactiveValidator,
+    CollectValidatorFee,
+    WithdrawInactiveStake,
+    StakeDeposit,
+    UnstakeFromActiveValidators,
+    ClaimValidatorFee,
+    RemoveValidator,
+}
+
+impl MaintenanceTask {
+    /// All tasks, in the default order of priority.
+    pub const ALL: [MaintenanceTask; 9] = [
+        // Merging stake accounts goes before updating validator balance, to
+        // ensure that the balance update needs to reference as few accounts
+        // as possible.
+        MaintenanceTask::MergeStake,
+        MaintenanceTask::UpdateExchangeRate,
+        MaintenanceTask::UnstakeFromInactiveValidator,
+        // Collecting validator fees goes after updating the exchange rate,
+        // because it may be rejected if the exchange rate is outdated.
+        MaintenanceTask::CollectValidatorFee,
+        // Same for updating the validator balance.
+        MaintenanceTask::WithdrawInactiveStake,
+        MaintenanceTask::StakeDeposit,
+        MaintenanceTask::UnstakeFromActiveValidators,
+        MaintenanceTask::ClaimValidatorFee,
+        MaintenanceTask::RemoveValidator,
+    ];
+
+    /// Return the name of the task as used in the configuration, e.g. `stake-deposit`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            MaintenanceTask::MergeStake => "merge-stake",
+            MaintenanceTask::UpdateExchangeRate => "update-exchange-rate",
+            MaintenanceTask::UnstakeFromInactiveValidator => "unstake-from-inactive-validator",
+            MaintenanceTask::CollectValidatorFee => "collect-validator-fee",
+            MaintenanceTask::WithdrawInactiveStake => "withdraw-inactive-stake",
+            MaintenanceTask::StakeDeposit => "stake-deposit",
+            MaintenanceTask::UnstakeFromActiveValidators => "unstake-from-active-validators",
+            MaintenanceTask::ClaimValidatorFee => "claim-validator-fee",
+            MaintenanceTask::RemoveValidator => "remove-validator",
+        }
+    }
+}
+
+impl FromStr for MaintenanceTask {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<MaintenanceTask, String> {
+        MaintenanceTask::ALL
+            .iter()
+            .find(|task| task.name() == s)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<&str> = MaintenanceTask::ALL.iter().map(|t| t.name()).collect();
+                format!(
+                    "Invalid maintenance task '{}', expected one of: {}.",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Type to represent a vector of `MaintenanceTask`, in order of priority.
+#[derive(Clone, Debug)]
+pub struct MaintenanceTaskVec(pub Vec<MaintenanceTask>);
+
+impl Default for MaintenanceTaskVec {
+    fn default() -> Self {
+        MaintenanceTaskVec(MaintenanceTask::ALL.to_vec())
+    }
+}
+
+/// Constructs a `MaintenanceTaskVec` from a string by splitting the string by
+/// ',' and constructing a `MaintenanceTask` for each of the tokens. The empty
+/// string is the empty vector.
+impl FromStr for MaintenanceTaskVec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Ok(MaintenanceTaskVec(Vec::new()));
+        }
+        let tasks = s
+            .split(',')
+            .map(|task| MaintenanceTask::from_str(task.trim()))
+            .collect::<Result<Vec<MaintenanceTask>, Self::Err>>()?;
+        Ok(MaintenanceTaskVec(tasks))
+    }
+}
+
+impl MaintenanceTaskVec {
+    /// Return the position of the task in the list, if it is enabled.
+    fn position(&self, task: MaintenanceTask) -> Option<usize> {
+        self.0.iter().position(|t| *t == task)
+    }
+
+    /// Check that every task is listed at most once, and that tasks that rely
+    /// on the effects of other tasks come after them.
+    pub fn validate(&self) -> Result<(), String> {
+        for (i, task) in self.0.iter().enumerate() {
+            if self.0[..i].contains(task) {
+                return Err(format!(
+                    "Maintenance task '{}' is listed more than once.",
+                    task.name()
+                ));
+            }
+        }
+
+        // Collecting fees fails when the exchange rate is outdated, so without
+        // updating it first, we would send failing transactions every epoch.
+        if let Some(collect) = self.position(MaintenanceTask::CollectValidatorFee) {
+            match self.position(MaintenanceTask::UpdateExchangeRate) {
+                Some(update) if update < collect => {}
+                _ => {
+                    return Err("Maintenance task 'collect-validator-fee' requires \
+                        'update-exchange-rate' to be enabled, and to come before it."
+                        .to_string())
+                }
+            }
+        }
+
+        // When a validator has many stake accounts, the withdrawal needs to
+        // reference all of them, and it may not fit in a transaction before
+        // they are merged.
+        if let (Some(merge), Some(withdraw)) = (
+            self.position(MaintenanceTask::MergeStake),
+            self.position(MaintenanceTask::WithdrawInactiveStake),
+        ) {
+            if withdraw < merge {
+                return Err(
+                    "Maintenance task 'merge-stake' must come before 'withdraw-inactive-stake'."
+                        .to_string(),
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
 cli_opt_struct! {
     RunMaintainerOpts {
         /// Address of the Solido program.
@@ -655,7 +1194,7 @@ cli_opt_struct! {
         #[clap(long)]
         solido_address: Pubkey,
 
-        /// Listen address and port for the http server that serves a /metrics endpoint. Defaults to 0.0.0.0:8923.
+        /// Listen address and port for the http server that serves /metrics and the /api json endpoints. Defaults to 0.0.0.0:8923.
         #[clap(long)]
         listen: String => "0.0.0.0:8923".to_owned(),
 
@@ -666,12 +1205,118 @@ cli_opt_struct! {
         #[clap(long)]
         max_poll_interval_seconds: u64 => 30,
 
+        /// Maximum time in seconds since the last successful read of the on-chain state,
+        /// before /healthz and /readyz start failing. Defaults to 600s.
+        #[clap(long)]
+        max_staleness_seconds: u64 => 600,
+
         /// Try to do stake and unstake operations any time if set to
         /// `StakeTime::Anytime`. If set to `StakeTime::OnlyNearEpochEnd`, will
         /// try to stake/unstake only at the end of the epoch. Defaults to
         /// `StakeTime::OnlyNearEpochEnd`.
         #[clap(long, value_name = "anytime/only-near-epoch-end")]
         stake_time: StakeTime => StakeTime::OnlyNearEpochEnd,
+
+        /// Build maintenance transactions and simulate them, but do not send
+        /// them. In this mode the daemon does not wait for its maintainer duty.
+        #[clap(long, min_values = 0, default_missing_value = "true", value_name = "true/false")]
+        dry_run: bool => false,
+
+        /// Optional path of a journal file. If set, the daemon appends every
+        /// maintenance transaction that it sends to it, and restores its
+        /// metrics from it at startup.
+        #[clap(long)]
+        journal_path: PathBuf => PathBuf::default(),
+
+        /// Optional websocket url of the RPC node, e.g. ws://127.0.0.1:8900 for a
+        /// local test validator. If set, the daemon subscribes to changes to the
+        /// Solido instance, the reserve, and the stSOL mint, and to slot updates,
+        /// and refreshes its state when they change, rather than polling. It still
+        /// refreshes at least every max poll interval, so in this mode that
+        /// interval can be much longer. If the connection drops, the daemon falls
+        /// back to polling until it can subscribe again.
+        #[clap(long)]
+        websocket_url: String => String::new(),
+
+        /// Comma-separated list of additional Solido instances to maintain,
+        /// each as `<solido_program_id>:<solido_address>[:<stake_time>]`. The
+        /// instances must be on the same cluster. The stake time defaults to
+        /// `--stake-time`.
+        #[clap(long, value_name = "instances")]
+        additional_instances: SolidoInstanceVec => SolidoInstanceVec::default(),
+
+        /// Maximum number of instructions to bundle into one maintenance
+        /// transaction. Only operations that are independent per validator,
+        /// such as merging stake or claiming fees, get bundled. Bundles also
+        /// stop before the estimated compute units exceed the limit per
+        /// transaction, so in practice that caps the bundle size as well.
+        #[clap(long, value_name = "n")]
+        max_instructions_per_transaction: u64 => 8,
+
+        /// Comma-separated list of the maintenance tasks to perform, in order
+        /// of priority. Tasks that are not listed are disabled. Defaults to all
+        /// tasks: merge-stake, update-exchange-rate,
+        /// unstake-from-inactive-validator, collect-validator-fee,
+        /// withdraw-inactive-stake, stake-deposit,
+        /// unstake-from-active-validators, claim-validator-fee, remove-validator.
+        #[clap(long, value_name = "tasks")]
+        maintenance_tasks: MaintenanceTaskVec => MaintenanceTaskVec::default(),
+
+        /// Periodically record on-chain that this maintainer is alive. Other
+        /// maintainers skip the duty slices of a maintainer that sends
+        /// heartbeats, once it stops sending them. Every heartbeat is a
+        /// transaction that costs a fee.
+        #[clap(long, min_values = 0, default_missing_value = "true", value_name = "true/false")]
+        send_heartbeats: bool => false,
+    }
+}
+
+impl RunMaintainerOpts {
+    /// Check that the maintenance task policy is safe to run.
+    ///
+    /// Exits if validation fails.
+    pub fn validate_or_exit(&self) {
+        if let Err(err) = self.maintenance_tasks().validate() {
+            println!("{}", err);
+            std::process::exit(1);
+        }
+    }
+
+    /// Return the Solido instances to maintain: the one set with
+    /// `--solido-address`, followed by the additional instances.
+    pub fn instances(&self) -> Vec<SolidoInstance> {
+        let mut instances = vec![SolidoInstance {
+            solido_program_id: *self.solido_program_id(),
+            solido_address: *self.solido_address(),
+            stake_time: None,
+        }];
+        instances.extend_from_slice(&self.additional_instances().0);
+        instances
+    }
+}
+
+cli_opt_struct! {
+    MaintenanceLogOpts {
+        /// Path of the journal file written by the maintenance daemon.
+        #[clap(long)]
+        journal_path: PathBuf,
+
+        /// Only show maintenance performed at or after this Unix timestamp (in seconds).
+        #[clap(long)]
+        since: u64 => 0,
+
+        /// Only show maintenance performed before this Unix timestamp (in seconds).
+        #[clap(long)]
+        until: u64 => u64::MAX,
+
+        /// Only show maintenance of this operation, e.g. "StakeDeposit".
+        #[clap(long)]
+        operation: String => String::new(),
+
+        /// Only show maintenance of this Solido instance, for journals of a
+        /// daemon that maintains several instances.
+        #[clap(long, value_name = "address")]
+        solido_address: String => String::new(),
     }
 }
 
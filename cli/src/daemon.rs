@@ -8,26 +8,146 @@
 //! executes maintenance tasks if needed. It also publishes a snapshot of its
 //! most recently seen Solido state in an `Arc` so the http threads can serve it
 //! without blocking the main loop.
+//!
+//! The http server serves the following routes:
+//!
+//! * `/metrics` (and `/`): metrics in Prometheus text format.
+//! * `/api/solido`: overview of the Solido instance as json.
+//! * `/api/validators`: the validators and their balances as json.
+//! * `/api/maintenance-history`: the most recent maintenance operations that
+//!   this daemon performed, as json.
+//! * `/api/duty`: the current and next maintainer duty, as json.
+//! * `/healthz` and `/readyz`: liveness and readiness checks, see [`check_health`].
+//! * `/api/instances`: the Solido instances that the daemon maintains, as json.
+//! * `/api/maintenance-policy`: the maintenance tasks that the daemon performs,
+//!   in order of priority, as json.
+//!
+//! The daemon can maintain several Solido instances on the same cluster. The
+//! metrics of all instances are labelled with a `solido_address`. The other
+//! `/api` routes serve the first instance, unless the `solido_address` query
+//! parameter selects a different one.
+//!
+//! When the daemon has a websocket url, the main loop does not poll at a fixed
+//! interval, but waits for notifications of on-chain changes, see the
+//! [`subscription`](crate::subscription) module.
 
+use std::collections::VecDeque;
 use std::io;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::thread::JoinHandle;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+use lido::stake_account::{StakeAccount, StakeBalance};
+use lido::state::{Lido, Validator};
+use lido::token::{Lamports, StLamports};
+use lido::util::{serialize_b58, serialize_b58_option};
 use rand::{rngs::ThreadRng, Rng};
-use solana_sdk::clock::{Clock, Slot};
+use serde::Serialize;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::clock::{Clock, Epoch, Slot};
 use tiny_http::{Header, Request, Response, Server};
 
-use crate::config::RunMaintainerOpts;
-use crate::error::{AsPrettyError, Error};
-use crate::maintenance::{try_perform_maintenance, MaintenanceOutput, SolidoState};
-use crate::prometheus::{write_metric, Metric, MetricFamily};
+use crate::config::{MaintenanceTask, RunMaintainerOpts, SolidoInstance};
+use crate::error::{Abort, AsPrettyError, Error};
+use crate::journal::{read_journal, Journal, JournalEntry};
+use crate::maintenance::{
+    try_perform_maintenance, try_send_maintainer_heartbeat, try_simulate_maintenance,
+    MaintenanceOutput, SentMaintenance, SimulatedMaintenance, SolidoState,
+    MINIMUM_MAINTAINER_BALANCE,
+};
+use crate::prometheus::{write_metric, LabeledMetrics, Metric, MetricFamily, MetricSink};
 use crate::snapshot::SnapshotError;
+use crate::subscription::{Subscriptions, WakeReason};
 use crate::SnapshotClientConfig;
 
+/// A counter per maintenance operation.
+///
+/// Depending on the metric, this counts transactions or lamports.
+#[derive(Clone, Default)]
+struct OperationCounters {
+    /// Counter for `StakeDeposit`.
+    stake_deposit: u64,
+
+    /// Counter for `UpdateExchangeRate`.
+    update_exchange_rate: u64,
+
+    /// Counter for `WithdrawInactiveStake`.
+    withdraw_inactive_stake: u64,
+
+    /// Counter for `CollectValidatorFee`.
+    collect_validator_fee: u64,
+
+    /// Counter for `MergeStake`.
+    merge_stake: u64,
+
+    /// Counter for `ClaimValidatorFee`.
+    claim_validator_fee: u64,
+
+    /// Counter for `UnstakeFromInactiveValidator`.
+    unstake_from_inactive_validator: u64,
+
+    /// Counter for `RemoveValidator`.
+    remove_validator: u64,
+
+    /// Counter for `Unstake` on an active validator for balancing purposes.
+    unstake_from_active_validator: u64,
+
+    /// Counter for `MaintainerHeartbeat`.
+    maintainer_heartbeat: u64,
+}
+
+impl OperationCounters {
+    /// Return one metric per operation, labelled with the operation.
+    fn to_metrics(&self) -> Vec<Metric<'static>> {
+        vec![
+            (self.stake_deposit, "StakeDeposit"),
+            (self.update_exchange_rate, "UpdateExchangeRate"),
+            (self.withdraw_inactive_stake, "WithdrawInactiveStake"),
+            (self.collect_validator_fee, "CollectValidatorFee"),
+            (self.merge_stake, "MergeStake"),
+            (self.claim_validator_fee, "ClaimValidatorFee"),
+            (
+                self.unstake_from_inactive_validator,
+                "UnstakeFromInactiveValidator",
+            ),
+            (self.remove_validator, "RemoveValidator"),
+            (
+                self.unstake_from_active_validator,
+                "UnstakeFromActiveValidator",
+            ),
+            (self.maintainer_heartbeat, "MaintainerHeartbeat"),
+        ]
+        .into_iter()
+        .map(|(count, operation)| Metric::new(count).with_label("operation", operation.to_string()))
+        .collect()
+    }
+
+    /// Add to the counter for the given operation, e.g. `StakeDeposit`.
+    ///
+    /// Unknown operations are ignored, they can occur in a journal that was
+    /// written by a different version of the daemon.
+    fn add(&mut self, operation: &str, amount: u64) {
+        let counter = match operation {
+            "StakeDeposit" => &mut self.stake_deposit,
+            "UpdateExchangeRate" => &mut self.update_exchange_rate,
+            "WithdrawInactiveStake" => &mut self.withdraw_inactive_stake,
+            "CollectValidatorFee" => &mut self.collect_validator_fee,
+            "MergeStake" => &mut self.merge_stake,
+            "ClaimValidatorFee" => &mut self.claim_validator_fee,
+            "UnstakeFromInactiveValidator" => &mut self.unstake_from_inactive_validator,
+            "RemoveValidator" => &mut self.remove_validator,
+            "UnstakeFromActiveValidator" => &mut self.unstake_from_active_validator,
+            "MaintainerHeartbeat" => &mut self.maintainer_heartbeat,
+            _ => return,
+        };
+        *counter += amount;
+    }
+}
+
 /// Metrics counters that track how many maintenance operations we performed.
-#[derive(Clone)]
+#[derive(Clone, Default)]
 struct MaintenanceMetrics {
     /// Number of times that we checked if there was maintenance to perform.
     polls: u64,
@@ -35,39 +155,24 @@ struct MaintenanceMetrics {
     /// Number of times that we tried to perform maintenance, but encountered an error.
     errors: u64,
 
-    /// Number of times we performed `StakeDeposit`.
-    transactions_stake_deposit: u64,
+    /// Number of times that we took over the duty of a maintainer that did not
+    /// perform the pending maintenance.
+    duty_takeovers: u64,
 
-    /// Number of times we performed `UpdateExchangeRate`.
-    transactions_update_exchange_rate: u64,
+    /// Number of maintenance transactions that we executed.
+    transactions: OperationCounters,
 
-    /// Number of times we performed `WithdrawInactiveStake`.
-    transactions_withdraw_inactive_stake: u64,
+    /// Number of maintenance transactions that we simulated, in dry-run mode.
+    transactions_simulated: OperationCounters,
 
-    /// Number of times we performed `CollectValidatorFee`
-    transactions_collect_validator_fee: u64,
-
-    /// Number of times we performed a `MergeStake`.
-    transactions_merge_stake: u64,
-
-    /// Number of times we performed `ClaimValidatorFee`.
-    transactions_claim_validator_fee: u64,
-    // TODO(#96#issuecomment-859388866): Track how much the daemon spends on transaction fees,
-    // so we know how much SOL it costs to operate.
-    // spent_lamports_total: u64
-    /// Number of times we performed `UnstakeFromInactiveValidator`.
-    transactions_unstake_from_inactive_validator: u64,
-
-    /// Number of times we performed `RemoveValidator`.
-    transactions_remove_validator: u64,
-
-    /// Number of times we performed `Unstake` on an active validator for balancing purposes.
-    transactions_unstake_from_active_validator: u64,
+    /// Lamports that we spent on fees for maintenance transactions, including
+    /// transactions that executed but failed.
+    fees_lamports: OperationCounters,
 }
 
 impl MaintenanceMetrics {
     /// Serialize metrics in Prometheus text format.
-    pub fn write_prometheus<W: io::Write>(&self, out: &mut W) -> io::Result<()> {
+    pub fn write_prometheus<W: MetricSink>(&self, out: &mut W) -> io::Result<()> {
         write_metric(
             out,
             &MetricFamily {
@@ -84,75 +189,93 @@ impl MaintenanceMetrics {
             type_: "counter",
             metrics: vec![Metric::new(self.errors)]
         })?;
+        write_metric(out, &MetricFamily {
+            name: "solido_maintenance_duty_takeovers_total",
+            help: "Number of times we took over the duty of a maintainer that did not perform pending maintenance, since launch.",
+            type_: "counter",
+            metrics: vec![Metric::new(self.duty_takeovers)]
+        })?;
         write_metric(
             out,
             &MetricFamily {
                 name: "solido_maintenance_transactions_total",
                 help: "Number of maintenance transactions executed, since launch.",
                 type_: "counter",
-                metrics: vec![
-                    Metric::new(self.transactions_stake_deposit)
-                        .with_label("operation", "StakeDeposit".to_string()),
-                    Metric::new(self.transactions_update_exchange_rate)
-                        .with_label("operation", "UpdateExchangeRate".to_string()),
-                    Metric::new(self.transactions_withdraw_inactive_stake)
-                        .with_label("operation", "WithdrawInactiveStake".to_string()),
-                    Metric::new(self.transactions_collect_validator_fee)
-                        .with_label("operation", "CollectValidatorFee".to_string()),
-                    Metric::new(self.transactions_merge_stake)
-                        .with_label("operation", "MergeStake".to_string()),
-                    Metric::new(self.transactions_claim_validator_fee)
-                        .with_label("operation", "ClaimValidatorFee".to_string()),
-                    Metric::new(self.transactions_unstake_from_inactive_validator)
-                        .with_label("operation", "UnstakeFromInactiveValidator".to_string()),
-                    Metric::new(self.transactions_remove_validator)
-                        .with_label("operation", "RemoveValidator".to_string()),
-                    Metric::new(self.transactions_unstake_from_active_validator)
-                        .with_label("operation", "UnstakeFromActiveValidator".to_string()),
-                ],
+                metrics: self.transactions.to_metrics(),
+            },
+        )?;
+        write_metric(
+            out,
+            &MetricFamily {
+                name: "solido_maintenance_simulations_total",
+                help: "Number of maintenance transactions simulated in dry-run mode, since launch.",
+                type_: "counter",
+                metrics: self.transactions_simulated.to_metrics(),
+            },
+        )?;
+        write_metric(
+            out,
+            &MetricFamily {
+                name: "solido_maintenance_fees_lamports_total",
+                help:
+                    "Lamports spent on transaction fees for maintenance transactions, since launch.",
+                type_: "counter",
+                metrics: self.fees_lamports.to_metrics(),
             },
         )?;
         Ok(())
     }
 
-    /// Increment the counter for a maintenance operation.
-    pub fn observe_maintenance(&mut self, maintenance_output: &MaintenanceOutput) {
-        match *maintenance_output {
-            MaintenanceOutput::StakeDeposit { .. } => {
-                self.transactions_stake_deposit += 1;
-            }
-            MaintenanceOutput::UpdateExchangeRate => {
-                self.transactions_update_exchange_rate += 1;
-            }
-            MaintenanceOutput::WithdrawInactiveStake { .. } => {
-                self.transactions_withdraw_inactive_stake += 1;
-            }
-            MaintenanceOutput::CollectValidatorFee { .. } => {
-                self.transactions_collect_validator_fee += 1
-            }
-            MaintenanceOutput::MergeStake { .. } => self.transactions_merge_stake += 1,
-            MaintenanceOutput::ClaimValidatorFee { .. } => {
-                self.transactions_claim_validator_fee += 1
-            }
-            MaintenanceOutput::UnstakeFromInactiveValidator { .. } => {
-                self.transactions_unstake_from_inactive_validator += 1
-            }
-            MaintenanceOutput::RemoveValidator { .. } => self.transactions_remove_validator += 1,
-            MaintenanceOutput::UnstakeFromActiveValidator { .. } => {
-                self.transactions_unstake_from_active_validator += 1
-            }
+    /// Count a maintenance transaction that we sent, and the fee that it paid.
+    ///
+    /// This is used both for new transactions, and to restore the counters
+    /// from the journal at startup.
+    pub fn observe_transaction(&mut self, entry: &JournalEntry) {
+        if entry.error.is_none() {
+            // Bundled instructions are always the same operation as the first.
+            self.transactions
+                .add(&entry.operation, 1 + entry.bundled.len() as u64);
         }
+        self.fees_lamports.add(&entry.operation, entry.fee_lamports);
+    }
+
+    /// Increment the counter for a simulated maintenance operation.
+    pub fn observe_simulation(&mut self, simulation: &SimulatedMaintenance) {
+        self.transactions_simulated.add(
+            simulation.maintenance.operation(),
+            1 + simulation.bundled.len() as u64,
+        );
+    }
+}
+
+/// Number of maintenance operations to keep for `/api/maintenance-history`.
+const MAINTENANCE_HISTORY_LEN: usize = 100;
+
+/// Append an entry to the history, drop the oldest one if the history is full.
+fn push_history(history: &mut VecDeque<JournalEntry>, entry: JournalEntry) {
+    if history.len() == MAINTENANCE_HISTORY_LEN {
+        history.pop_front();
     }
+    history.push_back(entry);
 }
 
-/// Snapshot of metrics and Solido state.
+/// Snapshot of metrics and Solido state of one Solido instance.
 struct Snapshot {
+    solido_program_id: Pubkey,
+    solido_address: Pubkey,
+
     /// Metrics about what the daemon has done so far.
     metrics: MaintenanceMetrics,
 
     /// The current state of on-chain accounts, and the time at which we obtained
     /// that data.
     solido: Option<SolidoState>,
+
+    /// The most recent maintenance operations, oldest first.
+    maintenance_history: VecDeque<JournalEntry>,
+
+    /// The instant after we successfully queried the on-chain state for the last time.
+    last_read_success: Instant,
 }
 
 enum MaintenanceResult {
@@ -165,33 +288,114 @@ enum MaintenanceResult {
     /// We have a state snapshot, and there was no maintenance to perform.
     OkIdle(SolidoState),
 
-    /// We have a state snapshot, and we performed maintenance.
-    OkMaintenance(SolidoState, MaintenanceOutput),
+    /// We have a state snapshot, and we sent one or more maintenance transactions.
+    ///
+    /// Note that the transactions themselves may still have failed.
+    OkMaintenance(SolidoState, Vec<SentMaintenance>),
+
+    /// We have a state snapshot, and we simulated maintenance in dry-run mode.
+    OkSimulated(SolidoState, SimulatedMaintenance),
+}
+
+/// Maintenance that stays pending while another maintainer is on duty.
+#[derive(Default)]
+struct DutyFailover {
+    /// The pending maintenance, and the slot at which we first saw it pending.
+    pending: Option<(MaintenanceOutput, Slot)>,
+
+    /// Whether we took over the duty of the maintainer on duty in the most
+    /// recent iteration.
+    taken_over: bool,
+}
+
+impl DutyFailover {
+    /// Track the maintenance that is pending, and return whether we should
+    /// take over the duty of the maintainer on duty.
+    fn observe(
+        &mut self,
+        state: &SolidoState,
+        tasks: &[MaintenanceTask],
+        is_on_duty: bool,
+    ) -> bool {
+        let pending_since = match (self.pending.take(), state.get_pending_maintenance(tasks)) {
+            // When we took over, the pending maintenance changes because of
+            // what we did, the maintainer on duty still has not done anything.
+            (Some((previous, since)), Some(output)) if previous == output || self.taken_over => {
+                self.pending = Some((output, since));
+                Some(since)
+            }
+            (_, Some(output)) => {
+                self.pending = Some((output, state.clock.slot));
+                Some(state.clock.slot)
+            }
+            (_, None) => None,
+        };
+        self.taken_over =
+            !is_on_duty && pending_since.map_or(false, |since| state.should_take_over_duty(since));
+        self.taken_over
+    }
 }
 
 /// Run a single maintenance iteration.
 fn run_maintenance_iteration(
     config: &mut SnapshotClientConfig,
     opts: &RunMaintainerOpts,
+    instance: &SolidoInstance,
+    failover: &mut DutyFailover,
 ) -> MaintenanceResult {
+    let tasks = &opts.maintenance_tasks().0;
+    let max_instructions = *opts.max_instructions_per_transaction() as usize;
     let result = config.with_snapshot(|config| {
         let state = SolidoState::new(
             config,
-            opts.solido_program_id(),
-            opts.solido_address(),
-            *opts.stake_time(),
+            &instance.solido_program_id,
+            &instance.solido_address,
+            instance.stake_time.unwrap_or(*opts.stake_time()),
         )?;
 
+        // In dry-run mode we only simulate, so there is no risk of racing
+        // with other maintainers, and we do not need to wait for our duty.
+        if *opts.dry_run() {
+            return match try_simulate_maintenance(config, &state, tasks, max_instructions) {
+                Ok(None) => Ok(MaintenanceResult::OkIdle(state)),
+                Ok(Some(output)) => Ok(MaintenanceResult::OkSimulated(state, output)),
+                Err(SnapshotError::MissingAccount) => Err(SnapshotError::MissingAccount),
+                Err(SnapshotError::MissingValidatorIdentity(addr)) => {
+                    Err(SnapshotError::MissingValidatorIdentity(addr))
+                }
+                Err(SnapshotError::OtherError(err)) => {
+                    Ok(MaintenanceResult::ErrMaintenance(state, err))
+                }
+            };
+        }
+
         // If it's not our maintainer duty at this time, then don't try to
         // perform maintenance; a different maintainer should be doing it
-        // right now.
-        if state.get_current_maintainer_duty() != Some(config.signer.pubkey()) {
-            return Ok(MaintenanceResult::OkIdle(state));
-        }
+        // right now. Unless that maintainer appears to be offline, and we are
+        // the one to take over.
+        let is_on_duty = state.get_current_maintainer_duty() == Some(config.signer.pubkey());
+        let take_over = failover.observe(&state, tasks, is_on_duty);
+
+        let result = if is_on_duty || take_over {
+            try_perform_maintenance(config, &state, tasks, max_instructions)
+        } else {
+            Ok(Vec::new())
+        };
 
-        match try_perform_maintenance(config, &state) {
-            Ok(None) => Ok(MaintenanceResult::OkIdle(state)),
-            Ok(Some(output)) => Ok(MaintenanceResult::OkMaintenance(state, output)),
+        // Heartbeats do not depend on our duty, a maintainer that was left out
+        // of the rotation needs them to get back in.
+        let result = result.and_then(|mut sent| {
+            if *opts.send_heartbeats() {
+                if let Some(heartbeat) = try_send_maintainer_heartbeat(config, &state)? {
+                    sent.push(heartbeat);
+                }
+            }
+            Ok(sent)
+        });
+
+        match result {
+            Ok(sent) if sent.is_empty() => Ok(MaintenanceResult::OkIdle(state)),
+            Ok(sent) => Ok(MaintenanceResult::OkMaintenance(state, sent)),
             Err(SnapshotError::MissingAccount) => Err(SnapshotError::MissingAccount),
             Err(SnapshotError::MissingValidatorIdentity(addr)) => {
                 Err(SnapshotError::MissingValidatorIdentity(addr))
@@ -257,15 +461,56 @@ impl BlockTimeEstimator {
     }
 }
 
-/// Mutex that holds the latest snapshot.
+/// Mutex that holds the latest snapshot of every Solido instance.
 ///
-/// At startup it holds None, after that it will always hold Some Arc.
-/// To read the current snapshot, we only have to lock the mutex briefly,
-/// so we can clone the arc, and then we can continue to work with that
-/// snapshot without any lock. This holds for publishing a new state as well:
-/// we can prepare it privately, and we only need to lock the mutex briefly
-/// to swap the Arc.
-type SnapshotMutex = Mutex<Option<Arc<Snapshot>>>;
+/// It holds one entry per instance, in the order of [`RunMaintainerOpts::instances`].
+/// At startup every entry is None, after the first iteration for an instance,
+/// its entry will always hold Some Arc. To read the current snapshots, we only
+/// have to lock the mutex briefly, so we can clone the arcs, and then we can
+/// continue to work with those snapshots without any lock. This holds for
+/// publishing a new state as well: we can prepare it privately, and we only
+/// need to lock the mutex briefly to swap the Arc.
+type SnapshotMutex = Mutex<Vec<Option<Arc<Snapshot>>>>;
+
+/// What the daemon keeps track of for every Solido instance that it maintains.
+struct MaintainedInstance {
+    instance: SolidoInstance,
+
+    /// The instant after we successfully queried the on-chain state for the last time.
+    last_read_success: Instant,
+
+    /// Metrics counters to track status.
+    metrics: MaintenanceMetrics,
+
+    /// The most recent maintenance operations, at most `MAINTENANCE_HISTORY_LEN`.
+    maintenance_history: VecDeque<JournalEntry>,
+
+    /// The snapshot that we published most recently.
+    snapshot: Option<Arc<Snapshot>>,
+
+    /// The accounts to subscribe to for changes, known after the first
+    /// successful read of the on-chain state.
+    subscription_accounts: Vec<Pubkey>,
+
+    /// The instant at which the next iteration is due.
+    next_iteration_at: Instant,
+
+    /// When we are subscribed to slot updates, the slot at which the next
+    /// iteration is due, if we reach it before `next_iteration_at`.
+    wake_slot: Option<Slot>,
+
+    /// Maintenance that is pending while another maintainer is on duty.
+    failover: DutyFailover,
+}
+
+impl MaintainedInstance {
+    /// Return the on-chain state, if the most recent read succeeded.
+    fn state(&self) -> Option<&SolidoState> {
+        self.snapshot
+            .as_ref()
+            .and_then(|snapshot| snapshot.solido.as_ref())
+    }
+}
 
 struct Daemon<'a, 'b> {
     config: &'a mut SnapshotClientConfig<'b>,
@@ -275,103 +520,192 @@ struct Daemon<'a, 'b> {
     /// Random number generator used for exponential backoff with jitter on errors.
     rng: ThreadRng,
 
-    /// The instant after we successfully queried the on-chain state for the last time.
-    last_read_success: Instant,
-
     /// Block time estimator used to tweak sleep times so we wake up for maintainer duty.
     block_time_estimator: BlockTimeEstimator,
 
-    /// Metrics counters to track status.
-    metrics: MaintenanceMetrics,
+    /// The Solido instances that we maintain, at least one.
+    instances: Vec<MaintainedInstance>,
+
+    /// Journal that we append maintenance transactions to, if configured.
+    journal: Option<Journal>,
 
-    /// Mutex where we publish the latest snapshot for use by the webserver.
+    /// Subscriptions to on-chain changes, if we have a websocket url and are
+    /// currently connected.
+    subscriptions: Option<Subscriptions>,
+
+    /// Mutex where we publish the latest snapshots for use by the webserver.
     snapshot_mutex: Arc<SnapshotMutex>,
 }
 
 impl<'a, 'b> Daemon<'a, 'b> {
     pub fn new(config: &'a mut SnapshotClientConfig<'b>, opts: &'a RunMaintainerOpts) -> Self {
-        let metrics = MaintenanceMetrics {
-            polls: 0,
-            errors: 0,
-            transactions_stake_deposit: 0,
-            transactions_update_exchange_rate: 0,
-            transactions_withdraw_inactive_stake: 0,
-            transactions_collect_validator_fee: 0,
-            transactions_merge_stake: 0,
-            transactions_claim_validator_fee: 0,
-            transactions_unstake_from_inactive_validator: 0,
-            transactions_remove_validator: 0,
-            transactions_unstake_from_active_validator: 0,
+        let now = Instant::now();
+        let mut instances: Vec<MaintainedInstance> = opts
+            .instances()
+            .into_iter()
+            .map(|instance| MaintainedInstance {
+                instance,
+                last_read_success: now,
+                metrics: MaintenanceMetrics::default(),
+                maintenance_history: VecDeque::with_capacity(MAINTENANCE_HISTORY_LEN),
+                snapshot: None,
+                subscription_accounts: Vec::new(),
+                next_iteration_at: now,
+                wake_slot: None,
+                failover: DutyFailover::default(),
+            })
+            .collect();
+
+        // If we have a journal, restore the counters and history from it, so
+        // they survive restarts.
+        let journal_path = opts.journal_path();
+        let journal = if journal_path == &PathBuf::default() {
+            None
+        } else {
+            let entries =
+                read_journal(journal_path).ok_or_abort_with("Failed to read maintenance journal.");
+            println!(
+                "Restoring {} entries from maintenance journal {}.",
+                entries.len(),
+                journal_path.display()
+            );
+            for entry in entries {
+                // Entries written before the daemon could maintain several
+                // instances have no address, they belong to the first instance.
+                let index = if entry.solido_address.is_empty() {
+                    Some(0)
+                } else {
+                    instances.iter().position(|maintained| {
+                        maintained.instance.solido_address.to_string() == entry.solido_address
+                    })
+                };
+                if let Some(i) = index {
+                    instances[i].metrics.observe_transaction(&entry);
+                    push_history(&mut instances[i].maintenance_history, entry);
+                }
+            }
+            Some(
+                Journal::open(journal_path).ok_or_abort_with("Failed to open maintenance journal."),
+            )
         };
+
+        let snapshot_mutex = Arc::new(Mutex::new(vec![None; instances.len()]));
+
         Daemon {
             config,
             opts,
             rng: rand::thread_rng(),
-            last_read_success: Instant::now(),
             block_time_estimator: BlockTimeEstimator::new(),
-            metrics,
-            snapshot_mutex: Arc::new(Mutex::new(None)),
+            instances,
+            journal,
+            subscriptions: None,
+            snapshot_mutex,
         }
     }
 
-    /// Publish a new snapshot that from now on will be served by the http server.
+    /// Return a prefix for log lines about the given instance, to tell the
+    /// instances apart when we maintain more than one.
+    fn log_prefix(&self, i: usize) -> String {
+        if self.instances.len() > 1 {
+            format!("[{}] ", self.instances[i].instance.solido_address)
+        } else {
+            String::new()
+        }
+    }
+
+    /// Publish a new snapshot of the instance that from now on will be served by the http server.
     ///
     /// This also updates the block time estimator, if applicable.
-    fn publish_snapshot(&mut self, solido: Option<SolidoState>) {
+    fn publish_snapshot(&mut self, i: usize, solido: Option<SolidoState>) {
         let now = Instant::now();
+        let maintained = &mut self.instances[i];
 
         if let Some(solido) = solido.as_ref() {
-            self.last_read_success = now;
+            maintained.last_read_success = now;
+            maintained.subscription_accounts = vec![
+                solido.solido_address,
+                solido.reserve_address,
+                solido.solido.st_sol_mint,
+            ];
             self.block_time_estimator.observe_clock(now, &solido.clock);
         }
 
-        let snapshot = Snapshot {
-            metrics: self.metrics.clone(),
+        let snapshot = Arc::new(Snapshot {
+            solido_program_id: maintained.instance.solido_program_id,
+            solido_address: maintained.instance.solido_address,
+            metrics: maintained.metrics.clone(),
             solido,
-        };
-        self.snapshot_mutex
-            .lock()
-            .unwrap()
-            .replace(Arc::new(snapshot));
+            maintenance_history: maintained.maintenance_history.clone(),
+            last_read_success: maintained.last_read_success,
+        });
+        maintained.snapshot = Some(snapshot.clone());
+        self.snapshot_mutex.lock().unwrap()[i] = Some(snapshot);
+    }
+
+    /// Record a maintenance transaction that we sent in the metrics, in the
+    /// history served by the http server, and in the journal.
+    fn observe_sent_maintenance(&mut self, i: usize, state: &SolidoState, sent: &SentMaintenance) {
+        let entry = JournalEntry::new(unix_timestamp(SystemTime::now()), state, sent);
+        self.instances[i].metrics.observe_transaction(&entry);
+
+        if let Some(journal) = self.journal.as_mut() {
+            // Failing to write to the journal should not stop us from
+            // performing maintenance, so we only report it.
+            if let Err(err) = journal.append(&entry) {
+                println!("Failed to append to the maintenance journal.");
+                err.print_pretty();
+            }
+        }
+
+        push_history(&mut self.instances[i].maintenance_history, entry);
+    }
+
+    /// Schedule the next iteration for the instance immediately.
+    fn schedule_immediately(&mut self, i: usize) {
+        let maintained = &mut self.instances[i];
+        maintained.next_iteration_at = Instant::now();
+        maintained.wake_slot = None;
     }
 
-    /// Sleep with exponential backoff and jitter.
-    fn sleep_after_error(&mut self) {
+    /// Schedule the next iteration for the instance with exponential backoff and jitter.
+    fn schedule_after_error(&mut self, i: usize) {
         // For the sleep time we use exponential backoff with jitter [1]. By taking
         // the time since the last success as the target sleep time, we get
         // exponential backoff. We clamp this to ensure we don't wait indefinitely.
         // 1: https://aws.amazon.com/blogs/architecture/exponential-backoff-and-jitter/
-        let time_since_last_success = self.last_read_success.elapsed();
+        let time_since_last_success = self.instances[i].last_read_success.elapsed();
         let min_sleep_time = Duration::from_secs_f32(0.2);
         let max_sleep_time = Duration::from_secs_f32(300.0);
         let target_sleep_time = time_since_last_success.clamp(min_sleep_time, max_sleep_time);
         let sleep_time = self
             .rng
             .gen_range(Duration::from_secs(0)..target_sleep_time);
-        println!("Sleeping {:?} after error ...", sleep_time);
-        std::thread::sleep(sleep_time);
+        println!(
+            "{}Sleeping {:?} after error ...",
+            self.log_prefix(i),
+            sleep_time
+        );
+
+        let maintained = &mut self.instances[i];
+        maintained.next_iteration_at = Instant::now() + sleep_time;
+        maintained.wake_slot = None;
     }
 
-    /// Sleep either for the configured poll interval, or until it is our maintainer duty.
-    fn sleep_until_next_iteration(&mut self) {
+    /// Schedule the next iteration for the instance, either after the
+    /// configured poll interval, or when it is our maintainer duty.
+    fn schedule_next_iteration(&mut self, i: usize) {
         let maintainer = self.config.signer.pubkey();
         let poll_interval = Duration::from_secs(*self.opts.max_poll_interval_seconds());
 
         // Find out when our next maintainer duty slice starts (if any), and
         // estimate how long it will take (after the previous snapshot publish,
         // but this method should be called right after) until then.
-        let is_on_duty_and_next_duty_slot = self
-            .snapshot_mutex
-            .lock()
-            .unwrap()
-            .as_ref()
-            .and_then(|snapshot| snapshot.solido.as_ref())
-            .map(|solido| {
-                (
-                    solido.get_current_maintainer_duty() == Some(maintainer),
-                    solido.get_next_maintainer_duty_slot(&maintainer),
-                )
-            });
+        let is_on_duty_and_next_duty_slot = self.instances[i].state().map(|solido| {
+            (
+                solido.get_current_maintainer_duty() == Some(maintainer),
+                solido.get_next_maintainer_duty_slot(&maintainer),
+            )
+        });
 
         let next_duty_slot = is_on_duty_and_next_duty_slot.and_then(|(_, slot)| slot);
 
@@ -380,6 +714,21 @@ impl<'a, 'b> Daemon<'a, 'b> {
             .unwrap_or(poll_interval)
             .min(poll_interval);
 
+        // If we can subscribe to slot updates, we also wake up exactly when our
+        // duty starts, and at the start of the next epoch, when the exchange
+        // rate needs to be updated.
+        let wake_slot = if self.opts.websocket_url().is_empty() {
+            None
+        } else {
+            self.instances[i].state().map(|solido| {
+                let next_epoch_slot = solido.get_next_epoch_start_slot();
+                match next_duty_slot {
+                    Some(slot) => slot.min(next_epoch_slot),
+                    None => next_epoch_slot,
+                }
+            })
+        };
+
         fn fmt_option<T: std::fmt::Debug>(opt_value: Option<T>) -> String {
             opt_value
                 .map(|x| format!("{:?}", x))
@@ -393,7 +742,8 @@ impl<'a, 'b> Daemon<'a, 'b> {
         }
 
         println!(
-            "{}Sleeping until next iteration. Slot: {}, next duty slot: {}, block time: {}, sleep time: {}",
+            "{}{}Sleeping until next iteration. Slot: {}, next duty slot: {}, block time: {}, sleep time: {}",
+            self.log_prefix(i),
             match is_on_duty_and_next_duty_slot {
                 Some((true, _)) => "ON-DUTY  ",
                 Some((false, _)) => "OFF-DUTY ",
@@ -405,79 +755,585 @@ impl<'a, 'b> Daemon<'a, 'b> {
             fmt_option_duration(Some(sleep_time)),
         );
 
-        std::thread::sleep(sleep_time);
+        let maintained = &mut self.instances[i];
+        maintained.next_iteration_at = Instant::now() + sleep_time;
+        maintained.wake_slot = wake_slot;
     }
 
-    /// Run maintenance in a loop.
-    fn run(mut self) -> ! {
-        loop {
-            self.metrics.polls += 1;
-            match run_maintenance_iteration(self.config, self.opts) {
-                MaintenanceResult::ErrSnapshot(err) => {
-                    println!("Error while obtaining on-chain state.");
-                    err.print_pretty();
-                    self.metrics.errors += 1;
-                    self.publish_snapshot(None);
-                    self.sleep_after_error();
+    /// Make sure that we are subscribed to changes to the accounts of all
+    /// instances that we know the accounts of.
+    ///
+    /// Returns false if we could not subscribe, in which case the caller should
+    /// fall back to polling.
+    fn ensure_subscribed(&mut self) -> bool {
+        let accounts: Vec<Pubkey> = self
+            .instances
+            .iter()
+            .flat_map(|maintained| maintained.subscription_accounts.iter().copied())
+            .collect();
+
+        // Before the first successful read, we don't know what to subscribe to.
+        if accounts.is_empty() {
+            return false;
+        }
+
+        if let Some(subscriptions) = self.subscriptions.as_ref() {
+            if subscriptions.accounts() == &accounts[..] {
+                return true;
+            }
+        }
+
+        // Drop the subscriptions for the old set of accounts first, if any.
+        self.subscriptions = None;
+        let websocket_url = self.opts.websocket_url();
+        match Subscriptions::subscribe(websocket_url, &accounts) {
+            Ok(subscriptions) => {
+                println!(
+                    "Subscribed to account and slot updates at {}.",
+                    websocket_url
+                );
+                self.subscriptions = Some(subscriptions);
+                true
+            }
+            Err(err) => {
+                println!(
+                    "Failed to subscribe to updates at {}, falling back to polling.",
+                    websocket_url
+                );
+                err.print_pretty();
+                false
+            }
+        }
+    }
+
+    /// Wait until the next iteration of any instance is due.
+    ///
+    /// If we are subscribed to changes, an instance becomes due early when one
+    /// of its accounts changes, or when we reach its wake slot.
+    fn wait_for_next_iteration(&mut self) {
+        let next_iteration_at = self
+            .instances
+            .iter()
+            .map(|maintained| maintained.next_iteration_at)
+            .min()
+            .expect("The daemon maintains at least one instance.");
+        let timeout = next_iteration_at.saturating_duration_since(Instant::now());
+        if timeout == Duration::from_secs(0) {
+            return;
+        }
+
+        if self.opts.websocket_url().is_empty() || !self.ensure_subscribed() {
+            std::thread::sleep(timeout);
+            return;
+        }
+
+        let wake_slot = self
+            .instances
+            .iter()
+            .filter_map(|maintained| maintained.wake_slot)
+            .min();
+        let subscriptions = self.subscriptions.as_ref().expect("We just subscribed.");
+        let wake_reason = subscriptions.wait(timeout, wake_slot);
+
+        let now = Instant::now();
+        match wake_reason {
+            WakeReason::AccountChanged(account) => {
+                println!("Account {} changed.", account);
+                for maintained in self.instances.iter_mut() {
+                    if maintained.subscription_accounts.contains(&account) {
+                        maintained.next_iteration_at = now;
+                    }
                 }
-                MaintenanceResult::ErrMaintenance(state, err) => {
-                    println!("Error while performing maintenance.");
-                    err.print_pretty();
-                    self.metrics.errors += 1;
-                    self.publish_snapshot(Some(state));
-                    // After a failed maintenance transaction, we sleep the regular
-                    // poll interval. This ensures that if there is a bug that causes
-                    // maintenance transactions to always fail (like [1]), we don't
-                    // go in a busy loop submitting failing transactions.
-                    // 1: https://github.com/ChorusOne/solido/issues/422
-                    self.sleep_until_next_iteration();
+            }
+            WakeReason::Slot(slot) => {
+                println!("Reached slot {}.", slot);
+                for maintained in self.instances.iter_mut() {
+                    if maintained
+                        .wake_slot
+                        .map_or(false, |wake_slot| wake_slot <= slot)
+                    {
+                        maintained.next_iteration_at = now;
+                    }
                 }
-                MaintenanceResult::OkIdle(state) => {
-                    self.publish_snapshot(Some(state));
-                    self.sleep_until_next_iteration();
+            }
+            WakeReason::Timeout => {}
+            WakeReason::Disconnected => {
+                println!(
+                    "Lost connection to {}, falling back to polling.",
+                    self.opts.websocket_url()
+                );
+                self.subscriptions = None;
+                // Sleep for the remainder of the timeout, so we don't
+                // resubscribe in a busy loop if the connection keeps dropping.
+                std::thread::sleep(next_iteration_at.saturating_duration_since(now));
+            }
+        }
+    }
+
+    /// Run one maintenance iteration for the instance, and schedule the next one.
+    fn run_iteration(&mut self, i: usize) {
+        let prefix = self.log_prefix(i);
+        let instance = self.instances[i].instance;
+        self.instances[i].metrics.polls += 1;
+
+        let was_taken_over = self.instances[i].failover.taken_over;
+        let result = run_maintenance_iteration(
+            self.config,
+            self.opts,
+            &instance,
+            &mut self.instances[i].failover,
+        );
+        if self.instances[i].failover.taken_over && !was_taken_over {
+            println!(
+                "{}The maintainer on duty did not perform pending maintenance, taking over its duty.",
+                prefix
+            );
+            self.instances[i].metrics.duty_takeovers += 1;
+        }
+
+        match result {
+            MaintenanceResult::ErrSnapshot(err) => {
+                println!("{}Error while obtaining on-chain state.", prefix);
+                err.print_pretty();
+                self.instances[i].metrics.errors += 1;
+                self.publish_snapshot(i, None);
+                self.schedule_after_error(i);
+            }
+            MaintenanceResult::ErrMaintenance(state, err) => {
+                println!("{}Error while performing maintenance.", prefix);
+                err.print_pretty();
+                self.instances[i].metrics.errors += 1;
+                self.publish_snapshot(i, Some(state));
+                // After a failed maintenance transaction, we wait the regular
+                // poll interval. This ensures that if there is a bug that causes
+                // maintenance transactions to always fail (like [1]), we don't
+                // go in a busy loop submitting failing transactions.
+                // 1: https://github.com/ChorusOne/solido/issues/422
+                self.schedule_next_iteration(i);
+            }
+            MaintenanceResult::OkIdle(state) => {
+                self.publish_snapshot(i, Some(state));
+                self.schedule_next_iteration(i);
+            }
+            MaintenanceResult::OkMaintenance(state, sent_all) => {
+                let mut any_succeeded = false;
+                for sent in &sent_all {
+                    self.observe_sent_maintenance(i, &state, sent);
+                    match sent.result {
+                        Ok(()) => {
+                            any_succeeded = true;
+                            for output in &sent.outputs {
+                                print!("{}", prefix);
+                                println!("{}", output);
+                            }
+                        }
+                        Err(ref err) => {
+                            println!("{}Error while performing maintenance.", prefix);
+                            err.print_pretty();
+                            self.instances[i].metrics.errors += 1;
+                        }
+                    }
                 }
-                MaintenanceResult::OkMaintenance(state, output) => {
-                    println!("{}", output);
-                    self.metrics.observe_maintenance(&output);
-                    self.publish_snapshot(Some(state));
-                    // Note, we do not sleep here. If we performed maintenance, we
+                self.publish_snapshot(i, Some(state));
+                if any_succeeded {
+                    // Note, we do not wait here. If we performed maintenance, we
                     // might not be done yet, so we should immediately check again.
+                    self.schedule_immediately(i);
+                } else {
+                    // Wait after failed transactions, for the same reason as
+                    // for `ErrMaintenance` above.
+                    self.schedule_next_iteration(i);
                 }
-            };
+            }
+            MaintenanceResult::OkSimulated(state, output) => {
+                print!("{}", prefix);
+                println!("{}", output);
+                self.instances[i].metrics.observe_simulation(&output);
+                self.publish_snapshot(i, Some(state));
+                // A simulation does not change the on-chain state, so if we
+                // checked again immediately, we would simulate the same
+                // transaction again. Wait the regular poll interval instead.
+                self.schedule_next_iteration(i);
+            }
+        };
+    }
+
+    /// Run maintenance in a loop.
+    ///
+    /// We run the iterations of all instances from this one loop, in the order
+    /// in which they become due. An error for one instance only delays the next
+    /// iteration of that instance, it does not stall the others.
+    fn run(mut self) -> ! {
+        loop {
+            for i in 0..self.instances.len() {
+                if self.instances[i].next_iteration_at <= Instant::now() {
+                    self.run_iteration(i);
+                }
+            }
+            self.wait_for_next_iteration();
         }
     }
 }
 
-fn serve_request(request: Request, snapshot_mutex: &SnapshotMutex) -> Result<(), std::io::Error> {
-    // Take the current snapshot. This only holds the lock briefly, and does
-    // not prevent other threads from updating the snapshot while this request
-    // handler is running.
-    let option_snapshot = snapshot_mutex.lock().unwrap().clone();
+/// Return the number of seconds since the Unix epoch.
+fn unix_timestamp(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
 
-    // It might be that no snapshot is available yet. This happens when we just
-    // started the server, and the main loop has not yet queried the RPC for the
-    // latest state.
-    let snapshot = match option_snapshot {
-        Some(arc_snapshot) => arc_snapshot,
-        None => {
-            return request.respond(
-                Response::from_string(
-                    "Service Unavailable\n\nServer is still starting, try again shortly.",
-                )
-                .with_status_code(503),
-            );
+/// Overview of the Solido instance, served at `/api/solido`.
+#[derive(Serialize)]
+struct SolidoOutput<'a> {
+    /// Unix timestamp (in seconds) at which we obtained the state.
+    produced_at: u64,
+    slot: Slot,
+    epoch: Epoch,
+
+    #[serde(serialize_with = "serialize_b58")]
+    solido_program_id: Pubkey,
+
+    #[serde(serialize_with = "serialize_b58")]
+    solido_address: Pubkey,
+
+    solido: &'a Lido,
+
+    #[serde(serialize_with = "serialize_b58")]
+    reserve_address: Pubkey,
+
+    /// Balance of the reserve, excluding the rent-exempt amount.
+    #[serde(rename = "reserve_balance_lamports")]
+    reserve_balance: Lamports,
+
+    #[serde(rename = "st_sol_supply_st_lamports")]
+    st_sol_supply: StLamports,
+}
+
+impl<'a> SolidoOutput<'a> {
+    fn new(state: &'a SolidoState) -> Self {
+        SolidoOutput {
+            produced_at: unix_timestamp(state.produced_at),
+            slot: state.clock.slot,
+            epoch: state.clock.epoch,
+            solido_program_id: state.solido_program_id,
+            solido_address: state.solido_address,
+            solido: &state.solido,
+            reserve_address: state.reserve_address,
+            reserve_balance: state.get_effective_reserve(),
+            st_sol_supply: StLamports(state.st_sol_mint.supply),
         }
+    }
+}
+
+/// A validator and its balances, served at `/api/validators`.
+#[derive(Serialize)]
+struct ValidatorOutput<'a> {
+    #[serde(serialize_with = "serialize_b58")]
+    vote_account: Pubkey,
+
+    name: &'a str,
+    keybase_username: Option<&'a str>,
+
+    entry: &'a Validator,
+
+    /// Sum of the balances of the validator's stake accounts.
+    stake_balance: StakeBalance,
+
+    /// Sum of the balances of the validator's unstake accounts.
+    unstake_balance: StakeBalance,
+
+    #[serde(rename = "vote_account_balance_lamports")]
+    vote_account_balance: Lamports,
+
+    #[serde(rename = "identity_account_balance_lamports")]
+    identity_account_balance: Lamports,
+
+    /// The slot that the validator last voted on.
+    last_voted_slot: Slot,
+}
+
+impl<'a> ValidatorOutput<'a> {
+    fn from_state(state: &'a SolidoState) -> Vec<Self> {
+        fn sum_balances(accounts: &[(Pubkey, StakeAccount)]) -> StakeBalance {
+            accounts
+                .iter()
+                .map(|(_addr, stake_account)| stake_account.balance)
+                .sum()
+        }
+
+        state
+            .validators
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(i, validator)| {
+                let info = &state.validator_infos[i];
+                ValidatorOutput {
+                    vote_account: validator.pubkey,
+                    name: &info.name,
+                    keybase_username: info.keybase_username.as_deref(),
+                    entry: &validator.entry,
+                    stake_balance: sum_balances(&state.validator_stake_accounts[i]),
+                    unstake_balance: sum_balances(&state.validator_unstake_accounts[i]),
+                    vote_account_balance: state.validator_vote_account_balances[i],
+                    identity_account_balance: state.validator_identity_account_balances[i],
+                    last_voted_slot: state.validator_vote_accounts[i].last_timestamp.slot,
+                }
+            })
+            .collect()
+    }
+}
+
+/// The next duty slice of a maintainer.
+#[derive(Serialize)]
+struct MaintainerDutyOutput {
+    #[serde(serialize_with = "serialize_b58")]
+    maintainer: Pubkey,
+
+    /// Slot at which the maintainer's next duty slice starts, none if the
+    /// maintainer stopped sending heartbeats and is left out of the rotation.
+    next_duty_slot: Option<Slot>,
+}
+
+/// Current and next maintainer duty, served at `/api/duty`.
+#[derive(Serialize)]
+struct DutyOutput {
+    slot: Slot,
+
+    /// The maintainer that is on duty at `slot`, if any.
+    #[serde(serialize_with = "serialize_b58_option")]
+    current_maintainer: Option<Pubkey>,
+
+    /// The maintainer that this daemon runs as.
+    #[serde(serialize_with = "serialize_b58")]
+    maintainer_address: Pubkey,
+
+    /// Whether this daemon is the maintainer on duty.
+    is_on_duty: bool,
+
+    /// The maintainer that takes over if the maintainer on duty does not
+    /// perform pending maintenance.
+    #[serde(serialize_with = "serialize_b58_option")]
+    failover_maintainer: Option<Pubkey>,
+
+    /// Slot at which the next duty slice of this daemon starts.
+    next_duty_slot: Option<Slot>,
+
+    /// The next duty slice of every maintainer, ordered by slot.
+    schedule: Vec<MaintainerDutyOutput>,
+}
+
+impl DutyOutput {
+    fn new(state: &SolidoState) -> Self {
+        let current_maintainer = state.get_current_maintainer_duty();
+        let mut schedule: Vec<MaintainerDutyOutput> = state
+            .solido
+            .maintainers
+            .entries
+            .iter()
+            .map(|maintainer| MaintainerDutyOutput {
+                maintainer: maintainer.pubkey,
+                next_duty_slot: state.get_next_maintainer_duty_slot(&maintainer.pubkey),
+            })
+            .collect();
+        // Maintainers that are left out of the rotation go last.
+        schedule.sort_by_key(|duty| (duty.next_duty_slot.is_none(), duty.next_duty_slot));
+
+        DutyOutput {
+            slot: state.clock.slot,
+            current_maintainer,
+            maintainer_address: state.maintainer_address,
+            is_on_duty: current_maintainer == Some(state.maintainer_address),
+            failover_maintainer: state.get_failover_maintainer(),
+            next_duty_slot: state.get_next_maintainer_duty_slot(&state.maintainer_address),
+            schedule,
+        }
+    }
+}
+
+/// A Solido instance that the daemon maintains, served at `/api/instances`.
+#[derive(Serialize)]
+struct InstanceOutput {
+    #[serde(serialize_with = "serialize_b58")]
+    solido_program_id: Pubkey,
+
+    #[serde(serialize_with = "serialize_b58")]
+    solido_address: Pubkey,
+
+    /// Slot of the on-chain state, if the most recent read succeeded.
+    slot: Option<Slot>,
+}
+
+impl InstanceOutput {
+    fn new(instances: &[SolidoInstance], snapshots: &[Option<Arc<Snapshot>>]) -> Vec<Self> {
+        instances
+            .iter()
+            .zip(snapshots)
+            .map(|(instance, snapshot)| InstanceOutput {
+                solido_program_id: instance.solido_program_id,
+                solido_address: instance.solido_address,
+                slot: snapshot
+                    .as_ref()
+                    .and_then(|snapshot| snapshot.solido.as_ref())
+                    .map(|solido| solido.clock.slot),
+            })
+            .collect()
+    }
+}
+
+/// The maintenance policy of the daemon, served at `/api/maintenance-policy`.
+#[derive(Clone, Serialize)]
+struct PolicyOutput {
+    /// The maintenance tasks that the daemon performs, in order of priority.
+    enabled_tasks: Vec<MaintenanceTask>,
+
+    /// The maintenance tasks that the daemon does not perform.
+    disabled_tasks: Vec<MaintenanceTask>,
+
+    max_instructions_per_transaction: u64,
+    dry_run: bool,
+}
+
+impl PolicyOutput {
+    fn new(opts: &RunMaintainerOpts) -> Self {
+        let enabled_tasks = opts.maintenance_tasks().0.clone();
+        let disabled_tasks = MaintenanceTask::ALL
+            .iter()
+            .filter(|task| !enabled_tasks.contains(task))
+            .copied()
+            .collect();
+        PolicyOutput {
+            enabled_tasks,
+            disabled_tasks,
+            max_instructions_per_transaction: *opts.max_instructions_per_transaction(),
+            dry_run: *opts.dry_run(),
+        }
+    }
+}
+
+/// Thresholds for the `/healthz` and `/readyz` checks.
+#[derive(Copy, Clone)]
+struct HealthThresholds {
+    /// The instant at which the daemon started, which counts as the last
+    /// successful read before the first snapshot is published.
+    started_at: Instant,
+
+    /// Maximum time since the last successful read of the on-chain state.
+    max_staleness: Duration,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum HealthCheck {
+    Liveness,
+    Readiness,
+}
+
+/// Check whether the daemon is healthy, return the reason if it is not.
+///
+/// Liveness only fails when we have not been able to read the on-chain state
+/// for too long; restarting the daemon might help in that case. Readiness also
+/// fails before we read the state for the first time, and when the maintainer
+/// does not have enough SOL to pay for maintenance transactions. A restart will
+/// not fix that, so it does not affect liveness.
+fn check_health(
+    snapshot: Option<&Snapshot>,
+    thresholds: &HealthThresholds,
+    check: HealthCheck,
+    now: Instant,
+) -> Result<(), String> {
+    let last_read_success = snapshot
+        .map(|snapshot| snapshot.last_read_success)
+        .unwrap_or(thresholds.started_at);
+    let time_since_last_read = now.saturating_duration_since(last_read_success);
+    if time_since_last_read > thresholds.max_staleness {
+        return Err(format!(
+            "Last successful read of the on-chain state was {}s ago, \
+            which is more than the maximum of {}s.",
+            time_since_last_read.as_secs(),
+            thresholds.max_staleness.as_secs(),
+        ));
+    }
+
+    if check == HealthCheck::Liveness {
+        return Ok(());
+    }
+
+    let snapshot = match snapshot {
+        Some(snapshot) => snapshot,
+        None => return Err("Server is still starting, no on-chain state read yet.".to_string()),
     };
 
-    // We don't even look at the request, for now we always serve the metrics.
+    // If the most recent read failed, there is no balance to check, but a
+    // previous read was recent enough, so we still consider the daemon ready.
+    if let Some(balance) = snapshot
+        .solido
+        .as_ref()
+        .and_then(|solido| solido.get_maintainer_balance())
+    {
+        if balance < MINIMUM_MAINTAINER_BALANCE {
+            return Err(format!(
+                "Balance of the maintainer account is {}, which is less than the minimum of {}.",
+                balance, MINIMUM_MAINTAINER_BALANCE,
+            ));
+        }
+    }
 
-    let mut out: Vec<u8> = Vec::new();
-    let mut is_ok = snapshot.metrics.write_prometheus(&mut out).is_ok();
+    Ok(())
+}
 
-    if let Some(ref solido) = snapshot.solido {
-        is_ok = is_ok && solido.write_prometheus(&mut out).is_ok();
+/// Respond with the outcome of a health check, with the reasons in the body.
+///
+/// The daemon is only healthy if it is healthy for every instance.
+fn serve_health(
+    request: Request,
+    instances: &[SolidoInstance],
+    snapshots: &[Option<Arc<Snapshot>>],
+    thresholds: &HealthThresholds,
+    check: HealthCheck,
+) -> Result<(), std::io::Error> {
+    let now = Instant::now();
+    let mut reasons = Vec::new();
+    for (instance, snapshot) in instances.iter().zip(snapshots) {
+        if let Err(reason) = check_health(snapshot.as_deref(), thresholds, check, now) {
+            if instances.len() > 1 {
+                reasons.push(format!("Instance {}: {}", instance.solido_address, reason));
+            } else {
+                reasons.push(reason);
+            }
+        }
+    }
+
+    if reasons.is_empty() {
+        request.respond(Response::from_string("OK"))
+    } else {
+        request.respond(
+            Response::from_string(format!("Service Unavailable\n\n{}", reasons.join("\n")))
+                .with_status_code(503),
+        )
+    }
+}
+
+/// Respond with the metrics of all instances in Prometheus text format.
+///
+/// Every metric gets a `solido_address` label with the instance it belongs to.
+fn serve_metrics(
+    request: Request,
+    snapshots: &[Option<Arc<Snapshot>>],
+) -> Result<(), std::io::Error> {
+    let mut collector = LabeledMetrics::new("solido_address");
+    let mut is_ok = true;
+
+    for snapshot in snapshots.iter().flatten() {
+        collector.set_label_value(snapshot.solido_address.to_string());
+        is_ok = is_ok && snapshot.metrics.write_prometheus(&mut collector).is_ok();
+        if let Some(ref solido) = snapshot.solido {
+            is_ok = is_ok && solido.write_prometheus(&mut collector).is_ok();
+        }
     }
 
+    let mut out: Vec<u8> = Vec::new();
+    is_ok = is_ok && collector.write_to(&mut out).is_ok();
+
     if is_ok {
         // text/plain with version=0.0.4 is what Prometheus expects as the content type,
         // see also https://prometheus.io/docs/instrumenting/exposition_formats/.
@@ -494,10 +1350,138 @@ fn serve_request(request: Request, snapshot_mutex: &SnapshotMutex) -> Result<(),
     }
 }
 
+/// Respond with the value serialized as json.
+fn serve_json<T: Serialize>(request: Request, value: &T) -> Result<(), std::io::Error> {
+    match serde_json::to_vec(value) {
+        Ok(out) => {
+            let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+                .expect("Static header value, does not fail at runtime.");
+            request.respond(Response::from_data(out).with_header(content_type))
+        }
+        Err(..) => request.respond(Response::from_string("error").with_status_code(500)),
+    }
+}
+
+/// Respond with json derived from the Solido state, if we have it.
+///
+/// When the most recent attempt to read the on-chain state failed, we have no
+/// state to serve, and we respond with a 503 instead of serving stale data.
+fn serve_solido_json<'a, T: Serialize>(
+    request: Request,
+    snapshot: &'a Snapshot,
+    f: impl FnOnce(&'a SolidoState) -> T,
+) -> Result<(), std::io::Error> {
+    match snapshot.solido {
+        Some(ref solido) => serve_json(request, &f(solido)),
+        None => request.respond(
+            Response::from_string(
+                "Service Unavailable\n\nFailed to obtain the on-chain state, try again shortly.",
+            )
+            .with_status_code(503),
+        ),
+    }
+}
+
+fn serve_request(
+    request: Request,
+    instances: &[SolidoInstance],
+    policy: &PolicyOutput,
+    snapshot_mutex: &SnapshotMutex,
+    thresholds: &HealthThresholds,
+) -> Result<(), std::io::Error> {
+    // Take the current snapshots. This only holds the lock briefly, and does
+    // not prevent other threads from updating the snapshots while this request
+    // handler is running.
+    let snapshots = snapshot_mutex.lock().unwrap().clone();
+
+    // Route on the path, the query string can only select the instance.
+    let mut url_parts = request.url().splitn(2, '?');
+    let path = url_parts.next().unwrap_or("").to_string();
+    let query = url_parts.next().unwrap_or("").to_string();
+
+    // The health checks should respond also before we have a snapshot.
+    match path.as_str() {
+        "/healthz" => {
+            return serve_health(
+                request,
+                instances,
+                &snapshots,
+                thresholds,
+                HealthCheck::Liveness,
+            )
+        }
+        "/readyz" => {
+            return serve_health(
+                request,
+                instances,
+                &snapshots,
+                thresholds,
+                HealthCheck::Readiness,
+            )
+        }
+        "/api/instances" => {
+            return serve_json(request, &InstanceOutput::new(instances, &snapshots));
+        }
+        "/api/maintenance-policy" => return serve_json(request, policy),
+        _ => {}
+    }
+
+    // The `solido_address` query parameter selects the instance to serve from
+    // the other `/api` routes, by default we serve the first one.
+    let selected_address = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("solido_address="));
+    let index = match selected_address {
+        None => 0,
+        Some(address) => match instances
+            .iter()
+            .position(|instance| instance.solido_address.to_string() == address)
+        {
+            Some(index) => index,
+            None => {
+                return request.respond(
+                    Response::from_string(
+                        "Not Found\n\nThis daemon does not maintain that instance.",
+                    )
+                    .with_status_code(404),
+                )
+            }
+        },
+    };
+
+    // It might be that no snapshot is available yet. This happens when we just
+    // started the server, and the main loop has not yet queried the RPC for the
+    // latest state.
+    let snapshot = match snapshots[index].clone() {
+        Some(arc_snapshot) => arc_snapshot,
+        None => {
+            return request.respond(
+                Response::from_string(
+                    "Service Unavailable\n\nServer is still starting, try again shortly.",
+                )
+                .with_status_code(503),
+            );
+        }
+    };
+
+    // We also serve the metrics at the root, because that is where we used to
+    // serve them for any path.
+    match path.as_str() {
+        "/" | "/metrics" => serve_metrics(request, &snapshots),
+        "/api/solido" => serve_solido_json(request, &snapshot, SolidoOutput::new),
+        "/api/validators" => serve_solido_json(request, &snapshot, ValidatorOutput::from_state),
+        "/api/maintenance-history" => serve_json(request, &snapshot.maintenance_history),
+        "/api/duty" => serve_solido_json(request, &snapshot, DutyOutput::new),
+        _ => request.respond(Response::from_string("Not Found").with_status_code(404)),
+    }
+}
+
 /// Spawn threads that run the http server.
 fn start_http_server(
     opts: &RunMaintainerOpts,
+    instances: Vec<SolidoInstance>,
     snapshot_mutex: Arc<SnapshotMutex>,
+    thresholds: HealthThresholds,
 ) -> Vec<JoinHandle<()>> {
     let server = match Server::http(opts.listen().clone()) {
         Ok(server) => Arc::new(server),
@@ -513,21 +1497,31 @@ fn start_http_server(
 
     println!("Http server listening on {}", opts.listen());
 
+    let policy = PolicyOutput::new(opts);
+
     // Spawn a number of http handler threads, so we can handle requests in
-    // parallel. This server is only used to serve metrics, it can be super basic,
-    // but some degree of parallelism is nice in case a client is slow to send
-    // its request or something like that.
+    // parallel. This server only serves metrics and read-only json, it can be
+    // super basic, but some degree of parallelism is nice in case a client is
+    // slow to send its request or something like that.
     (0..num_cpus::get())
         .map(|i| {
             let server_clone = server.clone();
             let snapshot_mutex_clone = snapshot_mutex.clone();
+            let instances_clone = instances.clone();
+            let policy_clone = policy.clone();
             std::thread::Builder::new()
                 .name(format!("http_handler_{}", i))
                 .spawn(move || {
                     for request in server_clone.incoming_requests() {
                         // Ignore any errors; if we fail to respond, then there's little
                         // we can do about it here ... the client should just retry.
-                        let _ = serve_request(request, &*snapshot_mutex_clone);
+                        let _ = serve_request(
+                            request,
+                            &instances_clone,
+                            &policy_clone,
+                            &*snapshot_mutex_clone,
+                            &thresholds,
+                        );
                     }
                 })
                 .expect("Failed to spawn http handler thread.")
@@ -537,14 +1531,308 @@ fn start_http_server(
 
 /// Run the maintenance daemon.
 pub fn main(config: &mut SnapshotClientConfig, opts: &RunMaintainerOpts) {
+    opts.validate_or_exit();
+    let thresholds = HealthThresholds {
+        started_at: Instant::now(),
+        max_staleness: Duration::from_secs(*opts.max_staleness_seconds()),
+    };
+    let instances = opts.instances();
+    for (i, instance) in instances.iter().enumerate() {
+        if instances[..i]
+            .iter()
+            .any(|other| other.solido_address == instance.solido_address)
+        {
+            eprintln!(
+                "Error: Solido instance {} is listed more than once.",
+                instance.solido_address
+            );
+            std::process::exit(1);
+        }
+    }
+
     let daemon = Daemon::new(config, opts);
-    let _http_threads = start_http_server(opts, daemon.snapshot_mutex.clone());
+    let _http_threads =
+        start_http_server(opts, instances, daemon.snapshot_mutex.clone(), thresholds);
     daemon.run();
 }
 
 #[cfg(test)]
 mod test {
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
     use super::*;
+    use crate::maintenance::test::new_empty_solido;
+    use crate::prometheus::MetricValue;
+    use crate::validator_info_utils::ValidatorInfo;
+    use solana_vote_program::vote_state::VoteState;
+
+    fn snapshot_with_last_read_success(last_read_success: Instant) -> Snapshot {
+        Snapshot {
+            solido_program_id: Pubkey::default(),
+            solido_address: Pubkey::default(),
+            metrics: MaintenanceMetrics::default(),
+            solido: None,
+            maintenance_history: VecDeque::new(),
+            last_read_success,
+        }
+    }
+
+    #[test]
+    fn check_health_fails_when_last_read_is_stale() {
+        let t0 = Instant::now();
+        let thresholds = HealthThresholds {
+            started_at: t0,
+            max_staleness: Duration::from_secs(60),
+        };
+        let t1 = t0 + Duration::from_secs(30);
+        let t2 = t0 + Duration::from_secs(90);
+
+        // Before the first snapshot, the start time counts as the last read.
+        assert_eq!(
+            check_health(None, &thresholds, HealthCheck::Liveness, t1),
+            Ok(())
+        );
+        assert!(check_health(None, &thresholds, HealthCheck::Liveness, t2).is_err());
+        assert!(check_health(None, &thresholds, HealthCheck::Readiness, t1).is_err());
+
+        let snapshot = snapshot_with_last_read_success(t1);
+        for &check in &[HealthCheck::Liveness, HealthCheck::Readiness] {
+            assert_eq!(
+                check_health(Some(&snapshot), &thresholds, check, t2),
+                Ok(())
+            );
+            assert_eq!(
+                check_health(
+                    Some(&snapshot),
+                    &thresholds,
+                    check,
+                    t1 + Duration::from_secs(61)
+                ),
+                Err("Last successful read of the on-chain state was 61s ago, \
+                    which is more than the maximum of 60s."
+                    .to_string()),
+            );
+        }
+    }
+
+    /// Serve a single GET request for `url`, return the status code and body.
+    fn get(
+        url: &str,
+        instances: &[SolidoInstance],
+        snapshots: Vec<Option<Arc<Snapshot>>>,
+    ) -> (u16, String) {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let request = format!("GET {} HTTP/1.0\r\nConnection: close\r\n\r\n", url);
+        let address = server.server_addr();
+        let client = std::thread::spawn(move || {
+            let mut stream = TcpStream::connect(address).unwrap();
+            stream.write_all(request.as_bytes()).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        });
+
+        let policy = PolicyOutput {
+            enabled_tasks: MaintenanceTask::ALL.to_vec(),
+            disabled_tasks: Vec::new(),
+            max_instructions_per_transaction: 1,
+            dry_run: false,
+        };
+        let thresholds = HealthThresholds {
+            started_at: Instant::now(),
+            max_staleness: Duration::from_secs(60),
+        };
+        serve_request(
+            server.recv().unwrap(),
+            instances,
+            &policy,
+            &Mutex::new(snapshots),
+            &thresholds,
+        )
+        .unwrap();
+
+        let response = client.join().unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+        (status, body.to_string())
+    }
+
+    /// Return a snapshot with a Solido state that has a single validator.
+    fn snapshot_with_validator() -> Snapshot {
+        let mut state = new_empty_solido();
+        let vote_account = Pubkey::new_unique();
+        state.validators.maximum_entries = 1;
+        state
+            .validators
+            .add(vote_account, Validator::new(Pubkey::new_unique()))
+            .unwrap();
+        state.validator_stake_accounts.push(vec![]);
+        state.validator_unstake_accounts.push(vec![]);
+        state.validator_vote_account_balances.push(Lamports(0));
+        state.validator_vote_accounts.push(VoteState::default());
+        state.validator_identity_account_balances.push(Lamports(0));
+        state.validator_infos.push(ValidatorInfo {
+            name: "Validator".to_string(),
+            keybase_username: None,
+        });
+
+        let mut snapshot = snapshot_with_last_read_success(Instant::now());
+        snapshot.solido_program_id = state.solido_program_id;
+        snapshot.solido_address = state.solido_address;
+        snapshot.solido = Some(state);
+        snapshot
+    }
+
+    fn instance_of(snapshot: &Snapshot) -> SolidoInstance {
+        SolidoInstance {
+            solido_program_id: snapshot.solido_program_id,
+            solido_address: snapshot.solido_address,
+            stake_time: None,
+        }
+    }
+
+    fn parse_json(body: &str) -> serde_json::Value {
+        serde_json::from_str(body).expect("Response should be valid json.")
+    }
+
+    #[test]
+    fn serve_request_serves_solido_state_as_json() {
+        let snapshot = snapshot_with_validator();
+        let instances = vec![instance_of(&snapshot)];
+        let solido_address = snapshot.solido_address.to_string();
+        let maintainer_address = snapshot.solido.as_ref().unwrap().maintainer_address;
+        let vote_account = snapshot.solido.as_ref().unwrap().validators.entries[0].pubkey;
+        let snapshots = vec![Some(Arc::new(snapshot))];
+
+        let (status, body) = get("/api/solido", &instances, snapshots.clone());
+        assert_eq!(status, 200);
+        assert_eq!(parse_json(&body)["solido_address"], solido_address);
+
+        let (status, body) = get("/api/validators", &instances, snapshots.clone());
+        assert_eq!(status, 200);
+        let validators = parse_json(&body);
+        assert_eq!(validators.as_array().unwrap().len(), 1);
+        assert_eq!(validators[0]["vote_account"], vote_account.to_string());
+        assert_eq!(validators[0]["name"], "Validator");
+
+        let (status, body) = get("/api/duty", &instances, snapshots.clone());
+        assert_eq!(status, 200);
+        let duty = parse_json(&body);
+        assert_eq!(duty["maintainer_address"], maintainer_address.to_string());
+        assert_eq!(duty["is_on_duty"], false);
+
+        // The query string selects the instance, also when it is the only one.
+        let url = format!("/api/solido?solido_address={}", solido_address);
+        let (status, _body) = get(&url, &instances, snapshots);
+        assert_eq!(status, 200);
+    }
+
+    #[test]
+    fn serve_request_serves_maintenance_history_as_json() {
+        let mut snapshot = snapshot_with_validator();
+        for operation in &["StakeDeposit", "UpdateExchangeRate"] {
+            push_history(
+                &mut snapshot.maintenance_history,
+                new_journal_entry(operation, 0, None),
+            );
+        }
+        let instances = vec![instance_of(&snapshot)];
+        let snapshots = vec![Some(Arc::new(snapshot))];
+
+        let (status, body) = get("/api/maintenance-history", &instances, snapshots);
+        assert_eq!(status, 200);
+        let history = parse_json(&body);
+        assert_eq!(history.as_array().unwrap().len(), 2);
+        assert_eq!(history[0]["operation"], "StakeDeposit");
+        assert_eq!(history[1]["operation"], "UpdateExchangeRate");
+    }
+
+    #[test]
+    fn serve_request_responds_with_errors_when_it_cannot_serve() {
+        let snapshot = snapshot_with_validator();
+        let instances = vec![instance_of(&snapshot)];
+
+        // Before the first snapshot, there is nothing to serve.
+        let (status, _body) = get("/api/solido", &instances, vec![None]);
+        assert_eq!(status, 503);
+
+        // When the most recent read failed, we don't serve the stale state,
+        // but the maintenance history is still available.
+        let mut failed_snapshot = snapshot_with_last_read_success(Instant::now());
+        failed_snapshot.solido_address = snapshot.solido_address;
+        let failed_snapshots = vec![Some(Arc::new(failed_snapshot))];
+        for &url in &["/api/solido", "/api/validators", "/api/duty"] {
+            let (status, _body) = get(url, &instances, failed_snapshots.clone());
+            assert_eq!(status, 503, "Expected 503 for {}.", url);
+        }
+        let (status, body) = get("/api/maintenance-history", &instances, failed_snapshots);
+        assert_eq!(status, 200);
+        assert_eq!(parse_json(&body), serde_json::json!([]));
+
+        let snapshots = vec![Some(Arc::new(snapshot))];
+        let (status, _body) = get("/api/does-not-exist", &instances, snapshots.clone());
+        assert_eq!(status, 404);
+
+        let url = format!("/api/solido?solido_address={}", Pubkey::new_unique());
+        let (status, _body) = get(&url, &instances, snapshots);
+        assert_eq!(status, 404);
+    }
+
+    fn new_journal_entry(operation: &str, bundled: usize, error: Option<&str>) -> JournalEntry {
+        JournalEntry {
+            timestamp: 0,
+            solido_address: String::new(),
+            slot: 0,
+            epoch: 0,
+            signature: String::new(),
+            operation: operation.to_string(),
+            output: serde_json::Value::Null,
+            bundled: vec![serde_json::Value::Null; bundled],
+            fee_lamports: 5000,
+            error: error.map(|err| err.to_string()),
+        }
+    }
+
+    #[test]
+    fn maintenance_metrics_accumulate_transactions_and_fees_per_operation() {
+        let mut metrics = MaintenanceMetrics::default();
+
+        // A transaction with two bundled instructions counts as three operations,
+        // but it paid the fee only once.
+        metrics.observe_transaction(&new_journal_entry("StakeDeposit", 2, None));
+        metrics.observe_transaction(&new_journal_entry("StakeDeposit", 0, None));
+        assert_eq!(metrics.transactions.stake_deposit, 4);
+        assert_eq!(metrics.fees_lamports.stake_deposit, 10_000);
+
+        // A failed transaction still paid the fee, but it performed nothing.
+        metrics.observe_transaction(&new_journal_entry(
+            "UpdateExchangeRate",
+            0,
+            Some("Transaction failed."),
+        ));
+        assert_eq!(metrics.transactions.update_exchange_rate, 0);
+        assert_eq!(metrics.fees_lamports.update_exchange_rate, 5000);
+
+        // Operations that we don't know are ignored.
+        metrics.observe_transaction(&new_journal_entry("Frobnicate", 0, None));
+        let totals: Vec<u64> = [&metrics.transactions, &metrics.fees_lamports]
+            .iter()
+            .map(|counters| {
+                counters
+                    .to_metrics()
+                    .iter()
+                    .map(|metric| match metric.value {
+                        MetricValue::Int(n) => n,
+                        _ => panic!("Expected integer metrics."),
+                    })
+                    .sum::<u64>()
+            })
+            .collect();
+        assert_eq!(totals, vec![4, 15_000]);
+
+        assert_eq!(metrics.transactions_simulated.stake_deposit, 0);
+    }
 
     #[test]
     fn block_time_estimator_computes_block_time_from_two_or_more_observations() {
@@ -5,6 +5,7 @@
 
 use num_traits::cast::FromPrimitive;
 use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::pubsub_client::PubsubClientError;
 use solana_client::rpc_request::{RpcError, RpcResponseErrorData};
 use solana_program::instruction::InstructionError;
 use solana_program::program_error::ProgramError;
@@ -410,6 +411,13 @@ impl AsPrettyError for serde_json::Error {
     }
 }
 
+impl AsPrettyError for PubsubClientError {
+    fn print_pretty(&self) {
+        print_red("Solana websocket client error:");
+        println!(" {}", self);
+    }
+}
+
 impl AsPrettyError for PubkeyError {
     fn print_pretty(&self) {
         print_red("Solana public key error:");
@@ -4,9 +4,10 @@
 use std::{fmt, path::PathBuf};
 
 use serde::Serialize;
-use solana_program::{pubkey::Pubkey, system_instruction};
+use solana_program::{instruction::Instruction, pubkey::Pubkey, system_instruction};
 use solana_sdk::{
     account::ReadableAccount,
+    borsh::try_from_slice_unchecked,
     signature::{Keypair, Signer},
 };
 
@@ -14,17 +15,26 @@ use lido::{
     balance::get_validator_to_withdraw,
     find_authority_program_address,
     metrics::LamportsHistogram,
+    oracle::ExchangeRateOracle,
     processor::StakeType,
-    state::{Lido, RewardDistribution},
+    state::{
+        LegacyValidators, Lido, LidoV1, RewardDistribution, Validators, ValidatorsV4, ValidatorsV8,
+    },
     token::{Lamports, StLamports},
+    token_metadata::{find_metadata_address, TokenMetadata, METADATA_ACCOUNT_SIZE},
     util::serialize_b58,
     MINT_AUTHORITY, RESERVE_ACCOUNT, REWARDS_WITHDRAW_AUTHORITY, STAKE_AUTHORITY,
 };
 
 use crate::{
     config::{
-        AddRemoveMaintainerOpts, AddValidatorOpts, CreateSolidoOpts, DeactivateValidatorOpts,
-        DepositOpts, ShowSolidoAuthoritiesOpts, ShowSolidoOpts, WithdrawOpts,
+        AddRemoveMaintainerOpts, AddValidatorOpts, ChangeDepositCutoffOpts,
+        ChangeFeePayoutModeOpts, ClaimMaintainerFeeOpts, ClaimValidatorFeesOpts,
+        CreateExchangeRateOracleOpts, CreateSolidoOpts, DeactivateValidatorOpts, DepositOpts,
+        ForceRemoveValidatorOpts, MigrateValidatorListOpts, SetTokenMetadataOpts,
+        ShowSolidoAuthoritiesOpts, ShowSolidoOpts, SortAccountMapsOpts, UpgradeFeePayoutModeOpts,
+        UpgradeMaintainersOpts, UpgradeMetricsOpts, UpgradeValidatorEntriesOpts,
+        UpgradeValidatorListOpts, WithdrawOpts,
     },
     error::CliError,
     get_signer_from_path,
@@ -43,6 +53,10 @@ pub struct CreateSolidoOutput {
     #[serde(serialize_with = "serialize_b58")]
     pub solido_address: Pubkey,
 
+    /// Account that stores the list of validators for this Solido instance.
+    #[serde(serialize_with = "serialize_b58")]
+    pub validator_list: Pubkey,
+
     /// Manages the deposited sol.
     #[serde(serialize_with = "serialize_b58")]
     pub reserve_account: Pubkey,
@@ -62,6 +76,9 @@ pub struct CreateSolidoOutput {
     /// Authority for the minting.
     #[serde(serialize_with = "serialize_b58")]
     pub mint_authority: Pubkey,
+
+    /// Multisig transaction that creates the stSOL token metadata, if requested.
+    pub token_metadata_proposal: Option<ProposeInstructionOutput>,
 }
 
 impl fmt::Display for CreateSolidoOutput {
@@ -72,6 +89,11 @@ impl fmt::Display for CreateSolidoOutput {
             "  Solido address:                {}",
             self.solido_address
         )?;
+        writeln!(
+            f,
+            "  Validator list:                {}",
+            self.validator_list
+        )?;
         writeln!(
             f,
             "  Reserve account:               {}",
@@ -97,6 +119,13 @@ impl fmt::Display for CreateSolidoOutput {
             "  Developer fee SPL token account: {}",
             self.developer_account
         )?;
+        if let Some(proposal) = &self.token_metadata_proposal {
+            writeln!(
+                f,
+                "  Token metadata transaction:    {}",
+                proposal.transaction_address
+            )?;
+        }
         Ok(())
     }
 }
@@ -130,11 +159,34 @@ pub fn command_create_solido(
     let (manager, _nonce) =
         get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
 
-    let lido_size = Lido::calculate_size(*opts.max_validators(), *opts.max_maintainers());
+    let lido_size = Lido::calculate_size(*opts.max_maintainers());
     let lido_account_balance = config
         .client
         .get_minimum_balance_for_rent_exemption(lido_size)?;
 
+    let validator_list_signer = Keypair::new();
+    let validator_list_size = Validators::calculate_size(*opts.max_validators());
+    let validator_list_balance = config
+        .client
+        .get_minimum_balance_for_rent_exemption(validator_list_size)?;
+
+    // Proposing the token metadata reads from the snapshot, so we have to do
+    // those reads before we send the first transaction. The metadata account
+    // of a new mint does not exist yet, so the manager always needs the rent.
+    let token_metadata = if opts.token_name().is_empty()
+        && opts.token_symbol().is_empty()
+        && opts.token_uri().is_empty()
+    {
+        None
+    } else {
+        let token_metadata =
+            get_token_metadata(opts.token_name(), opts.token_symbol(), opts.token_uri())?;
+        let fund_manager = fund_manager_for_token_metadata(config, &manager)?;
+        // Also load the multisig, `propose_instruction` needs it.
+        config.client.get_account(opts.multisig_address())?;
+        Some((token_metadata, fund_manager))
+    };
+
     let mut instructions = Vec::new();
 
     // We need to fund Lido's reserve account so it is rent-exempt, otherwise it
@@ -195,6 +247,15 @@ pub fn command_create_solido(
         opts.solido_program_id(),
     ));
 
+    // And the account that holds the validator list.
+    instructions.push(system_instruction::create_account(
+        &config.signer.pubkey(),
+        &validator_list_signer.pubkey(),
+        validator_list_balance.0,
+        validator_list_size as u64,
+        opts.solido_program_id(),
+    ));
+
     instructions.push(lido::instruction::initialize(
         opts.solido_program_id(),
         RewardDistribution {
@@ -202,11 +263,13 @@ pub fn command_create_solido(
             validation_fee: *opts.validation_fee_share(),
             developer_fee: *opts.developer_fee_share(),
             st_sol_appreciation: *opts.st_sol_appreciation_share(),
+            maintainer_fee: *opts.maintainer_fee_share(),
         },
         *opts.max_validators(),
         *opts.max_maintainers(),
         &lido::instruction::InitializeAccountsMeta {
             lido: lido_signer.pubkey(),
+            validator_list: validator_list_signer.pubkey(),
             st_sol_mint: st_sol_mint_pubkey,
             manager,
             treasury_account: treasury_keypair.pubkey(),
@@ -215,20 +278,156 @@ pub fn command_create_solido(
         },
     ));
 
-    config.sign_and_send_transaction(&instructions[..], &[config.signer, &*lido_signer])?;
+    config.sign_and_send_transaction(
+        &instructions[..],
+        &[config.signer, &*lido_signer, &validator_list_signer],
+    )?;
     eprintln!("Did send Lido init.");
 
+    let token_metadata_proposal = match token_metadata {
+        None => None,
+        Some((token_metadata, fund_manager)) => {
+            let instruction = set_token_metadata_instruction(
+                opts.solido_program_id(),
+                &lido_signer.pubkey(),
+                &st_sol_mint_pubkey,
+                &manager,
+                token_metadata,
+            );
+            let proposal = propose_instruction(
+                config,
+                opts.multisig_program_id(),
+                *opts.multisig_address(),
+                instruction,
+            )?;
+            if let Some(fund_manager) = fund_manager {
+                config.sign_and_send_transaction(&[fund_manager], &[config.signer])?;
+            }
+            eprintln!("Did propose token metadata.");
+            Some(proposal)
+        }
+    };
+
     let result = CreateSolidoOutput {
         solido_address: lido_signer.pubkey(),
+        validator_list: validator_list_signer.pubkey(),
         reserve_account,
         mint_authority,
         st_sol_mint_address: st_sol_mint_pubkey,
         treasury_account: treasury_keypair.pubkey(),
         developer_account: developer_keypair.pubkey(),
+        token_metadata_proposal,
     };
     Ok(result)
 }
 
+/// Build the token metadata, and check that the metadata program accepts it.
+fn get_token_metadata(name: &str, symbol: &str, uri: &str) -> Result<TokenMetadata> {
+    let token_metadata = TokenMetadata {
+        name: name.to_string(),
+        symbol: symbol.to_string(),
+        uri: uri.to_string(),
+    };
+    if !token_metadata.is_valid() {
+        return Err(CliError::new(
+            "The token name, symbol, or URI is too long for the token metadata program.",
+        )
+        .into());
+    }
+    Ok(token_metadata)
+}
+
+/// Return a transfer that funds the manager with the rent of a new token
+/// metadata account, if the manager does not hold enough for it yet.
+///
+/// When the multisig executes `SetTokenMetadata`, the manager is the only
+/// signer, so the manager pays for the metadata account if it does not exist.
+fn fund_manager_for_token_metadata(
+    config: &mut SnapshotConfig,
+    manager: &Pubkey,
+) -> Result<Option<Instruction>> {
+    let rent = config
+        .client
+        .get_minimum_balance_for_rent_exemption(METADATA_ACCOUNT_SIZE)?;
+    let manager_balance = if config.client.account_exists(manager)? {
+        Lamports(config.client.get_account(manager)?.lamports)
+    } else {
+        Lamports(0)
+    };
+    if manager_balance >= rent {
+        return Ok(None);
+    }
+    let amount = (rent - manager_balance).expect("Does not underflow, balance is less than rent.");
+    Ok(Some(system_instruction::transfer(
+        &config.signer.pubkey(),
+        manager,
+        amount.0,
+    )))
+}
+
+fn set_token_metadata_instruction(
+    solido_program_id: &Pubkey,
+    solido_address: &Pubkey,
+    st_sol_mint: &Pubkey,
+    manager: &Pubkey,
+    token_metadata: TokenMetadata,
+) -> Instruction {
+    let (mint_authority, _) =
+        find_authority_program_address(solido_program_id, solido_address, MINT_AUTHORITY);
+    let (token_metadata_address, _) = find_metadata_address(st_sol_mint);
+    lido::instruction::set_token_metadata(
+        solido_program_id,
+        token_metadata,
+        &lido::instruction::SetTokenMetadataMeta {
+            lido: *solido_address,
+            manager: *manager,
+            st_sol_mint: *st_sol_mint,
+            mint_authority,
+            token_metadata: token_metadata_address,
+            // The manager is the only signer when the multisig executes the
+            // instruction, so it pays for the metadata account too.
+            payer: *manager,
+        },
+    )
+}
+
+/// CLI entry point to create or update the stSOL token metadata.
+pub fn command_set_token_metadata(
+    config: &mut SnapshotConfig,
+    opts: &SetTokenMetadataOpts,
+) -> Result<ProposeInstructionOutput> {
+    let (manager, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+    let solido = config.client.get_solido(opts.solido_address())?;
+    let token_metadata =
+        get_token_metadata(opts.token_name(), opts.token_symbol(), opts.token_uri())?;
+
+    let (token_metadata_address, _) = find_metadata_address(&solido.st_sol_mint);
+    let fund_manager = if config.client.account_exists(&token_metadata_address)? {
+        None
+    } else {
+        fund_manager_for_token_metadata(config, &manager)?
+    };
+
+    let instruction = set_token_metadata_instruction(
+        opts.solido_program_id(),
+        opts.solido_address(),
+        &solido.st_sol_mint,
+        &manager,
+        token_metadata,
+    );
+    let result = propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )?;
+    if let Some(fund_manager) = fund_manager {
+        config.sign_and_send_transaction(&[fund_manager], &[config.signer])?;
+    }
+    Ok(result)
+}
+
 /// CLI entry point to add a validator to Solido.
 pub fn command_add_validator(
     config: &mut SnapshotConfig,
@@ -236,14 +435,18 @@ pub fn command_add_validator(
 ) -> Result<ProposeInstructionOutput> {
     let (multisig_address, _) =
         get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+    let solido = config.client.get_solido(opts.solido_address())?;
 
     let instruction = lido::instruction::add_validator(
         opts.solido_program_id(),
         &lido::instruction::AddValidatorMeta {
             lido: *opts.solido_address(),
+            validator_list: solido.validator_list,
             manager: multisig_address,
             validator_vote_account: *opts.validator_vote_account(),
             validator_fee_st_sol_account: *opts.validator_fee_account(),
+            validator_vote_withdrawer: *opts.validator_vote_withdrawer(),
+            validator_authority: *opts.validator_authority(),
         },
     );
     propose_instruction(
@@ -261,11 +464,13 @@ pub fn command_deactivate_validator(
 ) -> Result<ProposeInstructionOutput> {
     let (multisig_address, _) =
         get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+    let solido = config.client.get_solido(opts.solido_address())?;
 
     let instruction = lido::instruction::deactivate_validator(
         opts.solido_program_id(),
         &lido::instruction::DeactivateValidatorMeta {
             lido: *opts.solido_address(),
+            validator_list: solido.validator_list,
             manager: multisig_address,
             validator_vote_account_to_deactivate: *opts.validator_vote_account(),
         },
@@ -278,6 +483,476 @@ pub fn command_deactivate_validator(
     )
 }
 
+/// CLI entry point to deactivate a validator, deactivate all of its stake, and
+/// pay out its fee credit in one go.
+///
+/// The proposal lists the stake accounts that the validator has at the time of
+/// proposing. If the maintainer changes them before the proposal is executed,
+/// the transaction fails, and the removal needs to be proposed again.
+pub fn command_force_remove_validator(
+    config: &mut SnapshotConfig,
+    opts: &ForceRemoveValidatorOpts,
+) -> Result<ProposeInstructionOutput> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+    let solido = config.client.get_solido(opts.solido_address())?;
+    let validators = config.client.get_validators(&solido.validator_list)?;
+    let validator = validators
+        .get(opts.validator_vote_account())
+        .map_err(|err| CliError::with_cause("Validator is not part of this instance.", err))?;
+
+    let stake_accounts = validator
+        .entry
+        .stake_seeds
+        .into_iter()
+        .map(|seed| {
+            validator
+                .find_stake_account_address(
+                    opts.solido_program_id(),
+                    opts.solido_address(),
+                    seed,
+                    StakeType::Stake,
+                )
+                .0
+        })
+        .collect();
+    let (mint_authority, _) = find_authority_program_address(
+        opts.solido_program_id(),
+        opts.solido_address(),
+        MINT_AUTHORITY,
+    );
+    let (stake_authority, _) = find_authority_program_address(
+        opts.solido_program_id(),
+        opts.solido_address(),
+        STAKE_AUTHORITY,
+    );
+
+    let instruction = lido::instruction::force_remove_validator(
+        opts.solido_program_id(),
+        &lido::instruction::ForceRemoveValidatorMeta {
+            lido: *opts.solido_address(),
+            manager: multisig_address,
+            validator_list: solido.validator_list,
+            validator_vote_account_to_remove: *opts.validator_vote_account(),
+            st_sol_mint: solido.st_sol_mint,
+            mint_authority,
+            validator_fee_st_sol_account: validator.entry.fee_address,
+            stake_authority,
+            stake_accounts,
+        },
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+/// CLI entry point to move the validators of an instance into a validator list account.
+pub fn command_migrate_validator_list(
+    config: &mut SnapshotConfig,
+    opts: &MigrateValidatorListOpts,
+) -> Result<ProposeInstructionOutput> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    // The instance still uses the old layout, so we can't use `get_solido` here.
+    let solido_account = config.client.get_account(opts.solido_address())?;
+    let solido_v1 = match try_from_slice_unchecked::<LidoV1>(&solido_account.data) {
+        Ok(solido) if solido.lido_version == 1 => solido,
+        Ok(solido) => {
+            return Err(CliError::with_cause(
+                "This Solido instance does not need to be migrated.",
+                format!("Found version {}, expected version 1.", solido.lido_version),
+            )
+            .into())
+        }
+        Err(err) => {
+            return Err(CliError::with_cause("Failed to deserialize Solido instance.", err).into())
+        }
+    };
+
+    let validator_list_signer = Keypair::new();
+    let validator_list_size = Validators::calculate_size(solido_v1.validators.maximum_entries);
+    let validator_list_balance = config
+        .client
+        .get_minimum_balance_for_rent_exemption(validator_list_size)?;
+    let create_instruction = system_instruction::create_account(
+        &config.signer.pubkey(),
+        &validator_list_signer.pubkey(),
+        validator_list_balance.0,
+        validator_list_size as u64,
+        opts.solido_program_id(),
+    );
+    config.sign_and_send_transaction(
+        &[create_instruction],
+        &[config.signer, &validator_list_signer],
+    )?;
+    eprintln!(
+        "Created validator list account {}.",
+        validator_list_signer.pubkey()
+    );
+
+    let instruction = lido::instruction::migrate_validator_list(
+        opts.solido_program_id(),
+        &lido::instruction::MigrateValidatorListMeta {
+            lido: *opts.solido_address(),
+            validator_list: validator_list_signer.pubkey(),
+            manager: multisig_address,
+        },
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+/// CLI entry point to sort the validators and maintainers of an instance.
+pub fn command_sort_account_maps(
+    config: &mut SnapshotConfig,
+    opts: &SortAccountMapsOpts,
+) -> Result<ProposeInstructionOutput> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    let solido = config.client.get_legacy_solido(opts.solido_address())?;
+    if solido.lido_version != 2 {
+        return Err(CliError::with_cause(
+            "This Solido instance cannot be sorted.",
+            format!("Found version {}, expected version 2.", solido.lido_version),
+        )
+        .into());
+    }
+
+    let instruction = lido::instruction::sort_account_maps(
+        opts.solido_program_id(),
+        &lido::instruction::SortAccountMapsMeta {
+            lido: *opts.solido_address(),
+            validator_list: solido.validator_list,
+            manager: multisig_address,
+        },
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+/// CLI entry point to move the validators of an instance into a new validator
+/// list account that has room for per-validator metrics.
+pub fn command_upgrade_validator_list(
+    config: &mut SnapshotConfig,
+    opts: &UpgradeValidatorListOpts,
+) -> Result<ProposeInstructionOutput> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    let solido = config.client.get_legacy_solido(opts.solido_address())?;
+    if solido.lido_version != 2 && solido.lido_version != 3 {
+        return Err(CliError::with_cause(
+            "This Solido instance cannot be upgraded.",
+            format!(
+                "Found version {}, expected version 2 or 3.",
+                solido.lido_version
+            ),
+        )
+        .into());
+    }
+
+    // The validator list still uses the old layout, so we can't use
+    // `get_validators` here.
+    let validator_list_account = config.client.get_account(&solido.validator_list)?;
+    let legacy_validators =
+        match try_from_slice_unchecked::<LegacyValidators>(&validator_list_account.data) {
+            Ok(validators) => validators,
+            Err(err) => {
+                return Err(
+                    CliError::with_cause("Failed to deserialize validator list.", err).into(),
+                )
+            }
+        };
+
+    let validator_list_signer = Keypair::new();
+    let validator_list_size = ValidatorsV4::calculate_size(legacy_validators.maximum_entries);
+    let validator_list_balance = config
+        .client
+        .get_minimum_balance_for_rent_exemption(validator_list_size)?;
+    let create_instruction = system_instruction::create_account(
+        &config.signer.pubkey(),
+        &validator_list_signer.pubkey(),
+        validator_list_balance.0,
+        validator_list_size as u64,
+        opts.solido_program_id(),
+    );
+    config.sign_and_send_transaction(
+        &[create_instruction],
+        &[config.signer, &validator_list_signer],
+    )?;
+    eprintln!(
+        "Created validator list account {}.",
+        validator_list_signer.pubkey()
+    );
+
+    let instruction = lido::instruction::upgrade_validator_list(
+        opts.solido_program_id(),
+        &lido::instruction::UpgradeValidatorListMeta {
+            lido: *opts.solido_address(),
+            validator_list: solido.validator_list,
+            new_validator_list: validator_list_signer.pubkey(),
+            manager: multisig_address,
+        },
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+/// CLI entry point to convert an instance to the `Lido` layout that tracks
+/// withdrawal sizes and per-epoch flows.
+pub fn command_upgrade_metrics(
+    config: &mut SnapshotConfig,
+    opts: &UpgradeMetricsOpts,
+) -> Result<ProposeInstructionOutput> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    let solido = config.client.get_legacy_solido(opts.solido_address())?;
+    if solido.lido_version < 4 || solido.lido_version > 6 {
+        return Err(CliError::with_cause(
+            "This Solido instance cannot be upgraded.",
+            format!(
+                "Found version {}, expected version 4, 5, or 6. Run upgrade-validator-list \
+                first for versions 2 and 3.",
+                solido.lido_version
+            ),
+        )
+        .into());
+    }
+
+    let instruction = lido::instruction::upgrade_metrics(
+        opts.solido_program_id(),
+        &lido::instruction::UpgradeMetricsMeta {
+            lido: *opts.solido_address(),
+            manager: multisig_address,
+        },
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+/// CLI entry point to move the validators of an instance into a new validator
+/// list account that has room for tombstones and vote withdrawers.
+pub fn command_upgrade_validator_entries(
+    config: &mut SnapshotConfig,
+    opts: &UpgradeValidatorEntriesOpts,
+) -> Result<ProposeInstructionOutput> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    // Versions 7 and 8 have the current `Lido` header, only the validator list
+    // and the maintainers are old.
+    let solido = config.client.get_solido_header(opts.solido_address())?;
+    if solido.lido_version != 7 && solido.lido_version != 8 {
+        return Err(CliError::with_cause(
+            "This Solido instance cannot be upgraded.",
+            format!(
+                "Found version {}, expected version 7 or 8. Run the earlier upgrades \
+                first for versions 0 through 6.",
+                solido.lido_version
+            ),
+        )
+        .into());
+    }
+
+    let validator_list_account = config.client.get_account(&solido.validator_list)?;
+    let max_validators = if solido.lido_version == 7 {
+        try_from_slice_unchecked::<ValidatorsV4>(&validator_list_account.data)
+            .map(|validators| validators.maximum_entries)
+    } else {
+        try_from_slice_unchecked::<ValidatorsV8>(&validator_list_account.data)
+            .map(|validators| validators.maximum_entries)
+    };
+    let max_validators = match max_validators {
+        Ok(max_validators) => max_validators,
+        Err(err) => {
+            return Err(CliError::with_cause("Failed to deserialize validator list.", err).into())
+        }
+    };
+
+    let validator_list_signer = Keypair::new();
+    let validator_list_size = Validators::calculate_size(max_validators);
+    let validator_list_balance = config
+        .client
+        .get_minimum_balance_for_rent_exemption(validator_list_size)?;
+    let create_instruction = system_instruction::create_account(
+        &config.signer.pubkey(),
+        &validator_list_signer.pubkey(),
+        validator_list_balance.0,
+        validator_list_size as u64,
+        opts.solido_program_id(),
+    );
+    config.sign_and_send_transaction(
+        &[create_instruction],
+        &[config.signer, &validator_list_signer],
+    )?;
+    eprintln!(
+        "Created validator list account {}.",
+        validator_list_signer.pubkey()
+    );
+
+    let instruction = lido::instruction::upgrade_validator_entries(
+        opts.solido_program_id(),
+        &lido::instruction::UpgradeValidatorEntriesMeta {
+            lido: *opts.solido_address(),
+            validator_list: solido.validator_list,
+            new_validator_list: validator_list_signer.pubkey(),
+            manager: multisig_address,
+        },
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+/// CLI entry point to convert the maintainers of an instance to the layout
+/// that records heartbeats.
+pub fn command_upgrade_maintainers(
+    config: &mut SnapshotConfig,
+    opts: &UpgradeMaintainersOpts,
+) -> Result<ProposeInstructionOutput> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    // Version 9 has the current header, but not the current maintainer layout,
+    // so we read only the header here.
+    let solido = config.client.get_solido_header(opts.solido_address())?;
+    if solido.lido_version != 9 && solido.lido_version != 10 {
+        return Err(CliError::with_cause(
+            "This Solido instance cannot be upgraded.",
+            format!(
+                "Found version {}, expected version 9 or 10. Run the earlier upgrades \
+                first for versions 0 through 8.",
+                solido.lido_version
+            ),
+        )
+        .into());
+    }
+
+    let instruction = lido::instruction::upgrade_maintainers(
+        opts.solido_program_id(),
+        &lido::instruction::UpgradeMaintainersMeta {
+            lido: *opts.solido_address(),
+            manager: multisig_address,
+        },
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+/// CLI entry point to change how validation fees are paid out.
+pub fn command_change_fee_payout_mode(
+    config: &mut SnapshotConfig,
+    opts: &ChangeFeePayoutModeOpts,
+) -> Result<ProposeInstructionOutput> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    let instruction = lido::instruction::change_fee_payout_mode(
+        opts.solido_program_id(),
+        *opts.fee_payout_mode(),
+        &lido::instruction::ChangeFeePayoutModeMeta {
+            lido: *opts.solido_address(),
+            manager: multisig_address,
+        },
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+/// CLI entry point to upgrade an instance to the layout with a fee payout mode.
+pub fn command_upgrade_fee_payout_mode(
+    config: &mut SnapshotConfig,
+    opts: &UpgradeFeePayoutModeOpts,
+) -> Result<ProposeInstructionOutput> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    // The instance still uses the old layout, so we can't use `get_solido`
+    // here. The version is the first byte of the account.
+    let solido_account = config.client.get_account(opts.solido_address())?;
+    match solido_account.data.first() {
+        Some(0) => {}
+        version => {
+            return Err(CliError::with_cause(
+                "This Solido instance does not need to be upgraded.",
+                format!("Found version {:?}, expected version 0.", version),
+            )
+            .into())
+        }
+    }
+
+    let instruction = lido::instruction::upgrade_fee_payout_mode(
+        opts.solido_program_id(),
+        &lido::instruction::UpgradeFeePayoutModeMeta {
+            lido: *opts.solido_address(),
+            manager: multisig_address,
+        },
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+/// CLI entry point to change the deposit cutoff window near the epoch end.
+pub fn command_change_deposit_cutoff(
+    config: &mut SnapshotConfig,
+    opts: &ChangeDepositCutoffOpts,
+) -> Result<ProposeInstructionOutput> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    let instruction = lido::instruction::change_deposit_cutoff(
+        opts.solido_program_id(),
+        *opts.deposit_cutoff_slots(),
+        &lido::instruction::ChangeDepositCutoffMeta {
+            lido: *opts.solido_address(),
+            manager: multisig_address,
+        },
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
 /// CLI entry point to to add a maintainer to Solido.
 pub fn command_add_maintainer(
     config: &mut SnapshotConfig,
@@ -328,6 +1003,8 @@ pub fn command_remove_maintainer(
 pub struct ShowSolidoOutput {
     pub solido: Lido,
 
+    pub validators: Validators,
+
     #[serde(serialize_with = "serialize_b58")]
     pub solido_program_id: Pubkey,
 
@@ -346,10 +1023,10 @@ pub struct ShowSolidoOutput {
     #[serde(serialize_with = "serialize_b58")]
     pub rewards_withdraw_authority: Pubkey,
 
-    /// Identity account address for all validators in the same order as `solido.validators`.
+    /// Identity account address for all validators in the same order as `validators`.
     pub validator_identities: Vec<Pubkey>,
 
-    /// Contains validator info in the same order as `solido.validators`.
+    /// Contains validator info in the same order as `validators`.
     pub validator_infos: Vec<ValidatorInfo>,
 }
 
@@ -414,6 +1091,7 @@ impl fmt::Display for ShowSolidoOutput {
         print_reward("Treasury", |d| d.treasury_fee)?;
         print_reward("Validation fee", |d| d.validation_fee)?;
         print_reward("Developer fee", |d| d.developer_fee)?;
+        print_reward("Maintainer fee", |d| d.maintainer_fee)?;
 
         writeln!(f, "\nFee recipients:")?;
         writeln!(
@@ -426,6 +1104,21 @@ impl fmt::Display for ShowSolidoOutput {
             "Developer fee SPL token account: {}",
             self.solido.fee_recipients.developer_account
         )?;
+        writeln!(
+            f,
+            "Validator fee payout mode:       {}",
+            self.solido.fee_payout_mode
+        )?;
+
+        if self.solido.deposit_cutoff_slots == 0 {
+            writeln!(f, "\nDeposit cutoff: disabled")?;
+        } else {
+            writeln!(
+                f,
+                "\nDeposit cutoff: deposits refused in the last {} slots of every epoch",
+                self.solido.deposit_cutoff_slots
+            )?;
+        }
 
         writeln!(f, "\nMetrics:")?;
         writeln!(
@@ -482,15 +1175,39 @@ impl fmt::Display for ShowSolidoOutput {
                 count
             )?;
         }
+        for (count, upper_bound) in self
+            .solido
+            .metrics
+            .withdraw_amount_histogram
+            .counts
+            .iter()
+            .zip(&LamportsHistogram::BUCKET_UPPER_BOUNDS)
+        {
+            writeln!(
+                f,
+                "  Number of withdrawals ≤ {:>22}: {}",
+                format!("{}", upper_bound),
+                count
+            )?;
+        }
+        for flow in &[
+            &self.solido.metrics.flow_previous_epoch,
+            &self.solido.metrics.flow_current_epoch,
+        ] {
+            writeln!(
+                f,
+                "  Epoch {:>6}: deposited {}, withdrawn {}",
+                flow.epoch, flow.deposit_total, flow.withdraw_total,
+            )?;
+        }
 
         writeln!(
             f,
             "\nValidators: {} in use out of {} that the instance can support",
-            self.solido.validators.len(),
-            self.solido.validators.maximum_entries
+            self.validators.len(),
+            self.validators.maximum_entries
         )?;
         for ((pe, identity), info) in self
-            .solido
             .validators
             .entries
             .iter()
@@ -525,6 +1242,24 @@ impl fmt::Display for ShowSolidoOutput {
                 pe.entry.unstake_accounts_balance,
             )?;
 
+            if pe.entry.tombstone {
+                writeln!(f, "    Being removed with ForceRemoveValidator:")?;
+                writeln!(
+                    f,
+                    "      Deactivating stake accounts left: {}",
+                    pe.entry.stake_seeds.end - pe.entry.stake_seeds.begin
+                )?;
+                writeln!(
+                    f,
+                    "      Unstake accounts left:            {}",
+                    pe.entry.unstake_seeds.end - pe.entry.unstake_seeds.begin
+                )?;
+                match pe.entry.check_can_be_removed() {
+                    Ok(()) => writeln!(f, "      Ready for removal:                yes")?,
+                    Err(err) => writeln!(f, "      Ready for removal:                no, {}", err)?,
+                }
+            }
+
             writeln!(f, "    Stake accounts (seed, address):")?;
             if pe.entry.stake_seeds.begin == pe.entry.stake_seeds.end {
                 writeln!(f, "      This validator has no stake accounts.")?;
@@ -571,6 +1306,21 @@ impl fmt::Display for ShowSolidoOutput {
         )?;
         for pe in &self.solido.maintainers.entries {
             writeln!(f, "  - {}", pe.pubkey)?;
+            writeln!(f, "    Unclaimed maintainer fee: {}", pe.entry.fee_credit)?;
+            writeln!(
+                f,
+                "    Operations since the last distribution: {}",
+                pe.entry.operations
+            )?;
+            if pe.entry.last_heartbeat_slot == 0 {
+                writeln!(f, "    Last heartbeat:                         never")?;
+            } else {
+                writeln!(
+                    f,
+                    "    Last heartbeat:                         slot {}",
+                    pe.entry.last_heartbeat_slot
+                )?;
+            }
         }
         Ok(())
     }
@@ -581,6 +1331,7 @@ pub fn command_show_solido(
     opts: &ShowSolidoOpts,
 ) -> Result<ShowSolidoOutput> {
     let lido = config.client.get_solido(opts.solido_address())?;
+    let validators = config.client.get_validators(&lido.validator_list)?;
     let reserve_account =
         lido.get_reserve_account(opts.solido_program_id(), opts.solido_address())?;
     let stake_authority =
@@ -592,7 +1343,7 @@ pub fn command_show_solido(
 
     let mut validator_identities = Vec::new();
     let mut validator_infos = Vec::new();
-    for validator in lido.validators.entries.iter() {
+    for validator in validators.entries.iter() {
         let vote_state = config.client.get_vote_account(&validator.pubkey)?;
         validator_identities.push(vote_state.node_pubkey);
         let info = config.client.get_validator_info(&vote_state.node_pubkey)?;
@@ -603,6 +1354,7 @@ pub fn command_show_solido(
         solido_program_id: *opts.solido_program_id(),
         solido_address: *opts.solido_address(),
         solido: lido,
+        validators,
         validator_identities,
         validator_infos,
         reserve_account,
@@ -631,6 +1383,9 @@ pub struct ShowSolidoAuthorities {
 
     #[serde(serialize_with = "serialize_b58")]
     pub rewards_withdraw_authority: Pubkey,
+
+    #[serde(serialize_with = "serialize_b58")]
+    pub exchange_rate_oracle: Pubkey,
 }
 
 impl fmt::Display for ShowSolidoAuthorities {
@@ -643,6 +1398,11 @@ impl fmt::Display for ShowSolidoAuthorities {
             self.rewards_withdraw_authority,
         )?;
         writeln!(f, "Reserve account:            {}", self.reserve_account)?;
+        writeln!(
+            f,
+            "Exchange rate oracle:       {}",
+            self.exchange_rate_oracle
+        )?;
         Ok(())
     }
 }
@@ -670,6 +1430,8 @@ pub fn command_show_solido_authorities(
         opts.solido_address(),
         REWARDS_WITHDRAW_AUTHORITY,
     );
+    let (exchange_rate_oracle, _) =
+        ExchangeRateOracle::find_address(opts.solido_program_id(), opts.solido_address());
     Ok(ShowSolidoAuthorities {
         solido_program_id: *opts.solido_program_id(),
         solido_address: *opts.solido_address(),
@@ -677,6 +1439,182 @@ pub fn command_show_solido_authorities(
         stake_authority,
         mint_authority,
         rewards_withdraw_authority,
+        exchange_rate_oracle,
+    })
+}
+
+#[derive(Serialize)]
+pub struct CreateExchangeRateOracleOutput {
+    #[serde(serialize_with = "serialize_b58")]
+    pub exchange_rate_oracle: Pubkey,
+}
+
+impl fmt::Display for CreateExchangeRateOracleOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Exchange rate oracle: {}", self.exchange_rate_oracle)?;
+        Ok(())
+    }
+}
+
+pub fn command_create_exchange_rate_oracle(
+    config: &mut SnapshotConfig,
+    opts: &CreateExchangeRateOracleOpts,
+) -> Result<CreateExchangeRateOracleOutput> {
+    let (exchange_rate_oracle, _) =
+        ExchangeRateOracle::find_address(opts.solido_program_id(), opts.solido_address());
+
+    if config.client.account_exists(&exchange_rate_oracle)? {
+        let oracle_account = config.client.get_account(&exchange_rate_oracle)?;
+        if !oracle_account.data.is_empty() {
+            return Err(CliError::new("The exchange rate oracle exists already.").into());
+        }
+    }
+
+    let instruction = lido::instruction::initialize_exchange_rate_oracle(
+        opts.solido_program_id(),
+        &lido::instruction::InitializeExchangeRateOracleMeta {
+            lido: *opts.solido_address(),
+            exchange_rate_oracle,
+            funder: config.signer.pubkey(),
+        },
+    );
+    config.sign_and_send_transaction(&[instruction], &[config.signer])?;
+
+    Ok(CreateExchangeRateOracleOutput {
+        exchange_rate_oracle,
+    })
+}
+
+#[derive(Serialize)]
+pub struct ClaimMaintainerFeeOutput {
+    #[serde(serialize_with = "serialize_b58")]
+    pub recipient: Pubkey,
+
+    /// The maintainer fee credit that we claimed.
+    #[serde(rename = "claimed_st_lamports")]
+    pub claimed_st_sol: StLamports,
+
+    /// Whether we had to create the associated stSOL account. False if one existed already.
+    pub created_associated_st_sol_account: bool,
+}
+
+impl fmt::Display for ClaimMaintainerFeeOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.created_associated_st_sol_account {
+            writeln!(f, "Created recipient stSOL account, it did not yet exist.")?;
+        }
+        writeln!(f, "Recipient stSOL account: {}", self.recipient)?;
+        writeln!(f, "Claimed maintainer fee:  {}", self.claimed_st_sol)?;
+        Ok(())
+    }
+}
+
+/// Claim the maintainer fee of the signer into its associated stSOL account.
+pub fn command_claim_maintainer_fee(
+    config: &mut SnapshotConfig,
+    opts: &ClaimMaintainerFeeOpts,
+) -> Result<ClaimMaintainerFeeOutput> {
+    let solido = config.client.get_solido(opts.solido_address())?;
+    let maintainer = config.signer.pubkey();
+    let claimed_st_sol = match solido.maintainers.get(&maintainer) {
+        Ok(pe) => pe.entry.fee_credit,
+        Err(_) => {
+            return Err(CliError::with_cause(
+                "The signer is not a maintainer of this Solido instance.",
+                format!("Signer: {}", maintainer),
+            )
+            .into())
+        }
+    };
+
+    let recipient = spl_associated_token_account::get_associated_token_address(
+        &maintainer,
+        &solido.st_sol_mint,
+    );
+    let mint_authority =
+        solido.get_mint_authority(opts.solido_program_id(), opts.solido_address())?;
+
+    let mut instructions = Vec::new();
+    let created_associated_st_sol_account = !config.client.account_exists(&recipient)?;
+    if created_associated_st_sol_account {
+        instructions.push(
+            spl_associated_token_account::create_associated_token_account(
+                &maintainer,
+                &maintainer,
+                &solido.st_sol_mint,
+            ),
+        );
+    }
+    instructions.push(lido::instruction::claim_maintainer_fee(
+        opts.solido_program_id(),
+        &lido::instruction::ClaimMaintainerFeeMeta {
+            lido: *opts.solido_address(),
+            maintainer,
+            st_sol_mint: solido.st_sol_mint,
+            mint_authority,
+            maintainer_st_sol_account: recipient,
+        },
+    ));
+    config.sign_and_send_transaction(&instructions, &[config.signer])?;
+
+    Ok(ClaimMaintainerFeeOutput {
+        recipient,
+        claimed_st_sol,
+        created_associated_st_sol_account,
+    })
+}
+
+#[derive(Serialize)]
+pub struct ClaimValidatorFeesOutput {
+    #[serde(serialize_with = "serialize_b58")]
+    pub recipient: Pubkey,
+
+    /// The validator fee credit that we claimed.
+    #[serde(rename = "claimed_st_lamports")]
+    pub claimed_st_sol: StLamports,
+}
+
+impl fmt::Display for ClaimValidatorFeesOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Recipient stSOL account: {}", self.recipient)?;
+        writeln!(f, "Claimed validator fee:   {}", self.claimed_st_sol)?;
+        Ok(())
+    }
+}
+
+/// Claim the fee credit of a validator into the given recipient, signed by the
+/// validator identity or the authorized voter of the vote account.
+pub fn command_claim_validator_fees(
+    config: &mut SnapshotConfig,
+    opts: &ClaimValidatorFeesOpts,
+) -> Result<ClaimValidatorFeesOutput> {
+    let solido = config.client.get_solido(opts.solido_address())?;
+    let validators = config.client.get_validators(&solido.validator_list)?;
+    let claimed_st_sol = validators
+        .get(opts.validator_vote_account())
+        .map_err(|err| CliError::with_cause("Validator is not part of this instance.", err))?
+        .entry
+        .fee_credit;
+    let mint_authority =
+        solido.get_mint_authority(opts.solido_program_id(), opts.solido_address())?;
+
+    let instruction = lido::instruction::claim_validator_fee_to(
+        opts.solido_program_id(),
+        &lido::instruction::ClaimValidatorFeeToMeta {
+            lido: *opts.solido_address(),
+            validator_list: solido.validator_list,
+            validator_vote_account: *opts.validator_vote_account(),
+            validator_authority: config.signer.pubkey(),
+            st_sol_mint: solido.st_sol_mint,
+            mint_authority,
+            recipient_st_sol_account: *opts.recipient(),
+        },
+    );
+    config.sign_and_send_transaction(&[instruction], &[config.signer])?;
+
+    Ok(ClaimValidatorFeesOutput {
+        recipient: *opts.recipient(),
+        claimed_st_sol,
     })
 }
 
@@ -843,7 +1781,8 @@ pub fn command_withdraw(
             solido.get_stake_authority(opts.solido_program_id(), opts.solido_address())?;
 
         // Get heaviest validator.
-        let heaviest_validator = get_validator_to_withdraw(&solido.validators).map_err(|err| {
+        let validators = config.client.get_validators(&solido.validator_list)?;
+        let heaviest_validator = get_validator_to_withdraw(&validators).map_err(|err| {
             CliError::with_cause(
                 "The instance has no active validators to withdraw from.",
                 err,
@@ -863,6 +1802,7 @@ pub fn command_withdraw(
             opts.solido_program_id(),
             &lido::instruction::WithdrawAccountsMeta {
                 lido: *opts.solido_address(),
+                validator_list: solido.validator_list,
                 st_sol_mint: solido.st_sol_mint,
                 st_sol_account_owner: config.signer.pubkey(),
                 st_sol_account: st_sol_address,
@@ -0,0 +1,267 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+//! Persistent journal of maintenance transactions.
+//!
+//! The maintenance daemon appends every maintenance transaction that it sends,
+//! whether it succeeded or failed, to a journal file. The journal is in json
+//! lines format: every line holds one [`JournalEntry`] as a json object. This
+//! makes it cheap to append to, and easy to inspect with standard tools. The
+//! `maintenance-log` command queries it.
+
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::clock::{Epoch, Slot};
+
+use crate::config::MaintenanceLogOpts;
+use crate::error::Error;
+use crate::maintenance::{SentMaintenance, SolidoState};
+
+/// A maintenance transaction that the daemon sent.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct JournalEntry {
+    /// Unix timestamp (in seconds) at which we recorded the entry.
+    pub timestamp: u64,
+
+    /// The Solido instance that the maintenance was for, in base58. Empty in
+    /// entries written before the daemon could maintain several instances.
+    #[serde(default)]
+    pub solido_address: String,
+
+    /// Slot and epoch of the state that the maintenance was based on.
+    pub slot: Slot,
+    pub epoch: Epoch,
+
+    /// Signature of the transaction, in base58.
+    pub signature: String,
+
+    /// The maintenance operation, e.g. `StakeDeposit`.
+    pub operation: String,
+
+    /// The `MaintenanceOutput` that describes the maintenance, as json.
+    pub output: serde_json::Value,
+
+    /// Other maintenance that was bundled into the same transaction, as json.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bundled: Vec<serde_json::Value>,
+
+    /// The fee that the transaction paid. Zero if it did not execute.
+    pub fee_lamports: u64,
+
+    /// The error, if the transaction failed.
+    pub error: Option<String>,
+}
+
+impl JournalEntry {
+    pub fn new(timestamp: u64, state: &SolidoState, sent: &SentMaintenance) -> JournalEntry {
+        let mut outputs = sent.outputs.iter().map(|output| {
+            serde_json::to_value(output)
+                .expect("Maintenance output can always be serialized as json.")
+        });
+        JournalEntry {
+            timestamp,
+            solido_address: state.solido_address.to_string(),
+            slot: state.clock.slot,
+            epoch: state.clock.epoch,
+            signature: sent.signature.to_string(),
+            operation: sent.outputs[0].operation().to_string(),
+            output: outputs
+                .next()
+                .expect("A maintenance transaction has at least one instruction."),
+            bundled: outputs.collect(),
+            fee_lamports: sent.fee.0,
+            error: sent.result.as_ref().err().map(|err| err.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for JournalEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} at {} (slot {}, epoch {})",
+            self.operation, self.timestamp, self.slot, self.epoch
+        )?;
+        if !self.solido_address.is_empty() {
+            writeln!(f, "  Instance:  {}", self.solido_address)?;
+        }
+        writeln!(f, "  Signature: {}", self.signature)?;
+        writeln!(f, "  Fee:       {} lamports", self.fee_lamports)?;
+        match self.error {
+            None => writeln!(f, "  Status:    ok")?,
+            Some(ref error) => writeln!(f, "  Status:    failed: {}", error)?,
+        }
+        writeln!(f, "  Output:    {}", self.output)?;
+        for output in &self.bundled {
+            writeln!(f, "  Bundled:   {}", output)?;
+        }
+        Ok(())
+    }
+}
+
+/// A journal file that we append entries to.
+pub struct Journal {
+    file: File,
+}
+
+impl Journal {
+    /// Open the journal at the given path for appending, create it if needed.
+    pub fn open(path: &Path) -> io::Result<Journal> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)?;
+
+        // If we crashed while writing the last entry, start on a new line, so
+        // the next entry does not get appended to the truncated one.
+        let len = file.metadata()?.len();
+        if len > 0 {
+            let mut last_byte = [0_u8];
+            file.seek(SeekFrom::Start(len - 1))?;
+            file.read_exact(&mut last_byte)?;
+            if last_byte != [b'\n'] {
+                file.write_all(b"\n")?;
+            }
+        }
+
+        Ok(Journal { file })
+    }
+
+    /// Append an entry to the journal, and flush it to disk.
+    pub fn append(&mut self, entry: &JournalEntry) -> io::Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        // Write the line with a single call, so a crash can at worst leave a
+        // truncated last line, which `read_journal` skips.
+        self.file.write_all(&line)?;
+        self.file.sync_data()
+    }
+}
+
+/// Read all entries from the journal at the given path, oldest first.
+///
+/// Returns no entries if the journal does not exist yet. Lines that do not hold
+/// a valid entry, such as a last line that was truncated by a crash, are skipped
+/// with a warning.
+pub fn read_journal(path: &Path) -> io::Result<Vec<JournalEntry>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+
+    let mut entries = Vec::new();
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(entry) => entries.push(entry),
+            Err(err) => eprintln!(
+                "Warning: Skipping invalid entry on line {} of {}: {}",
+                i + 1,
+                path.display(),
+                err
+            ),
+        }
+    }
+    Ok(entries)
+}
+
+#[derive(Serialize)]
+pub struct MaintenanceLogOutput {
+    entries: Vec<JournalEntry>,
+}
+
+impl fmt::Display for MaintenanceLogOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.entries.is_empty() {
+            return writeln!(f, "No maintenance in the journal matches the query.");
+        }
+        for (i, entry) in self.entries.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", entry)?;
+        }
+        Ok(())
+    }
+}
+
+/// Read the entries from the journal that match the time range and operation.
+pub fn command_maintenance_log(opts: &MaintenanceLogOpts) -> Result<MaintenanceLogOutput, Error> {
+    let entries = read_journal(opts.journal_path())?
+        .into_iter()
+        .filter(|entry| entry.timestamp >= *opts.since() && entry.timestamp < *opts.until())
+        .filter(|entry| opts.operation().is_empty() || &entry.operation == opts.operation())
+        .filter(|entry| {
+            opts.solido_address().is_empty() || &entry.solido_address == opts.solido_address()
+        })
+        .collect();
+    Ok(MaintenanceLogOutput { entries })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn new_entry(timestamp: u64, operation: &str) -> JournalEntry {
+        JournalEntry {
+            timestamp,
+            solido_address: String::new(),
+            slot: timestamp * 2,
+            epoch: 0,
+            signature: "1111111111111111111111111111111111111111111111111111111111111111"
+                .to_string(),
+            operation: operation.to_string(),
+            output: serde_json::Value::String(operation.to_string()),
+            bundled: Vec::new(),
+            fee_lamports: 5000,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn journal_entries_roundtrip_and_truncated_lines_are_skipped() {
+        let path =
+            std::env::temp_dir().join(format!("solido-journal-test-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(read_journal(&path).unwrap(), vec![]);
+
+        let entries = vec![
+            new_entry(10, "UpdateExchangeRate"),
+            JournalEntry {
+                error: Some("Transaction failed.".to_string()),
+                ..new_entry(20, "StakeDeposit")
+            },
+            JournalEntry {
+                bundled: vec![serde_json::Value::String("ClaimValidatorFee".to_string())],
+                ..new_entry(25, "ClaimValidatorFee")
+            },
+        ];
+        let mut journal = Journal::open(&path).unwrap();
+        for entry in &entries {
+            journal.append(entry).unwrap();
+        }
+
+        // Simulate a crash halfway through writing an entry.
+        journal.file.write_all(b"{\"timestamp\":30,").unwrap();
+        assert_eq!(read_journal(&path).unwrap(), entries);
+
+        // After a restart, new entries should not be appended to the truncated one.
+        let mut entries = entries;
+        entries.push(new_entry(40, "MergeStake"));
+        let mut journal = Journal::open(&path).unwrap();
+        journal.append(&entries[3]).unwrap();
+        assert_eq!(read_journal(&path).unwrap(), entries);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -9,6 +9,7 @@ use helpers::command_show_solido_authorities;
 use helpers::command_withdraw;
 use serde::Serialize;
 use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_response::RpcSimulateTransactionResult;
 use solana_remote_wallet::locator::Locator;
 use solana_remote_wallet::remote_keypair::generate_remote_keypair;
 use solana_remote_wallet::remote_wallet::maybe_wallet_manager;
@@ -23,8 +24,13 @@ use solana_sdk::transaction::Transaction;
 use crate::config::*;
 use crate::error::{Abort, CliError, Error};
 use crate::helpers::{
-    command_add_maintainer, command_add_validator, command_create_solido,
-    command_deactivate_validator, command_deposit, command_remove_maintainer, command_show_solido,
+    command_add_maintainer, command_add_validator, command_change_deposit_cutoff,
+    command_change_fee_payout_mode, command_claim_maintainer_fee, command_claim_validator_fees,
+    command_create_exchange_rate_oracle, command_create_solido, command_deactivate_validator,
+    command_deposit, command_force_remove_validator, command_migrate_validator_list,
+    command_remove_maintainer, command_set_token_metadata, command_show_solido,
+    command_sort_account_maps, command_upgrade_fee_payout_mode, command_upgrade_maintainers,
+    command_upgrade_metrics, command_upgrade_validator_entries, command_upgrade_validator_list,
 };
 use crate::multisig::MultisigOpts;
 use crate::snapshot::{Snapshot, SnapshotClient};
@@ -33,11 +39,13 @@ mod config;
 mod daemon;
 mod error;
 mod helpers;
+mod journal;
 mod maintenance;
 mod multisig;
 mod prometheus;
 mod snapshot;
 mod spl_token_utils;
+mod subscription;
 mod validator_info_utils;
 
 /// Solido -- Interact with Lido for Solana.
@@ -138,6 +146,8 @@ enum SubCommand {
     * An SPL token mint for stSOL.
     * stSOL-denominated SPL token accounts for fee receivers.
     * The Solido instance itself.
+    * Optionally, a multisig proposal to create the stSOL token metadata, when
+      --token-name, --token-symbol, or --token-uri is passed.
 
 REWARDS
 
@@ -145,12 +155,13 @@ REWARDS
     remainder gets distributed implicitly to stSOL holders because they now own
     a share of a larger pool of SOL.
 
-    The SOL rewards get split according to the ratio T : V : D : A, where
+    The SOL rewards get split according to the ratio T : V : D : A : M, where
 
       T: Treasury fee share
       V: Validation fee share (this is for all validators combined)
       D: Developer fee share
       A: stSOL value appreciation share
+      M: Maintainer fee share (optional, defaults to 0)
 
     For example, if the reward distribution is set to '5 : 3 : 2 : 90', then 90%
     of the rewards go to stSOL value appreciation, and 10% go to fees. Of those
@@ -165,12 +176,109 @@ REWARDS
     /// Deactivates a validator and initiates the removal process.
     DeactivateValidator(DeactivateValidatorOpts),
 
+    /// Deactivates a validator and all of its stake, and pays out its fees.
+    ///
+    /// The validator stays in the validator list until its stake is inactive,
+    /// after which the maintainer withdraws the stake and removes it.
+    ForceRemoveValidator(ForceRemoveValidatorOpts),
+
+    /// Changes how validation fees are paid out to validators.
+    ChangeFeePayoutMode(ChangeFeePayoutModeOpts),
+
+    /// Upgrades a Solido instance to the layout that has a fee payout mode.
+    ///
+    /// Proposes the upgrade instruction to the multisig. This is needed only
+    /// once, for instances created before the fee payout mode existed.
+    UpgradeFeePayoutMode(UpgradeFeePayoutModeOpts),
+
+    /// Changes the window at the end of every epoch in which deposits are refused.
+    ///
+    /// Near the end of an epoch, the rewards of that epoch are nearly certain,
+    /// but the exchange rate only includes them after the next update. The
+    /// cutoff prevents depositors from timing deposits around the epoch
+    /// boundary to capture rewards that their deposit did not earn.
+    ChangeDepositCutoff(ChangeDepositCutoffOpts),
+
+    /// Moves the validators of a Solido instance into a separate validator list account.
+    ///
+    /// Creates the validator list account, and proposes the instruction that
+    /// migrates the instance to the multisig. This is needed only once, for
+    /// instances created before validators were stored in their own account.
+    MigrateValidatorList(MigrateValidatorListOpts),
+
+    /// Sorts the validators and maintainers of a Solido instance by public key.
+    ///
+    /// Proposes the instruction that sorts the instance to the multisig. This
+    /// is needed only once, for instances created before validators and
+    /// maintainers were kept sorted.
+    SortAccountMaps(SortAccountMapsOpts),
+
+    /// Moves the validators of a Solido instance into a new validator list
+    /// account that holds per-validator metrics.
+    ///
+    /// Creates the new validator list account, and proposes the instruction
+    /// that upgrades the instance to the multisig. This is needed only once,
+    /// for instances created before validators had metrics.
+    UpgradeValidatorList(UpgradeValidatorListOpts),
+
+    /// Converts a Solido instance to the layout that tracks more metrics.
+    ///
+    /// Proposes the instruction that upgrades the instance to the multisig.
+    /// This is needed only once, for instances created before Solido tracked
+    /// withdrawal sizes and per-epoch flows, or before it had a deposit cutoff
+    /// and a maintainer fee. The instance keeps its size, so this may reduce
+    /// the maximum number of maintainers.
+    UpgradeMetrics(UpgradeMetricsOpts),
+
+    /// Moves the validators of a Solido instance into a new validator list
+    /// account that has room for tombstones and vote withdrawers.
+    ///
+    /// Creates the new validator list account, and proposes the instruction
+    /// that upgrades the instance to the multisig. This is needed only once,
+    /// for instances created before validators could be force-removed, or
+    /// before they registered a vote withdrawer.
+    UpgradeValidatorEntries(UpgradeValidatorEntriesOpts),
+
+    /// Converts the maintainers of a Solido instance to the layout that
+    /// records heartbeats.
+    ///
+    /// Proposes the instruction that upgrades the instance to the multisig.
+    /// This is needed only once, for instances created before maintainers
+    /// could send heartbeats, or before the maintainer became optional in the
+    /// permissionless maintenance instructions. The instance keeps its size,
+    /// so this may reduce the maximum number of maintainers.
+    UpgradeMaintainers(UpgradeMaintainersOpts),
+
+    /// Creates the account that mirrors the exchange rate of a Solido instance.
+    ///
+    /// The exchange rate oracle is a small account with a stable layout, that
+    /// other programs can read to price stSOL. After it is created, every
+    /// exchange rate update writes to it. This is needed only once per
+    /// instance, anybody can do it, and the signer pays for the rent.
+    CreateExchangeRateOracle(CreateExchangeRateOracleOpts),
+
+    /// Creates or updates the name, symbol, and URI of the stSOL token.
+    ///
+    /// Wallets and explorers read these from the token metadata account of the
+    /// stSOL mint. Proposes the instruction that sets the metadata to the
+    /// multisig. The manager pays for the metadata account when the multisig
+    /// creates it, so if it does not exist yet, this also transfers the rent
+    /// from the signer to the manager.
+    SetTokenMetadata(SetTokenMetadataOpts),
+
     /// Adds a maintainer to the Solido instance.
     AddMaintainer(AddRemoveMaintainerOpts),
 
     /// Removes a maintainer from the Solido instance.
     RemoveMaintainer(AddRemoveMaintainerOpts),
 
+    /// Claim the maintainer fee that the signer accrued as a maintainer.
+    ///
+    /// The fee is minted as stSOL to the associated token account for the
+    /// signer. If the associated token account does not yet exist, it will be
+    /// created.
+    ClaimMaintainerFee(ClaimMaintainerFeeOpts),
+
     /// Deposit some SOL, receive stSOL in return.
     ///
     /// The recipient will be set to the associated token account for the signer.
@@ -191,6 +299,14 @@ REWARDS
     /// time, to be used later when initializing the Solido instance.
     ShowAuthorities(ShowSolidoAuthoritiesOpts),
 
+    /// Print a machine-readable description of the Solido program as JSON.
+    ///
+    /// The description lists the instructions with the accounts that they
+    /// expect, and the Borsh layout of the instruction data and of the accounts
+    /// that the program owns. It describes the program version that this CLI
+    /// was built with.
+    ExportIdl,
+
     /// Execute one iteration of periodic maintenance logic.
     ///
     /// This is mainly useful for testing. To perform maintenance continuously,
@@ -200,8 +316,23 @@ REWARDS
     /// Start the maintainer daemon.
     RunMaintainer(RunMaintainerOpts),
 
+    /// Show maintenance from the journal of the maintainer daemon.
+    MaintenanceLog(MaintenanceLogOpts),
+
     /// Interact with a deployed Multisig program for governance tasks.
     Multisig(MultisigOpts),
+
+    /// Operations for validators that are part of a Solido instance.
+    Validator(ValidatorSubCommand),
+}
+
+#[derive(Clap, Debug)]
+enum ValidatorSubCommand {
+    /// Claim the fees of a validator into an stSOL account of its choice.
+    ///
+    /// The signer must be the validator identity, or the authorized voter of
+    /// the vote account.
+    ClaimFees(ClaimValidatorFeesOpts),
 }
 
 /// Determines which network to connect to, and who pays the fees.
@@ -266,17 +397,25 @@ impl<'a> SnapshotConfig<'a> {
         signers: &T,
     ) -> snapshot::Result<Signature> {
         let transaction = self.sign_transaction(instructions, signers)?;
+        Ok(self.send_transaction(&transaction)?)
+    }
+
+    /// Send a signed transaction, and wait for it to be confirmed.
+    pub fn send_transaction(
+        &mut self,
+        transaction: &Transaction,
+    ) -> solana_client::client_error::Result<Signature> {
         let signature_result = match self.output_mode {
             OutputMode::Text => {
                 // In text mode, we can display a spinner.
                 self.client
-                    .send_and_confirm_transaction_with_spinner(&transaction)
+                    .send_and_confirm_transaction_with_spinner(transaction)
             }
             OutputMode::Json => {
                 // In json mode, printing a spinner to stdout would break the
                 // json that we also print to stdout, so opt for the silent
                 // version.
-                self.client.send_and_confirm_transaction(&transaction)
+                self.client.send_and_confirm_transaction(transaction)
             }
         };
 
@@ -294,7 +433,17 @@ impl<'a> SnapshotConfig<'a> {
             _ => {}
         }
 
-        Ok(signature_result?)
+        signature_result
+    }
+
+    /// Sign a transaction and simulate it, without sending it.
+    pub fn sign_and_simulate_transaction<T: Signers>(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &T,
+    ) -> snapshot::Result<RpcSimulateTransactionResult> {
+        let transaction = self.sign_transaction(instructions, signers)?;
+        Ok(self.client.simulate_transaction(&transaction)?)
     }
 }
 
@@ -353,6 +502,25 @@ fn main() {
             print_output(output_mode, &output);
         }
         SubCommand::Multisig(cmd_opts) => multisig::main(&mut config, cmd_opts),
+        SubCommand::Validator(ValidatorSubCommand::ClaimFees(cmd_opts)) => {
+            let result =
+                config.with_snapshot(|config| command_claim_validator_fees(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to claim validator fees.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::PerformMaintenance(cmd_opts) if *cmd_opts.dry_run() => {
+            let result = config
+                .with_snapshot(|config| maintenance::run_simulate_maintenance(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to simulate maintenance.");
+            match (output_mode, output) {
+                (OutputMode::Text, None) => {
+                    println!("Nothing simulated, there was no maintenance to perform.")
+                }
+                (OutputMode::Json, None) => println!("null"),
+
+                (mode, Some(output)) => print_output(mode, &output),
+            }
+        }
         SubCommand::PerformMaintenance(cmd_opts) => {
             // This command only performs one iteration, `RunMaintainer` runs continuously.
             let result = config
@@ -370,6 +538,11 @@ fn main() {
         SubCommand::RunMaintainer(cmd_opts) => {
             daemon::main(&mut config, &cmd_opts);
         }
+        SubCommand::MaintenanceLog(cmd_opts) => {
+            let result = journal::command_maintenance_log(&cmd_opts);
+            let output = result.ok_or_abort_with("Failed to read the maintenance journal.");
+            print_output(output_mode, &output);
+        }
         SubCommand::AddValidator(cmd_opts) => {
             let result = config.with_snapshot(|config| command_add_validator(config, &cmd_opts));
             let output = result.ok_or_abort_with("Failed to add validator.");
@@ -381,6 +554,65 @@ fn main() {
             let output = result.ok_or_abort_with("Failed to deactivate validator.");
             print_output(output_mode, &output);
         }
+        SubCommand::ForceRemoveValidator(cmd_opts) => {
+            let result =
+                config.with_snapshot(|config| command_force_remove_validator(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to force remove validator.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::ChangeFeePayoutMode(cmd_opts) => {
+            let result =
+                config.with_snapshot(|config| command_change_fee_payout_mode(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to change fee payout mode.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::UpgradeFeePayoutMode(cmd_opts) => {
+            let result =
+                config.with_snapshot(|config| command_upgrade_fee_payout_mode(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to upgrade fee payout mode.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::ChangeDepositCutoff(cmd_opts) => {
+            let result =
+                config.with_snapshot(|config| command_change_deposit_cutoff(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to change deposit cutoff.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::MigrateValidatorList(cmd_opts) => {
+            let result =
+                config.with_snapshot(|config| command_migrate_validator_list(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to migrate validator list.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::SortAccountMaps(cmd_opts) => {
+            let result =
+                config.with_snapshot(|config| command_sort_account_maps(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to sort account maps.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::UpgradeValidatorList(cmd_opts) => {
+            let result =
+                config.with_snapshot(|config| command_upgrade_validator_list(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to upgrade validator list.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::UpgradeMetrics(cmd_opts) => {
+            let result = config.with_snapshot(|config| command_upgrade_metrics(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to upgrade metrics.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::UpgradeValidatorEntries(cmd_opts) => {
+            let result =
+                config.with_snapshot(|config| command_upgrade_validator_entries(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to upgrade validator entries.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::UpgradeMaintainers(cmd_opts) => {
+            let result =
+                config.with_snapshot(|config| command_upgrade_maintainers(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to upgrade maintainers.");
+            print_output(output_mode, &output);
+        }
         SubCommand::AddMaintainer(cmd_opts) => {
             let result = config.with_snapshot(|config| command_add_maintainer(config, &cmd_opts));
             let output = result.ok_or_abort_with("Failed to add maintainer.");
@@ -404,6 +636,30 @@ fn main() {
                 result.ok_or_abort_with("Failed to show authorities for Solido public key.");
             print_output(output_mode, &output);
         }
+        SubCommand::CreateExchangeRateOracle(cmd_opts) => {
+            let result = config
+                .with_snapshot(|config| command_create_exchange_rate_oracle(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to create exchange rate oracle.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::SetTokenMetadata(cmd_opts) => {
+            let result =
+                config.with_snapshot(|config| command_set_token_metadata(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to set token metadata.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::ExportIdl => {
+            // The IDL is only useful as JSON, so ignore the output mode.
+            let json_string = serde_json::to_string_pretty(&lido::idl::Idl::new())
+                .expect("Failed to serialize IDL as json.");
+            println!("{}", json_string);
+        }
+        SubCommand::ClaimMaintainerFee(cmd_opts) => {
+            let result =
+                config.with_snapshot(|config| command_claim_maintainer_fee(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to claim maintainer fee.");
+            print_output(output_mode, &output);
+        }
         SubCommand::Deposit(cmd_opts) => {
             let result = command_deposit(&mut config, &cmd_opts);
             let output = result.ok_or_abort_with("Failed to deposit.");
@@ -427,16 +683,50 @@ fn merge_with_config_and_environment(
         SubCommand::DeactivateValidator(opts) => {
             opts.merge_with_config_and_environment(config_file)
         }
+        SubCommand::ForceRemoveValidator(opts) => {
+            opts.merge_with_config_and_environment(config_file)
+        }
+        SubCommand::ChangeFeePayoutMode(opts) => {
+            opts.merge_with_config_and_environment(config_file)
+        }
+        SubCommand::UpgradeFeePayoutMode(opts) => {
+            opts.merge_with_config_and_environment(config_file)
+        }
+        SubCommand::ChangeDepositCutoff(opts) => {
+            opts.merge_with_config_and_environment(config_file)
+        }
+        SubCommand::MigrateValidatorList(opts) => {
+            opts.merge_with_config_and_environment(config_file)
+        }
+        SubCommand::SortAccountMaps(opts) => opts.merge_with_config_and_environment(config_file),
+        SubCommand::UpgradeValidatorList(opts) => {
+            opts.merge_with_config_and_environment(config_file)
+        }
+        SubCommand::UpgradeMetrics(opts) => opts.merge_with_config_and_environment(config_file),
+        SubCommand::UpgradeValidatorEntries(opts) => {
+            opts.merge_with_config_and_environment(config_file)
+        }
+        SubCommand::UpgradeMaintainers(opts) => opts.merge_with_config_and_environment(config_file),
         SubCommand::AddMaintainer(opts) | SubCommand::RemoveMaintainer(opts) => {
             opts.merge_with_config_and_environment(config_file)
         }
+        SubCommand::CreateExchangeRateOracle(opts) => {
+            opts.merge_with_config_and_environment(config_file)
+        }
+        SubCommand::SetTokenMetadata(opts) => opts.merge_with_config_and_environment(config_file),
+        SubCommand::ClaimMaintainerFee(opts) => opts.merge_with_config_and_environment(config_file),
         SubCommand::Deposit(opts) => opts.merge_with_config_and_environment(config_file),
         SubCommand::Withdraw(opts) => opts.merge_with_config_and_environment(config_file),
         SubCommand::ShowSolido(opts) => opts.merge_with_config_and_environment(config_file),
         SubCommand::ShowAuthorities(opts) => opts.merge_with_config_and_environment(config_file),
+        SubCommand::ExportIdl => {}
         SubCommand::PerformMaintenance(opts) => opts.merge_with_config_and_environment(config_file),
         SubCommand::Multisig(opts) => opts.merge_with_config_and_environment(config_file),
+        SubCommand::Validator(ValidatorSubCommand::ClaimFees(opts)) => {
+            opts.merge_with_config_and_environment(config_file)
+        }
         SubCommand::RunMaintainer(opts) => opts.merge_with_config_and_environment(config_file),
+        SubCommand::MaintenanceLog(opts) => opts.merge_with_config_and_environment(config_file),
     }
 }
 
@@ -9,11 +9,13 @@ use std::time::SystemTime;
 
 use itertools::izip;
 
+use lido::oracle::ExchangeRateOracle;
 use lido::processor::StakeType;
 use lido::token;
 use lido::token::Rational;
 use lido::REWARDS_WITHDRAW_AUTHORITY;
 use serde::Serialize;
+use solana_client::client_error::ClientError;
 use solana_program::program_pack::Pack;
 use solana_program::{
     clock::{Clock, Slot},
@@ -24,6 +26,10 @@ use solana_program::{
 };
 use solana_sdk::account::ReadableAccount;
 use solana_sdk::fee_calculator::DEFAULT_TARGET_LAMPORTS_PER_SIGNATURE;
+use solana_sdk::message::Message;
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::Transaction;
 use solana_sdk::{account::Account, instruction::Instruction};
 use solana_vote_program::vote_state::VoteState;
 use spl_token::state::Mint;
@@ -35,20 +41,25 @@ use lido::{
     util::serialize_b58,
 };
 use lido::{
-    state::{Lido, Validator},
+    state::{FeePayoutMode, Lido, Maintainer, Validator, Validators},
     token::Lamports,
     MINIMUM_STAKE_ACCOUNT_BALANCE, STAKE_AUTHORITY,
 };
 
-use crate::config::StakeTime;
+use crate::config::{MaintenanceTask, StakeTime};
 use crate::error::MaintenanceError;
+use crate::prometheus::MetricSink;
 use crate::snapshot::Result;
 use crate::validator_info_utils::ValidatorInfo;
 use crate::{config::PerformMaintenanceOpts, SnapshotConfig};
 
+/// Minimum balance that the maintainer needs to pay for transaction fees,
+/// below this we do not attempt to perform maintenance.
+pub const MINIMUM_MAINTAINER_BALANCE: Lamports = Lamports(100_000_000);
+
 /// A brief description of the maintenance performed. Not relevant functionally,
 /// but helpful for automated testing, and just for info.
-#[derive(Debug, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
 pub enum MaintenanceOutput {
     StakeDeposit {
         #[serde(serialize_with = "serialize_b58")]
@@ -78,6 +89,11 @@ pub enum MaintenanceOutput {
 
         #[serde(rename = "unstake_withdrawn_to_reserve_lamports")]
         unstake_withdrawn_to_reserve: Lamports,
+
+        /// For a validator that is being removed with `ForceRemoveValidator`,
+        /// the balance of the fully inactive stake accounts that we withdraw.
+        #[serde(rename = "stake_withdrawn_to_reserve_lamports")]
+        stake_withdrawn_to_reserve: Lamports,
     },
 
     CollectValidatorFee {
@@ -111,9 +127,14 @@ pub enum MaintenanceOutput {
         validator_vote_account: Pubkey,
     },
     UnstakeFromActiveValidator(Unstake),
+
+    MaintainerHeartbeat {
+        /// Slot of the previous heartbeat, 0 if this is the first one.
+        previous_heartbeat_slot: Slot,
+    },
 }
 
-#[derive(Debug, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
 pub struct Unstake {
     #[serde(serialize_with = "serialize_b58")]
     validator_vote_account: Pubkey,
@@ -148,6 +169,26 @@ impl fmt::Display for Unstake {
     }
 }
 
+impl MaintenanceOutput {
+    /// Return the name of the maintenance operation, e.g. `StakeDeposit`.
+    ///
+    /// This matches the variant name that we use when serializing as json.
+    pub fn operation(&self) -> &'static str {
+        match self {
+            MaintenanceOutput::StakeDeposit { .. } => "StakeDeposit",
+            MaintenanceOutput::UpdateExchangeRate => "UpdateExchangeRate",
+            MaintenanceOutput::WithdrawInactiveStake { .. } => "WithdrawInactiveStake",
+            MaintenanceOutput::CollectValidatorFee { .. } => "CollectValidatorFee",
+            MaintenanceOutput::ClaimValidatorFee { .. } => "ClaimValidatorFee",
+            MaintenanceOutput::MergeStake { .. } => "MergeStake",
+            MaintenanceOutput::UnstakeFromInactiveValidator(..) => "UnstakeFromInactiveValidator",
+            MaintenanceOutput::RemoveValidator { .. } => "RemoveValidator",
+            MaintenanceOutput::UnstakeFromActiveValidator(..) => "UnstakeFromActiveValidator",
+            MaintenanceOutput::MaintainerHeartbeat { .. } => "MaintainerHeartbeat",
+        }
+    }
+}
+
 impl fmt::Display for MaintenanceOutput {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -168,6 +209,7 @@ impl fmt::Display for MaintenanceOutput {
                 validator_vote_account,
                 expected_difference_stake,
                 unstake_withdrawn_to_reserve,
+                stake_withdrawn_to_reserve,
             } => {
                 writeln!(f, "Withdrew inactive stake.")?;
                 writeln!(
@@ -185,6 +227,11 @@ impl fmt::Display for MaintenanceOutput {
                     "  Amount withdrawn from unstake: {}",
                     unstake_withdrawn_to_reserve
                 )?;
+                writeln!(
+                    f,
+                    "  Amount withdrawn from stake:   {}",
+                    stake_withdrawn_to_reserve
+                )?;
             }
             MaintenanceOutput::CollectValidatorFee {
                 validator_vote_account,
@@ -235,6 +282,63 @@ impl fmt::Display for MaintenanceOutput {
                 writeln!(f, "Remove validator")?;
                 writeln!(f, "  Validator vote account: {}", validator_vote_account)?;
             }
+            MaintenanceOutput::MaintainerHeartbeat {
+                previous_heartbeat_slot,
+            } => {
+                writeln!(f, "Sent maintainer heartbeat.")?;
+                writeln!(f, "  Previous heartbeat slot: {}", previous_heartbeat_slot)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A maintenance transaction that we sent.
+pub struct SentMaintenance {
+    /// The maintenance that the transaction performs, one per instruction.
+    pub outputs: Vec<MaintenanceOutput>,
+
+    /// Signature of the transaction.
+    pub signature: Signature,
+
+    /// The fee that the transaction paid. Zero if it did not execute.
+    pub fee: Lamports,
+
+    /// The outcome of sending the transaction and waiting for confirmation.
+    pub result: std::result::Result<(), ClientError>,
+}
+
+/// The outcome of simulating a maintenance transaction, in dry-run mode.
+#[derive(Debug, Serialize)]
+pub struct SimulatedMaintenance {
+    /// The maintenance that the transaction would perform.
+    pub maintenance: MaintenanceOutput,
+
+    /// Other maintenance bundled into the same transaction, if any.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub bundled: Vec<MaintenanceOutput>,
+
+    /// The error that the simulated transaction failed with, if any.
+    pub error: Option<String>,
+
+    /// Log messages produced by the simulated transaction.
+    pub logs: Vec<String>,
+}
+
+impl fmt::Display for SimulatedMaintenance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Dry run, simulated but did not send the transaction.")?;
+        write!(f, "{}", self.maintenance)?;
+        for output in &self.bundled {
+            write!(f, "{}", output)?;
+        }
+        match self.error {
+            None => writeln!(f, "Simulation succeeded.")?,
+            Some(ref error) => writeln!(f, "Simulation failed: {}", error)?,
+        }
+        writeln!(f, "Logs:")?;
+        for log in &self.logs {
+            writeln!(f, "  {}", log)?;
         }
         Ok(())
     }
@@ -264,7 +368,10 @@ pub struct SolidoState {
     pub solido_address: Pubkey,
     pub solido: Lido,
 
-    /// For each validator, in the same order as in `solido.validators`, holds
+    /// The validators, read from the validator list account `solido.validator_list`.
+    pub validators: Validators,
+
+    /// For each validator, in the same order as in `validators`, holds
     /// the stake balance of the derived stake accounts from the begin seed until
     /// end seed.
     pub validator_stake_accounts: Vec<Vec<(Pubkey, StakeAccount)>>,
@@ -273,20 +380,20 @@ pub struct SolidoState {
     /// unstake accounts from the begin seed until end seed.
     pub validator_unstake_accounts: Vec<Vec<(Pubkey, StakeAccount)>>,
 
-    /// For each validator, in the same order as in `solido.validators`, holds
+    /// For each validator, in the same order as in `validators`, holds
     /// the number of Lamports of the validator's vote account.
     pub validator_vote_account_balances: Vec<Lamports>,
 
-    /// For each validator, in the same order as in `solido.validators`, holds
+    /// For each validator, in the same order as in `validators`, holds
     /// the deserialized vote account.
     pub validator_vote_accounts: Vec<VoteState>,
 
-    /// For each validator, in the same order as in `solido.validators`, holds
+    /// For each validator, in the same order as in `validators`, holds
     /// the balance of the validator's identity account (which pays for the
     /// votes).
     pub validator_identity_account_balances: Vec<Lamports>,
 
-    /// For each validator, in the same order as in `solido.validators`, holds
+    /// For each validator, in the same order as in `validators`, holds
     /// the validator info (name and Keybase username).
     pub validator_infos: Vec<ValidatorInfo>,
 
@@ -299,6 +406,10 @@ pub struct SolidoState {
 
     pub reserve_address: Pubkey,
     pub reserve_account: Account,
+
+    /// Address of the exchange rate oracle, which may not exist yet.
+    pub exchange_rate_oracle_address: Pubkey,
+
     pub rent: Rent,
     pub clock: Clock,
     pub epoch_schedule: EpochSchedule,
@@ -412,9 +523,12 @@ impl SolidoState {
         stake_time: StakeTime,
     ) -> Result<SolidoState> {
         let solido = config.client.get_solido(solido_address)?;
+        let validators = config.client.get_validators(&solido.validator_list)?;
 
         let reserve_address = solido.get_reserve_account(solido_program_id, solido_address)?;
         let reserve_account = config.client.get_account(&reserve_address)?;
+        let (exchange_rate_oracle_address, _) =
+            ExchangeRateOracle::find_address(solido_program_id, solido_address);
 
         let st_sol_mint_account = config.client.get_account(&solido.st_sol_mint)?;
         let st_sol_mint = Mint::unpack(&st_sol_mint_account.data)?;
@@ -430,7 +544,7 @@ impl SolidoState {
         let mut validator_identity_account_balances = Vec::new();
         let mut validator_vote_accounts = Vec::new();
         let mut validator_infos = Vec::new();
-        for validator in solido.validators.entries.iter() {
+        for validator in validators.entries.iter() {
             let vote_account = config.client.get_account(&validator.pubkey)?;
             let vote_state = config.client.get_vote_account(&validator.pubkey)?;
             let validator_info = config.client.get_validator_info(&vote_state.node_pubkey)?;
@@ -479,6 +593,7 @@ impl SolidoState {
             solido_program_id: *solido_program_id,
             solido_address: *solido_address,
             solido,
+            validators,
             validator_stake_accounts,
             validator_unstake_accounts,
             validator_vote_account_balances,
@@ -488,6 +603,7 @@ impl SolidoState {
             maintainer_balances,
             reserve_address,
             reserve_account: reserve_account.clone(),
+            exchange_rate_oracle_address,
             st_sol_mint,
             rent,
             clock,
@@ -513,7 +629,7 @@ impl SolidoState {
         self.confirm_should_stake_unstake_in_current_slot()?;
         // We can only stake if there is an active validator. If there is none,
         // this will short-circuit and return None.
-        self.solido.validators.iter_active().next()?;
+        self.validators.iter_active().next()?;
 
         let reserve_balance = self.get_effective_reserve();
 
@@ -522,17 +638,13 @@ impl SolidoState {
         // deposit to that validator. If we get here there is at least one active
         // validator, so computing the target balance should not fail.
         let undelegated_lamports = reserve_balance;
-        let targets =
-            lido::balance::get_target_balance(undelegated_lamports, &self.solido.validators)
-                .expect("Failed to compute target balance.");
+        let targets = lido::balance::get_target_balance(undelegated_lamports, &self.validators)
+            .expect("Failed to compute target balance.");
 
         let (validator_index, amount_below_target) =
-            lido::balance::get_minimum_stake_validator_index_amount(
-                &self.solido.validators,
-                &targets[..],
-            );
+            lido::balance::get_minimum_stake_validator_index_amount(&self.validators, &targets[..]);
 
-        let validator = &self.solido.validators.entries[validator_index];
+        let validator = &self.validators.entries[validator_index];
 
         let (stake_account_end, _bump_seed_end) = validator.find_stake_account_address(
             &self.solido_program_id,
@@ -574,6 +686,7 @@ impl SolidoState {
             &self.solido_program_id,
             &lido::instruction::StakeDepositAccountsMeta {
                 lido: self.solido_address,
+                validator_list: self.solido.validator_list,
                 maintainer: self.maintainer_address,
                 reserve: self.reserve_address,
                 validator_vote_account: validator.pubkey,
@@ -612,6 +725,7 @@ impl SolidoState {
                 &self.solido_program_id,
                 &lido::instruction::UnstakeAccountsMeta {
                     lido: self.solido_address,
+                    validator_list: self.solido.validator_list,
                     maintainer: self.maintainer_address,
                     validator_vote_account: validator.pubkey,
                     source_stake_account: *stake_account_address,
@@ -626,15 +740,15 @@ impl SolidoState {
     /// If there is a validator being deactivated, try to unstake its funds.
     pub fn try_unstake_from_inactive_validator(&self) -> Option<(Instruction, MaintenanceOutput)> {
         for (validator, stake_accounts) in self
-            .solido
             .validators
             .entries
             .iter()
             .zip(self.validator_stake_accounts.iter())
         {
             // We are only interested in unstaking from inactive validators that
-            // have stake accounts.
-            if validator.entry.active {
+            // have stake accounts. The stake accounts of a tombstone are
+            // deactivating already.
+            if validator.entry.active || validator.entry.tombstone {
                 continue;
             }
             // Validator already has 3 unstake accounts.
@@ -669,7 +783,12 @@ impl SolidoState {
 
     /// If there is a validator ready for removal, try to remove it.
     pub fn try_remove_validator(&self) -> Option<(Instruction, MaintenanceOutput)> {
-        for validator in &self.solido.validators.entries {
+        for (validator, vote_state) in self
+            .validators
+            .entries
+            .iter()
+            .zip(self.validator_vote_accounts.iter())
+        {
             // We are only interested in validators that can be removed.
             if validator.entry.check_can_be_removed().is_err() {
                 continue;
@@ -677,13 +796,23 @@ impl SolidoState {
             let task = MaintenanceOutput::RemoveValidator {
                 validator_vote_account: validator.pubkey,
             };
+            // Validators that did not register a vote withdrawer get the
+            // withdraw authority handed to their identity.
+            let vote_withdrawer = if validator.entry.vote_withdrawer == Pubkey::default() {
+                vote_state.node_pubkey
+            } else {
+                validator.entry.vote_withdrawer
+            };
 
             return Some((
                 lido::instruction::remove_validator(
                     &self.solido_program_id,
                     &lido::instruction::RemoveValidatorMeta {
                         lido: self.solido_address,
+                        validator_list: self.solido.validator_list,
                         validator_vote_account_to_remove: validator.pubkey,
+                        validator_vote_withdrawer: vote_withdrawer,
+                        rewards_withdraw_authority: self.get_rewards_withdraw_authority(),
                     },
                 ),
                 task,
@@ -717,10 +846,12 @@ impl SolidoState {
             &self.solido_program_id,
             &lido::instruction::MergeStakeMeta {
                 lido: self.solido_address,
+                validator_list: self.solido.validator_list,
                 validator_vote_account: validator.pubkey,
                 from_stake,
                 to_stake,
                 stake_authority: self.get_stake_authority(),
+                maintainer: Some(self.maintainer_address),
             },
         )
     }
@@ -728,13 +859,23 @@ impl SolidoState {
     // Tries to merge accounts from the beginning of the validator's
     // stake accounts.  May return None or one instruction.
     pub fn try_merge_on_all_stakes(&self) -> Option<(Instruction, MaintenanceOutput)> {
+        self.get_stake_merges().into_iter().next()
+    }
+
+    /// Return a merge for every validator that has stake accounts to merge.
+    fn get_stake_merges(&self) -> Vec<(Instruction, MaintenanceOutput)> {
+        let mut candidates = Vec::new();
         for (validator, stake_accounts) in self
-            .solido
             .validators
             .entries
             .iter()
             .zip(self.validator_stake_accounts.iter())
         {
+            // The stake accounts of a tombstone are deactivating, they get
+            // withdrawn rather than merged.
+            if validator.entry.tombstone {
+                continue;
+            }
             // Try to merge from beginning
             if stake_accounts.len() > 1 {
                 let from_stake = stake_accounts[0];
@@ -749,11 +890,11 @@ impl SolidoState {
                         from_stake_seed: from_stake.1.seed,
                         to_stake_seed: to_stake.1.seed,
                     };
-                    return Some((instruction, task));
+                    candidates.push((instruction, task));
                 }
             }
         }
-        None
+        candidates
     }
 
     /// If a new epoch started, and we haven't updated the exchange rate yet, do so.
@@ -767,8 +908,11 @@ impl SolidoState {
             &self.solido_program_id,
             &lido::instruction::UpdateExchangeRateAccountsMeta {
                 lido: self.solido_address,
+                validator_list: self.solido.validator_list,
                 reserve: self.reserve_address,
                 st_sol_mint: self.solido.st_sol_mint,
+                exchange_rate_oracle: self.exchange_rate_oracle_address,
+                maintainer: Some(self.maintainer_address),
             },
         );
         let task = MaintenanceOutput::UpdateExchangeRate;
@@ -782,8 +926,14 @@ impl SolidoState {
     /// or if some joker donates to one of the stake accounts we can use the same function
     /// to claim these rewards back to the reserve account so they can be re-staked.
     pub fn try_withdraw_inactive_stake(&self) -> Option<(Instruction, MaintenanceOutput)> {
+        self.get_inactive_stake_withdrawals().into_iter().next()
+    }
+
+    /// Return a withdrawal of inactive stake for every validator whose balance is outdated.
+    fn get_inactive_stake_withdrawals(&self) -> Vec<(Instruction, MaintenanceOutput)> {
+        let mut candidates = Vec::new();
         for (validator, stake_accounts, unstake_accounts) in izip!(
-            self.solido.validators.entries.iter(),
+            self.validators.entries.iter(),
             self.validator_stake_accounts.iter(),
             self.validator_unstake_accounts.iter()
         ) {
@@ -801,6 +951,19 @@ impl SolidoState {
                     Lamports(0)
                 };
 
+            // For a tombstone, `WithdrawInactiveStake` withdraws the fully
+            // inactive stake accounts at the beginning entirely.
+            let mut removed_stake = Lamports(0);
+            if validator.entry.tombstone {
+                for (_addr, stake_account) in stake_accounts.iter() {
+                    if stake_account.balance.inactive != stake_account.balance.total() {
+                        break;
+                    }
+                    removed_stake = (removed_stake + stake_account.balance.total())
+                        .expect("Summing stake accounts should not overflow.");
+                }
+            }
+
             let mut removed_unstake = Lamports(0);
 
             for (_addr, unstake_account) in unstake_accounts.iter() {
@@ -814,9 +977,11 @@ impl SolidoState {
             // If the expected difference is less than some defined amount
             // of Lamports, we don't bother withdrawing. We try to do this
             // so we don't pay more for fees than the amount that we'll
-            // withdraw. Or if we have stake to remove from unstake accounts.
+            // withdraw. Or if we have stake to remove from unstake accounts,
+            // or from the stake accounts of a tombstone.
             if expected_difference_stake > SolidoState::MINIMUM_WITHDRAW_AMOUNT
                 || removed_unstake > Lamports(0)
+                || removed_stake > Lamports(0)
             {
                 // The balance of this validator is not up to date, try to update it.
                 let mut stake_account_addrs = Vec::new();
@@ -826,22 +991,25 @@ impl SolidoState {
                     &self.solido_program_id,
                     &lido::instruction::WithdrawInactiveStakeMeta {
                         lido: self.solido_address,
+                        validator_list: self.solido.validator_list,
                         validator_vote_account: validator.pubkey,
                         stake_accounts: stake_account_addrs,
                         reserve: self.reserve_address,
                         stake_authority: self.get_stake_authority(),
+                        maintainer: Some(self.maintainer_address),
                     },
                 );
                 let task = MaintenanceOutput::WithdrawInactiveStake {
                     validator_vote_account: validator.pubkey,
                     expected_difference_stake,
                     unstake_withdrawn_to_reserve: removed_unstake,
+                    stake_withdrawn_to_reserve: removed_stake,
                 };
-                return Some((instruction, task));
+                candidates.push((instruction, task));
             }
         }
 
-        None
+        candidates
     }
 
     /// Check if any validator's vote account is eligible for fee collection, and if
@@ -851,12 +1019,18 @@ impl SolidoState {
     /// every epoch, they should be collected and the fees they've generated
     /// should be spread to the Solido participants.
     pub fn try_collect_validator_fee(&self) -> Option<(Instruction, MaintenanceOutput)> {
-        for (validator, vote_account_balance) in self
-            .solido
+        self.get_validator_fee_collections().into_iter().next()
+    }
+
+    /// Return a fee collection for every validator whose vote account has rewards to collect.
+    fn get_validator_fee_collections(&self) -> Vec<(Instruction, MaintenanceOutput)> {
+        let mut candidates = Vec::new();
+        for (i, (validator, vote_account_balance)) in self
             .validators
             .entries
             .iter()
             .zip(self.validator_vote_account_balances.iter())
+            .enumerate()
         {
             // Need to collect some rewards if the balance is more than
             // the minimum predefined amount.
@@ -865,6 +1039,7 @@ impl SolidoState {
                     &self.solido_program_id,
                     &lido::instruction::CollectValidatorFeeMeta {
                         lido: self.solido_address,
+                        validator_list: self.solido.validator_list,
                         validator_vote_account: validator.pubkey,
                         mint_authority: self.get_mint_authority(),
                         st_sol_mint: self.solido.st_sol_mint,
@@ -872,23 +1047,35 @@ impl SolidoState {
                         developer_st_sol_account: self.solido.fee_recipients.developer_account,
                         reserve: self.reserve_address,
                         rewards_withdraw_authority: self.get_rewards_withdraw_authority(),
+                        validator_fee_st_sol_accounts: self.get_validator_fee_st_sol_accounts(i),
+                        maintainer: Some(self.maintainer_address),
                     },
                 );
                 let task = MaintenanceOutput::CollectValidatorFee {
                     validator_vote_account: validator.pubkey,
                     fee_rewards: *vote_account_balance,
                 };
-                return Some((instruction, task));
+                candidates.push((instruction, task));
             }
         }
 
-        None
+        candidates
     }
 
     /// Checks if any of the validators has unclaimed fees in stSOL. If so,
     /// claims it on behalf of the validator.
     pub fn try_claim_validator_fee(&self) -> Option<(Instruction, MaintenanceOutput)> {
-        for validator in self.solido.validators.entries.iter() {
+        self.get_validator_fee_claims().into_iter().next()
+    }
+
+    /// Return a fee claim for every validator with unclaimed fees.
+    fn get_validator_fee_claims(&self) -> Vec<(Instruction, MaintenanceOutput)> {
+        // In direct mode, validators whose fee account was in the last page
+        // that `CollectValidatorFee` paid out to have no credit left, so we
+        // skip them below. Validators that were not in the page, and credit
+        // from before the mode changed, we still claim.
+        let mut candidates = Vec::new();
+        for validator in self.validators.entries.iter() {
             if validator.entry.fee_credit == StLamports(0) {
                 continue;
             }
@@ -897,6 +1084,7 @@ impl SolidoState {
                 &self.solido_program_id,
                 &lido::instruction::ClaimValidatorFeeMeta {
                     lido: self.solido_address,
+                    validator_list: self.solido.validator_list,
                     st_sol_mint: self.solido.st_sol_mint,
                     mint_authority: self.get_mint_authority(),
                     validator_fee_st_sol_account: validator.entry.fee_address,
@@ -907,32 +1095,30 @@ impl SolidoState {
                 fee_rewards: validator.entry.fee_credit,
             };
 
-            return Some((instruction, task));
+            candidates.push((instruction, task));
         }
 
-        None
+        candidates
     }
 
     /// Unstake from active validators in order to rebalance validators.
     pub fn try_unstake_from_active_validators(&self) -> Option<(Instruction, MaintenanceOutput)> {
         self.confirm_should_stake_unstake_in_current_slot()?;
         // Return None if there's no active validator to unstake from.
-        self.solido.validators.iter_active().next()?;
+        self.validators.iter_active().next()?;
 
         // Get the target for each validator. Undelegated Lamports can be
         // sent when staking with validators.
-        let targets = lido::balance::get_target_balance(
-            self.get_effective_reserve(),
-            &self.solido.validators,
-        )
-        .expect("Failed to compute target balance.");
+        let targets =
+            lido::balance::get_target_balance(self.get_effective_reserve(), &self.validators)
+                .expect("Failed to compute target balance.");
 
         let (validator_index, unstake_amount) = lido::balance::get_unstake_validator_index(
-            &self.solido.validators,
+            &self.validators,
             &targets,
             SolidoState::UNBALANCE_THRESHOLD,
         )?;
-        let validator = &self.solido.validators.entries[validator_index];
+        let validator = &self.validators.entries[validator_index];
         let stake_account = &self.validator_stake_accounts[validator_index][0];
 
         let maximum_unstake = (stake_account.1.balance.total() - MINIMUM_STAKE_ACCOUNT_BALANCE)
@@ -961,8 +1147,98 @@ impl SolidoState {
         Some((instruction, task))
     }
 
+    /// If the maintainer's heartbeat is due, send one.
+    ///
+    /// This is not a maintenance task, maintainers that opt in to heartbeats
+    /// send them also when they are not on duty, see [`get_duty_rotation`].
+    pub fn try_maintainer_heartbeat(&self) -> Option<(Instruction, MaintenanceOutput)> {
+        let maintainer = &self
+            .solido
+            .maintainers
+            .get(&self.maintainer_address)
+            .ok()?
+            .entry;
+        let heartbeat_interval =
+            Self::MAINTAINER_HEARTBEAT_INTERVAL_SLICES * Self::MAINTAINER_DUTY_SLICE_LENGTH;
+        if maintainer.last_heartbeat_slot != 0
+            && self
+                .clock
+                .slot
+                .saturating_sub(maintainer.last_heartbeat_slot)
+                < heartbeat_interval
+        {
+            return None;
+        }
+
+        let instruction = lido::instruction::maintainer_heartbeat(
+            &self.solido_program_id,
+            &lido::instruction::MaintainerHeartbeatMeta {
+                lido: self.solido_address,
+                maintainer: self.maintainer_address,
+            },
+        );
+        let task = MaintenanceOutput::MaintainerHeartbeat {
+            previous_heartbeat_slot: maintainer.last_heartbeat_slot,
+        };
+        Some((instruction, task))
+    }
+
+    /// Return the maintenance that is pending, if any. This is the maintenance
+    /// that the maintainer on duty should perform next.
+    pub fn get_pending_maintenance(&self, tasks: &[MaintenanceTask]) -> Option<MaintenanceOutput> {
+        tasks
+            .iter()
+            .find_map(|task| self.try_maintenance_task(*task))
+            .map(|(_instruction, output)| output)
+    }
+
+    /// Return the instruction for the given task, if there is something to do.
+    pub fn try_maintenance_task(
+        &self,
+        task: MaintenanceTask,
+    ) -> Option<(Instruction, MaintenanceOutput)> {
+        match task {
+            MaintenanceTask::MergeStake => self.try_merge_on_all_stakes(),
+            MaintenanceTask::UpdateExchangeRate => self.try_update_exchange_rate(),
+            MaintenanceTask::UnstakeFromInactiveValidator => {
+                self.try_unstake_from_inactive_validator()
+            }
+            MaintenanceTask::CollectValidatorFee => self.try_collect_validator_fee(),
+            MaintenanceTask::WithdrawInactiveStake => self.try_withdraw_inactive_stake(),
+            MaintenanceTask::StakeDeposit => self.try_stake_deposit(),
+            MaintenanceTask::UnstakeFromActiveValidators => {
+                self.try_unstake_from_active_validators()
+            }
+            MaintenanceTask::ClaimValidatorFee => self.try_claim_validator_fee(),
+            MaintenanceTask::RemoveValidator => self.try_remove_validator(),
+        }
+    }
+
+    /// Return the instructions that can go in the same transaction as the
+    /// maintenance described by `output`.
+    ///
+    /// These are the same operation for all validators that need it, including
+    /// the one in `output`. Only operations that touch a single validator, and
+    /// do not depend on each other, can be bundled. For the others, such as
+    /// staking a deposit, the outcome of one instruction changes what the next
+    /// one should be, so we return nothing.
+    pub fn get_bundle_candidates(
+        &self,
+        output: &MaintenanceOutput,
+    ) -> Vec<(Instruction, MaintenanceOutput)> {
+        match output {
+            MaintenanceOutput::MergeStake { .. } => self.get_stake_merges(),
+            MaintenanceOutput::WithdrawInactiveStake { .. } => {
+                self.get_inactive_stake_withdrawals()
+            }
+            MaintenanceOutput::CollectValidatorFee { .. } => self.get_validator_fee_collections(),
+            MaintenanceOutput::ClaimValidatorFee { .. } => self.get_validator_fee_claims(),
+            _ => Vec::new(),
+        }
+    }
+
     /// Write metrics about the current Solido instance in Prometheus format.
-    pub fn write_prometheus<W: io::Write>(&self, out: &mut W) -> io::Result<()> {
+    pub fn write_prometheus<W: MetricSink>(&self, out: &mut W) -> io::Result<()> {
         use crate::prometheus::{
             write_metric, write_solido_metrics_as_prometheus, Metric, MetricFamily,
         };
@@ -1057,6 +1333,69 @@ impl SolidoState {
             },
         )?;
 
+        // Include the maintainer fee that every maintainer accrued, the
+        // operations that count towards the next distribution, and the most
+        // recent heartbeat.
+        write_metric(
+            out,
+            &MetricFamily {
+                name: "solido_maintainer_fee_credit_st_sol",
+                help: "Maintainer fee that the maintainer can claim, in stSOL.",
+                type_: "gauge",
+                metrics: self
+                    .solido
+                    .maintainers
+                    .entries
+                    .iter()
+                    .map(|maintainer| {
+                        Metric::new_st_sol(maintainer.entry.fee_credit)
+                            .at(self.produced_at)
+                            .with_label("maintainer_address", maintainer.pubkey.to_string())
+                    })
+                    .collect(),
+            },
+        )?;
+        write_metric(
+            out,
+            &MetricFamily {
+                name: "solido_maintainer_operations",
+                help: "Maintenance operations performed since the maintainer fee was last \
+                       distributed.",
+                type_: "gauge",
+                metrics: self
+                    .solido
+                    .maintainers
+                    .entries
+                    .iter()
+                    .map(|maintainer| {
+                        Metric::new(maintainer.entry.operations)
+                            .at(self.produced_at)
+                            .with_label("maintainer_address", maintainer.pubkey.to_string())
+                    })
+                    .collect(),
+            },
+        )?;
+        write_metric(
+            out,
+            &MetricFamily {
+                name: "solido_maintainer_last_heartbeat_slot",
+                help:
+                    "Slot of the most recent heartbeat of the maintainer, 0 if it never sent one.",
+                type_: "gauge",
+                metrics: self
+                    .solido
+                    .maintainers
+                    .entries
+                    .iter()
+                    .map(|maintainer| {
+                        Metric::new(maintainer.entry.last_heartbeat_slot)
+                            .at(self.produced_at)
+                            .with_label("maintainer_address", maintainer.pubkey.to_string())
+                    })
+                    .collect(),
+            },
+        )?;
+
         // Gather the different components that make up Solido's SOL balance.
         let mut balance_sol_metrics = vec![Metric::new_sol(self.get_effective_reserve())
             .at(self.produced_at)
@@ -1066,13 +1405,16 @@ impl SolidoState {
         let mut last_voted_timestamp_metrics = Vec::new();
         let mut identity_account_balance_metrics = Vec::new();
         let mut vote_credits_metrics = Vec::new();
+        let mut rewards_metrics = Vec::new();
+        let mut fee_metrics = Vec::new();
+        let mut epochs_active_metrics = Vec::new();
+        let mut last_rewards_epoch_metrics = Vec::new();
 
         // Track if there are any unclaimed (and therefore unminted) validation
         // fees.
         let mut unclaimed_fees = StLamports(0);
 
         for ((((validator, stake_accounts), vote_account), identity_account_balance), info) in self
-            .solido
             .validators
             .entries
             .iter()
@@ -1139,6 +1481,16 @@ impl SolidoState {
             identity_account_balance_metrics
                 .push(annotator.add_labels(Metric::new_sol(*identity_account_balance)));
             vote_credits_metrics.push(annotator.add_labels(Metric::new(vote_account.credits())));
+
+            let validator_metrics = &validator.entry.metrics;
+            rewards_metrics
+                .push(annotator.add_labels(Metric::new_sol(validator_metrics.rewards_total)));
+            fee_metrics
+                .push(annotator.add_labels(Metric::new_st_sol(validator_metrics.fee_st_sol_total)));
+            epochs_active_metrics
+                .push(annotator.add_labels(Metric::new(validator_metrics.epochs_active)));
+            last_rewards_epoch_metrics
+                .push(annotator.add_labels(Metric::new(validator_metrics.last_rewards_epoch)));
         }
 
         write_metric(
@@ -1195,6 +1547,50 @@ impl SolidoState {
             },
         )?;
 
+        write_metric(
+            out,
+            &MetricFamily {
+                name: "solido_validator_rewards_sol_total",
+                help: "Total rewards that Solido observed for the validator, both from the vote \
+                       account and from its stake accounts. On-chain this value can only increase, \
+                       but decreases in the observed value can happen due to reorgs.",
+                type_: "gauge",
+                metrics: rewards_metrics,
+            },
+        )?;
+
+        write_metric(
+            out,
+            &MetricFamily {
+                name: "solido_validator_fee_st_sol_total",
+                help: "Total validation fees attributed to the validator, whether credited or \
+                       paid directly. On-chain this value can only increase, but decreases in the \
+                       observed value can happen due to reorgs.",
+                type_: "gauge",
+                metrics: fee_metrics,
+            },
+        )?;
+
+        write_metric(
+            out,
+            &MetricFamily {
+                name: "solido_validator_epochs_active_total",
+                help: "Number of epochs in which the validator was observed to be active.",
+                type_: "gauge",
+                metrics: epochs_active_metrics,
+            },
+        )?;
+
+        write_metric(
+            out,
+            &MetricFamily {
+                name: "solido_validator_last_rewards_epoch",
+                help: "The last epoch in which Solido observed rewards for the validator.",
+                type_: "gauge",
+                metrics: last_rewards_epoch_metrics,
+            },
+        )?;
+
         let st_sol_supply = StLamports(self.st_sol_mint.supply);
 
         write_metric(
@@ -1210,6 +1606,9 @@ impl SolidoState {
                     Metric::new_st_sol(unclaimed_fees)
                         .at(self.produced_at)
                         .with_label("status", "unclaimed_fee".to_string()),
+                    Metric::new_st_sol(self.solido.maintainer_fee_credit)
+                        .at(self.produced_at)
+                        .with_label("status", "unclaimed_maintainer_fee".to_string()),
                 ],
             },
         )?;
@@ -1271,6 +1670,27 @@ impl SolidoState {
         rewards_withdraw_authority
     }
 
+    /// Return the page of validator stSOL fee accounts to pay out to directly
+    /// when collecting the fee of the validator at `validator_index`.
+    ///
+    /// The page starts at that validator and wraps around, so every validator
+    /// is in one of the pages, and over an epoch, when we collect from all
+    /// validators, the pages cover the validator list.
+    fn get_validator_fee_st_sol_accounts(&self, validator_index: usize) -> Vec<Pubkey> {
+        match self.solido.fee_payout_mode {
+            FeePayoutMode::Credit => Vec::new(),
+            FeePayoutMode::Direct => self
+                .validators
+                .entries
+                .iter()
+                .cycle()
+                .skip(validator_index)
+                .take(self.validators.len().min(lido::MAXIMUM_DIRECT_FEE_ACCOUNTS))
+                .map(|validator| validator.entry.fee_address)
+                .collect(),
+        }
+    }
+
     fn get_mint_authority(&self) -> Pubkey {
         let (mint_authority, _bump_seed_authority) = lido::find_authority_program_address(
             &self.solido_program_id,
@@ -1288,6 +1708,55 @@ impl SolidoState {
     /// is on duty, see [`get_current_maintainer_duty`].
     const MAINTAINER_DUTY_PAUSE_LENGTH: Slot = 10;
 
+    /// The number of duty slices after which a maintainer that sends heartbeats,
+    /// but did not send one recently, is skipped in the duty rotation, see
+    /// [`get_duty_rotation`].
+    const MAINTAINER_SILENCE_SLICES: u64 = 30;
+
+    /// The number of duty slices after which a maintainer that sends heartbeats
+    /// sends the next one. This is well below `MAINTAINER_SILENCE_SLICES`, so a
+    /// single failed heartbeat does not get the maintainer skipped.
+    const MAINTAINER_HEARTBEAT_INTERVAL_SLICES: u64 = 10;
+
+    /// The number of slots that maintenance needs to be pending in a duty slice
+    /// before the next maintainer in the rotation takes over, see
+    /// [`should_take_over_duty`].
+    const MAINTAINER_FAILOVER_DELAY: Slot = 50;
+
+    /// Return whether the maintainer stopped sending heartbeats.
+    ///
+    /// Heartbeats are optional, a maintainer that never sent one is never silent.
+    fn is_maintainer_silent(&self, maintainer: &Maintainer) -> bool {
+        maintainer.last_heartbeat_slot != 0
+            && self
+                .clock
+                .slot
+                .saturating_sub(maintainer.last_heartbeat_slot)
+                > Self::MAINTAINER_SILENCE_SLICES * Self::MAINTAINER_DUTY_SLICE_LENGTH
+    }
+
+    /// Return the maintainers that take turns being on duty, sorted by public key.
+    ///
+    /// Maintainers that stopped sending heartbeats are skipped, so their duty
+    /// slices do not go unused every cycle. If all of them are silent, we
+    /// rather have a slow rotation than none at all, so then nobody is skipped.
+    fn get_duty_rotation(&self) -> Vec<Pubkey> {
+        let maintainers = &self.solido.maintainers.entries;
+        let live: Vec<Pubkey> = maintainers
+            .iter()
+            .filter(|maintainer| !self.is_maintainer_silent(&maintainer.entry))
+            .map(|maintainer| maintainer.pubkey)
+            .collect();
+        if live.is_empty() {
+            maintainers
+                .iter()
+                .map(|maintainer| maintainer.pubkey)
+                .collect()
+        } else {
+            live
+        }
+    }
+
     /// Return the maintainer who is currently on "maintainer duty".
     ///
     /// The maintenance tasks need to be executed by somebody. Every task
@@ -1320,9 +1789,13 @@ impl SolidoState {
     /// As an middle ground between those two, we take 100 slots, which at a
     /// block time of 550ms is a little under a minute per maintainer. If only
     /// one maintainer is offline, this means maintenance operations get delayed
-    /// by at most ~55s.
+    /// by at most ~55s. To limit that further, the next maintainer in the
+    /// rotation takes over when maintenance stays pending for too long, see
+    /// [`should_take_over_duty`], and maintainers that stopped sending
+    /// heartbeats are left out of the rotation, see [`get_duty_rotation`].
     pub fn get_current_maintainer_duty(&self) -> Option<Pubkey> {
-        if self.solido.maintainers.entries.is_empty() {
+        let rotation = self.get_duty_rotation();
+        if rotation.is_empty() {
             return None;
         }
 
@@ -1337,8 +1810,64 @@ impl SolidoState {
             return None;
         }
 
-        let maintainer_index = duty_slice % self.solido.maintainers.len() as u64;
-        Some(self.solido.maintainers.entries[maintainer_index as usize].pubkey)
+        // The maintainers are sorted by public key, so every maintainer derives
+        // the same rotation from the on-chain state. Adding or removing a
+        // maintainer, or one going silent, can shift the others to a different
+        // slice.
+        let maintainer_index = duty_slice % rotation.len() as u64;
+        Some(rotation[maintainer_index as usize])
+    }
+
+    /// Return the maintainer that takes over from the maintainer on duty, if
+    /// that one fails to perform the pending maintenance. This is the next
+    /// maintainer in the rotation.
+    pub fn get_failover_maintainer(&self) -> Option<Pubkey> {
+        let current_maintainer = self.get_current_maintainer_duty()?;
+        let rotation = self.get_duty_rotation();
+        let current_index = rotation
+            .iter()
+            .position(|maintainer| *maintainer == current_maintainer)?;
+        let failover_maintainer = rotation[(current_index + 1) % rotation.len()];
+        if failover_maintainer == current_maintainer {
+            None
+        } else {
+            Some(failover_maintainer)
+        }
+    }
+
+    /// Return whether we should perform maintenance even though we are not on
+    /// duty, because the maintainer on duty appears to be offline.
+    ///
+    /// `pending_since` is the slot at which we first saw the maintenance that
+    /// is still pending. We take over if we are the failover maintainer, and
+    /// the maintainer on duty had at least `MAINTAINER_FAILOVER_DELAY` slots of
+    /// its slice to perform the maintenance, but did not do so.
+    pub fn should_take_over_duty(&self, pending_since: Slot) -> bool {
+        if self.get_failover_maintainer() != Some(self.maintainer_address) {
+            return false;
+        }
+        let duty_slice_start = (self.clock.slot / Self::MAINTAINER_DUTY_SLICE_LENGTH)
+            * Self::MAINTAINER_DUTY_SLICE_LENGTH;
+        let missed_since = pending_since.max(duty_slice_start);
+        self.clock.slot >= missed_since + Self::MAINTAINER_FAILOVER_DELAY
+    }
+
+    /// Return the balance of the account of the maintainer executing the
+    /// maintenance, or None if it is not a member of `solido.maintainers`.
+    pub fn get_maintainer_balance(&self) -> Option<Lamports> {
+        self.solido
+            .maintainers
+            .entries
+            .iter()
+            .zip(&self.maintainer_balances)
+            .find(|(m, _)| m.pubkey == self.maintainer_address)
+            .map(|(_, balance)| *balance)
+    }
+
+    /// Return the first slot of the next epoch.
+    pub fn get_next_epoch_start_slot(&self) -> Slot {
+        self.epoch_schedule
+            .get_first_slot_in_epoch(self.clock.epoch + 1)
     }
 
     /// Return the slot at which the given maintainer's next duty slice starts.
@@ -1348,23 +1877,19 @@ impl SolidoState {
     ///
     /// See also [`get_current_maintainer_duty`].
     pub fn get_next_maintainer_duty_slot(&self, maintainer: &Pubkey) -> Option<Slot> {
-        if self.solido.maintainers.entries.is_empty() {
+        let rotation = self.get_duty_rotation();
+        if rotation.is_empty() {
             return None;
         }
 
         // Compute the start of the current "cycle", where in every cycle, every
-        // maintainer has a single duty slice.
-        let cycle_length =
-            self.solido.maintainers.entries.len() as u64 * Self::MAINTAINER_DUTY_SLICE_LENGTH;
+        // maintainer in the rotation has a single duty slice.
+        let cycle_length = rotation.len() as u64 * Self::MAINTAINER_DUTY_SLICE_LENGTH;
         let current_cycle_start_slot = (self.clock.slot / cycle_length) * cycle_length;
 
-        // Compute the start of our slice within the current cycle.
-        let self_index = self
-            .solido
-            .maintainers
-            .entries
-            .iter()
-            .position(|m| m.pubkey == *maintainer)? as u64;
+        // Compute the start of our slice within the current cycle. A
+        // maintainer that is left out of the rotation has no next duty.
+        let self_index = rotation.iter().position(|m| m == maintainer)? as u64;
         let self_slice_start_slot =
             current_cycle_start_slot + self_index * Self::MAINTAINER_DUTY_SLICE_LENGTH;
 
@@ -1412,66 +1937,267 @@ impl SolidoState {
     }
 }
 
-pub fn try_perform_maintenance(
-    config: &mut SnapshotConfig,
+/// The maximum number of compute units that a transaction may consume.
+const MAX_COMPUTE_UNITS_PER_TRANSACTION: u64 = 200_000;
+
+/// Upper bound on the compute units that an instruction consumes, as a constant
+/// part plus a part per validator.
+struct ComputeBudget {
+    base: u64,
+    per_validator: u64,
+}
+
+impl ComputeBudget {
+    fn compute_units(&self, num_validators: usize) -> u64 {
+        self.base + self.per_validator * num_validators as u64
+    }
+}
+
+/// Return the compute budget of the operations that we bundle.
+///
+/// These are the budgets that `program/tests/tests/compute_units.rs` asserts,
+/// keep them in sync.
+fn get_bundle_compute_budget(output: &MaintenanceOutput) -> Option<ComputeBudget> {
+    match output {
+        MaintenanceOutput::MergeStake { .. } => Some(ComputeBudget {
+            base: 60_000,
+            per_validator: 200,
+        }),
+        MaintenanceOutput::WithdrawInactiveStake { .. } => Some(ComputeBudget {
+            base: 50_000,
+            per_validator: 200,
+        }),
+        MaintenanceOutput::CollectValidatorFee { .. } => Some(ComputeBudget {
+            base: 50_000,
+            per_validator: 200,
+        }),
+        MaintenanceOutput::ClaimValidatorFee { .. } => Some(ComputeBudget {
+            base: 40_000,
+            per_validator: 20,
+        }),
+        _ => None,
+    }
+}
+
+/// Return whether a transaction with these instructions, signed by the
+/// maintainer only, fits in a single packet.
+fn fits_in_transaction(state: &SolidoState, instructions: &[Instruction]) -> bool {
+    let message = Message::new(instructions, Some(&state.maintainer_address));
+    let transaction = Transaction::new_unsigned(message);
+    match bincode::serialized_size(&transaction) {
+        Ok(size) => size <= PACKET_DATA_SIZE as u64,
+        Err(..) => false,
+    }
+}
+
+/// Select the maintenance to perform, if there is any.
+///
+/// Only the given tasks are considered, they are in order of priority. The
+/// first instruction is the most urgent maintenance, the same that we
+/// would perform if we could only send one instruction. If it is one of the
+/// operations that is independent per validator, we bundle the same operation
+/// for other validators with it, up to `max_instructions`, and as long as the
+/// transaction fits in a packet and stays within the compute limit.
+///
+/// Returns an error if the maintainer does not have enough SOL to pay for the
+/// transaction.
+fn select_maintenance(
     state: &SolidoState,
-) -> Result<Option<MaintenanceOutput>> {
+    tasks: &[MaintenanceTask],
+    max_instructions: usize,
+) -> Result<Vec<(Instruction, MaintenanceOutput)>> {
     // To prevent the maintenance transactions failing with mysterious errors
     // that are difficult to debug, before we do any maintenance, do a sanity
     // check to ensure that the maintainer has at least some SOL to pay the
     // transaction fees.
-    let minimum_maintainer_balance = Lamports(100_000_000);
-    match state
-        .solido
-        .maintainers
-        .entries
-        .iter()
-        .zip(&state.maintainer_balances)
-        .filter(|(m, _)| m.pubkey == state.maintainer_address)
-        .map(|(_, balance)| balance)
-        .next()
-    {
-        Some(balance) if balance < &minimum_maintainer_balance => {
+    match state.get_maintainer_balance() {
+        Some(balance) if balance < MINIMUM_MAINTAINER_BALANCE => {
             return Err(MaintenanceError::new(format!(
                 "Balance of the maintainer account {} is less than {}. \
                 Please fund the maintainer account.",
-                state.maintainer_address, minimum_maintainer_balance,
+                state.maintainer_address, MINIMUM_MAINTAINER_BALANCE,
             ))
             .into())
         }
         _ => {}
     }
 
-    // Try all of these operations one by one, and select the first one that
-    // produces an instruction.
-    let instruction_output: Option<(Instruction, MaintenanceOutput)> = None
-        // Merging stake accounts goes before updating validator balance, to
-        // ensure that the balance update needs to reference as few accounts
-        // as possible.
-        .or_else(|| state.try_merge_on_all_stakes())
-        .or_else(|| state.try_update_exchange_rate())
-        .or_else(|| state.try_unstake_from_inactive_validator())
-        // Collecting validator fees goes after updating the exchange rate,
-        // because it may be rejected if the exchange rate is outdated.
-        .or_else(|| state.try_collect_validator_fee())
-        // Same for updating the validator balance.
-        .or_else(|| state.try_withdraw_inactive_stake())
-        .or_else(|| state.try_stake_deposit())
-        .or_else(|| state.try_unstake_from_active_validators())
-        .or_else(|| state.try_claim_validator_fee())
-        .or_else(|| state.try_remove_validator());
-
-    match instruction_output {
-        Some((instruction, output)) => {
-            // For maintenance operations, the maintainer is the only signer,
-            // and that should be sufficient.
-            config.sign_and_send_transaction(&[instruction], &[config.signer])?;
-            Ok(Some(output))
+    // Try the enabled operations one by one, in order of priority, and select
+    // the first one that produces an instruction.
+    let instruction_output = tasks
+        .iter()
+        .find_map(|task| state.try_maintenance_task(*task));
+
+    let (first_instruction, first_output) = match instruction_output {
+        Some(instruction_output) => instruction_output,
+        None => return Ok(Vec::new()),
+    };
+
+    // All instructions in the bundle are the same operation, so they have the
+    // same budget.
+    let compute_units_per_instruction = get_bundle_compute_budget(&first_output)
+        .map(|budget| budget.compute_units(state.validators.len()));
+
+    let mut instructions = vec![first_instruction];
+    let mut outputs = vec![first_output];
+
+    for (instruction, output) in state.get_bundle_candidates(&outputs[0]) {
+        if instructions.len() >= max_instructions {
+            break;
+        }
+        if let Some(compute_units) = compute_units_per_instruction {
+            if compute_units * (instructions.len() as u64 + 1) > MAX_COMPUTE_UNITS_PER_TRANSACTION {
+                break;
+            }
+        }
+        // The first candidate is the instruction we already selected.
+        if output == outputs[0] {
+            continue;
+        }
+        instructions.push(instruction);
+        if !fits_in_transaction(state, &instructions) {
+            instructions.pop();
+            break;
+        }
+        outputs.push(output);
+    }
+
+    Ok(instructions.into_iter().zip(outputs).collect())
+}
+
+/// Sign and send a transaction with the given maintenance instructions.
+///
+/// Returns the maintenance and the fee that its transaction paid, also if the
+/// transaction failed. An error is only returned if we did not send anything.
+fn send_maintenance(
+    config: &mut SnapshotConfig,
+    maintenance: Vec<(Instruction, MaintenanceOutput)>,
+) -> Result<SentMaintenance> {
+    let (instructions, outputs): (Vec<Instruction>, Vec<MaintenanceOutput>) =
+        maintenance.into_iter().unzip();
+
+    // For maintenance operations, the maintainer is the only signer,
+    // and that should be sufficient.
+    let transaction = config.sign_transaction(&instructions, &[config.signer])?;
+    let result = config.send_transaction(&transaction);
+
+    // A transaction that executed paid the fee, also if it failed. If we
+    // got an error, it may have failed before it executed (e.g. in
+    // preflight), or it may have executed and failed, or we may have
+    // failed to confirm it, so ask the RPC whether it executed.
+    let executed = match result {
+        Ok(..) => true,
+        Err(..) => matches!(
+            config
+                .client
+                .get_signature_status(&transaction.signatures[0]),
+            Ok(Some(..))
+        ),
+    };
+    let fee = if executed {
+        match config
+            .client
+            .get_transaction_fee(&transaction.signatures[0])
+        {
+            Ok(fee) => fee,
+            Err(err) => {
+                // The transaction was sent regardless, so we only lose track
+                // of the fee, don't fail the maintenance for that.
+                println!(
+                    "Failed to get the fee of transaction {}: {}",
+                    transaction.signatures[0], err
+                );
+                Lamports(0)
+            }
         }
+    } else {
+        Lamports(0)
+    };
+
+    Ok(SentMaintenance {
+        outputs,
+        signature: transaction.signatures[0],
+        fee,
+        result: result.map(|_signature| ()),
+    })
+}
+
+/// Perform the maintenance selected by [`select_maintenance`], if there is any.
+///
+/// Usually this sends a single transaction. When a transaction with several
+/// instructions fails, one of them may be the culprit, and that should not
+/// hold back the others, so then we retry the instructions one by one.
+///
+/// Returns the transactions that we sent, also the ones that failed. An error
+/// is only returned if we did not send anything.
+pub fn try_perform_maintenance(
+    config: &mut SnapshotConfig,
+    state: &SolidoState,
+    tasks: &[MaintenanceTask],
+    max_instructions: usize,
+) -> Result<Vec<SentMaintenance>> {
+    let maintenance = select_maintenance(state, tasks, max_instructions)?;
+    if maintenance.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let is_bundle = maintenance.len() > 1;
+    let sent = send_maintenance(config, maintenance.clone())?;
+    if !is_bundle || sent.result.is_ok() {
+        return Ok(vec![sent]);
+    }
+
+    let mut sent_all = vec![sent];
+    for instruction_output in maintenance {
+        match send_maintenance(config, vec![instruction_output]) {
+            Ok(sent) => sent_all.push(sent),
+            // We could not even sign, there is no point in trying the
+            // remaining instructions.
+            Err(..) => break,
+        }
+    }
+
+    Ok(sent_all)
+}
+
+/// Send a heartbeat for the maintainer, if one is due.
+///
+/// See [`SolidoState::try_maintainer_heartbeat`].
+pub fn try_send_maintainer_heartbeat(
+    config: &mut SnapshotConfig,
+    state: &SolidoState,
+) -> Result<Option<SentMaintenance>> {
+    match state.try_maintainer_heartbeat() {
+        Some(heartbeat) => Ok(Some(send_maintenance(config, vec![heartbeat])?)),
         None => Ok(None),
     }
 }
 
+/// Like [`try_perform_maintenance`], but simulate the transaction instead of
+/// sending it.
+pub fn try_simulate_maintenance(
+    config: &mut SnapshotConfig,
+    state: &SolidoState,
+    tasks: &[MaintenanceTask],
+    max_instructions: usize,
+) -> Result<Option<SimulatedMaintenance>> {
+    let maintenance = select_maintenance(state, tasks, max_instructions)?;
+    if maintenance.is_empty() {
+        return Ok(None);
+    }
+
+    let (instructions, mut outputs): (Vec<Instruction>, Vec<MaintenanceOutput>) =
+        maintenance.into_iter().unzip();
+    let result = config.sign_and_simulate_transaction(&instructions, &[config.signer])?;
+    let bundled = outputs.split_off(1);
+    Ok(Some(SimulatedMaintenance {
+        maintenance: outputs.remove(0),
+        bundled,
+        error: result.err.map(|err| err.to_string()),
+        logs: result.logs.unwrap_or_default(),
+    }))
+}
+
 /// Inspect the on-chain Solido state, and if there is maintenance that can be
 /// performed, do so. Returns a description of the task performed, if any.
 ///
@@ -1488,21 +2214,50 @@ pub fn run_perform_maintenance(
         opts.solido_address(),
         *opts.stake_time(),
     )?;
-    try_perform_maintenance(config, &state)
+    // This command performs a single step, so it sends a single instruction.
+    let mut sent = try_perform_maintenance(config, &state, &MaintenanceTask::ALL, 1)?;
+    match sent.pop() {
+        Some(SentMaintenance {
+            mut outputs,
+            result: Ok(()),
+            ..
+        }) => Ok(outputs.pop()),
+        Some(SentMaintenance {
+            result: Err(err), ..
+        }) => Err(err.into()),
+        None => Ok(None),
+    }
+}
+
+/// Like [`run_perform_maintenance`], but simulate the transaction instead of
+/// sending it. Returns the simulation result of the task, if any.
+pub fn run_simulate_maintenance(
+    config: &mut SnapshotConfig,
+    opts: &PerformMaintenanceOpts,
+) -> Result<Option<SimulatedMaintenance>> {
+    let state = SolidoState::new(
+        config,
+        opts.solido_program_id(),
+        opts.solido_address(),
+        *opts.stake_time(),
+    )?;
+    try_simulate_maintenance(config, &state, &MaintenanceTask::ALL, 1)
 }
 
 #[cfg(test)]
-mod test {
+pub mod test {
 
     use super::*;
+    use lido::state::Maintainer;
 
     /// Produce a new state with `default` Solido instance in it, and random pubkeys.
-    fn new_empty_solido() -> SolidoState {
+    pub fn new_empty_solido() -> SolidoState {
         let mut state = SolidoState {
             produced_at: SystemTime::UNIX_EPOCH,
             solido_program_id: Pubkey::new_unique(),
             solido_address: Pubkey::new_unique(),
             solido: Lido::default(),
+            validators: Validators::new(0),
             validator_stake_accounts: vec![],
             validator_unstake_accounts: vec![],
             validator_vote_account_balances: vec![],
@@ -1513,6 +2268,7 @@ mod test {
             st_sol_mint: Mint::default(),
             reserve_address: Pubkey::new_unique(),
             reserve_account: Account::default(),
+            exchange_rate_oracle_address: Pubkey::new_unique(),
             rent: Rent::default(),
             clock: Clock::default(),
             epoch_schedule: EpochSchedule::default(),
@@ -1538,9 +2294,8 @@ mod test {
         let mut state = new_empty_solido();
 
         // Add a validators, without any stake accounts yet.
-        state.solido.validators.maximum_entries = 1;
+        state.validators.maximum_entries = 1;
         state
-            .solido
             .validators
             .add(Pubkey::new_unique(), Validator::new(Pubkey::new_unique()))
             .unwrap();
@@ -1568,14 +2323,12 @@ mod test {
         let mut state = new_empty_solido();
 
         // Add two validators, both without any stake account yet.
-        state.solido.validators.maximum_entries = 2;
+        state.validators.maximum_entries = 2;
         state
-            .solido
             .validators
             .add(Pubkey::new_unique(), Validator::new(Pubkey::new_unique()))
             .unwrap();
         state
-            .solido
             .validators
             .add(Pubkey::new_unique(), Validator::new(Pubkey::new_unique()))
             .unwrap();
@@ -1586,7 +2339,7 @@ mod test {
         // balance.
         state.reserve_account.lamports += 4 * MINIMUM_STAKE_ACCOUNT_BALANCE.0;
 
-        let stake_account_0 = state.solido.validators.entries[0].find_stake_account_address(
+        let stake_account_0 = state.validators.entries[0].find_stake_account_address(
             &state.solido_program_id,
             &state.solido_address,
             0,
@@ -1597,13 +2350,13 @@ mod test {
         assert_eq!(
             state.try_stake_deposit().unwrap().1,
             MaintenanceOutput::StakeDeposit {
-                validator_vote_account: state.solido.validators.entries[0].pubkey,
+                validator_vote_account: state.validators.entries[0].pubkey,
                 amount: (MINIMUM_STAKE_ACCOUNT_BALANCE * 2).unwrap(),
                 stake_account: stake_account_0.0,
             }
         );
 
-        let stake_account_1 = state.solido.validators.entries[1].find_stake_account_address(
+        let stake_account_1 = state.validators.entries[1].find_stake_account_address(
             &state.solido_program_id,
             &state.solido_address,
             0,
@@ -1612,7 +2365,7 @@ mod test {
 
         // Pretend that the amount was actually staked.
         state.reserve_account.lamports -= 2 * MINIMUM_STAKE_ACCOUNT_BALANCE.0;
-        let validator = &mut state.solido.validators.entries[0].entry;
+        let validator = &mut state.validators.entries[0].entry;
         validator.stake_accounts_balance = validator
             .stake_accounts_balance
             .add((MINIMUM_STAKE_ACCOUNT_BALANCE * 2).unwrap())
@@ -1623,7 +2376,7 @@ mod test {
         assert_eq!(
             state.try_stake_deposit().unwrap().1,
             MaintenanceOutput::StakeDeposit {
-                validator_vote_account: state.solido.validators.entries[1].pubkey,
+                validator_vote_account: state.validators.entries[1].pubkey,
                 amount: (MINIMUM_STAKE_ACCOUNT_BALANCE * 2).unwrap(),
                 stake_account: stake_account_1.0,
             }
@@ -1639,7 +2392,7 @@ mod test {
                 state
                     .solido
                     .maintainers
-                    .add(Pubkey::new_unique(), ())
+                    .add(Pubkey::new_unique(), Maintainer::default())
                     .unwrap();
             }
 
@@ -1700,7 +2453,11 @@ mod test {
         let mut state = new_empty_solido();
         let maintainer = Pubkey::new_unique();
         state.solido.maintainers.maximum_entries = 1;
-        state.solido.maintainers.add(maintainer, ()).unwrap();
+        state
+            .solido
+            .maintainers
+            .add(maintainer, Maintainer::default())
+            .unwrap();
 
         for _ in 0..10 {
             let next_slot = state.get_next_maintainer_duty_slot(&maintainer).unwrap();
@@ -1709,6 +2466,261 @@ mod test {
         }
     }
 
+    #[test]
+    fn maintainer_duty_does_not_depend_on_insertion_order() {
+        let keys: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        let mut state_forward = new_empty_solido();
+        let mut state_backward = new_empty_solido();
+        state_forward.solido.maintainers.maximum_entries = 5;
+        state_backward.solido.maintainers.maximum_entries = 5;
+        for key in &keys {
+            state_forward
+                .solido
+                .maintainers
+                .add(*key, Maintainer::default())
+                .unwrap();
+        }
+        for key in keys.iter().rev() {
+            state_backward
+                .solido
+                .maintainers
+                .add(*key, Maintainer::default())
+                .unwrap();
+        }
+
+        for slot in 0..5 * SolidoState::MAINTAINER_DUTY_SLICE_LENGTH {
+            state_forward.clock.slot = slot;
+            state_backward.clock.slot = slot;
+            assert_eq!(
+                state_forward.get_current_maintainer_duty(),
+                state_backward.get_current_maintainer_duty(),
+            );
+        }
+    }
+
+    #[test]
+    fn select_maintenance_bundles_fee_claims_within_the_transaction_limits() {
+        let mut state = new_empty_solido();
+
+        let num_validators = 50;
+        state.validators.maximum_entries = num_validators;
+        for _ in 0..num_validators {
+            let mut validator = Validator::new(Pubkey::new_unique());
+            validator.fee_credit = StLamports(1_000);
+            state
+                .validators
+                .add(Pubkey::new_unique(), validator)
+                .unwrap();
+        }
+
+        // With a limit of one instruction, we claim the fee of the first validator only.
+        let maintenance = select_maintenance(&state, &MaintenanceTask::ALL, 1).unwrap();
+        assert_eq!(maintenance.len(), 1);
+        assert_eq!(
+            maintenance[0].1,
+            MaintenanceOutput::ClaimValidatorFee {
+                validator_vote_account: state.validators.entries[0].pubkey,
+                fee_rewards: StLamports(1_000),
+            }
+        );
+
+        let maintenance = select_maintenance(&state, &MaintenanceTask::ALL, 4).unwrap();
+        assert_eq!(maintenance.len(), 4);
+        for (validator, (_instruction, output)) in
+            state.validators.entries.iter().zip(maintenance.iter())
+        {
+            assert_eq!(output.operation(), "ClaimValidatorFee");
+            assert!(matches!(
+                output,
+                MaintenanceOutput::ClaimValidatorFee { validator_vote_account, .. }
+                if *validator_vote_account == validator.pubkey
+            ));
+        }
+
+        // Claims for all validators do not fit in a single transaction, we
+        // should stop at the compute limit, which is reached before the
+        // transaction size limit.
+        let maintenance =
+            select_maintenance(&state, &MaintenanceTask::ALL, num_validators as usize).unwrap();
+        let compute_units = get_bundle_compute_budget(&maintenance[0].1)
+            .unwrap()
+            .compute_units(num_validators as usize);
+        assert_eq!(
+            maintenance.len() as u64,
+            MAX_COMPUTE_UNITS_PER_TRANSACTION / compute_units
+        );
+        let instructions: Vec<Instruction> = state
+            .get_validator_fee_claims()
+            .into_iter()
+            .map(|(instruction, _output)| instruction)
+            .collect();
+        assert!(fits_in_transaction(
+            &state,
+            &instructions[..maintenance.len()]
+        ));
+        assert!(!fits_in_transaction(&state, &instructions));
+    }
+
+    #[test]
+    fn select_maintenance_respects_task_order_and_disabled_tasks() {
+        let mut state = new_empty_solido();
+        // The exchange rate has not been updated in this epoch yet.
+        state.clock.epoch = 1;
+        state.validators.maximum_entries = 1;
+        let mut validator = Validator::new(Pubkey::new_unique());
+        validator.fee_credit = StLamports(1_000);
+        state
+            .validators
+            .add(Pubkey::new_unique(), validator)
+            .unwrap();
+
+        let select_operation = |tasks: &[MaintenanceTask]| {
+            select_maintenance(&state, tasks, 1)
+                .unwrap()
+                .first()
+                .map(|(_instruction, output)| output.operation())
+        };
+
+        assert_eq!(
+            select_operation(&MaintenanceTask::ALL),
+            Some("UpdateExchangeRate")
+        );
+        assert_eq!(
+            select_operation(&[
+                MaintenanceTask::ClaimValidatorFee,
+                MaintenanceTask::UpdateExchangeRate,
+            ]),
+            Some("ClaimValidatorFee")
+        );
+        assert_eq!(
+            select_operation(&[MaintenanceTask::ClaimValidatorFee]),
+            Some("ClaimValidatorFee")
+        );
+        assert_eq!(select_operation(&[MaintenanceTask::StakeDeposit]), None);
+        assert_eq!(select_operation(&[]), None);
+    }
+
+    /// Produce a state with three maintainers, sorted by public key, where we are the second one.
+    fn new_solido_with_three_maintainers() -> (SolidoState, Vec<Pubkey>) {
+        let mut state = new_empty_solido();
+        state.solido.maintainers.maximum_entries = 3;
+        for _ in 0..3 {
+            state
+                .solido
+                .maintainers
+                .add(Pubkey::new_unique(), Maintainer::default())
+                .unwrap();
+        }
+        let keys: Vec<Pubkey> = state
+            .solido
+            .maintainers
+            .entries
+            .iter()
+            .map(|m| m.pubkey)
+            .collect();
+        state.maintainer_address = keys[1];
+        (state, keys)
+    }
+
+    #[test]
+    fn duty_rotation_skips_silent_maintainers() {
+        let (mut state, keys) = new_solido_with_three_maintainers();
+        let silence =
+            SolidoState::MAINTAINER_SILENCE_SLICES * SolidoState::MAINTAINER_DUTY_SLICE_LENGTH;
+        state.clock.slot = 10 * silence;
+
+        // Maintainers that never sent a heartbeat are never skipped.
+        assert_eq!(state.get_duty_rotation(), keys);
+
+        // The first maintainer sent a heartbeat recently, the third one did
+        // not send one for too long.
+        state.solido.maintainers.entries[0]
+            .entry
+            .last_heartbeat_slot = state.clock.slot - 1;
+        state.solido.maintainers.entries[2]
+            .entry
+            .last_heartbeat_slot = state.clock.slot - silence - 1;
+        assert_eq!(state.get_duty_rotation(), vec![keys[0], keys[1]]);
+        assert_eq!(state.get_next_maintainer_duty_slot(&keys[2]), None);
+        for slot in
+            state.clock.slot..state.clock.slot + 4 * SolidoState::MAINTAINER_DUTY_SLICE_LENGTH
+        {
+            state.clock.slot = slot;
+            assert_ne!(state.get_current_maintainer_duty(), Some(keys[2]));
+        }
+
+        // If all maintainers are silent, nobody is skipped.
+        for maintainer in state.solido.maintainers.entries.iter_mut() {
+            maintainer.entry.last_heartbeat_slot = 1;
+        }
+        assert_eq!(state.get_duty_rotation(), keys);
+    }
+
+    #[test]
+    fn failover_maintainer_takes_over_after_delay() {
+        let (mut state, keys) = new_solido_with_three_maintainers();
+
+        // The first maintainer is on duty at the start of its slice, we are next.
+        let slice_start = state.get_next_maintainer_duty_slot(&keys[0]).unwrap();
+        state.clock.slot = slice_start;
+        assert_eq!(state.get_current_maintainer_duty(), Some(keys[0]));
+        assert_eq!(state.get_failover_maintainer(), Some(keys[1]));
+
+        // Maintenance that was already pending before the slice started counts
+        // from the start of the slice.
+        let pending_since = slice_start - 20;
+        assert!(!state.should_take_over_duty(pending_since));
+        state.clock.slot = slice_start + SolidoState::MAINTAINER_FAILOVER_DELAY - 1;
+        assert!(!state.should_take_over_duty(pending_since));
+        state.clock.slot = slice_start + SolidoState::MAINTAINER_FAILOVER_DELAY;
+        assert!(state.should_take_over_duty(pending_since));
+
+        // Maintenance that became pending during the slice counts from then.
+        assert!(!state.should_take_over_duty(slice_start + 1));
+
+        // During the pause nobody is on duty, so there is nothing to take over.
+        state.clock.slot = slice_start + SolidoState::MAINTAINER_DUTY_SLICE_LENGTH - 1;
+        assert_eq!(state.get_failover_maintainer(), None);
+        assert!(!state.should_take_over_duty(pending_since));
+
+        // In our own slice, the third maintainer is the failover maintainer.
+        state.clock.slot = slice_start + SolidoState::MAINTAINER_DUTY_SLICE_LENGTH;
+        assert_eq!(state.get_current_maintainer_duty(), Some(keys[1]));
+        assert_eq!(state.get_failover_maintainer(), Some(keys[2]));
+        assert!(!state.should_take_over_duty(pending_since));
+    }
+
+    #[test]
+    fn maintainer_heartbeat_is_sent_when_due() {
+        let (mut state, _keys) = new_solido_with_three_maintainers();
+        let interval = SolidoState::MAINTAINER_HEARTBEAT_INTERVAL_SLICES
+            * SolidoState::MAINTAINER_DUTY_SLICE_LENGTH;
+        state.clock.slot = 2 * interval;
+
+        // The first heartbeat is always due.
+        assert_eq!(
+            state
+                .try_maintainer_heartbeat()
+                .map(|(_instruction, output)| output),
+            Some(MaintenanceOutput::MaintainerHeartbeat {
+                previous_heartbeat_slot: 0
+            })
+        );
+
+        state.solido.maintainers.entries[1]
+            .entry
+            .last_heartbeat_slot = interval + 1;
+        assert!(state.try_maintainer_heartbeat().is_none());
+        state.solido.maintainers.entries[1]
+            .entry
+            .last_heartbeat_slot = interval;
+        assert!(state.try_maintainer_heartbeat().is_some());
+
+        // A key that is not a maintainer cannot send a heartbeat.
+        state.maintainer_address = Pubkey::new_unique();
+        assert!(state.try_maintainer_heartbeat().is_none());
+    }
+
     #[test]
     fn test_below_epoch_threshold() {
         let mut state = new_empty_solido();
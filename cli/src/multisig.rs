@@ -23,10 +23,14 @@ use solana_sdk::sysvar;
 
 use lido::{
     instruction::{
-        AddMaintainerMeta, AddValidatorMeta, ChangeRewardDistributionMeta, DeactivateValidatorMeta,
-        LidoInstruction, RemoveMaintainerMeta,
+        AddMaintainerMeta, AddValidatorMeta, ChangeDepositCutoffMeta, ChangeFeePayoutModeMeta,
+        ChangeRewardDistributionMeta, DeactivateValidatorMeta, ForceRemoveValidatorMeta,
+        LidoInstruction, MigrateValidatorListMeta, RemoveMaintainerMeta, SetTokenMetadataMeta,
+        SortAccountMapsMeta, UpgradeFeePayoutModeMeta, UpgradeMaintainersMeta, UpgradeMetricsMeta,
+        UpgradeValidatorEntriesMeta, UpgradeValidatorListMeta,
     },
-    state::{FeeRecipients, Lido, RewardDistribution},
+    state::{FeePayoutMode, FeeRecipients, Lido, RewardDistribution},
+    token_metadata::TokenMetadata,
     util::{serialize_b58, serialize_b58_slice},
 };
 
@@ -400,6 +404,12 @@ enum SolidoInstruction {
 
         #[serde(serialize_with = "serialize_b58")]
         validator_fee_st_sol_account: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        validator_vote_withdrawer: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        validator_authority: Pubkey,
     },
     DeactivateValidator {
         #[serde(serialize_with = "serialize_b58")]
@@ -411,6 +421,22 @@ enum SolidoInstruction {
         #[serde(serialize_with = "serialize_b58")]
         validator_vote_account: Pubkey,
     },
+    ForceRemoveValidator {
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        validator_vote_account: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        validator_fee_st_sol_account: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58_slice")]
+        stake_accounts: Vec<Pubkey>,
+    },
     AddMaintainer {
         #[serde(serialize_with = "serialize_b58")]
         solido_instance: Pubkey,
@@ -443,6 +469,102 @@ enum SolidoInstruction {
 
         fee_recipients: FeeRecipients,
     },
+    MigrateValidatorList {
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        validator_list: Pubkey,
+    },
+    SortAccountMaps {
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        validator_list: Pubkey,
+    },
+    UpgradeValidatorList {
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        validator_list: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        new_validator_list: Pubkey,
+    },
+    UpgradeMetrics {
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+    },
+    UpgradeMaintainers {
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+    },
+    UpgradeValidatorEntries {
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        validator_list: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        new_validator_list: Pubkey,
+    },
+    ChangeFeePayoutMode {
+        current_fee_payout_mode: FeePayoutMode,
+        new_fee_payout_mode: FeePayoutMode,
+
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+    },
+    UpgradeFeePayoutMode {
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+    },
+    SetTokenMetadata {
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+
+        token_metadata: TokenMetadata,
+    },
+    ChangeDepositCutoff {
+        current_deposit_cutoff_slots: u64,
+        new_deposit_cutoff_slots: u64,
+
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+    },
 }
 
 #[derive(Serialize)]
@@ -556,6 +678,8 @@ impl fmt::Display for ShowTransactionOutput {
                         manager,
                         validator_vote_account,
                         validator_fee_st_sol_account,
+                        validator_vote_withdrawer,
+                        validator_authority,
                     } => {
                         writeln!(f, "It adds a validator to Solido")?;
                         writeln!(f, "    Solido instance:        {}", solido_instance)?;
@@ -566,6 +690,12 @@ impl fmt::Display for ShowTransactionOutput {
                             "    Validator fee account:  {}",
                             validator_fee_st_sol_account
                         )?;
+                        writeln!(
+                            f,
+                            "    Vote withdrawer:        {}",
+                            validator_vote_withdrawer
+                        )?;
+                        writeln!(f, "    Validator authority:    {}", validator_authority)?;
                     }
                     SolidoInstruction::DeactivateValidator {
                         solido_instance,
@@ -577,6 +707,30 @@ impl fmt::Display for ShowTransactionOutput {
                         writeln!(f, "    Manager:                {}", manager)?;
                         writeln!(f, "    Validator vote account: {}", validator_vote_account)?;
                     }
+                    SolidoInstruction::ForceRemoveValidator {
+                        solido_instance,
+                        manager,
+                        validator_vote_account,
+                        validator_fee_st_sol_account,
+                        stake_accounts,
+                    } => {
+                        writeln!(
+                            f,
+                            "It deactivates a validator and all of its stake, and pays out its fees."
+                        )?;
+                        writeln!(f, "    Solido instance:        {}", solido_instance)?;
+                        writeln!(f, "    Manager:                {}", manager)?;
+                        writeln!(f, "    Validator vote account: {}", validator_vote_account)?;
+                        writeln!(
+                            f,
+                            "    Validator fee account:  {}",
+                            validator_fee_st_sol_account
+                        )?;
+                        writeln!(f, "    Stake accounts to deactivate:")?;
+                        for stake_account in stake_accounts {
+                            writeln!(f, "      {}", stake_account)?;
+                        }
+                    }
                     SolidoInstruction::AddMaintainer {
                         solido_instance,
                         manager,
@@ -611,6 +765,129 @@ impl fmt::Display for ShowTransactionOutput {
                         print_changed_reward_distribution(f, current_solido, reward_distribution)?;
                         print_changed_recipients(f, current_solido, fee_recipients)?;
                     }
+                    SolidoInstruction::MigrateValidatorList {
+                        solido_instance,
+                        manager,
+                        validator_list,
+                    } => {
+                        writeln!(f, "It moves the validators into a validator list account")?;
+                        writeln!(f, "    Solido instance: {}", solido_instance)?;
+                        writeln!(f, "    Manager:         {}", manager)?;
+                        writeln!(f, "    Validator list:  {}", validator_list)?;
+                    }
+                    SolidoInstruction::SortAccountMaps {
+                        solido_instance,
+                        manager,
+                        validator_list,
+                    } => {
+                        writeln!(f, "It sorts the validators and maintainers by public key")?;
+                        writeln!(f, "    Solido instance: {}", solido_instance)?;
+                        writeln!(f, "    Manager:         {}", manager)?;
+                        writeln!(f, "    Validator list:  {}", validator_list)?;
+                    }
+                    SolidoInstruction::UpgradeValidatorList {
+                        solido_instance,
+                        manager,
+                        validator_list,
+                        new_validator_list,
+                    } => {
+                        writeln!(
+                            f,
+                            "It moves the validators into a new validator list account"
+                        )?;
+                        writeln!(f, "    Solido instance:    {}", solido_instance)?;
+                        writeln!(f, "    Manager:            {}", manager)?;
+                        writeln!(f, "    Validator list:     {}", validator_list)?;
+                        writeln!(f, "    New validator list: {}", new_validator_list)?;
+                    }
+                    SolidoInstruction::UpgradeMetrics {
+                        solido_instance,
+                        manager,
+                    } => {
+                        writeln!(
+                            f,
+                            "It converts the instance to the layout with more metrics"
+                        )?;
+                        writeln!(f, "    Solido instance: {}", solido_instance)?;
+                        writeln!(f, "    Manager:         {}", manager)?;
+                    }
+                    SolidoInstruction::UpgradeMaintainers {
+                        solido_instance,
+                        manager,
+                    } => {
+                        writeln!(
+                            f,
+                            "It converts the instance to the layout with maintainer heartbeats"
+                        )?;
+                        writeln!(f, "    Solido instance: {}", solido_instance)?;
+                        writeln!(f, "    Manager:         {}", manager)?;
+                    }
+                    SolidoInstruction::UpgradeValidatorEntries {
+                        solido_instance,
+                        manager,
+                        validator_list,
+                        new_validator_list,
+                    } => {
+                        writeln!(
+                            f,
+                            "It moves the validators into a validator list account with \
+                            room for tombstones and vote withdrawers"
+                        )?;
+                        writeln!(f, "    Solido instance:    {}", solido_instance)?;
+                        writeln!(f, "    Manager:            {}", manager)?;
+                        writeln!(f, "    Validator list:     {}", validator_list)?;
+                        writeln!(f, "    New validator list: {}", new_validator_list)?;
+                    }
+                    SolidoInstruction::ChangeFeePayoutMode {
+                        current_fee_payout_mode,
+                        new_fee_payout_mode,
+                        solido_instance,
+                        manager,
+                    } => {
+                        writeln!(f, "It changes the validator fee payout mode")?;
+                        writeln!(f, "    Solido instance: {}", solido_instance)?;
+                        writeln!(f, "    Manager:         {}", manager)?;
+                        writeln!(
+                            f,
+                            "    Payout mode:     {} -> {}",
+                            current_fee_payout_mode, new_fee_payout_mode
+                        )?;
+                    }
+                    SolidoInstruction::UpgradeFeePayoutMode {
+                        solido_instance,
+                        manager,
+                    } => {
+                        writeln!(f, "It upgrades the instance to have a fee payout mode")?;
+                        writeln!(f, "    Solido instance: {}", solido_instance)?;
+                        writeln!(f, "    Manager:         {}", manager)?;
+                    }
+                    SolidoInstruction::SetTokenMetadata {
+                        solido_instance,
+                        manager,
+                        token_metadata,
+                    } => {
+                        writeln!(f, "It sets the stSOL token metadata")?;
+                        writeln!(f, "    Solido instance: {}", solido_instance)?;
+                        writeln!(f, "    Manager:         {}", manager)?;
+                        writeln!(f, "    Name:            {}", token_metadata.name)?;
+                        writeln!(f, "    Symbol:          {}", token_metadata.symbol)?;
+                        writeln!(f, "    URI:             {}", token_metadata.uri)?;
+                    }
+                    SolidoInstruction::ChangeDepositCutoff {
+                        current_deposit_cutoff_slots,
+                        new_deposit_cutoff_slots,
+                        solido_instance,
+                        manager,
+                    } => {
+                        writeln!(f, "It changes the deposit cutoff near the epoch end")?;
+                        writeln!(f, "    Solido instance: {}", solido_instance)?;
+                        writeln!(f, "    Manager:         {}", manager)?;
+                        writeln!(
+                            f,
+                            "    Cutoff slots:    {} -> {}",
+                            current_deposit_cutoff_slots, new_deposit_cutoff_slots
+                        )?;
+                    }
                 }
             }
             ParsedInstruction::Unrecognized => {
@@ -722,6 +999,14 @@ fn print_changed_reward_distribution(
         new_sum,
         "stSOL appreciation",
     )?;
+    changed_fee(
+        f,
+        current_solido.reward_distribution.maintainer_fee,
+        reward_distribution.maintainer_fee,
+        current_sum,
+        new_sum,
+        "maintainer",
+    )?;
     Ok(())
 }
 
@@ -942,6 +1227,25 @@ fn try_parse_solido_instruction(
                 },
             })
         }
+        LidoInstruction::ChangeFeePayoutMode {
+            new_fee_payout_mode,
+        } => {
+            let accounts = ChangeFeePayoutModeMeta::try_from_slice(&instr.accounts)?;
+            let current_solido = config.client.get_solido(&accounts.lido)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::ChangeFeePayoutMode {
+                current_fee_payout_mode: current_solido.fee_payout_mode,
+                new_fee_payout_mode,
+                solido_instance: accounts.lido,
+                manager: accounts.manager,
+            })
+        }
+        LidoInstruction::UpgradeFeePayoutMode => {
+            let accounts = UpgradeFeePayoutModeMeta::try_from_slice(&instr.accounts)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::UpgradeFeePayoutMode {
+                solido_instance: accounts.lido,
+                manager: accounts.manager,
+            })
+        }
         LidoInstruction::AddValidator => {
             let accounts = AddValidatorMeta::try_from_slice(&instr.accounts)?;
             ParsedInstruction::SolidoInstruction(SolidoInstruction::AddValidator {
@@ -949,6 +1253,8 @@ fn try_parse_solido_instruction(
                 manager: accounts.manager,
                 validator_vote_account: accounts.validator_vote_account,
                 validator_fee_st_sol_account: accounts.validator_fee_st_sol_account,
+                validator_vote_withdrawer: accounts.validator_vote_withdrawer,
+                validator_authority: accounts.validator_authority,
             })
         }
         LidoInstruction::DeactivateValidator => {
@@ -959,6 +1265,16 @@ fn try_parse_solido_instruction(
                 validator_vote_account: accounts.validator_vote_account_to_deactivate,
             })
         }
+        LidoInstruction::ForceRemoveValidator => {
+            let accounts = ForceRemoveValidatorMeta::try_from_slice(&instr.accounts)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::ForceRemoveValidator {
+                solido_instance: accounts.lido,
+                manager: accounts.manager,
+                validator_vote_account: accounts.validator_vote_account_to_remove,
+                validator_fee_st_sol_account: accounts.validator_fee_st_sol_account,
+                stake_accounts: accounts.stake_accounts,
+            })
+        }
         LidoInstruction::AddMaintainer => {
             let accounts = AddMaintainerMeta::try_from_slice(&instr.accounts)?;
             ParsedInstruction::SolidoInstruction(SolidoInstruction::AddMaintainer {
@@ -975,6 +1291,74 @@ fn try_parse_solido_instruction(
                 maintainer: accounts.maintainer,
             })
         }
+        LidoInstruction::MigrateValidatorList => {
+            let accounts = MigrateValidatorListMeta::try_from_slice(&instr.accounts)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::MigrateValidatorList {
+                solido_instance: accounts.lido,
+                manager: accounts.manager,
+                validator_list: accounts.validator_list,
+            })
+        }
+        LidoInstruction::SortAccountMaps => {
+            let accounts = SortAccountMapsMeta::try_from_slice(&instr.accounts)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::SortAccountMaps {
+                solido_instance: accounts.lido,
+                manager: accounts.manager,
+                validator_list: accounts.validator_list,
+            })
+        }
+        LidoInstruction::UpgradeValidatorList => {
+            let accounts = UpgradeValidatorListMeta::try_from_slice(&instr.accounts)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::UpgradeValidatorList {
+                solido_instance: accounts.lido,
+                manager: accounts.manager,
+                validator_list: accounts.validator_list,
+                new_validator_list: accounts.new_validator_list,
+            })
+        }
+        LidoInstruction::UpgradeMetrics => {
+            let accounts = UpgradeMetricsMeta::try_from_slice(&instr.accounts)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::UpgradeMetrics {
+                solido_instance: accounts.lido,
+                manager: accounts.manager,
+            })
+        }
+        LidoInstruction::UpgradeMaintainers => {
+            let accounts = UpgradeMaintainersMeta::try_from_slice(&instr.accounts)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::UpgradeMaintainers {
+                solido_instance: accounts.lido,
+                manager: accounts.manager,
+            })
+        }
+        LidoInstruction::UpgradeValidatorEntries => {
+            let accounts = UpgradeValidatorEntriesMeta::try_from_slice(&instr.accounts)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::UpgradeValidatorEntries {
+                solido_instance: accounts.lido,
+                manager: accounts.manager,
+                validator_list: accounts.validator_list,
+                new_validator_list: accounts.new_validator_list,
+            })
+        }
+        LidoInstruction::SetTokenMetadata { token_metadata } => {
+            let accounts = SetTokenMetadataMeta::try_from_slice(&instr.accounts)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::SetTokenMetadata {
+                solido_instance: accounts.lido,
+                manager: accounts.manager,
+                token_metadata,
+            })
+        }
+        LidoInstruction::ChangeDepositCutoff {
+            deposit_cutoff_slots,
+        } => {
+            let accounts = ChangeDepositCutoffMeta::try_from_slice(&instr.accounts)?;
+            let current_solido = config.client.get_solido(&accounts.lido)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::ChangeDepositCutoff {
+                current_deposit_cutoff_slots: current_solido.deposit_cutoff_slots,
+                new_deposit_cutoff_slots: deposit_cutoff_slots,
+                solido_instance: accounts.lido,
+                manager: accounts.manager,
+            })
+        }
         _ => ParsedInstruction::InvalidSolidoInstruction,
     })
 }
@@ -1008,7 +1392,7 @@ fn try_parse_token_instruction(
 #[derive(Serialize)]
 pub struct ProposeInstructionOutput {
     #[serde(serialize_with = "serialize_b58")]
-    transaction_address: Pubkey,
+    pub transaction_address: Pubkey,
 }
 
 impl fmt::Display for ProposeInstructionOutput {
@@ -1372,6 +1756,9 @@ fn approve_transaction_interactive(
 struct TransactionAccounts {
     accounts: Vec<serum_multisig::TransactionAccount>,
     program_id: Pubkey,
+    /// The key that executes the transaction, it can sign for the inner
+    /// instruction as well.
+    executor: Pubkey,
 }
 
 impl anchor_lang::ToAccountMetas for TransactionAccounts {
@@ -1389,8 +1776,11 @@ impl anchor_lang::ToAccountMetas for TransactionAccounts {
                 // list with the right signers. But when we build the wrapper
                 // instruction that calls the `serum_multisig::execute_transaction,
                 // the signers of the inner instruction should not be signers of
-                // the outer one.
-                account_meta.is_signer = false;
+                // the outer one. The exception is the executor: it signs the
+                // outer transaction anyway, so it can pass on its signature to
+                // instructions that need it, such as `AddValidator`.
+                account_meta.is_signer =
+                    account_meta.is_signer && account_meta.pubkey == self.executor;
                 account_meta
             })
             .collect();
@@ -1440,6 +1830,7 @@ fn execute_transaction(
     let tx_inner_accounts = TransactionAccounts {
         accounts: transaction.accounts,
         program_id: transaction.program_id,
+        executor: config.signer.pubkey(),
     };
 
     let mut accounts = multisig_accounts::ExecuteTransaction {
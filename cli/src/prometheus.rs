@@ -5,7 +5,7 @@
 //!
 //! See also <https://prometheus.io/docs/instrumenting/exposition_formats/#text-based-format>.
 
-use lido::metrics::{LamportsHistogram, Metrics};
+use lido::metrics::{EpochFlowMetric, LamportsHistogram, Metrics};
 use lido::token::{Lamports, StLamports};
 use std::io;
 use std::io::Write;
@@ -95,9 +95,38 @@ impl<'a> Metric<'a> {
     }
 }
 
-pub fn write_metric<W: Write>(out: &mut W, family: &MetricFamily) -> io::Result<()> {
-    writeln!(out, "# HELP {} {}", family.name, family.help)?;
-    writeln!(out, "# TYPE {} {}", family.name, family.type_)?;
+/// Destination for metric families.
+///
+/// Any `io::Write` receives the families directly in text format. A
+/// [`LabeledMetrics`] instead collects them, so we can merge the metrics of
+/// several Solido instances into one exposition.
+pub trait MetricSink {
+    fn write_family(&mut self, family: &MetricFamily) -> io::Result<()>;
+}
+
+impl<W: Write> MetricSink for W {
+    fn write_family(&mut self, family: &MetricFamily) -> io::Result<()> {
+        writeln!(self, "# HELP {} {}", family.name, family.help)?;
+        writeln!(self, "# TYPE {} {}", family.name, family.type_)?;
+        write_metrics(self, family, None)?;
+
+        // Add a blank line for readability by humans.
+        writeln!(self)
+    }
+}
+
+pub fn write_metric<S: MetricSink>(out: &mut S, family: &MetricFamily) -> io::Result<()> {
+    out.write_family(family)
+}
+
+/// Write the metrics of the family, without the HELP and TYPE lines.
+///
+/// If `extra_label` is set, add it to every metric, before the metric's own labels.
+fn write_metrics<W: Write>(
+    out: &mut W,
+    family: &MetricFamily,
+    extra_label: Option<(&str, &str)>,
+) -> io::Result<()> {
     for metric in &family.metrics {
         write!(out, "{}{}", family.name, metric.suffix)?;
 
@@ -106,10 +135,19 @@ pub fn write_metric<W: Write>(out: &mut W, family: &MetricFamily) -> io::Result<
         // not exactly what Prometheus wants, but it is identical for
         // all of the values that we use it with; this is not a general
         // Prometheus formatter, just a quick one for our use.
-        if !metric.labels.is_empty() {
+        let labels: Vec<(&str, &str)> = extra_label
+            .into_iter()
+            .chain(
+                metric
+                    .labels
+                    .iter()
+                    .map(|(key, value)| (*key, value.as_str())),
+            )
+            .collect();
+        if !labels.is_empty() {
             write!(out, "{{")?;
             let mut separator = "";
-            for (key, value) in &metric.labels {
+            for (key, value) in &labels {
                 write!(out, "{}{}={:?}", separator, key, value)?;
                 separator = ",";
             }
@@ -137,8 +175,76 @@ pub fn write_metric<W: Write>(out: &mut W, family: &MetricFamily) -> io::Result<
         writeln!(out)?;
     }
 
-    // Add a blank line for readability by humans.
-    writeln!(out)
+    Ok(())
+}
+
+/// A metric family collected by [`LabeledMetrics`], with its metrics already rendered.
+struct CollectedFamily {
+    name: String,
+    help: String,
+    type_: String,
+    metrics: Vec<u8>,
+}
+
+/// Collects metric families from several sources, and labels every metric
+/// with the source it came from.
+///
+/// The text format requires all metrics of a family to be in one group, with
+/// one HELP and TYPE line, so we cannot simply concatenate the output of the
+/// sources. Instead, we collect the metrics per family, and write them out at
+/// the end with [`LabeledMetrics::write_to`].
+pub struct LabeledMetrics {
+    label_key: &'static str,
+    label_value: String,
+    /// Families in the order in which we first saw them.
+    families: Vec<CollectedFamily>,
+}
+
+impl LabeledMetrics {
+    pub fn new(label_key: &'static str) -> LabeledMetrics {
+        LabeledMetrics {
+            label_key,
+            label_value: String::new(),
+            families: Vec::new(),
+        }
+    }
+
+    /// Set the value of the label for the metrics that are written after this.
+    pub fn set_label_value(&mut self, label_value: String) {
+        self.label_value = label_value;
+    }
+
+    pub fn write_to<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        for family in &self.families {
+            writeln!(out, "# HELP {} {}", family.name, family.help)?;
+            writeln!(out, "# TYPE {} {}", family.name, family.type_)?;
+            out.write_all(&family.metrics)?;
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+}
+
+impl MetricSink for LabeledMetrics {
+    fn write_family(&mut self, family: &MetricFamily) -> io::Result<()> {
+        let index = match self.families.iter().position(|f| f.name == family.name) {
+            Some(index) => index,
+            None => {
+                self.families.push(CollectedFamily {
+                    name: family.name.to_string(),
+                    help: family.help.to_string(),
+                    type_: family.type_.to_string(),
+                    metrics: Vec::new(),
+                });
+                self.families.len() - 1
+            }
+        };
+        write_metrics(
+            &mut self.families[index].metrics,
+            family,
+            Some((self.label_key, &self.label_value)),
+        )
+    }
 }
 
 pub fn solido_histogram_to_metrics(at: SystemTime, histogram: &LamportsHistogram) -> Vec<Metric> {
@@ -175,7 +281,7 @@ pub fn solido_histogram_to_metrics(at: SystemTime, histogram: &LamportsHistogram
     metrics
 }
 
-pub fn write_solido_metrics_as_prometheus<W: io::Write>(
+pub fn write_solido_metrics_as_prometheus<W: MetricSink>(
     metrics: &Metrics,
     at: SystemTime,
     out: &mut W,
@@ -294,6 +400,52 @@ pub fn write_solido_metrics_as_prometheus<W: io::Write>(
             metrics: vec![Metric::new_st_sol(metrics.withdraw_amount.total_st_sol_amount).at(at)],
         },
     )?;
+    write_metric(
+        out,
+        &MetricFamily {
+            name: "solido_withdraw_amount_sol",
+            help: "Amount of SOL that we returned to users for withdrawals.",
+            type_: "histogram",
+            metrics: solido_histogram_to_metrics(at, &metrics.withdraw_amount_histogram),
+        },
+    )?;
+
+    // Before the first rollover, both records are for the same epoch, and we
+    // should not export the same series twice.
+    let mut flows = vec![&metrics.flow_current_epoch];
+    if metrics.flow_previous_epoch.epoch < metrics.flow_current_epoch.epoch {
+        flows.insert(0, &metrics.flow_previous_epoch);
+    }
+    let flow_metrics = |select: fn(&EpochFlowMetric) -> Lamports| {
+        flows
+            .iter()
+            .map(|flow| {
+                Metric::new_sol(select(flow))
+                    .with_label("epoch", flow.epoch.to_string())
+                    .at(at)
+            })
+            .collect()
+    };
+    write_metric(
+        out,
+        &MetricFamily {
+            name: "solido_epoch_deposit_sol",
+            help: "Amount of SOL deposited by users in the epoch given by the label, \
+                   for the current and the previous epoch.",
+            type_: "gauge",
+            metrics: flow_metrics(|flow| flow.deposit_total),
+        },
+    )?;
+    write_metric(
+        out,
+        &MetricFamily {
+            name: "solido_epoch_withdraw_sol",
+            help: "Amount of SOL that we returned to users for withdrawals in the epoch given \
+                   by the label, for the current and the previous epoch.",
+            type_: "gauge",
+            metrics: flow_metrics(|flow| flow.withdraw_total),
+        },
+    )?;
 
     Ok(())
 }
@@ -302,7 +454,7 @@ pub fn write_solido_metrics_as_prometheus<W: io::Write>(
 mod test {
     use std::str;
 
-    use super::{write_metric, Metric, MetricFamily, MetricValue};
+    use super::{write_metric, LabeledMetrics, Metric, MetricFamily, MetricValue};
 
     #[test]
     fn write_metric_without_labels() {
@@ -461,4 +613,51 @@ mod test {
                 ")
         )
     }
+
+    #[test]
+    fn labeled_metrics_groups_families_from_several_sources() {
+        let mut collector = LabeledMetrics::new("instance");
+        for (instance, teleported) in &[("a", 144), ("b", 12)] {
+            collector.set_label_value(instance.to_string());
+            write_metric(
+                &mut collector,
+                &MetricFamily {
+                    name: "goats_teleported_total",
+                    help: "Number of goats teleported since launch.",
+                    type_: "counter",
+                    metrics: vec![
+                        Metric::new(*teleported).with_label("color", "white".to_string())
+                    ],
+                },
+            )
+            .unwrap();
+            write_metric(
+                &mut collector,
+                &MetricFamily {
+                    name: "goats_lost_total",
+                    help: "Number of goats lost during teleportation since launch.",
+                    type_: "counter",
+                    metrics: vec![Metric::new(1)],
+                },
+            )
+            .unwrap();
+        }
+
+        let mut out: Vec<u8> = Vec::new();
+        collector.write_to(&mut out).unwrap();
+        assert_eq!(
+            str::from_utf8(&out[..]),
+            Ok(
+                "# HELP goats_teleported_total Number of goats teleported since launch.\n\
+                 # TYPE goats_teleported_total counter\n\
+                 goats_teleported_total{instance=\"a\",color=\"white\"} 144\n\
+                 goats_teleported_total{instance=\"b\",color=\"white\"} 12\n\n\
+                 # HELP goats_lost_total Number of goats lost during teleportation since launch.\n\
+                 # TYPE goats_lost_total counter\n\
+                 goats_lost_total{instance=\"a\"} 1\n\
+                 goats_lost_total{instance=\"b\"} 1\n\n\
+                "
+            )
+        )
+    }
 }
@@ -28,11 +28,14 @@ use std::time::Duration;
 use anchor_lang::AccountDeserialize;
 use solana_client::client_error::{ClientError, ClientErrorKind};
 use solana_client::rpc_client::RpcClient;
-use solana_client::rpc_config::RpcSendTransactionConfig;
+use solana_client::rpc_config::{
+    RpcSendTransactionConfig, RpcSimulateTransactionConfig, RpcTransactionConfig,
+};
 use solana_client::rpc_request::RpcError;
+use solana_client::rpc_response::RpcSimulateTransactionResult;
 use solana_sdk::account::{Account, ReadableAccount};
 use solana_sdk::borsh::try_from_slice_unchecked;
-use solana_sdk::commitment_config::CommitmentLevel;
+use solana_sdk::commitment_config::{CommitmentConfig, CommitmentLevel};
 use solana_sdk::program_pack::{IsInitialized, Pack};
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signature;
@@ -41,10 +44,10 @@ use solana_sdk::sysvar::{
     self, clock::Clock, epoch_schedule::EpochSchedule, recent_blockhashes::RecentBlockhashes,
     rent::Rent, Sysvar,
 };
-use solana_sdk::transaction::Transaction;
+use solana_sdk::transaction::{self, Transaction};
 use solana_vote_program::vote_state::VoteState;
 
-use lido::state::Lido;
+use lido::state::{Lido, LidoHeader, Validators};
 use lido::token::Lamports;
 use spl_token::solana_program::hash::Hash;
 
@@ -325,6 +328,66 @@ impl<'a> Snapshot<'a> {
         }
     }
 
+    /// Read a Solido instance that still uses the `Lido` layout of versions 2
+    /// through 6, see [`Lido::deserialize_legacy`].
+    pub fn get_legacy_solido(&mut self, solido_address: &Pubkey) -> Result<Lido> {
+        let account = self.get_account(solido_address)?;
+        match Lido::deserialize_legacy(&account.data) {
+            Ok(solido) => Ok(solido),
+            Err(err) => {
+                let error: Error = Box::new(SerializationError {
+                    cause: Some(err.into()),
+                    address: *solido_address,
+                    context: format!(
+                        "Failed to deserialize legacy Lido struct, data length is {} bytes.",
+                        account.data.len()
+                    ),
+                });
+                Err(error.into())
+            }
+        }
+    }
+
+    /// Read only the header of a Solido instance, for versions 7 and later,
+    /// which share the current header but not necessarily the current
+    /// maintainer layout.
+    pub fn get_solido_header(&mut self, solido_address: &Pubkey) -> Result<LidoHeader> {
+        let account = self.get_account(solido_address)?;
+        match try_from_slice_unchecked::<LidoHeader>(&account.data) {
+            Ok(header) => Ok(header),
+            Err(err) => {
+                let error: Error = Box::new(SerializationError {
+                    cause: Some(err.into()),
+                    address: *solido_address,
+                    context: format!(
+                        "Failed to deserialize Lido header, data length is {} bytes.",
+                        account.data.len()
+                    ),
+                });
+                Err(error.into())
+            }
+        }
+    }
+
+    /// Read the validator list account and deserialize the validators.
+    pub fn get_validators(&mut self, validator_list_address: &Pubkey) -> Result<Validators> {
+        let account = self.get_account(validator_list_address)?;
+        match try_from_slice_unchecked::<Validators>(&account.data) {
+            Ok(validators) => Ok(validators),
+            Err(err) => {
+                let error: Error = Box::new(SerializationError {
+                    cause: Some(err.into()),
+                    address: *validator_list_address,
+                    context: format!(
+                        "Failed to deserialize validator list, data length is {} bytes.",
+                        account.data.len()
+                    ),
+                });
+                Err(error.into())
+            }
+        }
+    }
+
     /// Return the amount in an SPL token account.
     pub fn get_spl_token_balance(&mut self, address: &Pubkey) -> Result<u64> {
         let account: spl_token::state::Account = self.get_unpack(address)?;
@@ -380,6 +443,61 @@ impl<'a> Snapshot<'a> {
         .into());
     }
 
+    /// Return the status of a transaction, or None if it has not been processed.
+    ///
+    /// Note, this queries the RPC directly, the status is not part of the snapshot.
+    pub fn get_signature_status(
+        &mut self,
+        signature: &Signature,
+    ) -> solana_client::client_error::Result<Option<transaction::Result<()>>> {
+        self.rpc_client.get_signature_status(signature)
+    }
+
+    /// Return the fee that an executed transaction paid, from its metadata.
+    ///
+    /// Note, this queries the RPC directly, the transaction is not part of the snapshot.
+    pub fn get_transaction_fee(
+        &mut self,
+        signature: &Signature,
+    ) -> solana_client::client_error::Result<Lamports> {
+        let transaction = self.rpc_client.get_transaction_with_config(
+            signature,
+            RpcTransactionConfig {
+                encoding: None,
+                commitment: Some(self.rpc_client.commitment()),
+            },
+        )?;
+        match transaction.transaction.meta {
+            Some(meta) => Ok(Lamports(meta.fee)),
+            None => Err(RpcError::ForUser(format!(
+                "The RPC returned no metadata for transaction {}.",
+                signature,
+            ))
+            .into()),
+        }
+    }
+
+    /// Simulate a transaction, without sending it.
+    ///
+    /// Unlike sending, simulating has no side effects, so this does not
+    /// prevent retrying the snapshot.
+    pub fn simulate_transaction(
+        &mut self,
+        transaction: &Transaction,
+    ) -> solana_client::client_error::Result<RpcSimulateTransactionResult> {
+        let response = self.rpc_client.simulate_transaction_with_config(
+            transaction,
+            // Simulate against the most recent known state, for the same
+            // reason as we preflight against it in `send_and_confirm_transaction`.
+            RpcSimulateTransactionConfig {
+                sig_verify: true,
+                commitment: Some(CommitmentConfig::processed()),
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )?;
+        Ok(response.value)
+    }
+
     /// Send a transaction, show a spinner on stdout.
     ///
     /// After this, avoid reads from accounts not accessed before. Note, you
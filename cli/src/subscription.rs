@@ -0,0 +1,209 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+//! Push-based notifications of on-chain changes, through websocket subscriptions.
+//!
+//! By default the maintenance daemon polls the on-chain state at a fixed
+//! interval. When it has a websocket url, it instead subscribes to changes to
+//! the accounts that most maintenance depends on (the Solido instance, the
+//! reserve, and the stSOL mint), and to slot updates, so it can refresh the
+//! state as soon as something changes, or when a slot of interest (such as the
+//! start of its duty slice) is reached.
+//!
+//! Every subscription has its own websocket connection and receiver. We spawn
+//! a thread per subscription that forwards the notifications into a single
+//! channel, so the daemon can wait for all of them at once.
+
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::{Duration, Instant};
+
+use serde::de::DeserializeOwned;
+use solana_client::pubsub_client::{PubsubClient, PubsubClientError, PubsubClientSubscription};
+use solana_client::rpc_config::RpcAccountInfoConfig;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::clock::Slot;
+use solana_sdk::commitment_config::CommitmentConfig;
+
+/// A notification forwarded from one of the subscriptions.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Event {
+    /// One of the accounts that we subscribed to changed.
+    AccountChanged(Pubkey),
+
+    /// The node that we are connected to reached this slot.
+    Slot(Slot),
+
+    /// One of the websocket connections was closed, we will not receive all
+    /// notifications any more.
+    Disconnected,
+}
+
+/// The reason that `Subscriptions::wait` returned.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum WakeReason {
+    /// One of the accounts that we subscribed to changed.
+    AccountChanged(Pubkey),
+
+    /// We reached the wake slot.
+    Slot(Slot),
+
+    /// Nothing happened before the timeout.
+    Timeout,
+
+    /// We lost the connection, the caller should resubscribe or fall back to
+    /// polling.
+    Disconnected,
+}
+
+/// Account and slot subscriptions over websocket connections to an RPC node.
+pub struct Subscriptions {
+    /// The accounts that we subscribed to.
+    accounts: Vec<Pubkey>,
+
+    events: Receiver<Event>,
+}
+
+/// Forward notifications from a subscription to the shared channel, until the
+/// connection closes, or until the receiving end is dropped.
+fn spawn_forwarder<T: DeserializeOwned + Send + 'static>(
+    name: String,
+    mut subscription: PubsubClientSubscription<T>,
+    receiver: Receiver<T>,
+    sender: Sender<Event>,
+    to_event: impl Fn(T) -> Event + Send + 'static,
+) {
+    std::thread::Builder::new()
+        .name(name)
+        .spawn(move || {
+            for message in receiver.iter() {
+                if sender.send(to_event(message)).is_err() {
+                    // The `Subscriptions` were dropped, nobody is interested
+                    // in the notifications any more.
+                    let _ = subscription.shutdown();
+                    return;
+                }
+            }
+            // The subscription stopped, which happens when the websocket
+            // connection is closed.
+            let _ = sender.send(Event::Disconnected);
+        })
+        .expect("Failed to spawn subscription thread.");
+}
+
+impl Subscriptions {
+    /// Subscribe to changes to the given accounts, and to slot updates.
+    pub fn subscribe(
+        websocket_url: &str,
+        accounts: &[Pubkey],
+    ) -> Result<Subscriptions, PubsubClientError> {
+        let (sender, events) = mpsc::channel();
+
+        for &account in accounts {
+            let config = RpcAccountInfoConfig {
+                commitment: Some(CommitmentConfig::confirmed()),
+                ..RpcAccountInfoConfig::default()
+            };
+            let (subscription, receiver) =
+                PubsubClient::account_subscribe(websocket_url, &account, Some(config))?;
+            spawn_forwarder(
+                format!("account_subscription_{}", account),
+                subscription,
+                receiver,
+                sender.clone(),
+                move |_| Event::AccountChanged(account),
+            );
+        }
+
+        let (subscription, receiver) = PubsubClient::slot_subscribe(websocket_url)?;
+        spawn_forwarder(
+            "slot_subscription".to_string(),
+            subscription,
+            receiver,
+            sender,
+            |slot_info| Event::Slot(slot_info.slot),
+        );
+
+        Ok(Subscriptions {
+            accounts: accounts.to_vec(),
+            events,
+        })
+    }
+
+    pub fn accounts(&self) -> &[Pubkey] {
+        &self.accounts
+    }
+
+    /// Wait until one of the accounts changes, until we reach `wake_slot`, or
+    /// until the timeout expires, whichever happens first.
+    pub fn wait(&self, timeout: Duration, wake_slot: Option<Slot>) -> WakeReason {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let time_left = deadline.saturating_duration_since(Instant::now());
+            match self.events.recv_timeout(time_left) {
+                Ok(Event::AccountChanged(account)) => {
+                    // A single transaction often changes several of the accounts
+                    // at once. Skip the notifications that are already queued,
+                    // the state that the caller reads next includes them.
+                    loop {
+                        match self.events.try_recv() {
+                            Ok(Event::AccountChanged(..)) | Ok(Event::Slot(..)) => continue,
+                            Ok(Event::Disconnected) => return WakeReason::Disconnected,
+                            Err(..) => return WakeReason::AccountChanged(account),
+                        }
+                    }
+                }
+                Ok(Event::Slot(slot)) => match wake_slot {
+                    Some(wake_slot) if slot >= wake_slot => return WakeReason::Slot(slot),
+                    _ => continue,
+                },
+                Ok(Event::Disconnected) => return WakeReason::Disconnected,
+                Err(RecvTimeoutError::Disconnected) => return WakeReason::Disconnected,
+                Err(RecvTimeoutError::Timeout) => return WakeReason::Timeout,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn wait_wakes_on_account_change_wake_slot_and_disconnect() {
+        let (sender, events) = mpsc::channel();
+        let subscriptions = Subscriptions {
+            accounts: Vec::new(),
+            events,
+        };
+        let timeout = Duration::from_millis(10);
+        let account = Pubkey::new_unique();
+
+        assert_eq!(subscriptions.wait(timeout, Some(10)), WakeReason::Timeout);
+
+        // Slots before the wake slot do not wake us up.
+        sender.send(Event::Slot(8)).unwrap();
+        sender.send(Event::Slot(9)).unwrap();
+        assert_eq!(subscriptions.wait(timeout, Some(10)), WakeReason::Timeout);
+        sender.send(Event::Slot(11)).unwrap();
+        assert_eq!(subscriptions.wait(timeout, Some(10)), WakeReason::Slot(11));
+        sender.send(Event::Slot(12)).unwrap();
+        assert_eq!(subscriptions.wait(timeout, None), WakeReason::Timeout);
+
+        // Queued account changes are coalesced into a single wakeup.
+        sender.send(Event::AccountChanged(account)).unwrap();
+        sender
+            .send(Event::AccountChanged(Pubkey::new_unique()))
+            .unwrap();
+        sender.send(Event::Slot(13)).unwrap();
+        assert_eq!(
+            subscriptions.wait(timeout, Some(10)),
+            WakeReason::AccountChanged(account)
+        );
+        assert_eq!(subscriptions.wait(timeout, None), WakeReason::Timeout);
+
+        sender.send(Event::Disconnected).unwrap();
+        assert_eq!(subscriptions.wait(timeout, None), WakeReason::Disconnected);
+        drop(sender);
+        assert_eq!(subscriptions.wait(timeout, None), WakeReason::Disconnected);
+    }
+}
@@ -21,6 +21,10 @@ pub struct PubkeyAndEntry<T> {
 }
 
 /// A map from public key to `T`, implemented as a vector of key-value pairs.
+///
+/// The entries are sorted by public key, so lookups can use binary search, and
+/// iteration order is deterministic. Instances created before this invariant
+/// was introduced can be sorted with [`AccountMap::sort`].
 #[derive(
     Clone, Default, Debug, Eq, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema, Serialize,
 )]
@@ -36,6 +40,9 @@ pub type AccountSet = AccountMap<()>;
 
 impl<T: Default + EntryConstantSize> AccountMap<T> {
     /// Creates a new instance with the `maximum_entries` positions filled with the default value
+    ///
+    /// All entries have the same (default) key, so the result is only suitable
+    /// for size computations and index-based access, not for lookups by key.
     pub fn new_fill_default(maximum_entries: u32) -> Self {
         let mut v = Vec::with_capacity(maximum_entries as usize);
         for _ in 0..maximum_entries {
@@ -66,42 +73,73 @@ impl<T: Default + EntryConstantSize> AccountMap<T> {
         self.entries.is_empty()
     }
 
+    /// Binary search for `address`.
+    ///
+    /// Returns `Ok` with the index of the entry if it is present, or `Err`
+    /// with the index where it should be inserted to keep the entries sorted.
+    fn search(&self, address: &Pubkey) -> Result<usize, usize> {
+        self.entries.binary_search_by(|pe| pe.pubkey.cmp(address))
+    }
+
+    /// Return the index of the entry for `address`, if it is present.
+    pub fn position(&self, address: &Pubkey) -> Option<usize> {
+        self.search(address).ok()
+    }
+
     pub fn add(&mut self, address: Pubkey, value: T) -> Result<(), LidoError> {
         if self.len() == self.maximum_entries as usize {
             return Err(LidoError::MaximumNumberOfAccountsExceeded);
         }
-        if !self.entries.iter().any(|pe| pe.pubkey == address) {
-            self.entries.push(PubkeyAndEntry {
-                pubkey: address,
-                entry: value,
-            });
-        } else {
-            return Err(LidoError::DuplicatedEntry);
+        match self.search(&address) {
+            Ok(_) => Err(LidoError::DuplicatedEntry),
+            Err(idx) => {
+                self.entries.insert(
+                    idx,
+                    PubkeyAndEntry {
+                        pubkey: address,
+                        entry: value,
+                    },
+                );
+                Ok(())
+            }
         }
-        Ok(())
     }
 
     pub fn remove(&mut self, address: &Pubkey) -> Result<T, LidoError> {
         let idx = self
-            .entries
-            .iter()
-            .position(|pe| &pe.pubkey == address)
+            .position(address)
             .ok_or(LidoError::InvalidAccountMember)?;
-        Ok(self.entries.swap_remove(idx).entry)
+        // Not `swap_remove`, that would break the ordering.
+        Ok(self.entries.remove(idx).entry)
     }
 
     pub fn get(&self, address: &Pubkey) -> Result<&PubkeyAndEntry<T>, LidoError> {
-        self.entries
-            .iter()
-            .find(|pe| &pe.pubkey == address)
-            .ok_or(LidoError::InvalidAccountMember)
+        let idx = self
+            .position(address)
+            .ok_or(LidoError::InvalidAccountMember)?;
+        Ok(&self.entries[idx])
     }
 
     pub fn get_mut(&mut self, address: &Pubkey) -> Result<&mut PubkeyAndEntry<T>, LidoError> {
+        let idx = self
+            .position(address)
+            .ok_or(LidoError::InvalidAccountMember)?;
+        Ok(&mut self.entries[idx])
+    }
+
+    /// Return whether the entries are sorted by key, without duplicates.
+    pub fn is_sorted(&self) -> bool {
         self.entries
-            .iter_mut()
-            .find(|pe| &pe.pubkey == address)
-            .ok_or(LidoError::InvalidAccountMember)
+            .windows(2)
+            .all(|pair| pair[0].pubkey < pair[1].pubkey)
+    }
+
+    /// Sort the entries by key.
+    ///
+    /// This is only needed to upgrade maps that were created before entries
+    /// were kept sorted, [`AccountMap::add`] preserves the ordering.
+    pub fn sort(&mut self) {
+        self.entries.sort_by(|a, b| a.pubkey.cmp(&b.pubkey));
     }
 
     /// Return how many bytes are needed to serialize an instance holding `max_entries`.
@@ -204,6 +242,59 @@ mod test {
         assert_eq!(map.remove(&key), Err(LidoError::InvalidAccountMember));
     }
 
+    #[test]
+    fn test_account_map_stays_sorted() {
+        let mut map: AccountMap<u32> = AccountMap::new(10);
+        let keys: Vec<Pubkey> = (0..10).map(|_| Pubkey::new_unique()).collect();
+
+        // Insert in an order that is neither ascending nor descending.
+        for (i, key) in keys.iter().enumerate().rev().step_by(2) {
+            map.add(*key, i as u32).unwrap();
+        }
+        for (i, key) in keys.iter().enumerate().step_by(2) {
+            map.add(*key, i as u32).unwrap();
+        }
+        assert!(map.is_sorted());
+
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(map.get(key).map(|pe| pe.entry), Ok(i as u32));
+        }
+
+        map.remove(&keys[3]).unwrap();
+        map.remove(&keys[0]).unwrap();
+        assert!(map.is_sorted());
+        assert_eq!(map.get(&keys[3]), Err(LidoError::InvalidAccountMember));
+        assert_eq!(map.get(&keys[9]).map(|pe| pe.entry), Ok(9));
+    }
+
+    #[test]
+    fn test_account_map_sort() {
+        // Explicit keys in ascending byte order, so the expected order does
+        // not depend on how `Pubkey::new_unique` generates its keys.
+        let keys: Vec<Pubkey> = (0..5u8).map(|i| Pubkey::new(&[i; 32])).collect();
+        let mut map: AccountMap<u32> = AccountMap {
+            entries: keys
+                .iter()
+                .enumerate()
+                .rev()
+                .map(|(i, key)| PubkeyAndEntry {
+                    pubkey: *key,
+                    entry: i as u32,
+                })
+                .collect(),
+            maximum_entries: 5,
+        };
+        assert!(!map.is_sorted());
+
+        map.sort();
+        assert!(map.is_sorted());
+        assert_eq!(
+            map.iter_entries().cloned().collect::<Vec<_>>(),
+            [0, 1, 2, 3, 4]
+        );
+        assert_eq!(map.position(&keys[2]), Some(2));
+    }
+
     #[test]
     fn test_account_map_iter_entries() {
         let mut map: AccountMap<u32> = AccountMap::new(2);
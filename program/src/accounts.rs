@@ -3,6 +3,38 @@
 
 //! Contains a utility macro that makes it safer to work with lists of accounts.
 
+use serde::Serialize;
+use solana_program::pubkey::Pubkey;
+
+use crate::util::serialize_b58_option;
+
+/// Description of one account that an instruction expects.
+///
+/// [`accounts_struct`] generates a `describe` function that returns these, so
+/// clients that are not written in Rust don't have to reverse-engineer the
+/// account order from the source.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct AccountDescription {
+    /// Name of the field in the accounts struct.
+    pub name: &'static str,
+
+    pub is_signer: bool,
+    pub is_writable: bool,
+
+    /// For accounts with a fixed address, such as sysvars, that address.
+    /// These are not part of the `Meta` struct, the caller must pass them
+    /// nonetheless.
+    #[serde(serialize_with = "serialize_b58_option")]
+    pub address: Option<Pubkey>,
+
+    /// Whether this stands for a variable number of accounts at the end.
+    pub is_variadic: bool,
+
+    /// Whether the caller may omit this account. Only the last account can be
+    /// optional.
+    pub is_optional: bool,
+}
+
 /// Implementation detail of [`accounts_struct`].
 #[macro_export]
 macro_rules! accounts_struct_meta {
@@ -39,6 +71,10 @@ macro_rules! accounts_struct_meta {
 ///
 ///  * Optionally, a vector with a variable number of accounts, with `pub ...`.
 ///
+///  * Optionally, one account at the very end that the caller may omit, with
+///    `pub ?`. It must be a read-only signer, so it can be told apart from the
+///    variadic accounts, which are never signers.
+///
 /// Example:
 /// ```
 /// # use lido::{accounts_struct, error::LidoError};
@@ -49,6 +85,7 @@ macro_rules! accounts_struct_meta {
 ///         pub frobnicator { is_signer: true, is_writable: false, },
 ///         const sysvar_rent = sysvar::rent::id(),
 ///         pub ...widgets { is_signer: false, is_writable: true, },
+///         pub ?operator { is_signer: true, is_writable: false, },
 ///     }
 /// }
 /// ```
@@ -57,6 +94,7 @@ macro_rules! accounts_struct_meta {
 /// # use solana_program::{pubkey::Pubkey, account_info::AccountInfo, instruction::AccountMeta, program_error::ProgramError};
 /// struct ExampleAccountsMeta {
 ///     frobnicator: Pubkey,
+///     operator: Option<Pubkey>,
 ///     widgets: Vec<Pubkey>,
 /// }
 ///
@@ -69,6 +107,7 @@ macro_rules! accounts_struct_meta {
 /// struct ExampleAccountsInfo<'a> {
 ///     frobnicator: &'a AccountInfo<'a>,
 ///     sysvar_rent: &'a AccountInfo<'a>,
+///     operator: Option<&'a AccountInfo<'a>>,
 ///     widgets: &'a [AccountInfo<'a>],
 /// }
 ///
@@ -115,6 +154,18 @@ macro_rules! accounts_struct {
                     is_writable: true,
                 }
             )?
+            // Per accounts struct you can have one optional field at the end,
+            // prefixed with a question mark.
+            $(
+                ,
+                pub ? $optional_account:ident {
+                    // Only allow read-only signers, so we can recognize the
+                    // optional account after the variadic accounts, which are
+                    // never signers.
+                    is_signer: true,
+                    is_writable: false,
+                }
+            )?
             // Require a trailing comma.
             ,
         }
@@ -126,6 +177,10 @@ macro_rules! accounts_struct {
             ),*
             // Const accounts are not included here, they are not a variable
             // input, they only show up in program, not in the call.
+            $(
+                ,
+                pub $optional_account: Option<Pubkey>
+            )?
             $(
                 ,
                 pub $multi_account: Vec<Pubkey>,
@@ -143,6 +198,10 @@ macro_rules! accounts_struct {
                     pub $const_account: &'a AccountInfo<'b>
                 ),*
             )?
+            $(
+                ,
+                pub $optional_account: Option<&'a AccountInfo<'b>>
+            )?
             $(
                 ,
                 pub $multi_account: &'a [AccountInfo<'b>],
@@ -181,6 +240,64 @@ macro_rules! accounts_struct {
                         ));
                     }
                 )?
+                $(
+                    if let Some(pubkey) = self.$optional_account {
+                        result.push(AccountMeta::new_readonly(pubkey, true /* is_signer */));
+                    }
+                )?
+                result
+            }
+
+            /// Describe the accounts, in the order that `to_vec` returns them.
+            #[allow(dead_code)]
+            pub fn describe() -> Vec<$crate::accounts::AccountDescription> {
+                // The mut is used depending on whether we have a variadic account at the end.
+                #[allow(unused_mut)]
+                let mut result = vec![
+                    $(
+                        $crate::accounts::AccountDescription {
+                            name: stringify!($var_account),
+                            is_signer: $is_signer,
+                            is_writable: $is_writable,
+                            address: None,
+                            is_variadic: false,
+                            is_optional: false,
+                        }
+                    ),*
+                    $(
+                        ,
+                        $(
+                            $crate::accounts::AccountDescription {
+                                name: stringify!($const_account),
+                                is_signer: false,
+                                is_writable: false,
+                                address: Some($const_value),
+                                is_variadic: false,
+                                is_optional: false,
+                            }
+                        ),*
+                    )?
+                ];
+                $(
+                    result.push($crate::accounts::AccountDescription {
+                        name: stringify!($multi_account),
+                        is_signer: false,
+                        is_writable: true,
+                        address: None,
+                        is_variadic: true,
+                        is_optional: false,
+                    });
+                )?
+                $(
+                    result.push($crate::accounts::AccountDescription {
+                        name: stringify!($optional_account),
+                        is_signer: true,
+                        is_writable: false,
+                        address: None,
+                        is_variadic: false,
+                        is_optional: true,
+                    });
+                )?
                 result
             }
 
@@ -221,6 +338,19 @@ macro_rules! accounts_struct {
                     )*
                 )?
 
+                $(
+                    // The optional account comes last, after the variadic
+                    // accounts if there are any. Those are never signers, so
+                    // if the last account is a signer, it is the optional one.
+                    let $optional_account = match accounts_iter.as_slice().last() {
+                        Some(meta) if meta.is_signer => {
+                            accounts_iter.next_back();
+                            Some(meta.pubkey)
+                        }
+                        _ => None,
+                    };
+                )?
+
                 $(
                     // Collect all remaining pubkeys in a vector.
                     let mut $multi_account = Vec::new();
@@ -241,6 +371,7 @@ macro_rules! accounts_struct {
 
                 let result = $NameAccountMeta {
                     $( $var_account ),*
+                    $( , $optional_account )?
                     $( , $multi_account )?
                 };
 
@@ -322,6 +453,18 @@ macro_rules! accounts_struct {
                     )*
                 )?
 
+                $(
+                    // See the `*Meta` version above, if the last account is a
+                    // signer, it is the optional one.
+                    let $optional_account = match accounts_iter.as_slice().last() {
+                        Some(account) if account.is_signer => {
+                            accounts_iter.next_back();
+                            Some(account)
+                        }
+                        _ => None,
+                    };
+                )?
+
                 $(
                     // Collect all remaining AccountInfos in a slice.
                     let $multi_account = accounts_iter.as_slice();
@@ -360,6 +503,7 @@ macro_rules! accounts_struct {
                         ,
                         $( $const_account ),*
                     )?
+                    $( , $optional_account )?
                     $( , $multi_account )?
                 };
 
@@ -647,4 +791,151 @@ mod test {
             Some(LidoError::InvalidAccountInfo.into()),
         );
     }
+
+    #[test]
+    // We only need the generated `describe`, not the structs themselves.
+    #[allow(dead_code)]
+    fn accounts_struct_describe() {
+        use crate::accounts::AccountDescription;
+        use solana_program::sysvar::clock;
+        accounts_struct! {
+            TestAccountsMeta, TestAccountsInfo {
+                pub s1_w0 { is_signer: true, is_writable: false, },
+                pub s0_w1 { is_signer: false, is_writable: true, },
+                const sysvar_clock = clock::id(),
+                pub ...remainder { is_signer: false, is_writable: true, },
+                pub ?last { is_signer: true, is_writable: false, },
+            }
+        }
+
+        let describe =
+            |name, is_signer, is_writable, address, is_variadic, is_optional| AccountDescription {
+                name,
+                is_signer,
+                is_writable,
+                address,
+                is_variadic,
+                is_optional,
+            };
+        assert_eq!(
+            TestAccountsMeta::describe(),
+            vec![
+                describe("s1_w0", true, false, None, false, false),
+                describe("s0_w1", false, true, None, false, false),
+                describe(
+                    "sysvar_clock",
+                    false,
+                    false,
+                    Some(clock::id()),
+                    false,
+                    false
+                ),
+                describe("remainder", false, true, None, true, false),
+                describe("last", true, false, None, false, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn accounts_struct_optional() {
+        accounts_struct! {
+            TestAccountsMeta, TestAccountsInfo {
+                pub single { is_signer: false, is_writable: false, },
+                pub ...remainder { is_signer: false, is_writable: true, },
+                pub ?last { is_signer: true, is_writable: false, },
+            }
+        }
+
+        for &(num_remainder, has_last) in &[(0, false), (0, true), (2, false), (2, true)] {
+            let input = TestAccountsMeta {
+                single: Pubkey::new_unique(),
+                remainder: (0..num_remainder).map(|_| Pubkey::new_unique()).collect(),
+                last: if has_last {
+                    Some(Pubkey::new_unique())
+                } else {
+                    None
+                },
+            };
+            let account_metas: Vec<AccountMeta> = input.to_vec();
+            assert_eq!(account_metas.len(), 1 + num_remainder + has_last as usize);
+            if let Some(last) = input.last {
+                // The optional account goes after the variadic accounts.
+                let last_meta = account_metas.last().unwrap();
+                assert_eq!(last_meta.pubkey, last);
+                assert_eq!(last_meta.is_signer, true);
+                assert_eq!(last_meta.is_writable, false);
+            }
+
+            let output_meta = TestAccountsMeta::try_from_slice(&account_metas).unwrap();
+            assert_eq!(output_meta.single, input.single);
+            assert_eq!(output_meta.remainder, input.remainder);
+            assert_eq!(output_meta.last, input.last);
+
+            let mut lamports = vec![0; account_metas.len()];
+            let mut datas = vec![vec![]; account_metas.len()];
+            let owner = Pubkey::new_unique();
+            let account_infos: Vec<AccountInfo> = account_metas
+                .iter()
+                .zip(lamports.iter_mut())
+                .zip(datas.iter_mut())
+                .map(|((meta, lamports), data)| {
+                    AccountInfo::new(
+                        &meta.pubkey,
+                        meta.is_signer,
+                        meta.is_writable,
+                        lamports,
+                        data,
+                        &owner,
+                        false,
+                        0,
+                    )
+                })
+                .collect();
+
+            let output_info = TestAccountsInfo::try_from_slice(&account_infos).unwrap();
+            assert_eq!(output_info.single.key, &input.single);
+            assert_eq!(output_info.remainder.len(), num_remainder);
+            assert_eq!(output_info.last.map(|a| *a.key), input.last);
+        }
+    }
+
+    #[test]
+    fn accounts_struct_optional_without_variadic() {
+        accounts_struct! {
+            TestAccountsMeta, TestAccountsInfo {
+                pub single { is_signer: false, is_writable: false, },
+                pub ?last { is_signer: true, is_writable: false, },
+            }
+        }
+
+        let single = Pubkey::new_unique();
+        let last = Pubkey::new_unique();
+        let input = TestAccountsMeta {
+            single,
+            last: Some(last),
+        };
+        let mut account_metas = input.to_vec();
+        assert_eq!(
+            TestAccountsMeta::try_from_slice(&account_metas)
+                .unwrap()
+                .last,
+            Some(last)
+        );
+
+        // Without variadic accounts, a trailing account that is not a signer
+        // cannot be the optional one, so it is one too many.
+        account_metas[1].is_signer = false;
+        assert_eq!(
+            TestAccountsMeta::try_from_slice(&account_metas).err(),
+            Some(LidoError::TooManyAccountKeys.into()),
+        );
+
+        account_metas.pop();
+        assert_eq!(
+            TestAccountsMeta::try_from_slice(&account_metas)
+                .unwrap()
+                .last,
+            None
+        );
+    }
 }
@@ -0,0 +1,138 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+//! Helpers for on-chain programs that call into Solido.
+//!
+//! To use these, depend on the `lido` crate with the `no-entrypoint` feature,
+//! so its entrypoint does not conflict with the entrypoint of your program:
+//!
+//! ```toml
+//! [dependencies]
+//! lido = { version = "1.1.0", features = ["no-entrypoint"] }
+//! ```
+//!
+//! The invoke helpers take the same accounts structs that the Solido program
+//! itself uses, so the accounts are passed in the order that Solido expects.
+//! Aside from the accounts of the instruction, they need the account of the
+//! Solido program itself, which the runtime requires for cross-program
+//! invocations.
+
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program::invoke_signed,
+    program_error::ProgramError, pubkey::Pubkey,
+};
+
+use crate::{
+    instruction::{
+        self, DepositAccountsInfo, DepositAccountsMeta, WithdrawAccountsInfo, WithdrawAccountsMeta,
+    },
+    logic::{check_lido_owner, check_lido_version},
+    state::{ExchangeRate, LIDO_VERSION},
+    token::{Lamports, StLamports},
+    zero_copy::LidoAccountView,
+};
+
+/// Deposit `amount` SOL from `accounts.user` into Solido, and mint stSOL to `accounts.recipient`.
+///
+/// If `user` is a program derived address of the calling program, pass its
+/// seeds in `signers_seeds`, otherwise pass an empty slice.
+pub fn deposit<'a>(
+    solido_program: &AccountInfo<'a>,
+    accounts: &DepositAccountsInfo<'_, 'a>,
+    amount: Lamports,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let instruction = instruction::deposit(
+        solido_program.key,
+        &DepositAccountsMeta {
+            lido: *accounts.lido.key,
+            user: *accounts.user.key,
+            recipient: *accounts.recipient.key,
+            st_sol_mint: *accounts.st_sol_mint.key,
+            reserve_account: *accounts.reserve_account.key,
+            mint_authority: *accounts.mint_authority.key,
+        },
+        amount,
+    );
+    invoke_signed(
+        &instruction,
+        &[
+            accounts.lido.clone(),
+            accounts.user.clone(),
+            accounts.recipient.clone(),
+            accounts.st_sol_mint.clone(),
+            accounts.reserve_account.clone(),
+            accounts.mint_authority.clone(),
+            accounts.spl_token.clone(),
+            accounts.system_program.clone(),
+            solido_program.clone(),
+        ],
+        signers_seeds,
+    )
+}
+
+/// Burn `amount` stSOL from `accounts.st_sol_account`, and split the
+/// corresponding SOL off `accounts.source_stake_account` into
+/// `accounts.destination_stake_account`.
+///
+/// If `st_sol_account_owner` is a program derived address of the calling
+/// program, pass its seeds in `signers_seeds`, otherwise pass an empty slice.
+pub fn withdraw<'a>(
+    solido_program: &AccountInfo<'a>,
+    accounts: &WithdrawAccountsInfo<'_, 'a>,
+    amount: StLamports,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let instruction = instruction::withdraw(
+        solido_program.key,
+        &WithdrawAccountsMeta {
+            lido: *accounts.lido.key,
+            validator_list: *accounts.validator_list.key,
+            st_sol_account_owner: *accounts.st_sol_account_owner.key,
+            st_sol_account: *accounts.st_sol_account.key,
+            st_sol_mint: *accounts.st_sol_mint.key,
+            validator_vote_account: *accounts.validator_vote_account.key,
+            source_stake_account: *accounts.source_stake_account.key,
+            destination_stake_account: *accounts.destination_stake_account.key,
+            stake_authority: *accounts.stake_authority.key,
+        },
+        amount,
+    );
+    invoke_signed(
+        &instruction,
+        &[
+            accounts.lido.clone(),
+            accounts.validator_list.clone(),
+            accounts.st_sol_account_owner.clone(),
+            accounts.st_sol_account.clone(),
+            accounts.st_sol_mint.clone(),
+            accounts.validator_vote_account.clone(),
+            accounts.source_stake_account.clone(),
+            accounts.destination_stake_account.clone(),
+            accounts.stake_authority.clone(),
+            accounts.spl_token.clone(),
+            accounts.sysvar_clock.clone(),
+            accounts.system_program.clone(),
+            accounts.stake_program.clone(),
+            solido_program.clone(),
+        ],
+        signers_seeds,
+    )
+}
+
+/// Read the current exchange rate from a Solido instance.
+///
+/// This reads only the exchange rate from the `Lido` account, it does not
+/// deserialize the rest of the account, and it does not need the validator
+/// list. The exchange rate is updated once per epoch, check
+/// [`ExchangeRate::computed_in_epoch`] against the current epoch if the
+/// caller relies on it being recent.
+pub fn get_exchange_rate(
+    solido_program_id: &Pubkey,
+    lido: &AccountInfo,
+) -> Result<ExchangeRate, ProgramError> {
+    check_lido_owner(solido_program_id, lido)?;
+    check_lido_version(lido, LIDO_VERSION)?;
+    let data = lido.data.borrow();
+    Ok(LidoAccountView::new(&data[..])?.exchange_rate())
+}
@@ -165,6 +165,43 @@ pub enum LidoError {
 
     /// We expected the StSol account to be owned by the SPL token program.
     InvalidStSolAccountOwner = 46,
+
+    /// The Lido account has a different version than this instruction expects.
+    ///
+    /// Older accounts need to be upgraded first, see `LIDO_VERSION` for the
+    /// instructions that do so.
+    InvalidLidoVersion = 47,
+
+    /// The validator list account is not the one referenced by the Lido account.
+    InvalidValidatorList = 48,
+
+    /// The exchange rate oracle account is not the one of this Solido instance,
+    /// or it is not initialized.
+    InvalidExchangeRateOracle = 49,
+
+    /// The token metadata account is not the one of the stSOL mint, or the
+    /// name, symbol, or URI is too long.
+    InvalidTokenMetadata = 50,
+
+    /// Deposits are not accepted this close to the end of the epoch.
+    DepositCutoff = 51,
+
+    /// The validator was force-removed already, it is waiting for its stake
+    /// accounts to become inactive.
+    ValidatorIsTombstone = 52,
+
+    /// The vote withdrawer to hand the vote account back to is not the one
+    /// that was registered when the validator was added.
+    InvalidVoteWithdrawer = 53,
+
+    /// The signer is not one of the validator's own keys: the validator
+    /// identity, the authorized voter, or the vote withdrawer, depending on
+    /// the instruction.
+    InvalidValidatorAuthority = 54,
+
+    /// More validator fee accounts were passed to `CollectValidatorFee` than
+    /// `MAXIMUM_DIRECT_FEE_ACCOUNTS`.
+    TooManyFeeAccounts = 55,
 }
 
 // Just reuse the generated Debug impl for Display. It shows the variant names.
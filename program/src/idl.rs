@@ -0,0 +1,293 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+//! Machine-readable description of the Solido program interface.
+//!
+//! The account order of every instruction is defined with [`accounts_struct`],
+//! and the data layout of instructions and accounts follows from their Borsh
+//! serialization. [`Idl`] collects both in a form that can be serialized to
+//! JSON, for explorers and clients that are not written in Rust.
+//!
+//! Type schemas use the names from [`BorshSchema`]. Declarations that have no
+//! entry in `definitions` are primitive types, such as `u8`, `u64`, or `bool`.
+//!
+//! [`accounts_struct`]: crate::accounts_struct
+
+use std::collections::{BTreeMap, HashMap};
+
+use borsh::schema::{Definition, Fields};
+use borsh::BorshSchema;
+use serde::Serialize;
+
+use crate::accounts::AccountDescription;
+use crate::instruction::{
+    AddMaintainerMeta, AddValidatorMeta, ChangeDepositCutoffMeta, ChangeFeePayoutModeMeta,
+    ChangeRewardDistributionMeta, ClaimMaintainerFeeMeta, ClaimValidatorFeeMeta,
+    ClaimValidatorFeeToMeta, CollectValidatorFeeMeta, DeactivateValidatorMeta, DepositAccountsMeta,
+    ForceRemoveValidatorMeta, InitializeAccountsMeta, InitializeExchangeRateOracleMeta,
+    LidoInstruction, MaintainerHeartbeatMeta, MergeStakeMeta, MigrateValidatorListMeta,
+    RemoveMaintainerMeta, RemoveValidatorMeta, SetTokenMetadataMeta, SortAccountMapsMeta,
+    StakeDepositAccountsMeta, UnstakeAccountsMeta, UpdateExchangeRateAccountsMeta,
+    UpgradeFeePayoutModeMeta, UpgradeMaintainersMeta, UpgradeMetricsMeta,
+    UpgradeValidatorEntriesMeta, UpgradeValidatorListMeta, WithdrawAccountsMeta,
+    WithdrawInactiveStakeMeta,
+};
+use crate::oracle::ExchangeRateOracle;
+use crate::state::{Lido, Validators, LIDO_VERSION};
+
+#[derive(Debug, Serialize)]
+pub struct Idl {
+    pub name: &'static str,
+
+    /// Version of the `lido` crate that produced this description.
+    pub version: &'static str,
+
+    /// Version of the `Lido` account layout, see [`LIDO_VERSION`].
+    pub lido_version: u8,
+
+    /// The instructions, in the order of their discriminant.
+    pub instructions: Vec<InstructionDescription>,
+
+    /// Layout of the instruction data, a Borsh-serialized `LidoInstruction`.
+    pub instruction_schema: TypeSchema,
+
+    /// Layout of the accounts that the program owns.
+    pub accounts: Vec<TypeSchema>,
+}
+
+impl Idl {
+    pub fn new() -> Idl {
+        Idl {
+            name: "lido",
+            version: env!("CARGO_PKG_VERSION"),
+            lido_version: LIDO_VERSION,
+            instructions: describe_instructions(),
+            instruction_schema: TypeSchema::of::<LidoInstruction>(),
+            accounts: vec![
+                TypeSchema::of::<Lido>(),
+                TypeSchema::of::<Validators>(),
+                TypeSchema::of::<ExchangeRateOracle>(),
+            ],
+        }
+    }
+}
+
+impl Default for Idl {
+    fn default() -> Idl {
+        Idl::new()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct InstructionDescription {
+    pub name: &'static str,
+
+    /// The first byte of the instruction data.
+    pub discriminant: u8,
+
+    /// The accounts that the instruction expects, in order.
+    pub accounts: Vec<AccountDescription>,
+}
+
+/// Describe all instructions, in the order of [`LidoInstruction`].
+///
+/// When you add an instruction, add it here too, the tests check that the
+/// names match the enum.
+pub fn describe_instructions() -> Vec<InstructionDescription> {
+    let instructions = vec![
+        ("Initialize", InitializeAccountsMeta::describe()),
+        ("Deposit", DepositAccountsMeta::describe()),
+        ("Withdraw", WithdrawAccountsMeta::describe()),
+        ("StakeDeposit", StakeDepositAccountsMeta::describe()),
+        ("Unstake", UnstakeAccountsMeta::describe()),
+        (
+            "UpdateExchangeRate",
+            UpdateExchangeRateAccountsMeta::describe(),
+        ),
+        (
+            "WithdrawInactiveStake",
+            WithdrawInactiveStakeMeta::describe(),
+        ),
+        ("CollectValidatorFee", CollectValidatorFeeMeta::describe()),
+        ("ClaimValidatorFee", ClaimValidatorFeeMeta::describe()),
+        (
+            "ChangeRewardDistribution",
+            ChangeRewardDistributionMeta::describe(),
+        ),
+        ("AddValidator", AddValidatorMeta::describe()),
+        ("DeactivateValidator", DeactivateValidatorMeta::describe()),
+        ("RemoveValidator", RemoveValidatorMeta::describe()),
+        ("AddMaintainer", AddMaintainerMeta::describe()),
+        ("RemoveMaintainer", RemoveMaintainerMeta::describe()),
+        ("MergeStake", MergeStakeMeta::describe()),
+        ("ChangeFeePayoutMode", ChangeFeePayoutModeMeta::describe()),
+        ("UpgradeFeePayoutMode", UpgradeFeePayoutModeMeta::describe()),
+        ("MigrateValidatorList", MigrateValidatorListMeta::describe()),
+        ("SortAccountMaps", SortAccountMapsMeta::describe()),
+        ("UpgradeValidatorList", UpgradeValidatorListMeta::describe()),
+        ("UpgradeMetrics", UpgradeMetricsMeta::describe()),
+        (
+            "InitializeExchangeRateOracle",
+            InitializeExchangeRateOracleMeta::describe(),
+        ),
+        ("SetTokenMetadata", SetTokenMetadataMeta::describe()),
+        ("ChangeDepositCutoff", ChangeDepositCutoffMeta::describe()),
+        ("ClaimMaintainerFee", ClaimMaintainerFeeMeta::describe()),
+        ("ForceRemoveValidator", ForceRemoveValidatorMeta::describe()),
+        (
+            "UpgradeValidatorEntries",
+            UpgradeValidatorEntriesMeta::describe(),
+        ),
+        ("ClaimValidatorFeeTo", ClaimValidatorFeeToMeta::describe()),
+        ("MaintainerHeartbeat", MaintainerHeartbeatMeta::describe()),
+        ("UpgradeMaintainers", UpgradeMaintainersMeta::describe()),
+    ];
+    instructions
+        .into_iter()
+        .enumerate()
+        .map(|(i, (name, accounts))| InstructionDescription {
+            name,
+            discriminant: i as u8,
+            accounts,
+        })
+        .collect()
+}
+
+/// The Borsh schema of a type, in a form that serializes to readable JSON.
+#[derive(Debug, Serialize)]
+pub struct TypeSchema {
+    pub declaration: String,
+
+    /// Definitions of the type and all types it contains, by name.
+    pub definitions: BTreeMap<String, TypeDefinition>,
+}
+
+impl TypeSchema {
+    pub fn of<T: BorshSchema>() -> TypeSchema {
+        let mut definitions = HashMap::new();
+        T::add_definitions_recursively(&mut definitions);
+        TypeSchema {
+            declaration: T::declaration(),
+            definitions: definitions
+                .into_iter()
+                .map(|(name, definition)| (name, TypeDefinition::from(definition)))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TypeDefinition {
+    /// A fixed number of elements of the same type.
+    Array {
+        length: u32,
+        elements: String,
+    },
+
+    /// A u32 length, followed by that many elements of the same type.
+    Sequence {
+        elements: String,
+    },
+
+    Tuple {
+        elements: Vec<String>,
+    },
+
+    /// A u8 discriminant, followed by the fields of the variant.
+    Enum {
+        variants: Vec<Field>,
+    },
+
+    Struct {
+        fields: Vec<Field>,
+    },
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct Field {
+    /// Name of the field or variant, or `None` for fields of tuple structs.
+    pub name: Option<String>,
+
+    #[serde(rename = "type")]
+    pub type_: String,
+}
+
+impl From<Definition> for TypeDefinition {
+    fn from(definition: Definition) -> TypeDefinition {
+        let named = |(name, type_)| Field {
+            name: Some(name),
+            type_,
+        };
+        match definition {
+            Definition::Array { length, elements } => TypeDefinition::Array { length, elements },
+            Definition::Sequence { elements } => TypeDefinition::Sequence { elements },
+            Definition::Tuple { elements } => TypeDefinition::Tuple { elements },
+            Definition::Enum { variants } => TypeDefinition::Enum {
+                variants: variants.into_iter().map(named).collect(),
+            },
+            Definition::Struct { fields } => TypeDefinition::Struct {
+                fields: match fields {
+                    Fields::NamedFields(fields) => fields.into_iter().map(named).collect(),
+                    Fields::UnnamedFields(fields) => fields
+                        .into_iter()
+                        .map(|type_| Field { name: None, type_ })
+                        .collect(),
+                    Fields::Empty => Vec::new(),
+                },
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_describe_instructions_matches_lido_instruction() {
+        let schema = TypeSchema::of::<LidoInstruction>();
+        let variants = match &schema.definitions[&schema.declaration] {
+            TypeDefinition::Enum { variants } => variants,
+            _ => panic!("Expected LidoInstruction to be an enum."),
+        };
+        let variant_names: Vec<&str> = variants
+            .iter()
+            .map(|v| v.name.as_deref().unwrap())
+            .collect();
+        let instruction_names: Vec<&str> = describe_instructions()
+            .iter()
+            .map(|instruction| instruction.name)
+            .collect();
+        assert_eq!(instruction_names, variant_names);
+    }
+
+    #[test]
+    fn test_describe_instructions_discriminant() {
+        let instructions = describe_instructions();
+        let deposit = instructions
+            .iter()
+            .find(|instruction| instruction.name == "Deposit")
+            .unwrap();
+        let data = LidoInstruction::Deposit {
+            amount: crate::token::Lamports(1),
+        }
+        .to_vec();
+        assert_eq!(data[0], deposit.discriminant);
+    }
+
+    #[test]
+    fn test_idl_serializes_to_json() {
+        let json = serde_json::to_value(&Idl::new()).unwrap();
+        assert_eq!(json["instructions"][1]["name"], "Deposit");
+        assert_eq!(json["instructions"][1]["accounts"][0]["name"], "lido");
+        assert_eq!(
+            json["instruction_schema"]["definitions"]["LidoInstructionDeposit"],
+            serde_json::json!({
+                "kind": "struct",
+                "fields": [{ "name": "amount", "type": "Lamports" }],
+            })
+        );
+        assert_eq!(json["accounts"][0]["declaration"], "Lido");
+    }
+}
@@ -18,8 +18,9 @@ use solana_program::{
 use crate::{
     accounts_struct, accounts_struct_meta,
     error::LidoError,
-    state::RewardDistribution,
+    state::{FeePayoutMode, RewardDistribution},
     token::{Lamports, StLamports},
+    token_metadata::{self, TokenMetadata},
 };
 
 #[repr(C)]
@@ -63,15 +64,25 @@ pub enum LidoInstruction {
     },
     /// Update the exchange rate, at the beginning of the epoch.
     ///
-    /// This can be called by anybody.
+    /// This is permissionless. A maintainer can pass itself as the optional
+    /// last account, then the update counts towards its share of the
+    /// maintainer fee, like the other maintenance instructions.
     UpdateExchangeRate,
 
     /// Observe any external changes in the balances of a validator's stake accounts.
     ///
     /// If there is inactive balance in stake accounts, withdraw this back to the reserve.
+    ///
+    /// This is permissionless. A maintainer can pass itself as the optional
+    /// last account, then the withdrawal counts towards its share of the
+    /// maintainer fee, if there was anything to withdraw.
     WithdrawInactiveStake,
 
     /// Claim rewards from the validator account and distribute rewards.
+    ///
+    /// This is permissionless. A maintainer can pass itself as the optional
+    /// last account, then the collection counts towards its share of the
+    /// maintainer fee, if there were any rewards to collect.
     CollectValidatorFee,
     ClaimValidatorFee,
     ChangeRewardDistribution {
@@ -81,7 +92,13 @@ pub enum LidoInstruction {
 
     /// Add a new validator to the validator set.
     ///
-    /// Requires the manager to sign.
+    /// The validator registers the withdraw authority that its vote account
+    /// gets back when it is removed, see
+    /// [`crate::state::Validator::vote_withdrawer`].
+    ///
+    /// Requires the manager to sign, and either the vote withdrawer or the
+    /// validator identity, so nobody can register a withdrawer on behalf of
+    /// the validator.
     AddValidator,
 
     /// Set the `active` flag to false for a given validator.
@@ -97,10 +114,162 @@ pub enum LidoInstruction {
     /// unclaimed fee credits, then the validator can be removed.
     DeactivateValidator,
 
+    /// Remove an inactive validator that has no stake and no fee credit left,
+    /// and set the withdraw authority of its vote account back to the vote
+    /// withdrawer that the validator registered, or to the validator identity
+    /// if it did not register one.
+    ///
+    /// This is permissionless.
     RemoveValidator,
     AddMaintainer,
     RemoveMaintainer,
+
+    /// Merge two adjacent stake accounts of a validator.
+    ///
+    /// This is permissionless. A maintainer can pass itself as the optional
+    /// last account, then the merge counts towards its share of the
+    /// maintainer fee.
     MergeStake,
+
+    /// Set how validation fees are paid out to validators.
+    ///
+    /// Requires the manager to sign.
+    ChangeFeePayoutMode {
+        #[allow(dead_code)] // but it's not
+        new_fee_payout_mode: FeePayoutMode,
+    },
+
+    /// Upgrade a version 0 `Lido` account to version 1, which has a fee payout mode.
+    ///
+    /// The fee payout mode starts out as `Credit`, the only behavior version 0
+    /// had. The account keeps its size, so if it has no room left for the
+    /// maximum number of maintainers, the maximum is reduced.
+    ///
+    /// Requires the manager to sign.
+    UpgradeFeePayoutMode,
+
+    /// Move the validators out of a version 1 `Lido` account, into a separate
+    /// validator list account, and upgrade the `Lido` account to the current
+    /// version.
+    ///
+    /// The validator list account must be owned by the Solido program, be
+    /// rent-exempt, be large enough to hold the validators, and be zeroed.
+    ///
+    /// Requires the manager to sign.
+    MigrateValidatorList,
+
+    /// Sort the validators and maintainers of a version 2 instance by public
+    /// key, and upgrade the `Lido` account to version 3.
+    ///
+    /// Requires the manager to sign.
+    SortAccountMaps,
+
+    /// Copy the validators of a version 2 or 3 instance into a new validator
+    /// list account in the current layout, which includes per-validator
+    /// metrics, and upgrade the `Lido` account to the current version.
+    ///
+    /// The new validator list account must be owned by the Solido program, be
+    /// rent-exempt, be large enough to hold as many validators as the old one,
+    /// and be zeroed. The old validator list account is no longer used
+    /// afterwards.
+    ///
+    /// Requires the manager to sign.
+    UpgradeValidatorList,
+
+    /// Convert the header of a version 4 through 6 instance to the current
+    /// layout, which tracks more metrics, has a deposit cutoff, and pays a
+    /// maintainer fee, and upgrade the `Lido` account to the current version.
+    /// The deposit cutoff and the maintainer fee start out at zero.
+    ///
+    /// The `Lido` account keeps its size, so this may reduce the maximum number
+    /// of maintainers.
+    ///
+    /// Requires the manager to sign.
+    UpgradeMetrics,
+
+    /// Create the exchange rate oracle of an instance, and write the current
+    /// exchange rate to it. See [`crate::oracle`].
+    ///
+    /// This is permissionless, the funder pays for the rent.
+    InitializeExchangeRateOracle,
+
+    /// Create or update the token metadata of the stSOL mint, so wallets can
+    /// show its name and symbol. See [`crate::token_metadata`].
+    ///
+    /// The mint authority signs for the metadata program, and it is also the
+    /// update authority of the metadata. The payer funds the metadata account
+    /// when it is created.
+    ///
+    /// Requires the manager to sign.
+    SetTokenMetadata {
+        #[allow(dead_code)] // but it's not
+        token_metadata: TokenMetadata,
+    },
+
+    /// Set the number of slots at the end of every epoch in which deposits
+    /// are refused, see [`crate::state::LidoHeader::deposit_cutoff_slots`].
+    ///
+    /// Requires the manager to sign.
+    ChangeDepositCutoff {
+        #[allow(dead_code)] // but it's not
+        deposit_cutoff_slots: u64,
+    },
+
+    /// Mint the maintainer fee that the signing maintainer is entitled to,
+    /// see [`crate::state::Maintainer::fee_credit`].
+    ///
+    /// Requires the maintainer to sign.
+    ClaimMaintainerFee,
+
+    /// Deactivate a validator, deactivate all of its stake accounts, and pay
+    /// out its fee credit, all at once.
+    ///
+    /// This turns the validator into a tombstone, see
+    /// [`crate::state::Validator::tombstone`]. Once the stake is inactive, the
+    /// maintainer withdraws it with `WithdrawInactiveStake`, and removes the
+    /// validator with `RemoveValidator`.
+    ///
+    /// Requires the manager to sign.
+    ForceRemoveValidator,
+
+    /// Copy the validators of a version 7 or 8 instance into a new validator
+    /// list account in the current layout, which has room for a tombstone flag
+    /// and a vote withdrawer, and upgrade the `Lido` account to version 9.
+    ///
+    /// The new validator list account must be owned by the Solido program, be
+    /// rent-exempt, be large enough to hold as many validators as the old one,
+    /// and be zeroed. The old validator list account is no longer used
+    /// afterwards.
+    ///
+    /// Requires the manager to sign.
+    UpgradeValidatorEntries,
+
+    /// Mint the fee credit of a validator to an stSOL account of its choice.
+    ///
+    /// Unlike `ClaimValidatorFee`, which is permissionless and always pays out
+    /// to the registered fee address, this lets the validator pull its own
+    /// fees to any recipient.
+    ///
+    /// Requires the validator identity or the authorized voter of the vote
+    /// account to sign.
+    ClaimValidatorFeeTo,
+
+    /// Record that the signing maintainer is alive, see
+    /// [`crate::state::Maintainer::last_heartbeat_slot`].
+    ///
+    /// Requires the maintainer to sign.
+    MaintainerHeartbeat,
+
+    /// Convert the maintainers of a version 9 instance to the current layout,
+    /// which records their most recent heartbeat, and upgrade the `Lido`
+    /// account to the current version. Version 10 instances already have the
+    /// current layout, for those this only updates the version.
+    ///
+    /// The `Lido` account keeps its size, so this may reduce the maximum number
+    /// of maintainers.
+    ///
+    /// Requires the manager to sign.
+    UpgradeMaintainers,
 }
 
 impl LidoInstruction {
@@ -120,6 +289,10 @@ accounts_struct! {
             is_signer: false,
             is_writable: true,
         },
+        pub validator_list {
+            is_signer: false,
+            is_writable: true,
+        },
         pub manager {
             is_signer: false,
             is_writable: false,
@@ -224,6 +397,10 @@ accounts_struct! {
             // Needs to be writable for us to update the metrics.
             is_writable: true,
         },
+        pub validator_list {
+            is_signer: false,
+            is_writable: true,
+        },
         pub st_sol_account_owner {
             is_signer: true,
             is_writable: false,
@@ -284,6 +461,11 @@ pub fn withdraw(
 accounts_struct! {
     StakeDepositAccountsMeta, StakeDepositAccountsInfo {
         pub lido {
+            is_signer: false,
+            // Needs to be writable to record the maintainer's operation.
+            is_writable: true,
+        },
+        pub validator_list {
             is_signer: false,
             is_writable: true,
         },
@@ -351,6 +533,11 @@ pub fn stake_deposit(
 accounts_struct! {
     UnstakeAccountsMeta, UnstakeAccountsInfo {
         pub lido {
+            is_signer: false,
+            // Needs to be writable to record the maintainer's operation.
+            is_writable: true,
+        },
+        pub validator_list {
             is_signer: false,
             is_writable: true,
         },
@@ -411,6 +598,10 @@ accounts_struct! {
             is_signer: false,
             is_writable: true,
         },
+        pub validator_list {
+            is_signer: false,
+            is_writable: false,
+        },
         pub reserve {
             is_signer: false,
             is_writable: false,
@@ -419,8 +610,21 @@ accounts_struct! {
             is_signer: false,
             is_writable: false,
         },
+        // The exchange rate is mirrored to this account, if it exists.
+        pub exchange_rate_oracle {
+            is_signer: false,
+            is_writable: true,
+        },
         const sysvar_clock = sysvar::clock::id(),
         const sysvar_rent = sysvar::rent::id(),
+
+        // Anybody can perform this maintenance, so the maintainer is optional.
+        // A maintainer that signs and passes itself here gets the operation
+        // counted towards its share of the maintainer fee.
+        pub ?maintainer {
+            is_signer: true,
+            is_writable: false,
+        },
     }
 }
 
@@ -436,10 +640,15 @@ pub fn update_exchange_rate(
 }
 
 accounts_struct! {
-    // Note: there are no signers among these accounts, updating validator
-    // balance is permissionless, anybody can do it.
+    // Note: updating validator balance is permissionless, anybody can do it.
     WithdrawInactiveStakeMeta, WithdrawInactiveStakeInfo {
         pub lido {
+            is_signer: false,
+            // Needs to be writable to record the operation of the maintainer,
+            // if one is passed.
+            is_writable: true,
+        },
+        pub validator_list {
             is_signer: false,
             is_writable: true,
         },
@@ -482,6 +691,14 @@ accounts_struct! {
             is_signer: false,
             is_writable: true,
         },
+
+        // Anybody can perform this maintenance, so the maintainer is optional.
+        // A maintainer that signs and passes itself here gets the operation
+        // counted towards its share of the maintainer fee.
+        pub ?maintainer {
+            is_signer: true,
+            is_writable: false,
+        },
     }
 }
 
@@ -497,13 +714,16 @@ pub fn withdraw_inactive_stake(
 }
 
 accounts_struct! {
-    // Note: there are no signers among these accounts, updating a validator
-    // account is permissionless, anybody can do it.
+    // Note: updating a validator account is permissionless, anybody can do it.
     CollectValidatorFeeMeta, CollectValidatorFeeInfo {
         pub lido {
             is_signer: false,
             is_writable: true,
         },
+        pub validator_list {
+            is_signer: false,
+            is_writable: true,
+        },
         // The validator to update the balance for.
         // Needs to be writable so we withdraw from it.
         pub validator_vote_account {
@@ -561,6 +781,24 @@ accounts_struct! {
 
         // Needed to withdraw from the vote account.
         const vote_program = vote::program::id(),
+
+        // When the fee payout mode is `FeePayoutMode::Direct`, the stSOL fee
+        // accounts of at most `MAXIMUM_DIRECT_FEE_ACCOUNTS` validators, in any
+        // order. Validators whose fee account is not passed get their fee as
+        // `fee_credit`, the others also get their remaining `fee_credit`.
+        pub ...validator_fee_st_sol_accounts {
+            is_signer: false,
+            // Is writable due to fee mint (spl_token::instruction::mint_to) to validators
+            is_writable: true,
+        },
+
+        // Anybody can perform this maintenance, so the maintainer is optional.
+        // A maintainer that signs and passes itself here gets the operation
+        // counted towards its share of the maintainer fee.
+        pub ?maintainer {
+            is_signer: true,
+            is_writable: false,
+        },
     }
 }
 
@@ -618,6 +856,10 @@ pub fn change_reward_distribution(
 accounts_struct! {
     AddValidatorMeta, AddValidatorInfo {
         pub lido {
+            is_signer: false,
+            is_writable: false,
+        },
+        pub validator_list {
             is_signer: false,
             is_writable: true,
         },
@@ -633,6 +875,16 @@ accounts_struct! {
             is_signer: false,
             is_writable: false,
         },
+        // The withdraw authority to hand the vote account back to on removal.
+        pub validator_vote_withdrawer {
+            is_signer: false,
+            is_writable: false,
+        },
+        // The vote withdrawer above, or the validator identity.
+        pub validator_authority {
+            is_signer: true,
+            is_writable: false,
+        },
         const sysvar_rent = sysvar::rent::id(),
     }
 }
@@ -648,13 +900,33 @@ pub fn add_validator(program_id: &Pubkey, accounts: &AddValidatorMeta) -> Instru
 accounts_struct! {
     RemoveValidatorMeta, RemoveValidatorInfo {
         pub lido {
+            is_signer: false,
+            is_writable: false,
+        },
+        pub validator_list {
             is_signer: false,
             is_writable: true,
         },
         pub validator_vote_account_to_remove {
+            is_signer: false,
+            // Is writable due to the change of withdraw authority
+            // (vote_instruction::authorize).
+            is_writable: true,
+        },
+        // The withdraw authority that the validator registered when it was
+        // added, or the validator identity if it did not register one.
+        pub validator_vote_withdrawer {
+            is_signer: false,
+            is_writable: false,
+        },
+        // The current withdraw authority of the vote account.
+        pub rewards_withdraw_authority {
             is_signer: false,
             is_writable: false,
         },
+        // Required to call `vote_instruction::authorize`.
+        const sysvar_clock = sysvar::clock::id(),
+        const vote_program = vote::program::id(),
     }
 }
 
@@ -669,6 +941,10 @@ pub fn remove_validator(program_id: &Pubkey, accounts: &RemoveValidatorMeta) ->
 accounts_struct! {
     DeactivateValidatorMeta, DeactivateValidatorInfo {
         pub lido {
+            is_signer: false,
+            is_writable: false,
+        },
+        pub validator_list {
             is_signer: false,
             is_writable: true,
         },
@@ -697,6 +973,10 @@ pub fn deactivate_validator(
 accounts_struct! {
     ClaimValidatorFeeMeta, ClaimValidatorFeeInfo {
         pub lido {
+            is_signer: false,
+            is_writable: false,
+        },
+        pub validator_list {
             is_signer: false,
             is_writable: true,
         },
@@ -781,6 +1061,12 @@ pub fn remove_maintainer(program_id: &Pubkey, accounts: &RemoveMaintainerMeta) -
 accounts_struct! {
     MergeStakeMeta, MergeStakeInfo {
         pub lido {
+            is_signer: false,
+            // Needs to be writable to record the operation of the maintainer,
+            // if one is passed.
+            is_writable: true,
+        },
+        pub validator_list {
             is_signer: false,
             is_writable: true,
         },
@@ -808,6 +1094,14 @@ accounts_struct! {
         const sysvar_clock = sysvar::clock::id(),
         const stake_history = stake_history::id(),
         const stake_program = stake_program::program::id(),
+
+        // Anybody can perform this maintenance, so the maintainer is optional.
+        // A maintainer that signs and passes itself here gets the operation
+        // counted towards its share of the maintainer fee.
+        pub ?maintainer {
+            is_signer: true,
+            is_writable: false,
+        },
     }
 }
 
@@ -818,3 +1112,500 @@ pub fn merge_stake(program_id: &Pubkey, accounts: &MergeStakeMeta) -> Instructio
         data: LidoInstruction::MergeStake.try_to_vec().unwrap(), // This should never fail.
     }
 }
+
+accounts_struct! {
+    ChangeFeePayoutModeMeta, ChangeFeePayoutModeInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+    }
+}
+
+pub fn change_fee_payout_mode(
+    program_id: &Pubkey,
+    new_fee_payout_mode: FeePayoutMode,
+    accounts: &ChangeFeePayoutModeMeta,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: LidoInstruction::ChangeFeePayoutMode {
+            new_fee_payout_mode,
+        }
+        .to_vec(),
+    }
+}
+
+accounts_struct! {
+    UpgradeFeePayoutModeMeta, UpgradeFeePayoutModeInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+    }
+}
+
+pub fn upgrade_fee_payout_mode(
+    program_id: &Pubkey,
+    accounts: &UpgradeFeePayoutModeMeta,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: LidoInstruction::UpgradeFeePayoutMode.to_vec(),
+    }
+}
+
+accounts_struct! {
+    MigrateValidatorListMeta, MigrateValidatorListInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub validator_list {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+        const sysvar_rent = sysvar::rent::id(),
+    }
+}
+
+pub fn migrate_validator_list(
+    program_id: &Pubkey,
+    accounts: &MigrateValidatorListMeta,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: LidoInstruction::MigrateValidatorList.to_vec(),
+    }
+}
+
+accounts_struct! {
+    SortAccountMapsMeta, SortAccountMapsInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub validator_list {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+    }
+}
+
+pub fn sort_account_maps(program_id: &Pubkey, accounts: &SortAccountMapsMeta) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: LidoInstruction::SortAccountMaps.to_vec(),
+    }
+}
+
+accounts_struct! {
+    UpgradeValidatorListMeta, UpgradeValidatorListInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub validator_list {
+            is_signer: false,
+            is_writable: false,
+        },
+        pub new_validator_list {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+        const sysvar_rent = sysvar::rent::id(),
+    }
+}
+
+pub fn upgrade_validator_list(
+    program_id: &Pubkey,
+    accounts: &UpgradeValidatorListMeta,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: LidoInstruction::UpgradeValidatorList.to_vec(),
+    }
+}
+
+accounts_struct! {
+    UpgradeMetricsMeta, UpgradeMetricsInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+    }
+}
+
+pub fn upgrade_metrics(program_id: &Pubkey, accounts: &UpgradeMetricsMeta) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: LidoInstruction::UpgradeMetrics.to_vec(),
+    }
+}
+
+accounts_struct! {
+    InitializeExchangeRateOracleMeta, InitializeExchangeRateOracleInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: false,
+        },
+        pub exchange_rate_oracle {
+            is_signer: false,
+            is_writable: true,
+        },
+        // Pays for the rent of the oracle account.
+        pub funder {
+            is_signer: true,
+            is_writable: true,
+        },
+        const system_program = system_program::id(),
+    }
+}
+
+pub fn initialize_exchange_rate_oracle(
+    program_id: &Pubkey,
+    accounts: &InitializeExchangeRateOracleMeta,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: LidoInstruction::InitializeExchangeRateOracle.to_vec(),
+    }
+}
+
+accounts_struct! {
+    SetTokenMetadataMeta, SetTokenMetadataInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: false,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+        pub st_sol_mint {
+            is_signer: false,
+            is_writable: false,
+        },
+        pub mint_authority {
+            is_signer: false,
+            is_writable: false,
+        },
+        pub token_metadata {
+            is_signer: false,
+            is_writable: true,
+        },
+        // Only needs to have funds when the metadata account does not exist yet.
+        pub payer {
+            is_signer: true,
+            is_writable: true,
+        },
+        const token_metadata_program = token_metadata::program::id(),
+        const system_program = system_program::id(),
+        const sysvar_rent = sysvar::rent::id(),
+    }
+}
+
+pub fn set_token_metadata(
+    program_id: &Pubkey,
+    token_metadata: TokenMetadata,
+    accounts: &SetTokenMetadataMeta,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: LidoInstruction::SetTokenMetadata { token_metadata }.to_vec(),
+    }
+}
+
+accounts_struct! {
+    ChangeDepositCutoffMeta, ChangeDepositCutoffInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+    }
+}
+
+pub fn change_deposit_cutoff(
+    program_id: &Pubkey,
+    deposit_cutoff_slots: u64,
+    accounts: &ChangeDepositCutoffMeta,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: LidoInstruction::ChangeDepositCutoff {
+            deposit_cutoff_slots,
+        }
+        .to_vec(),
+    }
+}
+
+accounts_struct! {
+    ClaimMaintainerFeeMeta, ClaimMaintainerFeeInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub maintainer {
+            is_signer: true,
+            is_writable: false,
+        },
+        pub st_sol_mint {
+            is_signer: false,
+            // Is writable due to fee mint (spl_token::instruction::mint_to) to maintainer
+            // st_sol account
+            is_writable: true,
+        },
+        pub mint_authority {
+            is_signer: false,
+            is_writable: false,
+        },
+        pub maintainer_st_sol_account {
+            is_signer: false,
+            // Is writable due to fee mint (spl_token::instruction::mint_to) to maintainer
+            // st_sol account
+            is_writable: true,
+        },
+        const spl_token = spl_token::id(),
+    }
+}
+
+pub fn claim_maintainer_fee(program_id: &Pubkey, accounts: &ClaimMaintainerFeeMeta) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: LidoInstruction::ClaimMaintainerFee.to_vec(),
+    }
+}
+
+accounts_struct! {
+    ForceRemoveValidatorMeta, ForceRemoveValidatorInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: false,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+        pub validator_list {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub validator_vote_account_to_remove {
+            is_signer: false,
+            is_writable: false,
+        },
+        pub st_sol_mint {
+            is_signer: false,
+            // Is writable due to fee mint (spl_token::instruction::mint_to) to validator fee
+            // st_sol account
+            is_writable: true,
+        },
+        pub mint_authority {
+            is_signer: false,
+            is_writable: false,
+        },
+        pub validator_fee_st_sol_account {
+            is_signer: false,
+            // Is writable due to fee mint (spl_token::instruction::mint_to) to validator fee
+            // st_sol account
+            is_writable: true,
+        },
+        // Stake authority, to be able to deactivate the stake.
+        pub stake_authority {
+            is_signer: false,
+            is_writable: false,
+        },
+        // Required to call `solana_program::stake::instruction::deactivate_stake`.
+        const sysvar_clock = sysvar::clock::id(),
+        const stake_program = stake_program::program::id(),
+        const spl_token = spl_token::id(),
+
+        // The validator's stake accounts, from the begin seed until (but
+        // excluding) the end seed.
+        pub ...stake_accounts {
+            is_signer: false,
+            // Is writable due to deactivation.
+            is_writable: true,
+        },
+    }
+}
+
+pub fn force_remove_validator(
+    program_id: &Pubkey,
+    accounts: &ForceRemoveValidatorMeta,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: LidoInstruction::ForceRemoveValidator.to_vec(),
+    }
+}
+
+accounts_struct! {
+    UpgradeValidatorEntriesMeta, UpgradeValidatorEntriesInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub validator_list {
+            is_signer: false,
+            is_writable: false,
+        },
+        pub new_validator_list {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+        const sysvar_rent = sysvar::rent::id(),
+    }
+}
+
+pub fn upgrade_validator_entries(
+    program_id: &Pubkey,
+    accounts: &UpgradeValidatorEntriesMeta,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: LidoInstruction::UpgradeValidatorEntries.to_vec(),
+    }
+}
+
+accounts_struct! {
+    ClaimValidatorFeeToMeta, ClaimValidatorFeeToInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: false,
+        },
+        pub validator_list {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub validator_vote_account {
+            is_signer: false,
+            is_writable: false,
+        },
+        // The validator identity, or the authorized voter of the vote account.
+        pub validator_authority {
+            is_signer: true,
+            is_writable: false,
+        },
+        pub st_sol_mint {
+            is_signer: false,
+            // Is writable due to fee mint (spl_token::instruction::mint_to) to
+            // the recipient st_sol account
+            is_writable: true,
+        },
+        pub mint_authority {
+            is_signer: false,
+            is_writable: false,
+        },
+        pub recipient_st_sol_account {
+            is_signer: false,
+            // Is writable due to fee mint (spl_token::instruction::mint_to) to
+            // the recipient st_sol account
+            is_writable: true,
+        },
+        // Needed to determine the authorized voter for the current epoch.
+        const sysvar_clock = sysvar::clock::id(),
+        const spl_token = spl_token::id(),
+    }
+}
+
+pub fn claim_validator_fee_to(
+    program_id: &Pubkey,
+    accounts: &ClaimValidatorFeeToMeta,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: LidoInstruction::ClaimValidatorFeeTo.to_vec(),
+    }
+}
+
+accounts_struct! {
+    MaintainerHeartbeatMeta, MaintainerHeartbeatInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub maintainer {
+            is_signer: true,
+            is_writable: false,
+        },
+        const sysvar_clock = sysvar::clock::id(),
+    }
+}
+
+pub fn maintainer_heartbeat(
+    program_id: &Pubkey,
+    accounts: &MaintainerHeartbeatMeta,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: LidoInstruction::MaintainerHeartbeat.to_vec(),
+    }
+}
+
+accounts_struct! {
+    UpgradeMaintainersMeta, UpgradeMaintainersInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+    }
+}
+
+pub fn upgrade_maintainers(program_id: &Pubkey, accounts: &UpgradeMaintainersMeta) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: LidoInstruction::UpgradeMaintainers.to_vec(),
+    }
+}
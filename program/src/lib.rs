@@ -9,16 +9,21 @@ pub mod entrypoint;
 pub mod account_map;
 pub mod accounts;
 pub mod balance;
+pub mod cpi;
 pub mod error;
+pub mod idl;
 pub mod instruction;
 pub(crate) mod logic;
 pub mod metrics;
+pub mod oracle;
 pub(crate) mod process_management;
 pub mod processor;
 pub mod stake_account;
 pub mod state;
 pub mod token;
+pub mod token_metadata;
 pub mod util;
+pub mod zero_copy;
 
 mod vote_instruction;
 mod vote_state;
@@ -40,6 +45,9 @@ pub const VALIDATOR_UNSTAKE_ACCOUNT: &[u8] = b"validator_unstake_account";
 /// Authority responsible for withdrawing the stake rewards.
 pub const REWARDS_WITHDRAW_AUTHORITY: &[u8] = b"rewards_withdraw_authority";
 
+/// Seed for the account that mirrors the exchange rate, see [`oracle`].
+pub const EXCHANGE_RATE_ORACLE: &[u8] = b"exchange_rate_oracle";
+
 /// Finds the public key and bump seed for a given authority.  Since this
 /// function can take some time to run, it's preferred to use
 /// `Pubkey::create_program_address(seeds, program_id)` inside programs.
@@ -67,6 +75,14 @@ pub const MINIMUM_STAKE_ACCOUNT_BALANCE: token::Lamports = token::Lamports(1_000
 /// The maximum number of unstake accounts that a validator can have simultaneously.
 pub const MAXIMUM_UNSTAKE_ACCOUNTS: u64 = 3;
 
+/// The maximum number of validator fee accounts that `CollectValidatorFee` pays
+/// out to in `FeePayoutMode::Direct`.
+///
+/// With more validators than this, the fee accounts do not fit in a single
+/// transaction, so the maintainer passes a different page of them every time.
+/// The validators that are not in the page get their fee as `fee_credit`.
+pub const MAXIMUM_DIRECT_FEE_ACCOUNTS: usize = 16;
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -14,11 +14,13 @@ use solana_program::{
 use crate::processor::StakeType;
 use crate::STAKE_AUTHORITY;
 use crate::{
+    account_map::PubkeyAndEntry,
     error::LidoError,
     instruction::{CollectValidatorFeeInfo, UnstakeAccountsInfo, WithdrawAccountsInfo},
     state::Fees,
-    state::Lido,
+    state::{FeePayoutMode, Lido, LidoHeader, Validator, Validators, LIDO_VERSION},
     token::{Lamports, StLamports},
+    zero_copy::LidoAccountView,
     MINT_AUTHORITY, RESERVE_ACCOUNT,
 };
 
@@ -34,6 +36,24 @@ pub(crate) fn check_rent_exempt(
     Ok(())
 }
 
+/// Check that the first `len` bytes of the account are zero, so we don't
+/// overwrite an account that is already in use.
+pub(crate) fn check_account_uninitialized(account_info: &AccountInfo, len: usize) -> ProgramResult {
+    let is_uninitialized = account_info
+        .data
+        .borrow()
+        .get(..len)
+        .map_or(false, |data| data.iter().all(|byte| *byte == 0));
+    if !is_uninitialized {
+        msg!(
+            "Account {} appears to be in use already, refusing to overwrite.",
+            account_info.key
+        );
+        return Err(LidoError::AlreadyInUse.into());
+    }
+    Ok(())
+}
+
 /// Check if the mint program coin supply is zero and the mint authority is set
 /// to `mint_authority`.
 /// The check has to be done only in Solido's initialization phase, since we
@@ -178,7 +198,7 @@ pub fn initialize_stake_account_undelegated<'a>(
 /// * The stSOL mint must be the one configured in the Solido instance.
 /// * The recipient account must be an stSOL SPL token account.
 pub fn mint_st_sol_to<'a>(
-    solido: &Lido,
+    solido: &LidoHeader,
     solido_address: &Pubkey,
     spl_token_program: &AccountInfo<'a>,
     st_sol_mint: &AccountInfo<'a>,
@@ -227,7 +247,7 @@ pub fn mint_st_sol_to<'a>(
 /// * The stSOL mint must be the one configured in the Solido instance.
 /// * The account account must be an stSOL SPL token account.
 pub fn burn_st_sol<'a, 'b>(
-    solido: &Lido,
+    solido: &LidoHeader,
     accounts: &WithdrawAccountsInfo<'a, 'b>,
     amount: StLamports,
 ) -> ProgramResult {
@@ -319,7 +339,8 @@ pub fn transfer_stake_authority(
 
 /// Mint stSOL for the given fees, and transfer them to the appropriate accounts.
 pub fn distribute_fees<'a, 'b>(
-    solido: &mut Lido,
+    solido: &mut LidoHeader,
+    validators: &mut Validators,
     accounts: &CollectValidatorFeeInfo<'a, 'b>,
     fees: Fees,
 ) -> ProgramResult {
@@ -355,17 +376,59 @@ pub fn distribute_fees<'a, 'b>(
         developer_amount,
     )?;
 
-    // For the validators, as there can be many of them, we can't pay all of
-    // them in a single transaction. Instead, we store how much they are
+    // For the validators, as there can be many of them, we can't always pay all
+    // of them in a single transaction. By default, we store how much they are
     // entitled to, and they can later claim it themselves with `ClaimValidatorFee`.
+    // In `FeePayoutMode::Direct`, we mint to every validator whose fee account
+    // was passed, and fall back to the credit for the others. The caller passes
+    // only a page of the fee accounts, so we look them up by address. Validators
+    // in the page also get the credit that they still had, from before the
+    // switch to direct mode or from earlier pages that they were not in, so
+    // they have nothing left to claim.
     let mut fee_validation_sol = Lamports(0);
     let mut fee_validation_st_sol = StLamports(0);
-    for validator in solido.validators.iter_entries_mut() {
-        validator.fee_credit = (validator.fee_credit + per_validator_amount)?;
+    for i in 0..validators.len() {
+        let fee_address = validators.entries[i].entry.fee_address;
+        let fee_account = match solido.fee_payout_mode {
+            FeePayoutMode::Credit => None,
+            FeePayoutMode::Direct => accounts
+                .validator_fee_st_sol_accounts
+                .iter()
+                .find(|account| account.key == &fee_address),
+        };
+        let is_paid_directly = match fee_account {
+            Some(account) if solido.check_is_st_sol_account(account).is_ok() => {
+                let fee_credit = validators.entries[i].entry.fee_credit;
+                mint_st_sol_to(
+                    solido,
+                    accounts.lido.key,
+                    accounts.spl_token_program,
+                    accounts.st_sol_mint,
+                    accounts.mint_authority,
+                    account,
+                    (per_validator_amount + fee_credit)?,
+                )?;
+                true
+            }
+            _ => false,
+        };
+        let validator = &mut validators.entries[i].entry;
+        if is_paid_directly {
+            validator.fee_credit = StLamports(0);
+        } else {
+            validator.fee_credit = (validator.fee_credit + per_validator_amount)?;
+        }
+        validator.metrics.observe_fee(per_validator_amount)?;
         fee_validation_sol = (fee_validation_sol + fees.reward_per_validator)?;
         fee_validation_st_sol = (fee_validation_st_sol + per_validator_amount)?;
     }
 
+    // Like the validation fee, the maintainer fee is only minted when claimed.
+    // It goes into a pool first, that `UpdateExchangeRate` divides among the
+    // maintainers, see `Lido::distribute_maintainer_fee`.
+    let maintainer_amount = solido.exchange_rate.exchange_sol(fees.maintainer_amount)?;
+    solido.maintainer_fee_credit = (solido.maintainer_fee_credit + maintainer_amount)?;
+
     // Also record our rewards in the metrics.
     solido
         .metrics
@@ -387,11 +450,9 @@ pub fn distribute_fees<'a, 'b>(
 /// by the validator's seeds. Returns the destination bump seed.
 pub fn check_unstake_accounts(
     program_id: &Pubkey,
-    lido: &Lido,
+    validator: &PubkeyAndEntry<Validator>,
     accounts: &UnstakeAccountsInfo,
 ) -> Result<u8, ProgramError> {
-    let validator = lido.validators.get(accounts.validator_vote_account.key)?;
-
     // If a validator doesn't have a stake account, it cannot be unstaked.
     if !validator.entry.has_stake_accounts() {
         msg!(
@@ -455,7 +516,7 @@ pub struct SplitStakeAccounts<'a, 'b> {
 ///   - Splits the stake.
 pub fn split_stake_account(
     lido_address: &Pubkey,
-    lido: &Lido,
+    lido: &LidoHeader,
     accounts: &SplitStakeAccounts,
     amount: Lamports,
     seeds: &[&[&[u8]]],
@@ -512,7 +573,7 @@ pub fn split_stake_account(
     Ok(())
 }
 
-pub fn deserialize_lido(program_id: &Pubkey, lido: &AccountInfo) -> Result<Lido, ProgramError> {
+pub fn check_lido_owner(program_id: &Pubkey, lido: &AccountInfo) -> ProgramResult {
     if lido.owner != program_id {
         msg!(
             "Lido state is owned by {}, but should be owned by the Lido program ({}).",
@@ -521,10 +582,61 @@ pub fn deserialize_lido(program_id: &Pubkey, lido: &AccountInfo) -> Result<Lido,
         );
         return Err(LidoError::InvalidOwner.into());
     }
+    Ok(())
+}
+
+/// Confirm that the `Lido` account has the layout that this program expects.
+///
+/// The version is the first byte of the account data.
+pub fn check_lido_version(lido: &AccountInfo, expected_version: u8) -> ProgramResult {
+    let version = lido.data.borrow().first().cloned();
+    if version != Some(expected_version) {
+        msg!(
+            "Lido account {} has version {:?}, but expected version {}.",
+            lido.key,
+            version,
+            expected_version
+        );
+        return Err(LidoError::InvalidLidoVersion.into());
+    }
+    Ok(())
+}
+
+pub fn deserialize_lido(program_id: &Pubkey, lido: &AccountInfo) -> Result<Lido, ProgramError> {
+    check_lido_owner(program_id, lido)?;
+    check_lido_version(lido, LIDO_VERSION)?;
     let lido = try_from_slice_unchecked::<Lido>(&lido.data.borrow())?;
     Ok(lido)
 }
 
+/// Deserialize only the constant-size part of the `Lido` account.
+///
+/// This is cheaper than [`deserialize_lido`] for instructions that don't need
+/// the maintainers. Use [`LidoAccountView`] to access those, and
+/// [`LidoHeader::save_header`] to write back the header.
+pub fn deserialize_lido_header(
+    program_id: &Pubkey,
+    lido: &AccountInfo,
+) -> Result<LidoHeader, ProgramError> {
+    check_lido_owner(program_id, lido)?;
+    check_lido_version(lido, LIDO_VERSION)?;
+    LidoAccountView::new(&lido.data.borrow()[..])?.header()
+}
+
+/// Deserialize the full validator list that `lido` references.
+///
+/// Instructions that need only a few validators should use a
+/// [`crate::zero_copy::ValidatorListView`] instead.
+pub fn deserialize_validators(
+    program_id: &Pubkey,
+    lido: &LidoHeader,
+    validator_list: &AccountInfo,
+) -> Result<Validators, ProgramError> {
+    lido.check_validator_list(program_id, validator_list)?;
+    let validators = try_from_slice_unchecked::<Validators>(&validator_list.data.borrow())?;
+    Ok(validators)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
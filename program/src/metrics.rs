@@ -11,7 +11,7 @@
 
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 use serde::Serialize;
-use solana_program::entrypoint::ProgramResult;
+use solana_program::{clock::Epoch, entrypoint::ProgramResult};
 
 use crate::token::{self, Lamports, StLamports};
 
@@ -67,11 +67,29 @@ pub struct Metrics {
     /// Histogram of deposits, including the total amount deposited since we started tracking.
     pub deposit_amount: LamportsHistogram,
     /// Total amount withdrawn since the beginning.
-    // Since the user cannot withdraw more than what is inside a single stake
-    // account, a histogram for tracking withdrawals does not make sense. We
-    // track the amount in StSOL, SOL and the total number the function was
-    // called.
+    ///
+    /// We track the amount in StSOL, SOL and the total number the function was
+    /// called. See [`Metrics::withdraw_amount_histogram`] for the distribution.
     pub withdraw_amount: WithdrawMetric,
+
+    /// Histogram of withdrawals, in SOL after the conversion.
+    ///
+    /// The user cannot withdraw more than what is inside a single stake account,
+    /// so the largest buckets will not see many observations, but the size
+    /// distribution of withdrawals is still useful to know.
+    ///
+    /// This and the fields below were added in version 5 of the `Lido` account.
+    /// They come last, so a [`LegacyMetrics`] is a prefix of `Metrics`.
+    pub withdraw_amount_histogram: LamportsHistogram,
+
+    /// Deposits and withdrawals in the epoch of the most recent observation.
+    pub flow_current_epoch: EpochFlowMetric,
+
+    /// Deposits and withdrawals in the epoch before `flow_current_epoch`.
+    ///
+    /// Unlike the current epoch, this epoch is complete, so it is a better
+    /// indicator of the net flow of the pool.
+    pub flow_previous_epoch: EpochFlowMetric,
 }
 
 impl Metrics {
@@ -88,7 +106,25 @@ impl Metrics {
 
             deposit_amount: LamportsHistogram::new(),
             withdraw_amount: WithdrawMetric::default(),
+            withdraw_amount_histogram: LamportsHistogram::new(),
+            flow_current_epoch: EpochFlowMetric::default(),
+            flow_previous_epoch: EpochFlowMetric::default(),
+        }
+    }
+
+    /// Return the flow record for `epoch`, starting a new one if `epoch` is
+    /// later than the current one.
+    fn flow_for_epoch(&mut self, epoch: Epoch) -> &mut EpochFlowMetric {
+        if epoch > self.flow_current_epoch.epoch {
+            self.flow_previous_epoch = if self.flow_current_epoch.epoch + 1 == epoch {
+                self.flow_current_epoch.clone()
+            } else {
+                // There were no deposits or withdrawals in the previous epoch.
+                EpochFlowMetric::new(epoch - 1)
+            };
+            self.flow_current_epoch = EpochFlowMetric::new(epoch);
         }
+        &mut self.flow_current_epoch
     }
 
     pub fn observe_fee_treasury(
@@ -130,15 +166,60 @@ impl Metrics {
         Ok(())
     }
 
-    pub fn observe_deposit(&mut self, amount: Lamports) -> ProgramResult {
-        self.deposit_amount.observe(amount)
+    pub fn observe_deposit(&mut self, amount: Lamports, epoch: Epoch) -> ProgramResult {
+        self.deposit_amount.observe(amount)?;
+        let flow = self.flow_for_epoch(epoch);
+        flow.deposit_total = (flow.deposit_total + amount)?;
+        Ok(())
     }
+
     pub fn observe_withdrawal(
         &mut self,
         st_sol_amount: StLamports,
         sol_amount: Lamports,
-    ) -> token::Result<()> {
-        self.withdraw_amount.observe(st_sol_amount, sol_amount)
+        epoch: Epoch,
+    ) -> ProgramResult {
+        self.withdraw_amount.observe(st_sol_amount, sol_amount)?;
+        self.withdraw_amount_histogram.observe(sol_amount)?;
+        let flow = self.flow_for_epoch(epoch);
+        flow.withdraw_total = (flow.withdraw_total + sol_amount)?;
+        Ok(())
+    }
+}
+
+/// Layout of [`Metrics`] in versions 0 through 4 of the `Lido` account, before
+/// it tracked the withdrawal histogram and the per-epoch flows.
+#[repr(C)]
+#[derive(
+    Clone, Debug, Default, BorshDeserialize, BorshSerialize, BorshSchema, Eq, PartialEq, Serialize,
+)]
+pub struct LegacyMetrics {
+    pub fee_treasury_sol_total: Lamports,
+    pub fee_validation_sol_total: Lamports,
+    pub fee_developer_sol_total: Lamports,
+    pub st_sol_appreciation_sol_total: Lamports,
+    pub fee_treasury_st_sol_total: StLamports,
+    pub fee_validation_st_sol_total: StLamports,
+    pub fee_developer_st_sol_total: StLamports,
+    pub deposit_amount: LamportsHistogram,
+    pub withdraw_amount: WithdrawMetric,
+}
+
+impl LegacyMetrics {
+    /// Convert to the current layout, the new metrics start out empty.
+    pub fn upgrade(self) -> Metrics {
+        Metrics {
+            fee_treasury_sol_total: self.fee_treasury_sol_total,
+            fee_validation_sol_total: self.fee_validation_sol_total,
+            fee_developer_sol_total: self.fee_developer_sol_total,
+            st_sol_appreciation_sol_total: self.st_sol_appreciation_sol_total,
+            fee_treasury_st_sol_total: self.fee_treasury_st_sol_total,
+            fee_validation_st_sol_total: self.fee_validation_st_sol_total,
+            fee_developer_st_sol_total: self.fee_developer_st_sol_total,
+            deposit_amount: self.deposit_amount,
+            withdraw_amount: self.withdraw_amount,
+            ..Metrics::new()
+        }
     }
 }
 
@@ -234,6 +315,93 @@ impl WithdrawMetric {
     }
 }
 
+/// Deposits and withdrawals within a single epoch.
+///
+/// The net flow of the epoch is `deposit_total - withdraw_total`.
+#[repr(C)]
+#[derive(
+    Clone, Debug, Default, BorshDeserialize, BorshSerialize, BorshSchema, Eq, PartialEq, Serialize,
+)]
+pub struct EpochFlowMetric {
+    /// The epoch that these totals apply to.
+    pub epoch: Epoch,
+
+    /// Total amount of SOL deposited in this epoch.
+    #[serde(rename = "deposit_total_lamports")]
+    pub deposit_total: Lamports,
+
+    /// Total amount of SOL withdrawn in this epoch, after the conversion.
+    #[serde(rename = "withdraw_total_lamports")]
+    pub withdraw_total: Lamports,
+}
+
+impl EpochFlowMetric {
+    pub fn new(epoch: Epoch) -> Self {
+        Self {
+            epoch,
+            deposit_total: Lamports(0),
+            withdraw_total: Lamports(0),
+        }
+    }
+}
+
+/// Counters for a single validator, stored next to its `Validator` entry.
+#[repr(C)]
+#[derive(
+    Clone, Debug, Default, BorshDeserialize, BorshSerialize, BorshSchema, Eq, PartialEq, Serialize,
+)]
+pub struct ValidatorMetrics {
+    /// Rewards collected from this validator, in total since we started tracking.
+    ///
+    /// This includes both the vote account rewards that `CollectValidatorFee`
+    /// withdraws, and the stake rewards that `WithdrawInactiveStake` observes.
+    #[serde(rename = "rewards_total_lamports")]
+    pub rewards_total: Lamports,
+
+    /// Validation fees attributed to this validator, in total since we started tracking.
+    ///
+    /// In `FeePayoutMode::Direct`, this includes fees that were minted to the
+    /// validator's fee account directly, rather than credited.
+    #[serde(rename = "fee_total_st_lamports")]
+    pub fee_st_sol_total: StLamports,
+
+    /// Number of epochs in which the validator was observed to be active.
+    pub epochs_active: u64,
+
+    /// Last epoch that was counted in `epochs_active`.
+    pub last_active_epoch: Epoch,
+
+    /// Last epoch in which we observed nonzero rewards for this validator.
+    pub last_rewards_epoch: Epoch,
+}
+
+impl ValidatorMetrics {
+    /// Record rewards collected from the validator in the given epoch.
+    pub fn observe_rewards(&mut self, amount: Lamports, epoch: Epoch) -> token::Result<()> {
+        if amount > Lamports(0) {
+            self.rewards_total = (self.rewards_total + amount)?;
+            self.last_rewards_epoch = epoch;
+        }
+        Ok(())
+    }
+
+    /// Record validation fees credited or paid to the validator.
+    pub fn observe_fee(&mut self, amount: StLamports) -> token::Result<()> {
+        self.fee_st_sol_total = (self.fee_st_sol_total + amount)?;
+        Ok(())
+    }
+
+    /// Record that the validator was active in the given epoch.
+    ///
+    /// Every epoch is counted at most once.
+    pub fn observe_active_epoch(&mut self, epoch: Epoch) {
+        if self.epochs_active == 0 || epoch > self.last_active_epoch {
+            self.epochs_active += 1;
+            self.last_active_epoch = epoch;
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -284,16 +452,17 @@ mod test {
         let mut m = Metrics::new();
 
         // 0.000_000_100 SOL, falls in bucket 0 (<= 0.000_1 SOL).
-        m.observe_deposit(Lamports(100)).unwrap();
+        m.observe_deposit(Lamports(100), 0).unwrap();
 
         // 1 SOL, falls in bucket 4. (<= 1 SOL)
-        m.observe_deposit(Lamports(1_000_000_000)).unwrap();
+        m.observe_deposit(Lamports(1_000_000_000), 0).unwrap();
 
         // 57 SOL, falls in bucket 6. (<= 100 SOL)
-        m.observe_deposit(Lamports(57_000_000_000)).unwrap();
+        m.observe_deposit(Lamports(57_000_000_000), 0).unwrap();
 
         // 21M SOL, falls in bucket 11. (<= u64::MAX SOL).
-        m.observe_deposit(Lamports(21_000_000_000_000_000)).unwrap();
+        m.observe_deposit(Lamports(21_000_000_000_000_000), 0)
+            .unwrap();
 
         assert_eq!(m.deposit_amount.counts[0], 1);
         assert_eq!(m.deposit_amount.counts[1], 1);
@@ -311,4 +480,101 @@ mod test {
         assert_eq!(m.deposit_amount.num_observations(), 4);
         assert_eq!(m.deposit_amount.total, Lamports(21_000_058_000_000_100));
     }
+
+    #[test]
+    fn test_metrics_observe_withdrawal() {
+        let mut m = Metrics::new();
+        m.observe_withdrawal(StLamports(90), Lamports(100), 0)
+            .unwrap();
+        m.observe_withdrawal(StLamports(900_000_000), Lamports(1_000_000_000), 0)
+            .unwrap();
+
+        assert_eq!(m.withdraw_amount.count, 2);
+        assert_eq!(
+            m.withdraw_amount.total_st_sol_amount,
+            StLamports(900_000_090)
+        );
+        assert_eq!(m.withdraw_amount_histogram.counts[0], 1);
+        assert_eq!(m.withdraw_amount_histogram.counts[3], 1);
+        assert_eq!(m.withdraw_amount_histogram.counts[4], 2);
+        assert_eq!(m.withdraw_amount_histogram.total, Lamports(1_000_000_100));
+    }
+
+    #[test]
+    fn test_metrics_flow_rolls_over_per_epoch() {
+        let mut m = Metrics::new();
+        m.observe_deposit(Lamports(100), 5).unwrap();
+        m.observe_withdrawal(StLamports(10), Lamports(20), 5)
+            .unwrap();
+        m.observe_deposit(Lamports(1), 5).unwrap();
+        assert_eq!(
+            m.flow_current_epoch,
+            EpochFlowMetric {
+                epoch: 5,
+                deposit_total: Lamports(101),
+                withdraw_total: Lamports(20),
+            }
+        );
+
+        // In the next epoch, the current epoch becomes the previous one.
+        m.observe_deposit(Lamports(7), 6).unwrap();
+        assert_eq!(m.flow_previous_epoch.epoch, 5);
+        assert_eq!(m.flow_previous_epoch.deposit_total, Lamports(101));
+        assert_eq!(m.flow_current_epoch.epoch, 6);
+        assert_eq!(m.flow_current_epoch.deposit_total, Lamports(7));
+
+        // If we skip an epoch, the previous epoch had no flow at all.
+        m.observe_withdrawal(StLamports(1), Lamports(2), 8).unwrap();
+        assert_eq!(m.flow_previous_epoch, EpochFlowMetric::new(7));
+        assert_eq!(m.flow_current_epoch.epoch, 8);
+        assert_eq!(m.flow_current_epoch.withdraw_total, Lamports(2));
+
+        // The totals are unaffected by the rollover.
+        assert_eq!(m.deposit_amount.total, Lamports(108));
+        assert_eq!(m.withdraw_amount.total_sol_amount, Lamports(22));
+    }
+
+    #[test]
+    fn test_legacy_metrics_is_prefix_of_metrics() {
+        let mut legacy = LegacyMetrics::default();
+        legacy.fee_developer_st_sol_total = StLamports(3);
+        legacy.deposit_amount.observe(Lamports(1_000)).unwrap();
+        legacy
+            .withdraw_amount
+            .observe(StLamports(4), Lamports(5))
+            .unwrap();
+
+        let legacy_bytes = legacy.try_to_vec().unwrap();
+        let metrics = legacy.upgrade();
+        assert_eq!(metrics.fee_developer_st_sol_total, StLamports(3));
+        assert_eq!(metrics.withdraw_amount.count, 1);
+        assert_eq!(metrics.withdraw_amount_histogram, LamportsHistogram::new());
+        let bytes = metrics.try_to_vec().unwrap();
+        assert_eq!(&bytes[..legacy_bytes.len()], &legacy_bytes[..]);
+    }
+
+    #[test]
+    fn test_validator_metrics_observe_rewards() {
+        let mut m = ValidatorMetrics::default();
+        m.observe_rewards(Lamports(100), 3).unwrap();
+        // Zero rewards do not count as an observation.
+        m.observe_rewards(Lamports(0), 4).unwrap();
+        m.observe_rewards(Lamports(50), 5).unwrap();
+        m.observe_fee(StLamports(7)).unwrap();
+        assert_eq!(m.rewards_total, Lamports(150));
+        assert_eq!(m.last_rewards_epoch, 5);
+        assert_eq!(m.fee_st_sol_total, StLamports(7));
+    }
+
+    #[test]
+    fn test_validator_metrics_counts_every_epoch_once() {
+        let mut m = ValidatorMetrics::default();
+        m.observe_active_epoch(0);
+        m.observe_active_epoch(0);
+        m.observe_active_epoch(1);
+        m.observe_active_epoch(3);
+        m.observe_active_epoch(3);
+        assert_eq!(m.epochs_active, 3);
+        assert_eq!(m.last_active_epoch, 3);
+    }
 }
@@ -0,0 +1,208 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+//! A small, fixed-layout account that mirrors the exchange rate.
+//!
+//! Programs that want to price stSOL, such as lending protocols, would
+//! otherwise need to deserialize the `Lido` account, whose layout changes
+//! between versions. Instead, every `UpdateExchangeRate` also writes the
+//! exchange rate to the exchange rate oracle, a program-derived account of
+//! the Solido instance, with seed [`EXCHANGE_RATE_ORACLE`]. The oracle is
+//! created once with `InitializeExchangeRateOracle`, which anybody can call.
+//!
+//! The layout of the oracle account is stable for a given
+//! [`EXCHANGE_RATE_ORACLE_VERSION`]. All integers are little-endian:
+//!
+//! ```text
+//! offset  size  field
+//!      0     1  version, currently 1
+//!      1     1  bump_seed, for Pubkey::create_program_address
+//!      2    32  solido, the address of the Solido instance
+//!     34     8  computed_in_epoch
+//!     42     8  st_sol_supply, in stLamports
+//!     50     8  sol_balance, in Lamports
+//!     58     8  updated_at_slot
+//! ```
+//!
+//! Consumers should check that the account is owned by the Solido program,
+//! that its address is the program-derived address for the Solido instance
+//! they expect, and that the version is one they understand. Future versions
+//! will only append fields.
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use serde::Serialize;
+use solana_program::{
+    account_info::AccountInfo,
+    clock::{Epoch, Slot},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::error::LidoError;
+use crate::state::ExchangeRate;
+use crate::token::{Lamports, StLamports};
+use crate::util::serialize_b58;
+use crate::EXCHANGE_RATE_ORACLE;
+
+/// Version of the [`ExchangeRateOracle`] account layout.
+pub const EXCHANGE_RATE_ORACLE_VERSION: u8 = 1;
+
+/// Size of a serialized [`ExchangeRateOracle`].
+pub const EXCHANGE_RATE_ORACLE_SIZE: usize = 66;
+
+#[repr(C)]
+#[derive(
+    Clone, Debug, Default, BorshDeserialize, BorshSerialize, BorshSchema, Eq, PartialEq, Serialize,
+)]
+pub struct ExchangeRateOracle {
+    /// Version of the layout, see [`EXCHANGE_RATE_ORACLE_VERSION`].
+    pub version: u8,
+
+    /// Bump seed of the program-derived address of this account.
+    pub bump_seed: u8,
+
+    /// The Solido instance that this oracle belongs to.
+    #[serde(serialize_with = "serialize_b58")]
+    pub solido: Pubkey,
+
+    /// The epoch in which the exchange rate was last updated.
+    pub computed_in_epoch: Epoch,
+
+    /// The amount of stSOL that existed at that time.
+    pub st_sol_supply: StLamports,
+
+    /// The amount of SOL that Solido managed at that time.
+    pub sol_balance: Lamports,
+
+    /// The slot in which this account was last written.
+    pub updated_at_slot: Slot,
+}
+
+impl ExchangeRateOracle {
+    /// Build the oracle contents for the given exchange rate.
+    pub fn new(
+        solido: Pubkey,
+        bump_seed: u8,
+        exchange_rate: &ExchangeRate,
+        updated_at_slot: Slot,
+    ) -> ExchangeRateOracle {
+        ExchangeRateOracle {
+            version: EXCHANGE_RATE_ORACLE_VERSION,
+            bump_seed,
+            solido,
+            computed_in_epoch: exchange_rate.computed_in_epoch,
+            st_sol_supply: exchange_rate.st_sol_supply,
+            sol_balance: exchange_rate.sol_balance,
+            updated_at_slot,
+        }
+    }
+
+    /// Return the exchange rate stored in the oracle.
+    pub fn exchange_rate(&self) -> ExchangeRate {
+        ExchangeRate {
+            computed_in_epoch: self.computed_in_epoch,
+            st_sol_supply: self.st_sol_supply,
+            sol_balance: self.sol_balance,
+        }
+    }
+
+    /// Return the address and bump seed of the oracle of a Solido instance.
+    pub fn find_address(program_id: &Pubkey, solido_address: &Pubkey) -> (Pubkey, u8) {
+        crate::find_authority_program_address(program_id, solido_address, EXCHANGE_RATE_ORACLE)
+    }
+
+    /// Confirm that the oracle account is the one of this Solido instance, and
+    /// return its bump seed.
+    pub fn check_address(
+        program_id: &Pubkey,
+        solido_address: &Pubkey,
+        oracle: &AccountInfo,
+    ) -> Result<u8, ProgramError> {
+        let (oracle_address, bump_seed) =
+            ExchangeRateOracle::find_address(program_id, solido_address);
+        if oracle_address != *oracle.key {
+            msg!(
+                "Expected exchange rate oracle {}, but got {}.",
+                oracle_address,
+                oracle.key
+            );
+            return Err(LidoError::InvalidExchangeRateOracle.into());
+        }
+        Ok(bump_seed)
+    }
+
+    /// Deserialize the oracle from an account, checking its owner and version.
+    pub fn from_account_info(
+        program_id: &Pubkey,
+        oracle: &AccountInfo,
+    ) -> Result<ExchangeRateOracle, ProgramError> {
+        if oracle.owner != program_id {
+            msg!(
+                "Exchange rate oracle {} is owned by {}, expected {}.",
+                oracle.key,
+                oracle.owner,
+                program_id
+            );
+            return Err(LidoError::InvalidExchangeRateOracle.into());
+        }
+        let result = ExchangeRateOracle::try_from_slice(&oracle.data.borrow())?;
+        if result.version != EXCHANGE_RATE_ORACLE_VERSION {
+            msg!(
+                "Expected exchange rate oracle version {}, but found {}.",
+                EXCHANGE_RATE_ORACLE_VERSION,
+                result.version
+            );
+            return Err(LidoError::InvalidExchangeRateOracle.into());
+        }
+        Ok(result)
+    }
+
+    pub fn save(&self, account: &AccountInfo) -> ProgramResult {
+        BorshSerialize::serialize(self, &mut *account.data.borrow_mut())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_exchange_rate_oracle_size() {
+        let oracle = ExchangeRateOracle::default();
+        assert_eq!(
+            oracle.try_to_vec().unwrap().len(),
+            EXCHANGE_RATE_ORACLE_SIZE
+        );
+    }
+
+    #[test]
+    fn test_exchange_rate_oracle_layout() {
+        let exchange_rate = ExchangeRate {
+            computed_in_epoch: 0x0101010101010101,
+            st_sol_supply: StLamports(0x0202020202020202),
+            sol_balance: Lamports(0x0303030303030303),
+        };
+        let oracle = ExchangeRateOracle::new(
+            Pubkey::new(&[0xaa; 32]),
+            0xbb,
+            &exchange_rate,
+            0x0404040404040404,
+        );
+        let data = oracle.try_to_vec().unwrap();
+
+        // The offsets here are part of the public interface, and they should
+        // match the module documentation.
+        assert_eq!(data[0], EXCHANGE_RATE_ORACLE_VERSION);
+        assert_eq!(data[1], 0xbb);
+        assert_eq!(&data[2..34], &[0xaa; 32]);
+        assert_eq!(&data[34..42], &[0x01; 8]);
+        assert_eq!(&data[42..50], &[0x02; 8]);
+        assert_eq!(&data[50..58], &[0x03; 8]);
+        assert_eq!(&data[58..66], &[0x04; 8]);
+
+        assert_eq!(oracle.exchange_rate(), exchange_rate);
+    }
+}
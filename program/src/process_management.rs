@@ -1,24 +1,42 @@
 // SPDX-FileCopyrightText: 2021 Chorus One AG
 // SPDX-License-Identifier: GPL-3.0
 
+use borsh::BorshSerialize;
+use solana_program::borsh::try_from_slice_unchecked;
+use solana_program::clock::Clock;
 use solana_program::program::invoke_signed;
 use solana_program::rent::Rent;
 use solana_program::sysvar::Sysvar;
 use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, msg, pubkey::Pubkey};
 
-use crate::logic::check_rent_exempt;
+use crate::logic::{
+    check_account_uninitialized, check_lido_owner, check_lido_version, check_rent_exempt,
+};
 use crate::processor::StakeType;
+use crate::vote_instruction::{self, VoteAuthorize};
 use crate::vote_state::PartialVoteState;
 use crate::{
+    account_map::{AccountMap, AccountSet, EntryConstantSize, PubkeyAndEntry},
     error::LidoError,
     instruction::{
-        AddMaintainerInfo, AddValidatorInfo, ChangeRewardDistributionInfo, ClaimValidatorFeeInfo,
-        DeactivateValidatorInfo, MergeStakeInfo, RemoveMaintainerInfo, RemoveValidatorInfo,
+        AddMaintainerInfo, AddValidatorInfo, ChangeDepositCutoffInfo, ChangeFeePayoutModeInfo,
+        ChangeRewardDistributionInfo, ClaimMaintainerFeeInfo, ClaimValidatorFeeInfo,
+        ClaimValidatorFeeToInfo, DeactivateValidatorInfo, ForceRemoveValidatorInfo,
+        MaintainerHeartbeatInfo, MergeStakeInfo, MigrateValidatorListInfo, RemoveMaintainerInfo,
+        RemoveValidatorInfo, SetTokenMetadataInfo, SortAccountMapsInfo, UpgradeFeePayoutModeInfo,
+        UpgradeMaintainersInfo, UpgradeMetricsInfo, UpgradeValidatorEntriesInfo,
+        UpgradeValidatorListInfo,
+    },
+    logic::{deserialize_lido, deserialize_lido_header, deserialize_validators, mint_st_sol_to},
+    state::{
+        FeePayoutMode, LegacyValidators, Lido, LidoV1, LidoV9, Maintainer, MaintainerV9,
+        MaintainersV9, RewardDistribution, Validator, Validators, ValidatorsV4, ValidatorsV8,
+        LIDO_CONSTANT_SIZE, LIDO_V1_CONSTANT_SIZE, LIDO_VERSION,
     },
-    logic::{deserialize_lido, mint_st_sol_to},
-    state::{RewardDistribution, Validator},
     token::StLamports,
-    STAKE_AUTHORITY,
+    token_metadata::{self, TokenMetadata},
+    zero_copy::{LidoAccountView, ValidatorListView},
+    MINT_AUTHORITY, REWARDS_WITHDRAW_AUTHORITY, STAKE_AUTHORITY, VALIDATOR_STAKE_ACCOUNT,
 };
 
 pub fn process_change_reward_distribution(
@@ -40,9 +58,89 @@ pub fn process_change_reward_distribution(
     lido.save(accounts.lido)
 }
 
+pub fn process_change_fee_payout_mode(
+    program_id: &Pubkey,
+    new_fee_payout_mode: FeePayoutMode,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = ChangeFeePayoutModeInfo::try_from_slice(accounts_raw)?;
+    let mut lido = deserialize_lido(program_id, accounts.lido)?;
+    lido.check_manager(accounts.manager)?;
+
+    // Switching back to `Credit` does not affect any existing credits, and
+    // switching to `Direct` leaves existing credits claimable with
+    // `ClaimValidatorFee`, so there is nothing to migrate here.
+    lido.fee_payout_mode = new_fee_payout_mode;
+
+    lido.save(accounts.lido)
+}
+
+/// Upgrade a version 0 `Lido` account to version 1, see
+/// [`LidoInstruction::UpgradeFeePayoutMode`](crate::instruction::LidoInstruction::UpgradeFeePayoutMode).
+pub fn process_upgrade_fee_payout_mode(
+    program_id: &Pubkey,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = UpgradeFeePayoutModeInfo::try_from_slice(accounts_raw)?;
+
+    check_lido_owner(program_id, accounts.lido)?;
+    check_lido_version(accounts.lido, 0)?;
+    let mut lido = LidoV1::deserialize_v0(&accounts.lido.data.borrow())?;
+    if &lido.manager != accounts.manager.key {
+        msg!("Invalid manager, not the same as the one stored in state");
+        return Err(LidoError::InvalidManager.into());
+    }
+
+    // The fee payout mode takes one more byte in the header. Version 0
+    // accounts were allocated for exactly the maximum number of validators
+    // and maintainers, so we may have to give up a maintainer slot for it.
+    let bytes_for_maintainers = accounts.lido.data_len().saturating_sub(
+        LIDO_V1_CONSTANT_SIZE
+            + LegacyValidators::required_bytes(lido.validators.maximum_entries as usize),
+    );
+    let max_maintainers = (AccountSet::maximum_entries(bytes_for_maintainers) as u32)
+        .min(lido.maintainers.maximum_entries);
+    if lido.maintainers.len() > max_maintainers as usize {
+        msg!(
+            "The upgraded Lido account has room for {} maintainers, but there are {}. \
+            Remove a maintainer before upgrading.",
+            max_maintainers,
+            lido.maintainers.len()
+        );
+        return Err(LidoError::MaximumNumberOfAccountsExceeded.into());
+    }
+    if max_maintainers < lido.maintainers.maximum_entries {
+        msg!(
+            "Reducing the maximum number of maintainers from {} to {}.",
+            lido.maintainers.maximum_entries,
+            max_maintainers
+        );
+        lido.maintainers.maximum_entries = max_maintainers;
+    }
+
+    lido.lido_version = 1;
+    BorshSerialize::serialize(&lido, &mut *accounts.lido.data.borrow_mut())?;
+    Ok(())
+}
+
+pub fn process_change_deposit_cutoff(
+    program_id: &Pubkey,
+    deposit_cutoff_slots: u64,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = ChangeDepositCutoffInfo::try_from_slice(accounts_raw)?;
+    let mut lido = deserialize_lido_header(program_id, accounts.lido)?;
+    lido.check_manager(accounts.manager)?;
+
+    lido.deposit_cutoff_slots = deposit_cutoff_slots;
+
+    lido.save_header(accounts.lido)
+}
+
 pub fn process_add_validator(program_id: &Pubkey, accounts_raw: &[AccountInfo]) -> ProgramResult {
     let accounts = AddValidatorInfo::try_from_slice(accounts_raw)?;
-    let mut lido = deserialize_lido(program_id, accounts.lido)?;
+    let lido = deserialize_lido_header(program_id, accounts.lido)?;
+    let mut validators = deserialize_validators(program_id, &lido, accounts.validator_list)?;
     let rent = &Rent::from_account_info(accounts.sysvar_rent)?;
     lido.check_manager(accounts.manager)?;
     lido.check_is_st_sol_account(accounts.validator_fee_st_sol_account)?;
@@ -56,18 +154,35 @@ pub fn process_add_validator(program_id: &Pubkey, accounts_raw: &[AccountInfo])
     // account: The vote account should be owned by the vote program, the
     // withdraw authority should be set to the program_id, and it should have
     // 100% commission.
-    let _partial_vote_state = PartialVoteState::deserialize(
+    let partial_vote_state = PartialVoteState::deserialize(
         program_id,
         accounts.lido.key,
         accounts.validator_vote_account,
     )?;
 
-    lido.validators.add(
+    // The vote withdrawer gets the vote account back on removal, so only the
+    // validator itself can decide where it goes.
+    let signer = accounts.validator_authority.key;
+    if signer != accounts.validator_vote_withdrawer.key && signer != &partial_vote_state.node_pubkey
+    {
+        msg!(
+            "Expected the vote withdrawer {} or the validator identity {} to sign, but got {}.",
+            accounts.validator_vote_withdrawer.key,
+            partial_vote_state.node_pubkey,
+            signer,
+        );
+        return Err(LidoError::InvalidValidatorAuthority.into());
+    }
+
+    validators.add(
         *accounts.validator_vote_account.key,
-        Validator::new(*accounts.validator_fee_st_sol_account.key),
+        Validator {
+            vote_withdrawer: *accounts.validator_vote_withdrawer.key,
+            ..Validator::new(*accounts.validator_fee_st_sol_account.key)
+        },
     )?;
 
-    lido.save(accounts.lido)
+    validators.save(accounts.validator_list)
 }
 
 /// Remove a validator.
@@ -76,22 +191,79 @@ pub fn process_add_validator(program_id: &Pubkey, accounts_raw: &[AccountInfo])
 /// and it is callable by anybody. Initiation of the removal (`DeactivateValidator`)
 /// is restricted to the manager, but once a validator is inactive, and there is
 /// no more stake delegated to it, removing it from the list can be done by anybody.
+///
+/// The withdraw authority of the vote account goes back to the vote withdrawer
+/// that the validator registered when it was added, or to the validator
+/// identity if it did not register one, see [`Validator::vote_withdrawer`].
 pub fn process_remove_validator(
     program_id: &Pubkey,
     accounts_raw: &[AccountInfo],
 ) -> ProgramResult {
     let accounts = RemoveValidatorInfo::try_from_slice(accounts_raw)?;
-    let mut lido = deserialize_lido(program_id, accounts.lido)?;
+    let lido = deserialize_lido_header(program_id, accounts.lido)?;
+    let rewards_withdraw_authority = lido.check_rewards_withdraw_authority(
+        program_id,
+        accounts.lido.key,
+        accounts.rewards_withdraw_authority,
+    )?;
+    let mut validators = deserialize_validators(program_id, &lido, accounts.validator_list)?;
 
-    let removed_validator = lido
-        .validators
-        .remove(accounts.validator_vote_account_to_remove.key)?;
+    let removed_validator = validators.remove(accounts.validator_vote_account_to_remove.key)?;
 
     let result = removed_validator.check_can_be_removed();
     Validator::show_removed_error_msg(&result);
     result?;
 
-    lido.save(accounts.lido)
+    // Validators that were added before we tracked the vote withdrawer did not
+    // register one, for those the withdraw authority goes to the validator
+    // identity.
+    let vote_withdrawer = if removed_validator.vote_withdrawer == Pubkey::default() {
+        let vote_state = PartialVoteState::deserialize(
+            program_id,
+            accounts.lido.key,
+            accounts.validator_vote_account_to_remove,
+        )?;
+        msg!(
+            "Validator {} has no vote withdrawer, handing the withdraw authority to \
+            its identity {}.",
+            accounts.validator_vote_account_to_remove.key,
+            vote_state.node_pubkey,
+        );
+        vote_state.node_pubkey
+    } else {
+        removed_validator.vote_withdrawer
+    };
+
+    if accounts.validator_vote_withdrawer.key != &vote_withdrawer {
+        msg!(
+            "Expected the vote withdrawer {}, but got {}.",
+            vote_withdrawer,
+            accounts.validator_vote_withdrawer.key,
+        );
+        return Err(LidoError::InvalidVoteWithdrawer.into());
+    }
+
+    invoke_signed(
+        &vote_instruction::authorize(
+            accounts.validator_vote_account_to_remove.key,
+            &rewards_withdraw_authority,
+            &vote_withdrawer,
+            VoteAuthorize::Withdrawer,
+        ),
+        &[
+            accounts.validator_vote_account_to_remove.clone(),
+            accounts.sysvar_clock.clone(),
+            accounts.rewards_withdraw_authority.clone(),
+            accounts.vote_program.clone(),
+        ],
+        &[&[
+            accounts.lido.key.as_ref(),
+            REWARDS_WITHDRAW_AUTHORITY,
+            &[lido.rewards_withdraw_authority_bump_seed],
+        ]],
+    )?;
+
+    validators.save(accounts.validator_list)
 }
 
 /// Set the `active` flag to false for a given validator.
@@ -104,17 +276,113 @@ pub fn process_deactivate_validator(
     accounts_raw: &[AccountInfo],
 ) -> ProgramResult {
     let accounts = DeactivateValidatorInfo::try_from_slice(accounts_raw)?;
-    let mut lido = deserialize_lido(program_id, accounts.lido)?;
+    let lido = deserialize_lido_header(program_id, accounts.lido)?;
     lido.check_manager(accounts.manager)?;
+    let mut validators = deserialize_validators(program_id, &lido, accounts.validator_list)?;
 
-    let validator = lido
-        .validators
-        .get_mut(accounts.validator_vote_account_to_deactivate.key)?;
+    let validator = validators.get_mut(accounts.validator_vote_account_to_deactivate.key)?;
 
     validator.entry.active = false;
     msg!("Validator {} deactivated.", validator.pubkey);
 
-    lido.save(accounts.lido)
+    validators.save(accounts.validator_list)
+}
+
+/// Deactivate a validator and all of its stake accounts, and pay out its fee
+/// credit, turning it into a tombstone.
+///
+/// Once the stake is inactive, `WithdrawInactiveStake` moves it back to the
+/// reserve, after which the validator can be removed with `RemoveValidator`.
+pub fn process_force_remove_validator(
+    program_id: &Pubkey,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = ForceRemoveValidatorInfo::try_from_slice(accounts_raw)?;
+    let lido = deserialize_lido_header(program_id, accounts.lido)?;
+    lido.check_manager(accounts.manager)?;
+    lido.check_stake_authority(program_id, accounts.lido.key, accounts.stake_authority)?;
+    let mut validators = deserialize_validators(program_id, &lido, accounts.validator_list)?;
+
+    let validator = validators.get(accounts.validator_vote_account_to_remove.key)?;
+    if validator.entry.tombstone {
+        msg!("Validator {} is already being removed.", validator.pubkey);
+        return Err(LidoError::ValidatorIsTombstone.into());
+    }
+    if accounts.validator_fee_st_sol_account.key != &validator.entry.fee_address {
+        msg!(
+            "Expected the fee account of validator {} to be {}, but got {}.",
+            validator.pubkey,
+            validator.entry.fee_address,
+            accounts.validator_fee_st_sol_account.key,
+        );
+        return Err(LidoError::InvalidValidatorCreditAccount.into());
+    }
+
+    let n_stake_accounts = validator.entry.stake_seeds.end - validator.entry.stake_seeds.begin;
+    if accounts.stake_accounts.len() as u64 != n_stake_accounts {
+        msg!(
+            "Wrong number of stake accounts provided, expected {} stake accounts, but got {} accounts.",
+            n_stake_accounts,
+            accounts.stake_accounts.len()
+        );
+        return Err(LidoError::InvalidStakeAccount.into());
+    }
+
+    for (seed, stake_account) in validator
+        .entry
+        .stake_seeds
+        .into_iter()
+        .zip(accounts.stake_accounts.iter())
+    {
+        Lido::check_stake_account(
+            program_id,
+            accounts.lido.key,
+            validator,
+            seed,
+            stake_account,
+            VALIDATOR_STAKE_ACCOUNT,
+        )?;
+
+        let deactivate_stake_instruction = solana_program::stake::instruction::deactivate_stake(
+            stake_account.key,
+            accounts.stake_authority.key,
+        );
+        invoke_signed(
+            &deactivate_stake_instruction,
+            &[
+                stake_account.clone(),
+                accounts.sysvar_clock.clone(),
+                accounts.stake_authority.clone(),
+                accounts.stake_program.clone(),
+            ],
+            &[&[
+                &accounts.lido.key.to_bytes(),
+                STAKE_AUTHORITY,
+                &[lido.stake_authority_bump_seed],
+            ]],
+        )?;
+    }
+
+    mint_st_sol_to(
+        &lido,
+        accounts.lido.key,
+        accounts.spl_token,
+        accounts.st_sol_mint,
+        accounts.mint_authority,
+        accounts.validator_fee_st_sol_account,
+        validator.entry.fee_credit,
+    )?;
+
+    let validator = validators.get_mut(accounts.validator_vote_account_to_remove.key)?;
+    validator.entry.fee_credit = StLamports(0);
+    validator.entry.active = false;
+    validator.entry.tombstone = true;
+    msg!(
+        "Validator {} deactivated, its stake is being deactivated.",
+        validator.pubkey
+    );
+
+    validators.save(accounts.validator_list)
 }
 
 pub fn process_claim_validator_fee(
@@ -122,17 +390,24 @@ pub fn process_claim_validator_fee(
     accounts_raw: &[AccountInfo],
 ) -> ProgramResult {
     let accounts = ClaimValidatorFeeInfo::try_from_slice(accounts_raw)?;
-    let mut lido = deserialize_lido(program_id, accounts.lido)?;
+    let lido = deserialize_lido_header(program_id, accounts.lido)?;
 
-    let pubkey_entry = lido
-        .validators
-        .entries
-        .iter_mut()
-        .find(|pe| &pe.entry.fee_address == accounts.validator_fee_st_sol_account.key)
-        .ok_or(LidoError::InvalidValidatorCreditAccount)?;
+    // Only the claiming validator changes, so we update it in place rather
+    // than deserializing and re-serializing the entire validator list.
+    lido.check_validator_list(program_id, accounts.validator_list)?;
+    let amount_claimed = {
+        let mut data = accounts.validator_list.data.borrow_mut();
+        let mut view = ValidatorListView::new(&mut data[..])?;
+        let index = view
+            .find_validator_by_fee_address(accounts.validator_fee_st_sol_account.key)
+            .ok_or(LidoError::InvalidValidatorCreditAccount)?;
 
-    let amount_claimed = pubkey_entry.entry.fee_credit;
-    pubkey_entry.entry.fee_credit = StLamports(0);
+        let mut validator = view.validator(index)?;
+        let amount_claimed = validator.entry.fee_credit;
+        validator.entry.fee_credit = StLamports(0);
+        view.set_validator(index, &validator.entry)?;
+        amount_claimed
+    };
 
     mint_st_sol_to(
         &lido,
@@ -142,8 +417,61 @@ pub fn process_claim_validator_fee(
         accounts.mint_authority,
         accounts.validator_fee_st_sol_account,
         amount_claimed,
+    )
+}
+
+/// Mint the fee credit of a validator to a recipient of its choice, signed by
+/// the validator identity or the authorized voter of its vote account.
+pub fn process_claim_validator_fee_to(
+    program_id: &Pubkey,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = ClaimValidatorFeeToInfo::try_from_slice(accounts_raw)?;
+    let lido = deserialize_lido_header(program_id, accounts.lido)?;
+    let clock = Clock::from_account_info(accounts.sysvar_clock)?;
+
+    let vote_state = PartialVoteState::deserialize(
+        program_id,
+        accounts.lido.key,
+        accounts.validator_vote_account,
     )?;
-    lido.save(accounts.lido)
+    let authorized_voter =
+        PartialVoteState::get_authorized_voter(accounts.validator_vote_account, clock.epoch)?;
+    let signer = accounts.validator_authority.key;
+    if signer != &vote_state.node_pubkey && signer != &authorized_voter {
+        msg!(
+            "Expected the validator identity {} or the authorized voter {} to sign, but got {}.",
+            vote_state.node_pubkey,
+            authorized_voter,
+            signer,
+        );
+        return Err(LidoError::InvalidValidatorAuthority.into());
+    }
+
+    // Only the claiming validator changes, so we update it in place rather
+    // than deserializing and re-serializing the entire validator list.
+    lido.check_validator_list(program_id, accounts.validator_list)?;
+    let amount_claimed = {
+        let mut data = accounts.validator_list.data.borrow_mut();
+        let mut view = ValidatorListView::new(&mut data[..])?;
+        let index = view.find_validator(accounts.validator_vote_account.key)?;
+
+        let mut validator = view.validator(index)?;
+        let amount_claimed = validator.entry.fee_credit;
+        validator.entry.fee_credit = StLamports(0);
+        view.set_validator(index, &validator.entry)?;
+        amount_claimed
+    };
+
+    mint_st_sol_to(
+        &lido,
+        accounts.lido.key,
+        accounts.spl_token,
+        accounts.st_sol_mint,
+        accounts.mint_authority,
+        accounts.recipient_st_sol_account,
+        amount_claimed,
+    )
 }
 
 /// Adds a maintainer to the list of maintainers
@@ -152,7 +480,8 @@ pub fn process_add_maintainer(program_id: &Pubkey, accounts_raw: &[AccountInfo])
     let mut lido = deserialize_lido(program_id, accounts.lido)?;
     lido.check_manager(accounts.manager)?;
 
-    lido.maintainers.add(*accounts.maintainer.key, ())?;
+    lido.maintainers
+        .add(*accounts.maintainer.key, Maintainer::default())?;
 
     lido.save(accounts.lido)
 }
@@ -166,11 +495,55 @@ pub fn process_remove_maintainer(
     let mut lido = deserialize_lido(program_id, accounts.lido)?;
     lido.check_manager(accounts.manager)?;
 
+    // Any unclaimed credit of the maintainer stays in `maintainer_fee_credit`,
+    // so it returns to the pool that the next `UpdateExchangeRate` distributes.
     lido.maintainers.remove(accounts.maintainer.key)?;
 
     lido.save(accounts.lido)
 }
 
+/// Mint the maintainer fee that the signing maintainer accrued.
+pub fn process_claim_maintainer_fee(
+    program_id: &Pubkey,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = ClaimMaintainerFeeInfo::try_from_slice(accounts_raw)?;
+    let mut lido = deserialize_lido(program_id, accounts.lido)?;
+    lido.check_maintainer(accounts.maintainer)?;
+
+    let maintainer = &mut lido.maintainers.get_mut(accounts.maintainer.key)?.entry;
+    let amount_claimed = maintainer.fee_credit;
+    maintainer.fee_credit = StLamports(0);
+    lido.maintainer_fee_credit = (lido.maintainer_fee_credit - amount_claimed)?;
+
+    mint_st_sol_to(
+        &lido,
+        accounts.lido.key,
+        accounts.spl_token,
+        accounts.st_sol_mint,
+        accounts.mint_authority,
+        accounts.maintainer_st_sol_account,
+        amount_claimed,
+    )?;
+
+    lido.save(accounts.lido)
+}
+
+/// Record the current slot as the heartbeat of the signing maintainer.
+pub fn process_maintainer_heartbeat(
+    program_id: &Pubkey,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = MaintainerHeartbeatInfo::try_from_slice(accounts_raw)?;
+    let mut lido = deserialize_lido(program_id, accounts.lido)?;
+    lido.check_maintainer(accounts.maintainer)?;
+
+    let clock = Clock::from_account_info(accounts.sysvar_clock)?;
+    lido.observe_maintainer_heartbeat(accounts.maintainer.key, clock.slot)?;
+
+    lido.save(accounts.lido)
+}
+
 /// TODO(#186) Allow validator to change fee account
 /// Called by the validator, changes the fee account which the validator
 /// receives tokens
@@ -190,11 +563,10 @@ pub fn _process_change_validator_fee_account(
 /// All fully active stake accounts precede the activating stake accounts.
 pub fn process_merge_stake(program_id: &Pubkey, accounts_raw: &[AccountInfo]) -> ProgramResult {
     let accounts = MergeStakeInfo::try_from_slice(accounts_raw)?;
-    let mut lido = deserialize_lido(program_id, accounts.lido)?;
+    let lido = deserialize_lido_header(program_id, accounts.lido)?;
+    let mut validators = deserialize_validators(program_id, &lido, accounts.validator_list)?;
 
-    let mut validator = lido
-        .validators
-        .get_mut(accounts.validator_vote_account.key)?;
+    let mut validator = validators.get_mut(accounts.validator_vote_account.key)?;
     let from_seed = validator.entry.stake_seeds.begin;
     let to_seed = validator.entry.stake_seeds.begin + 1;
 
@@ -267,5 +639,444 @@ pub fn process_merge_stake(program_id: &Pubkey, accounts_raw: &[AccountInfo]) ->
         ]],
     )?;
 
+    validators.save(accounts.validator_list)?;
+
+    match accounts.maintainer {
+        Some(maintainer) => {
+            let mut lido_data = accounts.lido.data.borrow_mut();
+            LidoAccountView::new(&mut lido_data[..])?
+                .observe_operation_if_maintainer(maintainer.key)
+        }
+        None => Ok(()),
+    }
+}
+
+/// Check that `validator_list` is a fresh account, owned by the Solido program,
+/// of exactly `bytes_for_validators` bytes.
+fn check_new_validator_list(
+    program_id: &Pubkey,
+    rent: &Rent,
+    validator_list: &AccountInfo,
+    bytes_for_validators: usize,
+) -> ProgramResult {
+    if validator_list.owner != program_id {
+        msg!(
+            "Validator list is owned by {}, but should be owned by the Lido program ({}).",
+            validator_list.owner,
+            program_id
+        );
+        return Err(LidoError::InvalidOwner.into());
+    }
+    check_rent_exempt(rent, validator_list, "Validator list account")?;
+    check_account_uninitialized(validator_list, 4)?;
+
+    if bytes_for_validators != validator_list.data_len() {
+        msg!(
+            "Incorrect allocated bytes for the validator list: max_validators bytes: {}, should be {}",
+            bytes_for_validators,
+            validator_list.data_len()
+        );
+        return Err(LidoError::InvalidLidoSize.into());
+    }
+    Ok(())
+}
+
+/// Move the validators of a version 1 `Lido` account into a separate validator
+/// list account, and upgrade the `Lido` account to the current version.
+pub fn process_migrate_validator_list(
+    program_id: &Pubkey,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = MigrateValidatorListInfo::try_from_slice(accounts_raw)?;
+    let rent = &Rent::from_account_info(accounts.sysvar_rent)?;
+
+    check_lido_owner(program_id, accounts.lido)?;
+    check_lido_version(accounts.lido, 1)?;
+    let lido_v1 = try_from_slice_unchecked::<LidoV1>(&accounts.lido.data.borrow())?;
+    if &lido_v1.manager != accounts.manager.key {
+        msg!("Invalid manager, not the same as the one stored in state");
+        return Err(LidoError::InvalidManager.into());
+    }
+
+    check_new_validator_list(
+        program_id,
+        rent,
+        accounts.validator_list,
+        Validators::calculate_size(lido_v1.validators.maximum_entries),
+    )?;
+
+    let num_validators = lido_v1.validators.len();
+    let (lido, validators) = lido_v1.migrate(*accounts.validator_list.key);
+    validators.save(accounts.validator_list)?;
+
+    // The `Lido` account keeps its size, the space that held the validators is
+    // now unused. Clear it, so no stale data remains after the maintainers.
+    accounts.lido.data.borrow_mut().fill(0);
+    lido.save(accounts.lido)?;
+
+    msg!(
+        "Moved {} validators to validator list {}.",
+        num_validators,
+        accounts.validator_list.key
+    );
+    Ok(())
+}
+
+/// Sort the validators and maintainers of a version 2 `Lido` account, and
+/// upgrade it to version 3.
+pub fn process_sort_account_maps(
+    program_id: &Pubkey,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = SortAccountMapsInfo::try_from_slice(accounts_raw)?;
+
+    check_lido_owner(program_id, accounts.lido)?;
+    check_lido_version(accounts.lido, 2)?;
+    // Version 2 has the legacy `Lido` and validator layouts, `UpgradeValidatorList`
+    // and `UpgradeMetrics` convert them afterwards.
+    let mut lido = Lido::deserialize_legacy(&accounts.lido.data.borrow())?;
+    lido.check_manager(accounts.manager)?;
+    lido.check_validator_list(program_id, accounts.validator_list)?;
+    let mut validators =
+        try_from_slice_unchecked::<LegacyValidators>(&accounts.validator_list.data.borrow())?;
+
+    validators.sort();
+    lido.maintainers.sort();
+    lido.lido_version = 3;
+
+    validators.save(accounts.validator_list)?;
+    lido.save_legacy(accounts.lido)?;
+
+    msg!(
+        "Sorted {} validators and {} maintainers.",
+        validators.len(),
+        lido.maintainers.len()
+    );
+    Ok(())
+}
+
+/// Move the validators of a version 2 or 3 `Lido` account into a new validator
+/// list account that holds per-validator metrics, and upgrade the `Lido`
+/// account to version 4.
+pub fn process_upgrade_validator_list(
+    program_id: &Pubkey,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = UpgradeValidatorListInfo::try_from_slice(accounts_raw)?;
+    let rent = &Rent::from_account_info(accounts.sysvar_rent)?;
+
+    check_lido_owner(program_id, accounts.lido)?;
+    let version = accounts.lido.data.borrow().first().cloned();
+    if version != Some(2) && version != Some(3) {
+        msg!(
+            "Lido account {} has version {:?}, but expected version 2 or 3.",
+            accounts.lido.key,
+            version,
+        );
+        return Err(LidoError::InvalidLidoVersion.into());
+    }
+    let mut lido = Lido::deserialize_legacy(&accounts.lido.data.borrow())?;
+    lido.check_manager(accounts.manager)?;
+    lido.check_validator_list(program_id, accounts.validator_list)?;
+    let mut legacy_validators =
+        try_from_slice_unchecked::<LegacyValidators>(&accounts.validator_list.data.borrow())?;
+
+    if accounts.new_validator_list.key == accounts.validator_list.key {
+        msg!("The new validator list must be a different account than the current one.");
+        return Err(LidoError::InvalidValidatorList.into());
+    }
+    check_new_validator_list(
+        program_id,
+        rent,
+        accounts.new_validator_list,
+        ValidatorsV4::calculate_size(legacy_validators.maximum_entries),
+    )?;
+
+    // Version 2 did not keep the maintainers and validators sorted yet.
+    legacy_validators.sort();
+    lido.maintainers.sort();
+
+    let validators = legacy_validators.upgrade();
+    validators.save(accounts.new_validator_list)?;
+
+    lido.validator_list = *accounts.new_validator_list.key;
+    lido.lido_version = 4;
+    lido.save_legacy(accounts.lido)?;
+
+    msg!(
+        "Moved {} validators to validator list {}.",
+        validators.len(),
+        accounts.new_validator_list.key
+    );
+    Ok(())
+}
+
+/// Convert the header of a version 4 through 6 `Lido` account to the layout of
+/// version 7, which has room for more metrics, the deposit cutoff, and the
+/// maintainer fee, and upgrade it to version 7.
+///
+/// The `Lido` account keeps its size, so the larger header takes space away
+/// from the maintainers. If the account has room for fewer maintainers than
+/// the instance currently has, this fails, and the manager needs to remove
+/// some maintainers first.
+pub fn process_upgrade_metrics(program_id: &Pubkey, accounts_raw: &[AccountInfo]) -> ProgramResult {
+    let accounts = UpgradeMetricsInfo::try_from_slice(accounts_raw)?;
+
+    check_lido_owner(program_id, accounts.lido)?;
+    let version = accounts.lido.data.borrow().first().cloned();
+    if version != Some(4) && version != Some(5) && version != Some(6) {
+        msg!(
+            "Lido account {} has version {:?}, but expected version 4, 5, or 6.",
+            accounts.lido.key,
+            version,
+        );
+        return Err(LidoError::InvalidLidoVersion.into());
+    }
+    let legacy_lido = Lido::deserialize_legacy(&accounts.lido.data.borrow())?;
+    legacy_lido.check_manager(accounts.manager)?;
+
+    // Versions 4 through 6 had no maintainer fee, so the maintainers start
+    // without credit or operations.
+    let mut lido = LidoV9 {
+        header: legacy_lido.header,
+        maintainers: MaintainersV9 {
+            entries: legacy_lido
+                .maintainers
+                .entries
+                .into_iter()
+                .map(|pe| PubkeyAndEntry {
+                    pubkey: pe.pubkey,
+                    entry: MaintainerV9::default(),
+                })
+                .collect(),
+            maximum_entries: legacy_lido.maintainers.maximum_entries,
+        },
+    };
+    limit_maintainers_to_account_size(accounts.lido, &mut lido.maintainers)?;
+
+    lido.header.lido_version = 7;
     lido.save(accounts.lido)
 }
+
+/// Reduce the maximum number of maintainers to what fits in the `Lido`
+/// account after the header, in the layout of `T`.
+///
+/// Fails if there are more maintainers than that.
+fn limit_maintainers_to_account_size<T: Default + EntryConstantSize>(
+    lido: &AccountInfo,
+    maintainers: &mut AccountMap<T>,
+) -> ProgramResult {
+    let bytes_for_maintainers = lido.data_len().saturating_sub(LIDO_CONSTANT_SIZE);
+    let max_maintainers = (AccountMap::<T>::maximum_entries(bytes_for_maintainers) as u32)
+        .min(maintainers.maximum_entries);
+    if maintainers.len() > max_maintainers as usize {
+        msg!(
+            "The upgraded Lido account has room for {} maintainers, but there are {}. \
+            Remove some maintainers before upgrading.",
+            max_maintainers,
+            maintainers.len()
+        );
+        return Err(LidoError::MaximumNumberOfAccountsExceeded.into());
+    }
+    if max_maintainers < maintainers.maximum_entries {
+        msg!(
+            "Reducing the maximum number of maintainers from {} to {}.",
+            maintainers.maximum_entries,
+            max_maintainers
+        );
+        maintainers.maximum_entries = max_maintainers;
+    }
+    Ok(())
+}
+
+/// Move the validators of a version 7 or 8 `Lido` account into a new validator
+/// list account in the current layout, which has room for a tombstone flag and
+/// a vote withdrawer, and upgrade the `Lido` account to version 9.
+pub fn process_upgrade_validator_entries(
+    program_id: &Pubkey,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = UpgradeValidatorEntriesInfo::try_from_slice(accounts_raw)?;
+    let rent = &Rent::from_account_info(accounts.sysvar_rent)?;
+
+    check_lido_owner(program_id, accounts.lido)?;
+    let version = accounts.lido.data.borrow().first().cloned();
+    if version != Some(7) && version != Some(8) {
+        msg!(
+            "Lido account {} has version {:?}, but expected version 7 or 8.",
+            accounts.lido.key,
+            version,
+        );
+        return Err(LidoError::InvalidLidoVersion.into());
+    }
+    // Versions 7 and 8 have the version 9 `Lido` layout, only the validator
+    // list changes.
+    let mut lido = try_from_slice_unchecked::<LidoV9>(&accounts.lido.data.borrow())?;
+    lido.header.check_manager(accounts.manager)?;
+    lido.header
+        .check_validator_list(program_id, accounts.validator_list)?;
+    let validators = {
+        let data = accounts.validator_list.data.borrow();
+        if version == Some(7) {
+            try_from_slice_unchecked::<ValidatorsV4>(&data)?
+                .upgrade()
+                .upgrade()
+        } else {
+            try_from_slice_unchecked::<ValidatorsV8>(&data)?.upgrade()
+        }
+    };
+
+    if accounts.new_validator_list.key == accounts.validator_list.key {
+        msg!("The new validator list must be a different account than the current one.");
+        return Err(LidoError::InvalidValidatorList.into());
+    }
+    check_new_validator_list(
+        program_id,
+        rent,
+        accounts.new_validator_list,
+        Validators::calculate_size(validators.maximum_entries),
+    )?;
+
+    validators.save(accounts.new_validator_list)?;
+
+    lido.header.validator_list = *accounts.new_validator_list.key;
+    lido.header.lido_version = 9;
+    lido.save(accounts.lido)?;
+
+    msg!(
+        "Moved {} validators to validator list {}.",
+        validators.len(),
+        accounts.new_validator_list.key
+    );
+    Ok(())
+}
+
+/// Convert the maintainers of a version 9 `Lido` account to the current
+/// layout, which has room for the most recent heartbeat, and upgrade it to the
+/// current version. Version 10 accounts already have the current layout, for
+/// those this only updates the version.
+///
+/// Like `UpgradeMetrics`, the `Lido` account keeps its size, so the larger
+/// entries may reduce the maximum number of maintainers.
+pub fn process_upgrade_maintainers(
+    program_id: &Pubkey,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = UpgradeMaintainersInfo::try_from_slice(accounts_raw)?;
+
+    check_lido_owner(program_id, accounts.lido)?;
+    let version = accounts.lido.data.borrow().first().cloned();
+    let mut lido = match version {
+        Some(9) => {
+            let lido_v9 = try_from_slice_unchecked::<LidoV9>(&accounts.lido.data.borrow())?;
+            lido_v9.header.check_manager(accounts.manager)?;
+            let mut lido = lido_v9.upgrade();
+            limit_maintainers_to_account_size(accounts.lido, &mut lido.maintainers)?;
+            lido
+        }
+        Some(10) => {
+            let lido = try_from_slice_unchecked::<Lido>(&accounts.lido.data.borrow())?;
+            lido.check_manager(accounts.manager)?;
+            lido
+        }
+        _ => {
+            msg!(
+                "Lido account {} has version {:?}, but expected version 9 or 10.",
+                accounts.lido.key,
+                version,
+            );
+            return Err(LidoError::InvalidLidoVersion.into());
+        }
+    };
+
+    lido.lido_version = LIDO_VERSION;
+    lido.save(accounts.lido)
+}
+
+pub fn process_set_token_metadata(
+    program_id: &Pubkey,
+    token_metadata: TokenMetadata,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = SetTokenMetadataInfo::try_from_slice(accounts_raw)?;
+    let lido = deserialize_lido_header(program_id, accounts.lido)?;
+    lido.check_manager(accounts.manager)?;
+    lido.check_mint_is_st_sol_mint(accounts.st_sol_mint)?;
+
+    let mint_authority = lido.get_mint_authority(program_id, accounts.lido.key)?;
+    if mint_authority != *accounts.mint_authority.key {
+        msg!(
+            "Expected mint authority {}, but got {}.",
+            mint_authority,
+            accounts.mint_authority.key
+        );
+        return Err(LidoError::InvalidMint.into());
+    }
+
+    let (metadata_address, _) = token_metadata::find_metadata_address(&lido.st_sol_mint);
+    if metadata_address != *accounts.token_metadata.key {
+        msg!(
+            "Expected token metadata account {}, but got {}.",
+            metadata_address,
+            accounts.token_metadata.key
+        );
+        return Err(LidoError::InvalidTokenMetadata.into());
+    }
+    if !token_metadata.is_valid() {
+        msg!(
+            "Token name, symbol, and URI can be at most {}, {}, and {} bytes.",
+            token_metadata::MAX_NAME_LENGTH,
+            token_metadata::MAX_SYMBOL_LENGTH,
+            token_metadata::MAX_URI_LENGTH,
+        );
+        return Err(LidoError::InvalidTokenMetadata.into());
+    }
+
+    // The mint authority is both the mint authority and the update authority
+    // of the metadata, so it signs in both cases.
+    let solido_address_bytes = accounts.lido.key.to_bytes();
+    let mint_authority_seeds = [
+        &solido_address_bytes[..],
+        MINT_AUTHORITY,
+        &[lido.mint_authority_bump_seed],
+    ];
+
+    if accounts.token_metadata.data_is_empty() {
+        msg!("Creating token metadata account {}.", metadata_address);
+        invoke_signed(
+            &token_metadata::create_metadata_account(
+                accounts.token_metadata.key,
+                accounts.st_sol_mint.key,
+                accounts.mint_authority.key,
+                accounts.payer.key,
+                accounts.mint_authority.key,
+                &token_metadata,
+            ),
+            &[
+                accounts.token_metadata.clone(),
+                accounts.st_sol_mint.clone(),
+                accounts.mint_authority.clone(),
+                accounts.payer.clone(),
+                accounts.system_program.clone(),
+                accounts.sysvar_rent.clone(),
+                accounts.token_metadata_program.clone(),
+            ],
+            &[&mint_authority_seeds[..]],
+        )
+    } else {
+        msg!("Updating token metadata account {}.", metadata_address);
+        invoke_signed(
+            &token_metadata::update_metadata_account(
+                accounts.token_metadata.key,
+                accounts.mint_authority.key,
+                &token_metadata,
+            ),
+            &[
+                accounts.token_metadata.clone(),
+                accounts.mint_authority.clone(),
+                accounts.token_metadata_program.clone(),
+            ],
+            &[&mint_authority_seeds[..]],
+        )
+    }
+}
@@ -6,33 +6,44 @@
 use std::ops::{Add, Sub};
 
 use crate::{
+    account_map::PubkeyAndEntry,
     error::LidoError,
     instruction::{
-        CollectValidatorFeeInfo, DepositAccountsInfo, InitializeAccountsInfo, LidoInstruction,
-        StakeDepositAccountsInfo, UnstakeAccountsInfo, UpdateExchangeRateAccountsInfo,
-        WithdrawAccountsInfo, WithdrawInactiveStakeInfo,
+        CollectValidatorFeeInfo, DepositAccountsInfo, InitializeAccountsInfo,
+        InitializeExchangeRateOracleInfo, LidoInstruction, StakeDepositAccountsInfo,
+        UnstakeAccountsInfo, UpdateExchangeRateAccountsInfo, WithdrawAccountsInfo,
+        WithdrawInactiveStakeInfo,
     },
     logic::{
-        burn_st_sol, check_mint, check_rent_exempt, check_unstake_accounts,
-        create_account_even_if_funded, deserialize_lido, distribute_fees,
+        burn_st_sol, check_account_uninitialized, check_mint, check_rent_exempt,
+        check_unstake_accounts, create_account_even_if_funded, deserialize_lido,
+        deserialize_lido_header, deserialize_validators, distribute_fees,
         initialize_stake_account_undelegated, mint_st_sol_to, split_stake_account,
         transfer_stake_authority, CreateAccountOptions, SplitStakeAccounts,
     },
     metrics::Metrics,
+    oracle::{ExchangeRateOracle, EXCHANGE_RATE_ORACLE_SIZE},
     process_management::{
-        process_add_maintainer, process_add_validator, process_change_reward_distribution,
-        process_claim_validator_fee, process_deactivate_validator, process_merge_stake,
-        process_remove_maintainer, process_remove_validator,
+        process_add_maintainer, process_add_validator, process_change_deposit_cutoff,
+        process_change_fee_payout_mode, process_change_reward_distribution,
+        process_claim_maintainer_fee, process_claim_validator_fee, process_claim_validator_fee_to,
+        process_deactivate_validator, process_force_remove_validator, process_maintainer_heartbeat,
+        process_merge_stake, process_migrate_validator_list, process_remove_maintainer,
+        process_remove_validator, process_set_token_metadata, process_sort_account_maps,
+        process_upgrade_fee_payout_mode, process_upgrade_maintainers, process_upgrade_metrics,
+        process_upgrade_validator_entries, process_upgrade_validator_list,
     },
     stake_account::{deserialize_stake_account, StakeAccount},
     state::{
-        ExchangeRate, FeeRecipients, Lido, Maintainers, RewardDistribution, Validators,
-        LIDO_CONSTANT_SIZE, LIDO_VERSION,
+        ExchangeRate, FeePayoutMode, FeeRecipients, Lido, LidoHeader, Maintainers,
+        RewardDistribution, Validator, Validators, LIDO_CONSTANT_SIZE, LIDO_VERSION,
     },
     token::{Lamports, Rational, StLamports},
-    vote_instruction, MAXIMUM_UNSTAKE_ACCOUNTS, MINIMUM_STAKE_ACCOUNT_BALANCE, MINT_AUTHORITY,
-    RESERVE_ACCOUNT, REWARDS_WITHDRAW_AUTHORITY, STAKE_AUTHORITY, VALIDATOR_STAKE_ACCOUNT,
-    VALIDATOR_UNSTAKE_ACCOUNT,
+    vote_instruction,
+    zero_copy::{LidoAccountView, ValidatorListView, VALIDATOR_LIST_PAGE_SIZE},
+    EXCHANGE_RATE_ORACLE, MAXIMUM_DIRECT_FEE_ACCOUNTS, MAXIMUM_UNSTAKE_ACCOUNTS,
+    MINIMUM_STAKE_ACCOUNT_BALANCE, MINT_AUTHORITY, RESERVE_ACCOUNT, REWARDS_WITHDRAW_AUTHORITY,
+    STAKE_AUTHORITY, VALIDATOR_STAKE_ACCOUNT, VALIDATOR_UNSTAKE_ACCOUNT,
 };
 
 use solana_program::stake::{self as stake_program};
@@ -43,6 +54,7 @@ use {
         account_info::AccountInfo,
         clock::Clock,
         entrypoint::ProgramResult,
+        epoch_schedule::EpochSchedule,
         msg,
         native_token::LAMPORTS_PER_SOL,
         program::{invoke, invoke_signed},
@@ -66,27 +78,30 @@ pub fn process_initialize(
     let accounts = InitializeAccountsInfo::try_from_slice(accounts_raw)?;
     let rent = &Rent::from_account_info(accounts.sysvar_rent)?;
     check_rent_exempt(rent, accounts.lido, "Solido account")?;
+    check_rent_exempt(rent, accounts.validator_list, "Validator list account")?;
     check_rent_exempt(rent, accounts.reserve_account, "Reserve account")?;
 
-    let is_uninitialized = accounts.lido.data.borrow()[..LIDO_CONSTANT_SIZE]
-        .iter()
-        .all(|byte| *byte == 0);
-    if !is_uninitialized {
-        msg!(
-            "Account {} appears to be in use already, refusing to overwrite.",
-            accounts.lido.key
-        );
-        return Err(LidoError::AlreadyInUse.into());
-    }
+    check_account_uninitialized(accounts.lido, LIDO_CONSTANT_SIZE)?;
+    // The first four bytes hold the number of validators.
+    check_account_uninitialized(accounts.validator_list, 4)?;
 
     // Bytes required for maintainers
     let bytes_for_maintainers = Maintainers::required_bytes(max_maintainers as usize);
-    // Bytes required for validators
-    let bytes_for_validators = Validators::required_bytes(max_validators as usize);
     // Calculate the expected lido's size
-    let bytes_sum = LIDO_CONSTANT_SIZE + bytes_for_validators + bytes_for_maintainers;
+    let bytes_sum = LIDO_CONSTANT_SIZE + bytes_for_maintainers;
     if bytes_sum != accounts.lido.data_len() {
-        msg!("Incorrect allocated bytes for the provided constrains: max_validator bytes: {}, max_maintainers bytes: {}, constant_size: {}, sum is {}, should be {}", bytes_for_validators, bytes_for_maintainers, LIDO_CONSTANT_SIZE, bytes_sum, accounts.lido.data_len());
+        msg!("Incorrect allocated bytes for the provided constrains: max_maintainers bytes: {}, constant_size: {}, sum is {}, should be {}", bytes_for_maintainers, LIDO_CONSTANT_SIZE, bytes_sum, accounts.lido.data_len());
+        return Err(LidoError::InvalidLidoSize.into());
+    }
+
+    // Bytes required for validators, they live in their own account.
+    let bytes_for_validators = Validators::calculate_size(max_validators);
+    if bytes_for_validators != accounts.validator_list.data_len() {
+        msg!(
+            "Incorrect allocated bytes for the validator list: max_validators bytes: {}, should be {}",
+            bytes_for_validators,
+            accounts.validator_list.data_len()
+        );
         return Err(LidoError::InvalidLidoSize.into());
     }
 
@@ -112,28 +127,35 @@ pub fn process_initialize(
 
     // Initialize fee structure
     let lido = Lido {
-        lido_version: version,
-        manager: *accounts.manager.key,
-        st_sol_mint: *accounts.st_sol_mint.key,
-        exchange_rate: ExchangeRate::default(),
-        sol_reserve_account_bump_seed: reserve_bump_seed,
-        mint_authority_bump_seed: mint_bump_seed,
-        stake_authority_bump_seed: deposit_bump_seed,
-        rewards_withdraw_authority_bump_seed,
-        reward_distribution,
-        fee_recipients: FeeRecipients {
-            treasury_account: *accounts.treasury_account.key,
-            developer_account: *accounts.developer_account.key,
+        header: LidoHeader {
+            lido_version: version,
+            manager: *accounts.manager.key,
+            st_sol_mint: *accounts.st_sol_mint.key,
+            validator_list: *accounts.validator_list.key,
+            exchange_rate: ExchangeRate::default(),
+            sol_reserve_account_bump_seed: reserve_bump_seed,
+            mint_authority_bump_seed: mint_bump_seed,
+            stake_authority_bump_seed: deposit_bump_seed,
+            rewards_withdraw_authority_bump_seed,
+            reward_distribution,
+            fee_recipients: FeeRecipients {
+                treasury_account: *accounts.treasury_account.key,
+                developer_account: *accounts.developer_account.key,
+            },
+            fee_payout_mode: FeePayoutMode::Credit,
+            metrics: Metrics::new(),
+            deposit_cutoff_slots: 0,
+            maintainer_fee_credit: StLamports(0),
         },
-        metrics: Metrics::new(),
         maintainers: Maintainers::new(max_maintainers),
-        validators: Validators::new(max_validators),
     };
+    let validators = Validators::new(max_validators);
 
     // Confirm that the fee recipients are actually stSOL accounts.
     lido.check_is_st_sol_account(accounts.treasury_account)?;
     lido.check_is_st_sol_account(accounts.developer_account)?;
 
+    validators.save(accounts.validator_list)?;
     lido.save(accounts.lido)
 }
 
@@ -149,9 +171,17 @@ pub fn process_deposit(
         return Err(ProgramError::InvalidArgument);
     }
 
-    let mut lido = deserialize_lido(program_id, accounts.lido)?;
+    // Deposit does not need the validators or maintainers, so we only touch the
+    // header, which keeps the cost independent of the number of validators.
+    let mut lido = deserialize_lido_header(program_id, accounts.lido)?;
     lido.check_reserve_account(program_id, accounts.lido.key, accounts.reserve_account)?;
 
+    // Deposit does not take the clock and epoch schedule sysvar accounts, so
+    // we get them through the syscalls instead.
+    let clock = Clock::get()?;
+    let epoch_schedule = EpochSchedule::get()?;
+    lido.check_deposit_allowed(&clock, &epoch_schedule)?;
+
     invoke(
         &system_instruction::transfer(accounts.user.key, accounts.reserve_account.key, amount.0),
         &[
@@ -182,8 +212,8 @@ pub fn process_deposit(
         st_sol_amount
     );
 
-    lido.metrics.deposit_amount.observe(amount)?;
-    lido.save(accounts.lido)
+    lido.metrics.observe_deposit(amount, clock.epoch)?;
+    lido.save_header(accounts.lido)
 }
 
 pub fn process_stake_deposit(
@@ -200,53 +230,73 @@ pub fn process_stake_deposit(
     lido.check_stake_authority(program_id, accounts.lido.key, accounts.stake_authority)?;
     lido.check_can_stake_amount(accounts.reserve, accounts.sysvar_rent, amount)?;
 
-    let validator = lido.validators.get(accounts.validator_vote_account.key)?;
+    // We only need one validator entry in full, so instead of deserializing the
+    // entire validator list, we read it through a view over the account data.
+    lido.check_validator_list(program_id, accounts.validator_list)?;
+    let (validator_index, mut validator) = {
+        let data = accounts.validator_list.data.borrow();
+        let view = ValidatorListView::new(&data[..])?;
 
-    if !validator.entry.active {
-        msg!(
-            "Validator {} is inactive, new deposits are not allowed",
-            validator.pubkey
-        );
-        return Err(LidoError::StakeToInactiveValidator.into());
-    }
+        let validator_index = view.find_validator(accounts.validator_vote_account.key)?;
+        let validator = view.validator(validator_index)?;
 
-    // Confirm that there is no other active validator with a lower balance that
-    // we could stake to. This alone is not sufficient to guarantee a uniform
-    // stake balance, but it limits the power that maintainers have to disturb
-    // the balance. More importantly, it ensures that when two maintainers create
-    // the same StakeDeposit transaction, only one of them succeeds.
-    let minimum_stake_validator = lido
-        .validators
-        .iter_active_entries()
-        .min_by_key(|pair| pair.entry.effective_stake_balance())
-        .ok_or(LidoError::NoActiveValidators)?;
-
-    // Note that we compare balances, not keys, because the minimum might not be unique.
-    if validator.entry.effective_stake_balance()
-        > minimum_stake_validator.entry.effective_stake_balance()
-    {
-        msg!(
-            "Refusing to stake with {}, who has {} stake, \
-            because {} has less stake: {}. Stake there instead.",
-            validator.pubkey,
-            validator.entry.effective_stake_balance(),
-            minimum_stake_validator.pubkey,
-            minimum_stake_validator.entry.effective_stake_balance(),
-        );
-        return Err(LidoError::ValidatorWithLessStakeExists.into());
-    }
+        if !validator.entry.active {
+            msg!(
+                "Validator {} is inactive, new deposits are not allowed",
+                validator.pubkey
+            );
+            return Err(LidoError::StakeToInactiveValidator.into());
+        }
 
-    // From now on we will not reference other Lido fields, so we can get the
-    // validator as mutable. This is a bit wasteful, but we can optimize when we
-    // need dozens of validators, for now we are under the compute limit.
-    let validator = lido
-        .validators
-        .get_mut(accounts.validator_vote_account.key)?;
+        // Confirm that there is no other active validator with a lower balance that
+        // we could stake to. This alone is not sufficient to guarantee a uniform
+        // stake balance, but it limits the power that maintainers have to disturb
+        // the balance. More importantly, it ensures that when two maintainers create
+        // the same StakeDeposit transaction, only one of them succeeds. We go over
+        // the list one page at a time, so we never hold the full validator list
+        // in memory.
+        let mut minimum_stake_validator: Option<PubkeyAndEntry<Validator>> = None;
+        for page in view.pages(VALIDATOR_LIST_PAGE_SIZE) {
+            for pair in page? {
+                if !pair.entry.active {
+                    continue;
+                }
+                let is_new_minimum = match &minimum_stake_validator {
+                    None => true,
+                    Some(min) => {
+                        pair.entry.effective_stake_balance() < min.entry.effective_stake_balance()
+                    }
+                };
+                if is_new_minimum {
+                    minimum_stake_validator = Some(pair);
+                }
+            }
+        }
+        let minimum_stake_validator =
+            minimum_stake_validator.ok_or(LidoError::NoActiveValidators)?;
+
+        // Note that we compare balances, not keys, because the minimum might not be unique.
+        if validator.entry.effective_stake_balance()
+            > minimum_stake_validator.entry.effective_stake_balance()
+        {
+            msg!(
+                "Refusing to stake with {}, who has {} stake, \
+                because {} has less stake: {}. Stake there instead.",
+                validator.pubkey,
+                validator.entry.effective_stake_balance(),
+                minimum_stake_validator.pubkey,
+                minimum_stake_validator.entry.effective_stake_balance(),
+            );
+            return Err(LidoError::ValidatorWithLessStakeExists.into());
+        }
+
+        (validator_index, validator)
+    };
 
     let stake_account_bump_seed = Lido::check_stake_account(
         program_id,
         accounts.lido.key,
-        validator,
+        &validator,
         validator.entry.stake_seeds.end,
         accounts.stake_account_end,
         VALIDATOR_STAKE_ACCOUNT,
@@ -355,7 +405,7 @@ pub fn process_stake_deposit(
         Lido::check_stake_account(
             program_id,
             accounts.lido.key,
-            validator,
+            &validator,
             // Does not underflow, because end > begin >= 0.
             validator.entry.stake_seeds.end - 1,
             accounts.stake_account_merge_into,
@@ -396,7 +446,11 @@ pub fn process_stake_deposit(
         )?;
     }
 
-    lido.save(accounts.lido)
+    lido.observe_maintainer_operation(accounts.maintainer.key)?;
+    lido.save(accounts.lido)?;
+
+    let mut data = accounts.validator_list.data.borrow_mut();
+    ValidatorListView::new(&mut data[..])?.set_validator(validator_index, &validator.entry)
 }
 
 /// Unstakes from a validator, the funds are moved to the stake defined by the
@@ -410,9 +464,26 @@ pub fn process_unstake(
     let mut lido = deserialize_lido(program_id, accounts.lido)?;
     lido.check_maintainer(accounts.maintainer)?;
     lido.check_stake_authority(program_id, accounts.lido.key, accounts.stake_authority)?;
-    let destination_bump_seed = check_unstake_accounts(program_id, &lido, &accounts)?;
 
-    let validator = lido.validators.get(accounts.validator_vote_account.key)?;
+    // Unstake touches only one validator, so we read just that entry through
+    // a view over the validator list, instead of deserializing all of it.
+    lido.check_validator_list(program_id, accounts.validator_list)?;
+    let (validator_index, mut validator) = {
+        let data = accounts.validator_list.data.borrow();
+        let view = ValidatorListView::new(&data[..])?;
+        let validator_index = view.find_validator(accounts.validator_vote_account.key)?;
+        (validator_index, view.validator(validator_index)?)
+    };
+    let destination_bump_seed = check_unstake_accounts(program_id, &validator, &accounts)?;
+
+    // The stake accounts of a tombstone are deactivating already.
+    if validator.entry.tombstone {
+        msg!(
+            "Validator {} is being removed, there is nothing left to unstake.",
+            validator.pubkey
+        );
+        return Err(LidoError::ValidatorIsTombstone.into());
+    }
 
     // Because `WithdrawInactiveStake` needs to reference all stake and unstake
     // accounts in a single transaction, we shouldn't have too many of them.
@@ -474,10 +545,6 @@ pub fn process_unstake(
         ]],
     )?;
 
-    let validator = lido
-        .validators
-        .get_mut(accounts.validator_vote_account.key)?;
-
     if validator.entry.active {
         // For active validators, we don't allow their stake accounts to contain
         // less than the minimum stake account balance.
@@ -511,7 +578,11 @@ pub fn process_unstake(
     validator.entry.unstake_accounts_balance = (validator.entry.unstake_accounts_balance + amount)?;
     validator.entry.unstake_seeds.end += 1;
 
-    lido.save(accounts.lido)
+    lido.observe_maintainer_operation(accounts.maintainer.key)?;
+    lido.save(accounts.lido)?;
+
+    let mut data = accounts.validator_list.data.borrow_mut();
+    ValidatorListView::new(&mut data[..])?.set_validator(validator_index, &validator.entry)
 }
 
 pub fn process_update_exchange_rate(
@@ -519,7 +590,7 @@ pub fn process_update_exchange_rate(
     raw_accounts: &[AccountInfo],
 ) -> ProgramResult {
     let accounts = UpdateExchangeRateAccountsInfo::try_from_slice(raw_accounts)?;
-    let mut lido = deserialize_lido(program_id, accounts.lido)?;
+    let mut lido = deserialize_lido_header(program_id, accounts.lido)?;
     lido.check_reserve_account(program_id, accounts.lido.key, accounts.reserve)?;
 
     let clock = Clock::from_account_info(accounts.sysvar_clock)?;
@@ -534,11 +605,131 @@ pub fn process_update_exchange_rate(
         return Err(LidoError::ExchangeRateAlreadyUpToDate.into());
     }
 
+    lido.check_validator_list(program_id, accounts.validator_list)?;
+    let (sol_balance, st_sol_supply) = {
+        let data = accounts.validator_list.data.borrow();
+        let view = ValidatorListView::new(&data[..])?;
+        (
+            view.get_sol_balance(&rent, accounts.reserve)?,
+            view.get_st_sol_supply(&lido, accounts.st_sol_mint)?,
+        )
+    };
+
     lido.exchange_rate.computed_in_epoch = clock.epoch;
-    lido.exchange_rate.sol_balance = lido.get_sol_balance(&rent, accounts.reserve)?;
-    lido.exchange_rate.st_sol_supply = lido.get_st_sol_supply(accounts.st_sol_mint)?;
+    lido.exchange_rate.sol_balance = sol_balance;
+    lido.exchange_rate.st_sol_supply = st_sol_supply;
 
-    lido.save(accounts.lido)
+    // Once per epoch, divide the maintainer fees collected since the previous
+    // update among the maintainers who did the work. We only need the header
+    // in full, the maintainers we update in place.
+    {
+        let mut lido_data = accounts.lido.data.borrow_mut();
+        let mut lido_view = LidoAccountView::new(&mut lido_data[..])?;
+        lido_view.set_header(&lido)?;
+        if let Some(maintainer) = accounts.maintainer {
+            lido_view.observe_operation_if_maintainer(maintainer.key)?;
+        }
+        lido_view.distribute_maintainer_fee(&lido)?;
+    }
+
+    // Mirror the new exchange rate to the oracle. The oracle is created
+    // separately, until somebody does that, there is nothing to update.
+    let oracle_bump_seed = ExchangeRateOracle::check_address(
+        program_id,
+        accounts.lido.key,
+        accounts.exchange_rate_oracle,
+    )?;
+    if accounts.exchange_rate_oracle.data_is_empty() {
+        msg!("The exchange rate oracle is not initialized, not updating it.");
+        return Ok(());
+    }
+    ExchangeRateOracle::from_account_info(program_id, accounts.exchange_rate_oracle)?;
+    ExchangeRateOracle::new(
+        *accounts.lido.key,
+        oracle_bump_seed,
+        &lido.exchange_rate,
+        clock.slot,
+    )
+    .save(accounts.exchange_rate_oracle)
+}
+
+pub fn process_initialize_exchange_rate_oracle(
+    program_id: &Pubkey,
+    raw_accounts: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = InitializeExchangeRateOracleInfo::try_from_slice(raw_accounts)?;
+    let lido = deserialize_lido_header(program_id, accounts.lido)?;
+    let bump_seed = ExchangeRateOracle::check_address(
+        program_id,
+        accounts.lido.key,
+        accounts.exchange_rate_oracle,
+    )?;
+
+    if !accounts.exchange_rate_oracle.data_is_empty() {
+        msg!(
+            "Exchange rate oracle {} is already initialized.",
+            accounts.exchange_rate_oracle.key
+        );
+        return Err(LidoError::AlreadyInUse.into());
+    }
+
+    // Somebody may have sent SOL to the oracle address already, in that case
+    // the funder only needs to top it up to be rent-exempt. For the same
+    // reason, we don't use `system_instruction::create_account`, which fails
+    // for funded accounts.
+    let rent = Rent::get()?;
+    let rent_exempt_balance = rent.minimum_balance(EXCHANGE_RATE_ORACLE_SIZE);
+    let missing_balance =
+        rent_exempt_balance.saturating_sub(accounts.exchange_rate_oracle.lamports());
+    if missing_balance > 0 {
+        invoke(
+            &system_instruction::transfer(
+                accounts.funder.key,
+                accounts.exchange_rate_oracle.key,
+                missing_balance,
+            ),
+            &[
+                accounts.funder.clone(),
+                accounts.exchange_rate_oracle.clone(),
+                accounts.system_program.clone(),
+            ],
+        )?;
+    }
+
+    let bump_seed = [bump_seed];
+    let oracle_seeds = &[
+        accounts.lido.key.as_ref(),
+        EXCHANGE_RATE_ORACLE,
+        &bump_seed[..],
+    ][..];
+    invoke_signed(
+        &system_instruction::allocate(
+            accounts.exchange_rate_oracle.key,
+            EXCHANGE_RATE_ORACLE_SIZE as u64,
+        ),
+        &[
+            accounts.exchange_rate_oracle.clone(),
+            accounts.system_program.clone(),
+        ],
+        &[oracle_seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(accounts.exchange_rate_oracle.key, program_id),
+        &[
+            accounts.exchange_rate_oracle.clone(),
+            accounts.system_program.clone(),
+        ],
+        &[oracle_seeds],
+    )?;
+
+    let clock = Clock::get()?;
+    ExchangeRateOracle::new(
+        *accounts.lido.key,
+        bump_seed[0],
+        &lido.exchange_rate,
+        clock.slot,
+    )
+    .save(accounts.exchange_rate_oracle)
 }
 
 #[derive(PartialEq, Clone, Copy)]
@@ -656,7 +847,8 @@ pub fn process_withdraw_inactive_stake(
     raw_accounts: &[AccountInfo],
 ) -> ProgramResult {
     let accounts = WithdrawInactiveStakeInfo::try_from_slice(raw_accounts)?;
-    let mut lido = deserialize_lido(program_id, accounts.lido)?;
+    let lido = deserialize_lido_header(program_id, accounts.lido)?;
+    let mut validators = deserialize_validators(program_id, &lido, accounts.validator_list)?;
     let stake_history = StakeHistory::from_account_info(accounts.sysvar_stake_history)?;
     let clock = Clock::from_account_info(accounts.sysvar_clock)?;
     let rent = Rent::from_account_info(accounts.sysvar_rent)?;
@@ -665,9 +857,7 @@ pub fn process_withdraw_inactive_stake(
     // and confirm that they can receive stSOL.
     lido.check_reserve_account(program_id, accounts.lido.key, accounts.reserve)?;
 
-    let validator = lido
-        .validators
-        .get_mut(accounts.validator_vote_account.key)?;
+    let validator = validators.get_mut(accounts.validator_vote_account.key)?;
 
     let mut stake_observed_total = Lamports(0);
     let mut excess_removed = Lamports(0);
@@ -713,9 +903,21 @@ pub fn process_withdraw_inactive_stake(
         };
 
         let stake_account = get_stake_account(&withdraw_opts)?;
-        let amount = (stake_account.balance.inactive
-            - Lamports(rent.minimum_balance(provided_stake_account.data_len())))
-        .expect("Should have at least the payed rent");
+        let amount = if validator.entry.tombstone
+            && validator.entry.stake_seeds.begin == seed
+            && stake_account.balance.inactive == stake_account.balance.total()
+        {
+            // The stake accounts of a tombstone were all deactivated by
+            // `ForceRemoveValidator`. Like for unstake accounts below, once
+            // the account at the beginning is fully inactive, we withdraw all
+            // of it, which leaves the account empty, so we bump the seed.
+            validator.entry.stake_seeds.begin += 1;
+            stake_account.balance.inactive
+        } else {
+            (stake_account.balance.inactive
+                - Lamports(rent.minimum_balance(provided_stake_account.data_len())))
+            .expect("Should have at least the payed rent")
+        };
 
         withdraw_inactive_sol(&withdraw_opts, amount)?;
 
@@ -741,6 +943,13 @@ pub fn process_withdraw_inactive_stake(
     let donation = (stake_observed_total - validator.entry.effective_stake_balance())
         .expect("Does not underflow because observed_total >= stake_accounts_balance.");
     msg!("{} in donations observed.", donation);
+    validator
+        .entry
+        .metrics
+        .observe_rewards(donation, clock.epoch)?;
+    if validator.entry.active {
+        validator.entry.metrics.observe_active_epoch(clock.epoch);
+    }
 
     // Try to withdraw from unstake accounts.
     let mut unstake_removed = Lamports(0);
@@ -818,7 +1027,18 @@ pub fn process_withdraw_inactive_stake(
         .add(validator.entry.unstake_accounts_balance)
         .expect("If Solido has enough SOL to make this overflow, something has gone very wrong.");
 
-    lido.save(accounts.lido)
+    validators.save(accounts.validator_list)?;
+
+    // Calling this when there is nothing to withdraw is not maintenance, so
+    // only count it for the maintainer when it withdrew something.
+    match accounts.maintainer {
+        Some(maintainer) if (excess_removed + unstake_removed)? > Lamports(0) => {
+            let mut lido_data = accounts.lido.data.borrow_mut();
+            LidoAccountView::new(&mut lido_data[..])?
+                .observe_operation_if_maintainer(maintainer.key)
+        }
+        _ => Ok(()),
+    }
 }
 
 /// Collects the validator fee from the validator vote account and distributes
@@ -832,7 +1052,8 @@ pub fn process_collect_validator_fee(
     raw_accounts: &[AccountInfo],
 ) -> ProgramResult {
     let accounts = CollectValidatorFeeInfo::try_from_slice(raw_accounts)?;
-    let mut lido = deserialize_lido(program_id, accounts.lido)?;
+    let mut lido = deserialize_lido_header(program_id, accounts.lido)?;
+    let mut validators = deserialize_validators(program_id, &lido, accounts.validator_list)?;
     let rent = Rent::from_account_info(accounts.sysvar_rent)?;
 
     // Confirm that the passed accounts are the ones configured in the state,
@@ -842,11 +1063,22 @@ pub fn process_collect_validator_fee(
     lido.check_developer_fee_st_sol_account(accounts.developer_st_sol_account)?;
     lido.check_reserve_account(program_id, accounts.lido.key, accounts.reserve)?;
 
+    // Paying out to every fee account costs compute units, so we bound how many
+    // we accept, rather than run out of budget halfway through.
+    if accounts.validator_fee_st_sol_accounts.len() > MAXIMUM_DIRECT_FEE_ACCOUNTS {
+        msg!(
+            "Received {} validator fee accounts, but at most {} are allowed.",
+            accounts.validator_fee_st_sol_accounts.len(),
+            MAXIMUM_DIRECT_FEE_ACCOUNTS,
+        );
+        return Err(LidoError::TooManyFeeAccounts.into());
+    }
+
     let clock = Clock::from_account_info(accounts.sysvar_clock)?;
     lido.check_exchange_rate_last_epoch(&clock, "CollectValidatorFee")?;
 
     // Confirm that the vote account passed in is actually part of the validator set.
-    lido.validators.get(accounts.validator_vote_account.key)?;
+    validators.get(accounts.validator_vote_account.key)?;
 
     let rewards_withdraw_authority = lido.check_rewards_withdraw_authority(
         program_id,
@@ -863,10 +1095,19 @@ pub fn process_collect_validator_fee(
         .checked_sub(vote_account_rent)
         .expect("Vote account should be rent exempt");
 
+    let validator = validators.get_mut(accounts.validator_vote_account.key)?;
+    validator
+        .entry
+        .metrics
+        .observe_rewards(Lamports(rewards), clock.epoch)?;
+    if validator.entry.active {
+        validator.entry.metrics.observe_active_epoch(clock.epoch);
+    }
+
     let fees = lido
         .reward_distribution
-        .split_reward(Lamports(rewards), lido.validators.len() as u64)?;
-    distribute_fees(&mut lido, &accounts, fees)?;
+        .split_reward(Lamports(rewards), validators.len() as u64)?;
+    distribute_fees(&mut lido, &mut validators, &accounts, fees)?;
 
     invoke_signed(
         &vote_instruction::withdraw(
@@ -887,7 +1128,19 @@ pub fn process_collect_validator_fee(
             &[lido.rewards_withdraw_authority_bump_seed],
         ]],
     )?;
-    lido.save(accounts.lido)
+    validators.save(accounts.validator_list)?;
+
+    let mut lido_data = accounts.lido.data.borrow_mut();
+    let mut lido_view = LidoAccountView::new(&mut lido_data[..])?;
+    lido_view.set_header(&lido)?;
+    // Like for `WithdrawInactiveStake`, only count it for the maintainer when
+    // there were rewards to collect.
+    match accounts.maintainer {
+        Some(maintainer) if rewards > 0 => {
+            lido_view.observe_operation_if_maintainer(maintainer.key)
+        }
+        _ => Ok(()),
+    }
 }
 
 /// Splits a stake account from a validator's stake account.
@@ -899,40 +1152,62 @@ pub fn process_withdraw(
     raw_accounts: &[AccountInfo],
 ) -> ProgramResult {
     let accounts = WithdrawAccountsInfo::try_from_slice(raw_accounts)?;
-    let mut lido = deserialize_lido(program_id, accounts.lido)?;
+    let mut lido = deserialize_lido_header(program_id, accounts.lido)?;
     let clock = Clock::from_account_info(accounts.sysvar_clock)?;
     lido.check_exchange_rate_last_epoch(&clock, "Withdraw")?;
 
-    // We should withdraw from the validator that has the most effective stake.
-    // With effective here we mean "total in stake accounts" - "total in unstake
-    // accounts", regardless of whether the stake in those accounts is active or not.
-    let validator = lido.validators.get(accounts.validator_vote_account.key)?;
-
-    // Confirm that there is no other validator with a higher balance that
-    // we could withdraw from. This alone is not sufficient to guarantee a uniform
-    // stake balance, but prevents things from becoming more unbalanced than
-    // necessary.
-    let maximum_stake_validator = lido
-        .validators
-        .entries
-        .iter()
-        .max_by_key(|pair| pair.entry.effective_stake_balance())
-        .ok_or(LidoError::NoActiveValidators)?;
-
-    // Note that we compare balances, not keys, because the maximum might not be unique.
-    if validator.entry.effective_stake_balance()
-        < maximum_stake_validator.entry.effective_stake_balance()
-    {
-        msg!(
-            "Refusing to withdraw from {}, who has {} stake, \
-            because {} has more stake: {}. Withdraw from there instead.",
-            validator.pubkey,
-            validator.entry.effective_stake_balance(),
-            maximum_stake_validator.pubkey,
-            maximum_stake_validator.entry.effective_stake_balance(),
-        );
-        return Err(LidoError::ValidatorWithMoreStakeExists.into());
-    }
+    // We only need one validator entry in full, so instead of deserializing the
+    // entire validator list, we read it through a view over the account data.
+    lido.check_validator_list(program_id, accounts.validator_list)?;
+    let (validator_index, mut validator) = {
+        let data = accounts.validator_list.data.borrow();
+        let view = ValidatorListView::new(&data[..])?;
+
+        // We should withdraw from the validator that has the most effective stake.
+        // With effective here we mean "total in stake accounts" - "total in unstake
+        // accounts", regardless of whether the stake in those accounts is active or not.
+        let validator_index = view.find_validator(accounts.validator_vote_account.key)?;
+        let validator = view.validator(validator_index)?;
+
+        // Confirm that there is no other validator with a higher balance that
+        // we could withdraw from. This alone is not sufficient to guarantee a uniform
+        // stake balance, but prevents things from becoming more unbalanced than
+        // necessary. We go over the list one page at a time, so we never hold
+        // the full validator list in memory.
+        let mut maximum_stake_validator: Option<PubkeyAndEntry<Validator>> = None;
+        for page in view.pages(VALIDATOR_LIST_PAGE_SIZE) {
+            for pair in page? {
+                let is_new_maximum = match &maximum_stake_validator {
+                    None => true,
+                    Some(max) => {
+                        pair.entry.effective_stake_balance() > max.entry.effective_stake_balance()
+                    }
+                };
+                if is_new_maximum {
+                    maximum_stake_validator = Some(pair);
+                }
+            }
+        }
+        let maximum_stake_validator =
+            maximum_stake_validator.ok_or(LidoError::NoActiveValidators)?;
+
+        // Note that we compare balances, not keys, because the maximum might not be unique.
+        if validator.entry.effective_stake_balance()
+            < maximum_stake_validator.entry.effective_stake_balance()
+        {
+            msg!(
+                "Refusing to withdraw from {}, who has {} stake, \
+                because {} has more stake: {}. Withdraw from there instead.",
+                validator.pubkey,
+                validator.entry.effective_stake_balance(),
+                maximum_stake_validator.pubkey,
+                maximum_stake_validator.entry.effective_stake_balance(),
+            );
+            return Err(LidoError::ValidatorWithMoreStakeExists.into());
+        }
+
+        (validator_index, validator)
+    };
 
     let (stake_account, _) = validator.find_stake_account_address(
         program_id,
@@ -948,9 +1223,6 @@ pub fn process_withdraw(
 
     // Reduce validator's balance
     let sol_to_withdraw = lido.exchange_rate.exchange_st_sol(amount)?;
-    let provided_validator = lido
-        .validators
-        .get_mut(accounts.validator_vote_account.key)?;
 
     let source_balance = Lamports(accounts.source_stake_account.lamports());
 
@@ -987,14 +1259,15 @@ pub fn process_withdraw(
         return Err(LidoError::InvalidAmount.into());
     }
 
-    provided_validator.entry.stake_accounts_balance =
-        (provided_validator.entry.stake_accounts_balance - sol_to_withdraw)?;
+    validator.entry.stake_accounts_balance =
+        (validator.entry.stake_accounts_balance - sol_to_withdraw)?;
 
     // Burn stSol tokens
     burn_st_sol(&lido, &accounts, amount)?;
 
     // Update withdrawal metrics.
-    lido.metrics.observe_withdrawal(amount, sol_to_withdraw)?;
+    lido.metrics
+        .observe_withdrawal(amount, sol_to_withdraw, clock.epoch)?;
 
     split_stake_account(
         accounts.lido.key,
@@ -1018,7 +1291,9 @@ pub fn process_withdraw(
     // Solido transactions. With the logs, we can still identify what happened.
     msg!("Solido: Withdrew {} for {}.", amount, sol_to_withdraw);
 
-    lido.save(accounts.lido)
+    let mut data = accounts.validator_list.data.borrow_mut();
+    ValidatorListView::new(&mut data[..])?.set_validator(validator_index, &validator.entry)?;
+    lido.save_header(accounts.lido)
 }
 
 /// Processes [Instruction](enum.Instruction.html).
@@ -1058,5 +1333,40 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> P
         LidoInstruction::AddMaintainer => process_add_maintainer(program_id, accounts),
         LidoInstruction::RemoveMaintainer => process_remove_maintainer(program_id, accounts),
         LidoInstruction::MergeStake => process_merge_stake(program_id, accounts),
+        LidoInstruction::ChangeFeePayoutMode {
+            new_fee_payout_mode,
+        } => process_change_fee_payout_mode(program_id, new_fee_payout_mode, accounts),
+        LidoInstruction::UpgradeFeePayoutMode => {
+            process_upgrade_fee_payout_mode(program_id, accounts)
+        }
+        LidoInstruction::MigrateValidatorList => {
+            process_migrate_validator_list(program_id, accounts)
+        }
+        LidoInstruction::SortAccountMaps => process_sort_account_maps(program_id, accounts),
+        LidoInstruction::UpgradeValidatorList => {
+            process_upgrade_validator_list(program_id, accounts)
+        }
+        LidoInstruction::UpgradeMetrics => process_upgrade_metrics(program_id, accounts),
+        LidoInstruction::InitializeExchangeRateOracle => {
+            process_initialize_exchange_rate_oracle(program_id, accounts)
+        }
+        LidoInstruction::SetTokenMetadata { token_metadata } => {
+            process_set_token_metadata(program_id, token_metadata, accounts)
+        }
+        LidoInstruction::ChangeDepositCutoff {
+            deposit_cutoff_slots,
+        } => process_change_deposit_cutoff(program_id, deposit_cutoff_slots, accounts),
+        LidoInstruction::ClaimMaintainerFee => process_claim_maintainer_fee(program_id, accounts),
+        LidoInstruction::ForceRemoveValidator => {
+            process_force_remove_validator(program_id, accounts)
+        }
+        LidoInstruction::UpgradeValidatorEntries => {
+            process_upgrade_validator_entries(program_id, accounts)
+        }
+        LidoInstruction::ClaimValidatorFeeTo => {
+            process_claim_validator_fee_to(program_id, accounts)
+        }
+        LidoInstruction::MaintainerHeartbeat => process_maintainer_heartbeat(program_id, accounts),
+        LidoInstruction::UpgradeMaintainers => process_upgrade_maintainers(program_id, accounts),
     }
 }
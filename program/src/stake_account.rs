@@ -7,6 +7,7 @@ use std::iter::Sum;
 use std::ops::Add;
 
 use crate::{error::LidoError, token, token::Lamports};
+use serde::Serialize;
 use solana_program::stake::{self as stake_program, instruction::StakeInstruction, state::Stake};
 use solana_program::{
     clock::{Clock, Epoch},
@@ -23,7 +24,7 @@ use solana_program::{instruction::Instruction, stake_history::StakeHistory};
 /// The sum of the four fields is equal to the SOL balance of the stake account.
 /// Note that a stake account can have a portion in `inactive` and a portion in
 /// `active`, with zero being activating or deactivating.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct StakeBalance {
     pub inactive: Lamports,
     pub activating: Lamports,
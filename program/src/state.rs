@@ -3,39 +3,135 @@
 
 //! State transition types
 
-use std::ops::Range;
+use std::ops::{Deref, DerefMut, Range};
 
 use serde::Serialize;
 
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
-use solana_program::borsh::get_instance_packed_len;
+use solana_program::borsh::{get_instance_packed_len, try_from_slice_unchecked};
 use solana_program::clock::Clock;
+use solana_program::epoch_schedule::EpochSchedule;
 use solana_program::{
-    account_info::AccountInfo, clock::Epoch, entrypoint::ProgramResult, msg,
-    program_error::ProgramError, program_pack::Pack, pubkey::Pubkey, rent::Rent, sysvar::Sysvar,
+    account_info::AccountInfo,
+    clock::{Epoch, Slot},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
 };
 use spl_token::state::Mint;
 
 use crate::error::LidoError;
 use crate::logic::get_reserve_available_balance;
-use crate::metrics::Metrics;
+use crate::metrics::{LegacyMetrics, Metrics, ValidatorMetrics};
 use crate::processor::StakeType;
 use crate::token::{self, Lamports, Rational, StLamports};
 use crate::util::serialize_b58;
+use crate::zero_copy::LidoAccountView;
 use crate::{
     account_map::{AccountMap, AccountSet, EntryConstantSize, PubkeyAndEntry},
     MINIMUM_STAKE_ACCOUNT_BALANCE, MINT_AUTHORITY, RESERVE_ACCOUNT, STAKE_AUTHORITY,
 };
 use crate::{REWARDS_WITHDRAW_AUTHORITY, VALIDATOR_STAKE_ACCOUNT, VALIDATOR_UNSTAKE_ACCOUNT};
 
-pub const LIDO_VERSION: u8 = 0;
-
-/// Size of a serialized `Lido` struct excluding validators and maintainers.
+/// Version of the `Lido` account layout.
+///
+/// Version 1 added [`LidoHeader::fee_payout_mode`]; version 0 always credited
+/// validation fees. Version 0 accounts can be upgraded with
+/// `UpgradeFeePayoutMode`, which sets the mode to [`FeePayoutMode::Credit`].
+///
+/// Versions 0 and 1 stored the validators inside the `Lido` account. Since
+/// version 2, they live in a separate validator list account, see
+/// [`LidoHeader::validator_list`]. Version 1 accounts can be upgraded with
+/// `MigrateValidatorList`.
+///
+/// Since version 3, the validators and maintainers are sorted by public key.
+/// Version 2 accounts can be upgraded to version 3 with `SortAccountMaps`.
+///
+/// Since version 4, every `Validator` holds [`ValidatorMetrics`], which makes
+/// the entries larger. Version 2 and 3 accounts can be upgraded with
+/// `UpgradeValidatorList`, which moves the validators to a new, larger
+/// validator list account.
+///
+/// Since version 5, [`Metrics`] tracks a withdrawal histogram and per-epoch
+/// flows, which makes the header larger. Version 4 accounts can be upgraded
+/// with `UpgradeMetrics`.
+///
+/// Version 6 added [`LidoHeader::deposit_cutoff_slots`] at the end of the
+/// header. Version 4 and 5 accounts can be upgraded with `UpgradeMetrics`,
+/// which disables the cutoff.
+///
+/// Version 7 added [`RewardDistribution::maintainer_fee`] and
+/// [`LidoHeader::maintainer_fee_credit`], and every [`Maintainer`] holds its
+/// share of the maintainer fee. Version 4 through 6 accounts can be upgraded
+/// with `UpgradeMetrics`.
+///
+/// Since version 8, every [`Validator`] has a tombstone flag, and since
+/// version 9 the withdraw authority to hand its vote account back to, which
+/// makes the entries larger. Version 7 and 8 accounts can be upgraded with
+/// `UpgradeValidatorEntries`, which moves the validators to a new, larger
+/// validator list account.
+///
+/// Since version 10, every [`Maintainer`] holds its most recent heartbeat,
+/// which makes the entries larger. Version 9 accounts can be upgraded with
+/// `UpgradeMaintainers`.
+///
+/// Version 11 has the same layout as version 10, but in `UpdateExchangeRate`,
+/// `WithdrawInactiveStake`, `CollectValidatorFee`, and `MergeStake` the
+/// maintainer moved to an optional last account, so the version tells clients
+/// which account order to use. Version 10 accounts can be upgraded with
+/// `UpgradeMaintainers` too.
+pub const LIDO_VERSION: u8 = 11;
+
+/// Size of a serialized `Lido` struct excluding maintainers.
 ///
 /// To update this, run the tests and replace the value here with the test output.
-pub const LIDO_CONSTANT_SIZE: usize = 357;
-pub const VALIDATOR_CONSTANT_SIZE: usize = 89;
+pub const LIDO_CONSTANT_SIZE: usize = 562;
+
+/// Size of the `Lido` header in versions 2 through 4, see [`Lido::deserialize_legacy`].
+pub const LEGACY_LIDO_CONSTANT_SIZE: usize = 390;
+
+/// Size of the `Lido` header in version 5, see [`Lido::deserialize_legacy`].
+pub const LIDO_V5_CONSTANT_SIZE: usize = 542;
+
+/// Size of the `Lido` header in version 6, see [`Lido::deserialize_legacy`].
+pub const LIDO_V6_CONSTANT_SIZE: usize = 550;
+
+/// Size of a serialized [`LidoV1`] struct excluding validators and maintainers.
+pub const LIDO_V1_CONSTANT_SIZE: usize = 358;
+
+/// Offset of [`LidoHeader::fee_payout_mode`] in version 1 accounts, see
+/// [`LidoV1::deserialize_v0`].
+const FEE_PAYOUT_MODE_OFFSET: usize = 173;
+
+/// Offset in the header of versions 0 through 6 where `RewardDistribution`
+/// ends. Version 7 added [`RewardDistribution::maintainer_fee`] there.
+const LEGACY_REWARD_DISTRIBUTION_END: usize = 141;
+
+pub const VALIDATOR_CONSTANT_SIZE: usize = 162;
 
+/// Size of a serialized [`Maintainer`].
+pub const MAINTAINER_CONSTANT_SIZE: usize = 24;
+
+/// Size of a serialized [`MaintainerV9`].
+pub const MAINTAINER_V9_CONSTANT_SIZE: usize = 16;
+
+/// Size of a serialized [`LegacyValidator`].
+pub const LEGACY_VALIDATOR_CONSTANT_SIZE: usize = 89;
+
+/// Size of a serialized [`ValidatorV4`].
+pub const VALIDATOR_V4_CONSTANT_SIZE: usize = 129;
+
+/// Size of a serialized [`ValidatorV8`].
+pub const VALIDATOR_V8_CONSTANT_SIZE: usize = 130;
+
+/// Map of enrolled validators, maps their vote account to `Validator` details.
+///
+/// This is the content of the validator list account that the `Lido` account
+/// references in [`LidoHeader::validator_list`].
 pub type Validators = AccountMap<Validator>;
 
 impl Validators {
@@ -46,13 +142,94 @@ impl Validators {
     pub fn iter_active_entries(&self) -> impl Iterator<Item = &PubkeyAndEntry<Validator>> {
         self.entries.iter().filter(|&v| v.entry.active)
     }
+
+    /// Calculates the size of a validator list account that can hold
+    /// `max_validators` validators.
+    pub fn calculate_size(max_validators: u32) -> usize {
+        Validators::required_bytes(max_validators as usize)
+    }
+
+    pub fn save(&self, account: &AccountInfo) -> ProgramResult {
+        BorshSerialize::serialize(self, &mut *account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Compute the total amount of SOL managed by this instance.
+    ///
+    /// This includes staked as well as non-staked SOL. It excludes SOL in the
+    /// reserve that effectively locked because it is needed to keep the reserve
+    /// rent-exempt.
+    ///
+    /// The computation is based on the amount of SOL per validator that we track
+    /// ourselves, so if there are any unobserved rewards in the stake accounts,
+    /// these will not be included.
+    pub fn get_sol_balance(
+        &self,
+        rent: &Rent,
+        reserve: &AccountInfo,
+    ) -> Result<Lamports, LidoError> {
+        let effective_reserve_balance = get_reserve_available_balance(rent, reserve)?;
+
+        // The remaining SOL managed is all in stake accounts.
+        let validator_balance: token::Result<Lamports> =
+            self.iter_entries().map(|v| v.stake_accounts_balance).sum();
+
+        let result = validator_balance.and_then(|s| s + effective_reserve_balance)?;
+
+        Ok(result)
+    }
+
+    /// Return the total amount of stSOL in existence.
+    ///
+    /// The total is the amount minted so far, plus any unminted rewards that validators
+    /// are entitled to, but haven’t claimed yet.
+    pub fn get_st_sol_supply(
+        &self,
+        lido: &LidoHeader,
+        st_sol_mint: &AccountInfo,
+    ) -> Result<StLamports, ProgramError> {
+        lido.check_mint_is_st_sol_mint(st_sol_mint)?;
+
+        let st_sol_mint = Mint::unpack_from_slice(&st_sol_mint.data.borrow())?;
+        let minted_supply = StLamports(st_sol_mint.supply);
+
+        let credit: token::Result<StLamports> = self.iter_entries().map(|v| v.fee_credit).sum();
+
+        let result = credit
+            .and_then(|s| s + minted_supply)
+            .and_then(|s| s + lido.maintainer_fee_credit)?;
+
+        Ok(result)
+    }
+}
+
+/// Map of maintainers, maps their address to `Maintainer` details.
+pub type Maintainers = AccountMap<Maintainer>;
+
+impl EntryConstantSize for Maintainer {
+    const SIZE: usize = MAINTAINER_CONSTANT_SIZE;
+}
+
+impl EntryConstantSize for MaintainerV9 {
+    const SIZE: usize = MAINTAINER_V9_CONSTANT_SIZE;
 }
-pub type Maintainers = AccountSet;
 
 impl EntryConstantSize for Validator {
     const SIZE: usize = VALIDATOR_CONSTANT_SIZE;
 }
 
+impl EntryConstantSize for LegacyValidator {
+    const SIZE: usize = LEGACY_VALIDATOR_CONSTANT_SIZE;
+}
+
+impl EntryConstantSize for ValidatorV4 {
+    const SIZE: usize = VALIDATOR_V4_CONSTANT_SIZE;
+}
+
+impl EntryConstantSize for ValidatorV8 {
+    const SIZE: usize = VALIDATOR_V8_CONSTANT_SIZE;
+}
+
 impl EntryConstantSize for () {
     const SIZE: usize = 0;
 }
@@ -181,11 +358,16 @@ impl ExchangeRate {
     }
 }
 
+/// The constant-size part of the `Lido` account: everything but the maintainers.
+///
+/// This is serialized at the start of the account, and it is exactly
+/// `LIDO_CONSTANT_SIZE` bytes, so it can be read and written without touching
+/// the maintainers that follow it. See [`crate::zero_copy`].
 #[repr(C)]
 #[derive(
     Clone, Debug, Default, BorshDeserialize, BorshSerialize, BorshSchema, Eq, PartialEq, Serialize,
 )]
-pub struct Lido {
+pub struct LidoHeader {
     /// Version number for the Lido
     pub lido_version: u8,
 
@@ -197,6 +379,13 @@ pub struct Lido {
     #[serde(serialize_with = "serialize_b58")]
     pub st_sol_mint: Pubkey,
 
+    /// The account that holds the `Validators`, the map of enrolled validators.
+    ///
+    /// The validators live in a separate account, so the number of validators
+    /// is not limited by the size of the `Lido` account.
+    #[serde(serialize_with = "serialize_b58")]
+    pub validator_list: Pubkey,
+
     /// Exchange rate to use when depositing.
     pub exchange_rate: ExchangeRate,
 
@@ -212,6 +401,12 @@ pub struct Lido {
     /// Accounts of the fee recipients.
     pub fee_recipients: FeeRecipients,
 
+    /// How validation fees are paid out to validators.
+    ///
+    /// This field does not exist in version 0, instances upgraded from it
+    /// start out in [`FeePayoutMode::Credit`].
+    pub fee_payout_mode: FeePayoutMode,
+
     /// Metrics for informational purposes.
     ///
     /// Metrics are only written to, no program logic should depend on these values.
@@ -219,10 +414,38 @@ pub struct Lido {
     /// these metrics.
     pub metrics: Metrics,
 
-    /// Map of enrolled validators, maps their vote account to `Validator` details.
-    pub validators: Validators,
+    /// Number of slots at the end of every epoch in which `Deposit` is refused.
+    ///
+    /// Near the end of an epoch, the rewards of that epoch are nearly certain,
+    /// but they only affect the exchange rate after the next
+    /// `UpdateExchangeRate`. Without a cutoff, a deposit just before the epoch
+    /// boundary, followed by a withdrawal after the update, captures rewards
+    /// that the deposit did not earn. When the cutoff is nonzero, deposits are
+    /// also refused until the exchange rate is updated in the new epoch.
+    /// Zero disables the cutoff.
+    pub deposit_cutoff_slots: u64,
+
+    /// Maintainer fees in stSOL that have not been minted yet.
+    ///
+    /// This is the `fee_credit` of all maintainers, plus the fees that have
+    /// not yet been divided among the maintainers, see
+    /// [`Lido::distribute_maintainer_fee`].
+    pub maintainer_fee_credit: StLamports,
+}
+
+#[repr(C)]
+#[derive(
+    Clone, Debug, Default, BorshDeserialize, BorshSerialize, BorshSchema, Eq, PartialEq, Serialize,
+)]
+pub struct Lido {
+    /// Everything but the maintainers.
+    ///
+    /// `Lido` dereferences to its header, so the header fields and methods can
+    /// be accessed directly on a `Lido`.
+    #[serde(flatten)]
+    pub header: LidoHeader,
 
-    /// The set of maintainers.
+    /// The maintainers, and the maintainer fee they are entitled to.
     ///
     /// Maintainers are granted low security risk privileges. Maintainers are
     /// expected to run the maintenance daemon, that invokes the maintenance
@@ -231,18 +454,62 @@ pub struct Lido {
     pub maintainers: Maintainers,
 }
 
-impl Lido {
-    /// Calculates the total size of Lido given two variables: `max_validators`
-    /// and `max_maintainers`, the maximum number of maintainers and validators,
-    /// respectively. It creates default structures for both and sum its sizes
-    /// with Lido's constant size.
-    pub fn calculate_size(max_validators: u32, max_maintainers: u32) -> usize {
-        let lido_instance = Lido {
-            validators: Validators::new_fill_default(max_validators),
-            maintainers: Maintainers::new_fill_default(max_maintainers),
-            ..Default::default()
-        };
-        get_instance_packed_len(&lido_instance).unwrap()
+impl Deref for Lido {
+    type Target = LidoHeader;
+
+    fn deref(&self) -> &LidoHeader {
+        &self.header
+    }
+}
+
+impl DerefMut for Lido {
+    fn deref_mut(&mut self) -> &mut LidoHeader {
+        &mut self.header
+    }
+}
+
+impl LidoHeader {
+    /// Write only the header into the `Lido` account, leave the maintainers
+    /// untouched.
+    ///
+    /// Only use this when the header was obtained with
+    /// [`crate::logic::deserialize_lido_header`]. A full `Lido` should be saved
+    /// with [`Lido::save`].
+    pub fn save_header(&self, account: &AccountInfo) -> ProgramResult {
+        let mut data = account.data.borrow_mut();
+        LidoAccountView::new(&mut data[..])?.set_header(self)
+    }
+
+    /// Divide the maintainer fees that are not yet assigned to a maintainer,
+    /// among the given maintainers, proportional to the number of operations
+    /// they performed since the previous distribution.
+    ///
+    /// If no maintainer performed any operation, the fees stay unassigned
+    /// until the next distribution. Rounding remainders also stay unassigned.
+    pub fn distribute_maintainer_fee_among(
+        &self,
+        maintainers: &mut [&mut Maintainer],
+    ) -> ProgramResult {
+        let assigned: token::Result<StLamports> = maintainers.iter().map(|m| m.fee_credit).sum();
+        let unassigned = (self.maintainer_fee_credit - assigned?)?;
+        let total_operations = maintainers
+            .iter()
+            .try_fold(0_u64, |acc, m| acc.checked_add(m.operations))
+            .ok_or(LidoError::CalculationFailure)?;
+        if total_operations == 0 {
+            return Ok(());
+        }
+
+        for maintainer in maintainers.iter_mut() {
+            let share = (unassigned
+                * Rational {
+                    numerator: maintainer.operations,
+                    denominator: total_operations,
+                })?;
+            maintainer.fee_credit = (maintainer.fee_credit + share)?;
+            maintainer.operations = 0;
+        }
+        Ok(())
     }
 
     /// Confirm that the given account is Solido's stSOL mint.
@@ -301,18 +568,28 @@ impl Lido {
         Ok(())
     }
 
-    /// Checks if the passed maintainer belong to the list of maintainers
-    pub fn check_maintainer(&self, maintainer: &AccountInfo) -> ProgramResult {
-        if !&self.maintainers.entries.contains(&PubkeyAndEntry {
-            pubkey: *maintainer.key,
-            entry: (),
-        }) {
+    /// Confirm that the validator list account is the one referenced by this
+    /// instance, and that it is owned by the Solido program.
+    pub fn check_validator_list(
+        &self,
+        program_id: &Pubkey,
+        validator_list: &AccountInfo,
+    ) -> ProgramResult {
+        if &self.validator_list != validator_list.key {
             msg!(
-                "Invalid maintainer, account {} is not present in the maintainers list.",
-                maintainer.key
+                "Expected validator list {}, but got {}.",
+                self.validator_list,
+                validator_list.key
             );
-
-            return Err(LidoError::InvalidMaintainer.into());
+            return Err(LidoError::InvalidValidatorList.into());
+        }
+        if validator_list.owner != program_id {
+            msg!(
+                "Validator list is owned by {}, but should be owned by the Lido program ({}).",
+                validator_list.owner,
+                program_id
+            );
+            return Err(LidoError::InvalidOwner.into());
         }
         Ok(())
     }
@@ -502,6 +779,113 @@ impl Lido {
         Ok(())
     }
 
+    pub fn check_exchange_rate_last_epoch(
+        &self,
+        clock: &Clock,
+        method: &str,
+    ) -> Result<(), LidoError> {
+        if self.exchange_rate.computed_in_epoch < clock.epoch {
+            msg!(
+                "The exchange rate is outdated, it was last computed in epoch {}, \
+                but now it is epoch {}.",
+                self.exchange_rate.computed_in_epoch,
+                clock.epoch,
+            );
+            msg!("Please call UpdateExchangeRate before calling {}.", method);
+            return Err(LidoError::ExchangeRateNotUpdatedInThisEpoch);
+        }
+        Ok(())
+    }
+
+    /// Confirm that we accept deposits in the current slot, see
+    /// [`LidoHeader::deposit_cutoff_slots`].
+    pub fn check_deposit_allowed(
+        &self,
+        clock: &Clock,
+        epoch_schedule: &EpochSchedule,
+    ) -> Result<(), LidoError> {
+        if self.deposit_cutoff_slots == 0 {
+            return Ok(());
+        }
+
+        self.check_exchange_rate_last_epoch(clock, "Deposit")?;
+
+        let next_epoch_first_slot = epoch_schedule.get_first_slot_in_epoch(clock.epoch + 1);
+        let slots_left = next_epoch_first_slot.saturating_sub(clock.slot);
+        if slots_left <= self.deposit_cutoff_slots {
+            msg!(
+                "Deposits are not accepted in the last {} slots of an epoch, \
+                but epoch {} ends in {} slots.",
+                self.deposit_cutoff_slots,
+                clock.epoch,
+                slots_left,
+            );
+            return Err(LidoError::DepositCutoff);
+        }
+        Ok(())
+    }
+}
+
+impl Lido {
+    /// Calculates the total size of Lido given `max_maintainers`, the maximum
+    /// number of maintainers. It creates a default structure for the
+    /// maintainers and sums its size with Lido's constant size.
+    ///
+    /// The validators live in a separate account, see [`Validators::calculate_size`].
+    pub fn calculate_size(max_maintainers: u32) -> usize {
+        let lido_instance = Lido {
+            maintainers: Maintainers::new_fill_default(max_maintainers),
+            ..Default::default()
+        };
+        get_instance_packed_len(&lido_instance).unwrap()
+    }
+
+    /// Checks if the passed maintainer belong to the list of maintainers
+    pub fn check_maintainer(&self, maintainer: &AccountInfo) -> ProgramResult {
+        if self.maintainers.position(maintainer.key).is_none() {
+            msg!(
+                "Invalid maintainer, account {} is not present in the maintainers list.",
+                maintainer.key
+            );
+
+            return Err(LidoError::InvalidMaintainer.into());
+        }
+        Ok(())
+    }
+
+    /// Record that the maintainer performed a maintenance operation, which
+    /// entitles it to a larger share of the maintainer fee.
+    pub fn observe_maintainer_operation(&mut self, maintainer: &Pubkey) -> ProgramResult {
+        let maintainer = &mut self.maintainers.get_mut(maintainer)?.entry;
+        maintainer.operations = maintainer
+            .operations
+            .checked_add(1)
+            .ok_or(LidoError::CalculationFailure)?;
+        Ok(())
+    }
+
+    /// Record that the maintainer is alive at the given slot.
+    pub fn observe_maintainer_heartbeat(
+        &mut self,
+        maintainer: &Pubkey,
+        slot: Slot,
+    ) -> ProgramResult {
+        let maintainer = &mut self.maintainers.get_mut(maintainer)?.entry;
+        maintainer.last_heartbeat_slot = slot;
+        Ok(())
+    }
+
+    /// Divide the maintainer fees that are not yet assigned to a maintainer,
+    /// among the maintainers, proportional to the number of operations they
+    /// performed since the previous distribution.
+    ///
+    /// See also [`LidoHeader::distribute_maintainer_fee_among`].
+    pub fn distribute_maintainer_fee(&mut self) -> ProgramResult {
+        let mut maintainers: Vec<&mut Maintainer> = self.maintainers.iter_entries_mut().collect();
+        self.header
+            .distribute_maintainer_fee_among(&mut maintainers)
+    }
+
     /// Confirm that `stake_account` is the account at the given seed for the validator.
     ///
     /// Returns the bump seed for the derived address.
@@ -546,68 +930,254 @@ impl Lido {
         Ok(())
     }
 
-    /// Compute the total amount of SOL managed by this instance.
+    /// Return the size of the `Lido` header in the layout of the given
+    /// version, for versions 2 through 6.
+    fn legacy_header_size(lido_version: u8) -> Result<usize, ProgramError> {
+        match lido_version {
+            2..=4 => Ok(LEGACY_LIDO_CONSTANT_SIZE),
+            5 => Ok(LIDO_V5_CONSTANT_SIZE),
+            6 => Ok(LIDO_V6_CONSTANT_SIZE),
+            _ => {
+                msg!(
+                    "Lido version {} does not have a legacy header layout.",
+                    lido_version
+                );
+                Err(LidoError::InvalidLidoVersion.into())
+            }
+        }
+    }
+
+    /// Deserialize a `Lido` account in the layout of versions 2 through 6.
     ///
-    /// This includes staked as well as non-staked SOL. It excludes SOL in the
-    /// reserve that effectively locked because it is needed to keep the reserve
-    /// rent-exempt.
+    /// In versions 2 through 4, the header was [`LEGACY_LIDO_CONSTANT_SIZE`]
+    /// bytes, in version 5 [`LIDO_V5_CONSTANT_SIZE`] bytes, and in version 6
+    /// [`LIDO_V6_CONSTANT_SIZE`] bytes. Version 7 added
+    /// [`RewardDistribution::maintainer_fee`] at the end of the reward
+    /// distribution. All other new fields are at the end of [`Metrics`] or at
+    /// the end of the header, so apart from the maintainer fee, the legacy
+    /// header is a prefix of the current one. The missing fields are zero
+    /// after deserializing. The maintainers were a plain [`AccountSet`], they
+    /// get an empty [`Maintainer`] entry.
+    pub fn deserialize_legacy(data: &[u8]) -> Result<Lido, ProgramError> {
+        let header_size = match data.first() {
+            Some(version) => Lido::legacy_header_size(*version)?,
+            None => return Err(ProgramError::InvalidAccountData),
+        };
+        if data.len() < header_size {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut header_data = data[..LEGACY_REWARD_DISTRIBUTION_END].to_vec();
+        header_data.extend_from_slice(&[0; 4]);
+        header_data.extend_from_slice(&data[LEGACY_REWARD_DISTRIBUTION_END..header_size]);
+        header_data.resize(LIDO_CONSTANT_SIZE, 0);
+        let header = LidoHeader::try_from_slice(&header_data)?;
+        let legacy_maintainers = try_from_slice_unchecked::<AccountSet>(&data[header_size..])?;
+        Ok(Lido {
+            header,
+            maintainers: upgrade_maintainers(legacy_maintainers),
+        })
+    }
+
+    /// Write the `Lido` account in the layout of its version, one of 2 through 6.
     ///
-    /// The computation is based on the amount of SOL per validator that we track
-    /// ourselves, so if there are any unobserved rewards in the stake accounts,
-    /// these will not be included.
-    pub fn get_sol_balance(
-        &self,
-        rent: &Rent,
-        reserve: &AccountInfo,
-    ) -> Result<Lamports, LidoError> {
-        let effective_reserve_balance = get_reserve_available_balance(rent, reserve)?;
+    /// This drops the fields that the version does not have, see
+    /// [`Lido::deserialize_legacy`].
+    pub fn save_legacy(&self, account: &AccountInfo) -> ProgramResult {
+        let header_size = Lido::legacy_header_size(self.lido_version)?;
+        let header_data = self.header.try_to_vec()?;
+        let mut data = account.data.borrow_mut();
+        if data.len() < header_size {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        data[..LEGACY_REWARD_DISTRIBUTION_END]
+            .copy_from_slice(&header_data[..LEGACY_REWARD_DISTRIBUTION_END]);
+        data[LEGACY_REWARD_DISTRIBUTION_END..header_size]
+            .copy_from_slice(&header_data[LEGACY_REWARD_DISTRIBUTION_END + 4..header_size + 4]);
+        let legacy_maintainers = AccountSet {
+            entries: self
+                .maintainers
+                .entries
+                .iter()
+                .map(|pe| PubkeyAndEntry {
+                    pubkey: pe.pubkey,
+                    entry: (),
+                })
+                .collect(),
+            maximum_entries: self.maintainers.maximum_entries,
+        };
+        BorshSerialize::serialize(&legacy_maintainers, &mut &mut data[header_size..])?;
+        Ok(())
+    }
+}
 
-        // The remaining SOL managed is all in stake accounts.
-        let validator_balance: token::Result<Lamports> = self
-            .validators
-            .iter_entries()
-            .map(|v| v.stake_accounts_balance)
-            .sum();
+/// Convert the maintainers of versions 0 through 6, which were a plain set, to
+/// [`Maintainers`] without credit or operations.
+pub fn upgrade_maintainers(maintainers: AccountSet) -> Maintainers {
+    Maintainers {
+        entries: maintainers
+            .entries
+            .into_iter()
+            .map(|pe| PubkeyAndEntry {
+                pubkey: pe.pubkey,
+                entry: Maintainer::default(),
+            })
+            .collect(),
+        maximum_entries: maintainers.maximum_entries,
+    }
+}
 
-        let result = validator_balance.and_then(|s| s + effective_reserve_balance)?;
+/// The layout of the `Lido` account in version 1, when the validators were
+/// stored inside the `Lido` account itself.
+///
+/// This is only used to migrate existing instances to the current layout with
+/// `UpgradeFeePayoutMode` and `MigrateValidatorList`, see [`LidoV1::migrate`].
+#[derive(Clone, Debug, Default, BorshDeserialize, BorshSerialize, BorshSchema, Eq, PartialEq)]
+pub struct LidoV1 {
+    pub lido_version: u8,
+    pub manager: Pubkey,
+    pub st_sol_mint: Pubkey,
+    pub exchange_rate: ExchangeRate,
+    pub sol_reserve_account_bump_seed: u8,
+    pub stake_authority_bump_seed: u8,
+    pub mint_authority_bump_seed: u8,
+    pub rewards_withdraw_authority_bump_seed: u8,
+    pub reward_distribution: LegacyRewardDistribution,
+    pub fee_recipients: FeeRecipients,
+    pub fee_payout_mode: FeePayoutMode,
+    pub metrics: LegacyMetrics,
+    pub validators: LegacyValidators,
+    pub maintainers: AccountSet,
+}
 
-        Ok(result)
+impl LidoV1 {
+    /// Deserialize a `Lido` account in the layout of version 0.
+    ///
+    /// Version 0 is the version 1 layout without a fee payout mode, fees were
+    /// always credited, so the result uses [`FeePayoutMode::Credit`]. The
+    /// version is left as-is.
+    pub fn deserialize_v0(data: &[u8]) -> Result<LidoV1, ProgramError> {
+        if data.len() < FEE_PAYOUT_MODE_OFFSET {
+            return Err(LidoError::InvalidLidoSize.into());
+        }
+        // `FeePayoutMode::Credit` serializes as a single zero byte, insert it
+        // where the current layout has the fee payout mode.
+        let mut upgraded_data = Vec::with_capacity(data.len() + 1);
+        upgraded_data.extend_from_slice(&data[..FEE_PAYOUT_MODE_OFFSET]);
+        upgraded_data.push(0);
+        upgraded_data.extend_from_slice(&data[FEE_PAYOUT_MODE_OFFSET..]);
+        Ok(try_from_slice_unchecked::<LidoV1>(&upgraded_data)?)
     }
 
-    /// Return the total amount of stSOL in existence.
+    /// Split a version 1 instance into a current `Lido`, and the validators
+    /// that move to the validator list account at `validator_list`.
     ///
-    /// The total is the amount minted so far, plus any unminted rewards that validators
-    /// are entitled to, but haven’t claimed yet.
-    pub fn get_st_sol_supply(&self, st_sol_mint: &AccountInfo) -> Result<StLamports, ProgramError> {
-        self.check_mint_is_st_sol_mint(st_sol_mint)?;
+    /// Version 1 did not keep the validators and maintainers sorted, so this
+    /// sorts them.
+    pub fn migrate(self, validator_list: Pubkey) -> (Lido, Validators) {
+        let mut validators = self.validators.upgrade().upgrade().upgrade();
+        let mut maintainers = upgrade_maintainers(self.maintainers);
+        validators.sort();
+        maintainers.sort();
+        let lido = Lido {
+            header: LidoHeader {
+                lido_version: LIDO_VERSION,
+                manager: self.manager,
+                st_sol_mint: self.st_sol_mint,
+                validator_list,
+                exchange_rate: self.exchange_rate,
+                sol_reserve_account_bump_seed: self.sol_reserve_account_bump_seed,
+                stake_authority_bump_seed: self.stake_authority_bump_seed,
+                mint_authority_bump_seed: self.mint_authority_bump_seed,
+                rewards_withdraw_authority_bump_seed: self.rewards_withdraw_authority_bump_seed,
+                reward_distribution: self.reward_distribution.upgrade(),
+                fee_recipients: self.fee_recipients,
+                fee_payout_mode: self.fee_payout_mode,
+                metrics: self.metrics.upgrade(),
+                deposit_cutoff_slots: 0,
+                maintainer_fee_credit: StLamports(0),
+            },
+            maintainers,
+        };
+        (lido, validators)
+    }
+}
 
-        let st_sol_mint = Mint::unpack_from_slice(&st_sol_mint.data.borrow())?;
-        let minted_supply = StLamports(st_sol_mint.supply);
+#[repr(C)]
+#[derive(
+    Clone, Debug, Default, Eq, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema, Serialize,
+)]
+pub struct Maintainer {
+    /// Maintainer fees in stSOL that the maintainer is entitled to, but hasn't
+    /// claimed yet.
+    pub fee_credit: StLamports,
 
-        let credit: token::Result<StLamports> =
-            self.validators.iter_entries().map(|v| v.fee_credit).sum();
+    /// Number of maintenance operations that the maintainer performed since
+    /// the maintainer fee was last distributed. Permissionless maintenance only
+    /// counts when it changed something, such as withdrawing stake or
+    /// collecting rewards.
+    pub operations: u64,
 
-        let result = credit.and_then(|s| s + minted_supply)?;
+    /// Slot of the most recent `MaintainerHeartbeat` of the maintainer, or 0
+    /// if it never sent one.
+    ///
+    /// Maintainers that send heartbeats can be skipped in the duty rotation
+    /// when they go silent. Heartbeats are optional, a maintainer that never
+    /// sent one is never skipped.
+    pub last_heartbeat_slot: Slot,
+}
 
-        Ok(result)
-    }
+/// Layout of `Maintainer` in `Lido` versions 7 through 9, before it held the
+/// most recent heartbeat.
+#[repr(C)]
+#[derive(
+    Clone, Debug, Default, Eq, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema, Serialize,
+)]
+pub struct MaintainerV9 {
+    pub fee_credit: StLamports,
+    pub operations: u64,
+}
 
-    pub fn check_exchange_rate_last_epoch(
-        &self,
-        clock: &Clock,
-        method: &str,
-    ) -> Result<(), LidoError> {
-        if self.exchange_rate.computed_in_epoch < clock.epoch {
-            msg!(
-                "The exchange rate is outdated, it was last computed in epoch {}, \
-                but now it is epoch {}.",
-                self.exchange_rate.computed_in_epoch,
-                clock.epoch,
-            );
-            msg!("Please call UpdateExchangeRate before calling {}.", method);
-            return Err(LidoError::ExchangeRateNotUpdatedInThisEpoch);
+/// Maintainers in the layout of `Lido` versions 7 through 9.
+pub type MaintainersV9 = AccountMap<MaintainerV9>;
+
+/// The layout of the `Lido` account in versions 7 through 9. The header is the
+/// same as in the current layout, only the maintainers differ.
+///
+/// This is only used to upgrade existing instances with `UpgradeMetrics`,
+/// `UpgradeValidatorEntries`, and `UpgradeMaintainers`.
+#[derive(Clone, Debug, Default, BorshDeserialize, BorshSerialize, BorshSchema, Eq, PartialEq)]
+pub struct LidoV9 {
+    pub header: LidoHeader,
+    pub maintainers: MaintainersV9,
+}
+
+impl LidoV9 {
+    pub fn save(&self, account: &AccountInfo) -> ProgramResult {
+        BorshSerialize::serialize(self, &mut *account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Convert to the current layout. No maintainer sent a heartbeat yet.
+    pub fn upgrade(self) -> Lido {
+        Lido {
+            header: self.header,
+            maintainers: Maintainers {
+                entries: self
+                    .maintainers
+                    .entries
+                    .into_iter()
+                    .map(|pe| PubkeyAndEntry {
+                        pubkey: pe.pubkey,
+                        entry: Maintainer {
+                            fee_credit: pe.entry.fee_credit,
+                            operations: pe.entry.operations,
+                            last_heartbeat_slot: 0,
+                        },
+                    })
+                    .collect(),
+                maximum_entries: self.maintainers.maximum_entries,
+            },
         }
-        Ok(())
     }
 }
 
@@ -635,6 +1205,189 @@ pub struct Validator {
     /// Controls if a validator is allowed to have new stake deposits.
     /// When removing a validator, this flag should be set to `false`.
     pub active: bool,
+
+    /// Counters that track the performance of this validator.
+    ///
+    /// Fields are only ever added at the end, so the offsets of the fields
+    /// before them are the same as in [`LegacyValidator`], [`ValidatorV4`], and
+    /// [`ValidatorV8`].
+    pub metrics: ValidatorMetrics,
+
+    /// Set by `ForceRemoveValidator`. A tombstone is inactive, and all of its
+    /// stake accounts are deactivating. `WithdrawInactiveStake` withdraws them
+    /// entirely once they are inactive, after which the validator can be
+    /// removed with `RemoveValidator`.
+    pub tombstone: bool,
+
+    /// The withdraw authority to set on the vote account when the validator is
+    /// removed. The validator registers it when it is added.
+    ///
+    /// Validators added before this existed, including all validators that
+    /// `UpgradeValidatorEntries` moved from the version 7 and 8 layouts, have
+    /// the default pubkey here. When those are removed, the withdraw authority goes
+    /// to the validator identity, the node pubkey of the vote account.
+    #[serde(serialize_with = "serialize_b58")]
+    pub vote_withdrawer: Pubkey,
+}
+
+/// Layout of `Validator` in `Lido` versions 0 through 3, before it held
+/// [`ValidatorMetrics`].
+#[repr(C)]
+#[derive(
+    Clone, Debug, Default, Eq, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema, Serialize,
+)]
+pub struct LegacyValidator {
+    pub fee_credit: StLamports,
+    #[serde(serialize_with = "serialize_b58")]
+    pub fee_address: Pubkey,
+    pub stake_seeds: SeedRange,
+    pub unstake_seeds: SeedRange,
+    pub stake_accounts_balance: Lamports,
+    pub unstake_accounts_balance: Lamports,
+    pub active: bool,
+}
+
+/// Validator list in the layout of `Lido` versions 0 through 3.
+pub type LegacyValidators = AccountMap<LegacyValidator>;
+
+impl LegacyValidators {
+    pub fn save(&self, account: &AccountInfo) -> ProgramResult {
+        BorshSerialize::serialize(self, &mut *account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Convert to the layout of version 4, with zeroed metrics.
+    pub fn upgrade(self) -> ValidatorsV4 {
+        AccountMap {
+            entries: self
+                .entries
+                .into_iter()
+                .map(|pe| PubkeyAndEntry {
+                    pubkey: pe.pubkey,
+                    entry: ValidatorV4 {
+                        fee_credit: pe.entry.fee_credit,
+                        fee_address: pe.entry.fee_address,
+                        stake_seeds: pe.entry.stake_seeds,
+                        unstake_seeds: pe.entry.unstake_seeds,
+                        stake_accounts_balance: pe.entry.stake_accounts_balance,
+                        unstake_accounts_balance: pe.entry.unstake_accounts_balance,
+                        active: pe.entry.active,
+                        metrics: ValidatorMetrics::default(),
+                    },
+                })
+                .collect(),
+            maximum_entries: self.maximum_entries,
+        }
+    }
+}
+
+/// Layout of `Validator` in `Lido` versions 4 through 7, before it had a
+/// tombstone flag.
+#[repr(C)]
+#[derive(
+    Clone, Debug, Default, Eq, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema, Serialize,
+)]
+pub struct ValidatorV4 {
+    pub fee_credit: StLamports,
+    #[serde(serialize_with = "serialize_b58")]
+    pub fee_address: Pubkey,
+    pub stake_seeds: SeedRange,
+    pub unstake_seeds: SeedRange,
+    pub stake_accounts_balance: Lamports,
+    pub unstake_accounts_balance: Lamports,
+    pub active: bool,
+    pub metrics: ValidatorMetrics,
+}
+
+/// Validator list in the layout of `Lido` versions 4 through 7.
+pub type ValidatorsV4 = AccountMap<ValidatorV4>;
+
+impl ValidatorsV4 {
+    pub fn save(&self, account: &AccountInfo) -> ProgramResult {
+        BorshSerialize::serialize(self, &mut *account.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Calculates the size of a validator list account in this layout that
+    /// can hold `max_validators` validators.
+    pub fn calculate_size(max_validators: u32) -> usize {
+        ValidatorsV4::required_bytes(max_validators as usize)
+    }
+
+    /// Convert to the layout of version 8. No validator is a tombstone.
+    pub fn upgrade(self) -> ValidatorsV8 {
+        AccountMap {
+            entries: self
+                .entries
+                .into_iter()
+                .map(|pe| PubkeyAndEntry {
+                    pubkey: pe.pubkey,
+                    entry: ValidatorV8 {
+                        fee_credit: pe.entry.fee_credit,
+                        fee_address: pe.entry.fee_address,
+                        stake_seeds: pe.entry.stake_seeds,
+                        unstake_seeds: pe.entry.unstake_seeds,
+                        stake_accounts_balance: pe.entry.stake_accounts_balance,
+                        unstake_accounts_balance: pe.entry.unstake_accounts_balance,
+                        active: pe.entry.active,
+                        metrics: pe.entry.metrics,
+                        tombstone: false,
+                    },
+                })
+                .collect(),
+            maximum_entries: self.maximum_entries,
+        }
+    }
+}
+
+/// Layout of `Validator` in `Lido` version 8, before it had a vote withdrawer.
+#[repr(C)]
+#[derive(
+    Clone, Debug, Default, Eq, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema, Serialize,
+)]
+pub struct ValidatorV8 {
+    pub fee_credit: StLamports,
+    #[serde(serialize_with = "serialize_b58")]
+    pub fee_address: Pubkey,
+    pub stake_seeds: SeedRange,
+    pub unstake_seeds: SeedRange,
+    pub stake_accounts_balance: Lamports,
+    pub unstake_accounts_balance: Lamports,
+    pub active: bool,
+    pub metrics: ValidatorMetrics,
+    pub tombstone: bool,
+}
+
+/// Validator list in the layout of `Lido` version 8.
+pub type ValidatorsV8 = AccountMap<ValidatorV8>;
+
+impl ValidatorsV8 {
+    /// Convert to the current layout. No validator has a vote withdrawer
+    /// registered.
+    pub fn upgrade(self) -> Validators {
+        AccountMap {
+            entries: self
+                .entries
+                .into_iter()
+                .map(|pe| PubkeyAndEntry {
+                    pubkey: pe.pubkey,
+                    entry: Validator {
+                        fee_credit: pe.entry.fee_credit,
+                        fee_address: pe.entry.fee_address,
+                        stake_seeds: pe.entry.stake_seeds,
+                        unstake_seeds: pe.entry.unstake_seeds,
+                        stake_accounts_balance: pe.entry.stake_accounts_balance,
+                        unstake_accounts_balance: pe.entry.unstake_accounts_balance,
+                        active: pe.entry.active,
+                        metrics: pe.entry.metrics,
+                        tombstone: pe.entry.tombstone,
+                        vote_withdrawer: Pubkey::default(),
+                    },
+                })
+                .collect(),
+            maximum_entries: self.maximum_entries,
+        }
+    }
 }
 
 #[repr(C)]
@@ -706,6 +1459,9 @@ impl Default for Validator {
             stake_accounts_balance: Lamports(0),
             unstake_accounts_balance: Lamports(0),
             active: true,
+            metrics: ValidatorMetrics::default(),
+            tombstone: false,
+            vote_withdrawer: Pubkey::default(),
         }
     }
 }
@@ -806,6 +1562,33 @@ pub struct RewardDistribution {
     pub validation_fee: u32,
     pub developer_fee: u32,
     pub st_sol_appreciation: u32,
+
+    /// Compensation for the maintainers, who pay the transaction fees of the
+    /// maintenance operations. See [`Lido::distribute_maintainer_fee`].
+    pub maintainer_fee: u32,
+}
+
+/// Layout of [`RewardDistribution`] in versions 0 through 6 of the `Lido`
+/// account, before there was a maintainer fee.
+#[derive(Clone, Default, Debug, Eq, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema)]
+pub struct LegacyRewardDistribution {
+    pub treasury_fee: u32,
+    pub validation_fee: u32,
+    pub developer_fee: u32,
+    pub st_sol_appreciation: u32,
+}
+
+impl LegacyRewardDistribution {
+    /// Convert to the current layout, without a maintainer fee.
+    pub fn upgrade(self) -> RewardDistribution {
+        RewardDistribution {
+            treasury_fee: self.treasury_fee,
+            validation_fee: self.validation_fee,
+            developer_fee: self.developer_fee,
+            st_sol_appreciation: self.st_sol_appreciation,
+            maintainer_fee: 0,
+        }
+    }
 }
 
 /// Specifies the fee recipients, accounts that should be created by Lido's minter
@@ -819,6 +1602,50 @@ pub struct FeeRecipients {
     pub developer_account: Pubkey,
 }
 
+/// Determines how the validation fee is paid out to validators.
+#[derive(
+    Clone, Copy, Debug, Eq, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema, Serialize,
+)]
+pub enum FeePayoutMode {
+    /// Fees are added to the validator's `fee_credit`, and must be claimed
+    /// separately with `ClaimValidatorFee`.
+    Credit,
+
+    /// Fees are minted straight to the validator's `fee_address` in
+    /// `CollectValidatorFee`. If the fee account of a validator is not passed,
+    /// or it is not a valid stSOL account, we fall back to `fee_credit` for
+    /// that validator. When it is passed, any remaining `fee_credit` is paid
+    /// out along with the fee.
+    Direct,
+}
+
+impl Default for FeePayoutMode {
+    fn default() -> Self {
+        FeePayoutMode::Credit
+    }
+}
+
+impl std::str::FromStr for FeePayoutMode {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<FeePayoutMode, &'static str> {
+        match s {
+            "credit" => Ok(FeePayoutMode::Credit),
+            "direct" => Ok(FeePayoutMode::Direct),
+            _ => Err("Invalid fee payout mode, expected 'credit' or 'direct'."),
+        }
+    }
+}
+
+impl std::fmt::Display for FeePayoutMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FeePayoutMode::Credit => write!(f, "credit"),
+            FeePayoutMode::Direct => write!(f, "direct"),
+        }
+    }
+}
+
 impl RewardDistribution {
     pub fn sum(&self) -> u64 {
         // These adds don't overflow because we widen from u32 to u64 first.
@@ -826,6 +1653,7 @@ impl RewardDistribution {
             + self.validation_fee as u64
             + self.developer_fee as u64
             + self.st_sol_appreciation as u64
+            + self.maintainer_fee as u64
     }
 
     pub fn treasury_fraction(&self) -> Rational {
@@ -849,6 +1677,13 @@ impl RewardDistribution {
         }
     }
 
+    pub fn maintainer_fraction(&self) -> Rational {
+        Rational {
+            numerator: self.maintainer_fee as u64,
+            denominator: self.sum(),
+        }
+    }
+
     /// Split the reward according to the distribution defined in this instance.
     ///
     /// Fees are all rounded down, and the remainder goes to stSOL appreciation.
@@ -866,6 +1701,7 @@ impl RewardDistribution {
 
         let treasury_amount = (amount * self.treasury_fraction())?;
         let developer_amount = (amount * self.developer_fraction())?;
+        let maintainer_amount = (amount * self.maintainer_fraction())?;
 
         // The actual amount that goes to validation can be a tiny bit lower
         // than the target amount, when the number of validators does not divide
@@ -878,6 +1714,7 @@ impl RewardDistribution {
         let total_fees = Lamports(0)
             .add(treasury_amount)?
             .add(developer_amount)?
+            .add(maintainer_amount)?
             .add((reward_per_validator * num_validators)?)?;
         assert!(total_fees <= amount);
 
@@ -887,6 +1724,7 @@ impl RewardDistribution {
             treasury_amount,
             reward_per_validator,
             developer_amount,
+            maintainer_amount,
             st_sol_appreciation_amount,
         };
 
@@ -903,6 +1741,7 @@ pub struct Fees {
     pub treasury_amount: Lamports,
     pub reward_per_validator: Lamports,
     pub developer_amount: Lamports,
+    pub maintainer_amount: Lamports,
 
     /// Remainder of the reward.
     ///
@@ -955,21 +1794,299 @@ mod test_lido {
         );
     }
 
+    #[test]
+    fn test_legacy_validators_size() {
+        let validator = get_instance_packed_len(&LegacyValidator::default()).unwrap();
+        assert_eq!(validator, LegacyValidator::SIZE);
+        let validator = get_instance_packed_len(&ValidatorV4::default()).unwrap();
+        assert_eq!(validator, ValidatorV4::SIZE);
+        // Version 4 added the metrics.
+        assert_eq!(
+            ValidatorV4::SIZE - LegacyValidator::SIZE,
+            get_instance_packed_len(&ValidatorMetrics::default()).unwrap()
+        );
+        let validator = get_instance_packed_len(&ValidatorV8::default()).unwrap();
+        assert_eq!(validator, ValidatorV8::SIZE);
+        // Version 8 added the tombstone flag.
+        assert_eq!(ValidatorV8::SIZE - ValidatorV4::SIZE, 1);
+        // Version 9 added the vote withdrawer.
+        assert_eq!(Validator::SIZE - ValidatorV8::SIZE, 32);
+    }
+
+    #[test]
+    fn test_legacy_validators_upgrade() {
+        let mut legacy = LegacyValidators::new(2);
+        let vote_account = Pubkey::new_unique();
+        let legacy_validator = LegacyValidator {
+            fee_credit: StLamports(5),
+            fee_address: Pubkey::new_unique(),
+            stake_seeds: SeedRange { begin: 1, end: 3 },
+            unstake_seeds: SeedRange { begin: 2, end: 3 },
+            stake_accounts_balance: Lamports(100),
+            unstake_accounts_balance: Lamports(10),
+            active: true,
+        };
+        legacy.add(vote_account, legacy_validator.clone()).unwrap();
+
+        let validators = legacy.upgrade();
+        assert_eq!(validators.maximum_entries, 2);
+        let validator = validators.get(&vote_account).unwrap();
+        assert_eq!(validator.entry.fee_credit, legacy_validator.fee_credit);
+        assert_eq!(validator.entry.fee_address, legacy_validator.fee_address);
+        assert_eq!(validator.entry.stake_seeds, legacy_validator.stake_seeds);
+        assert_eq!(
+            validator.entry.unstake_seeds,
+            legacy_validator.unstake_seeds
+        );
+        assert_eq!(validator.entry.stake_accounts_balance, Lamports(100));
+        assert_eq!(validator.entry.unstake_accounts_balance, Lamports(10));
+        assert!(validator.entry.active);
+        assert_eq!(validator.entry.metrics, ValidatorMetrics::default());
+
+        // The legacy fields are a prefix of the new layout.
+        let legacy_bytes = legacy_validator.try_to_vec().unwrap();
+        let new_bytes = validator.entry.try_to_vec().unwrap();
+        assert_eq!(&new_bytes[..legacy_bytes.len()], &legacy_bytes[..]);
+    }
+
+    #[test]
+    fn test_validators_v4_upgrade() {
+        use solana_sdk::borsh::try_from_slice_unchecked;
+
+        let vote_account = Pubkey::new_unique();
+        let validator_v4 = ValidatorV4 {
+            fee_credit: StLamports(5),
+            fee_address: Pubkey::new_unique(),
+            stake_seeds: SeedRange { begin: 1, end: 3 },
+            unstake_seeds: SeedRange { begin: 2, end: 3 },
+            stake_accounts_balance: Lamports(100),
+            unstake_accounts_balance: Lamports(10),
+            active: false,
+            metrics: ValidatorMetrics {
+                rewards_total: Lamports(7),
+                fee_st_sol_total: StLamports(3),
+                epochs_active: 11,
+                last_active_epoch: 13,
+                last_rewards_epoch: 12,
+            },
+        };
+        let mut validators_v4 = ValidatorsV4::new(2);
+        validators_v4
+            .add(vote_account, validator_v4.clone())
+            .unwrap();
+
+        // Read the list from an account in the version 4 layout, which is
+        // larger than the data in it.
+        let mut data = vec![0; ValidatorsV4::calculate_size(2)];
+        BorshSerialize::serialize(&validators_v4, &mut &mut data[..]).unwrap();
+        assert_eq!(data.len(), 4 + 2 * (32 + VALIDATOR_V4_CONSTANT_SIZE) + 4);
+        let restored: ValidatorsV4 = try_from_slice_unchecked(&data[..]).unwrap();
+        assert_eq!(restored, validators_v4);
+
+        let validators = restored.upgrade().upgrade();
+        assert_eq!(validators.maximum_entries, 2);
+        let validator = &validators.get(&vote_account).unwrap().entry;
+        assert_eq!(validator.fee_credit, validator_v4.fee_credit);
+        assert_eq!(validator.fee_address, validator_v4.fee_address);
+        assert_eq!(validator.unstake_seeds, validator_v4.unstake_seeds);
+        assert!(!validator.active);
+        assert_eq!(validator.metrics, validator_v4.metrics);
+        assert!(!validator.tombstone);
+        assert_eq!(validator.vote_withdrawer, Pubkey::default());
+
+        // The version 4 fields are a prefix of the new layout.
+        let v4_bytes = validator_v4.try_to_vec().unwrap();
+        let new_bytes = validator.try_to_vec().unwrap();
+        assert_eq!(&new_bytes[..v4_bytes.len()], &v4_bytes[..]);
+    }
+
+    #[test]
+    fn test_validators_v8_upgrade() {
+        let vote_account = Pubkey::new_unique();
+        let validator_v8 = ValidatorV8 {
+            fee_credit: StLamports(5),
+            fee_address: Pubkey::new_unique(),
+            active: false,
+            tombstone: true,
+            ..ValidatorV8::default()
+        };
+        let mut validators_v8 = ValidatorsV8::new(2);
+        validators_v8
+            .add(vote_account, validator_v8.clone())
+            .unwrap();
+
+        let validators = validators_v8.upgrade();
+        assert_eq!(validators.maximum_entries, 2);
+        let validator = &validators.get(&vote_account).unwrap().entry;
+        assert_eq!(validator.fee_address, validator_v8.fee_address);
+        assert!(validator.tombstone);
+        assert_eq!(validator.vote_withdrawer, Pubkey::default());
+
+        // The version 8 fields are a prefix of the new layout.
+        let v8_bytes = validator_v8.try_to_vec().unwrap();
+        let new_bytes = validator.try_to_vec().unwrap();
+        assert_eq!(&new_bytes[..v8_bytes.len()], &v8_bytes[..]);
+    }
+
     #[test]
     fn test_lido_constant_size() {
-        // The minimal size of the struct is its size without any validators and
-        // maintainers.
+        // The minimal size of the struct is its size without any maintainers.
         let minimal = Lido::default();
         let mut data = Vec::new();
         BorshSerialize::serialize(&minimal, &mut data).unwrap();
 
         let num_entries = 0;
-        let size_validators = Validators::required_bytes(num_entries);
         let size_maintainers = Maintainers::required_bytes(num_entries);
 
+        assert_eq!(data.len() - size_maintainers, LIDO_CONSTANT_SIZE);
+        assert_eq!(Lido::calculate_size(0), data.len());
+
+        // The header is everything but the maintainers.
+        let header_data = minimal.header.try_to_vec().unwrap();
+        assert_eq!(header_data.len(), LIDO_CONSTANT_SIZE);
+    }
+
+    #[test]
+    fn test_lido_legacy_layout_roundtrips() {
+        let mut maintainers = Maintainers::new(2);
+        maintainers
+            .add(Pubkey::new_unique(), Maintainer::default())
+            .unwrap();
+        let mut lido = Lido {
+            header: LidoHeader {
+                lido_version: 4,
+                manager: Pubkey::new_unique(),
+                validator_list: Pubkey::new_unique(),
+                ..LidoHeader::default()
+            },
+            maintainers,
+        };
+        // Only metrics that the legacy layout has can survive the roundtrip.
+        lido.metrics
+            .deposit_amount
+            .observe(Lamports(1_000))
+            .unwrap();
+
+        let key = Pubkey::default();
+        let mut lamports = 0;
+        let mut data = vec![0; LEGACY_LIDO_CONSTANT_SIZE + AccountSet::required_bytes(2)];
+        let account = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &key, false, 0);
+        lido.save_legacy(&account).unwrap();
+        let restored = Lido::deserialize_legacy(&account.data.borrow()).unwrap();
+        assert_eq!(restored, lido);
+
+        // Apart from the maintainer fee, the legacy header is a prefix of the
+        // current one.
+        let header_data = lido.header.try_to_vec().unwrap();
+        assert_eq!(
+            &account.data.borrow()[..LEGACY_REWARD_DISTRIBUTION_END],
+            &header_data[..LEGACY_REWARD_DISTRIBUTION_END]
+        );
+        assert_eq!(
+            &account.data.borrow()[LEGACY_REWARD_DISTRIBUTION_END..LEGACY_LIDO_CONSTANT_SIZE],
+            &header_data[LEGACY_REWARD_DISTRIBUTION_END + 4..LEGACY_LIDO_CONSTANT_SIZE + 4]
+        );
+    }
+
+    #[test]
+    fn test_lido_v5_and_v6_layouts_roundtrip() {
+        for &(lido_version, header_size) in
+            &[(5, LIDO_V5_CONSTANT_SIZE), (6, LIDO_V6_CONSTANT_SIZE)]
+        {
+            let mut maintainers = Maintainers::new(2);
+            maintainers
+                .add(Pubkey::new_unique(), Maintainer::default())
+                .unwrap();
+            let mut lido = Lido {
+                header: LidoHeader {
+                    lido_version,
+                    manager: Pubkey::new_unique(),
+                    validator_list: Pubkey::new_unique(),
+                    ..LidoHeader::default()
+                },
+                maintainers,
+            };
+            // Both layouts have all metrics, only version 6 has the deposit cutoff.
+            lido.metrics
+                .withdraw_amount_histogram
+                .observe(Lamports(1_000))
+                .unwrap();
+            if lido_version == 6 {
+                lido.deposit_cutoff_slots = 100;
+            }
+
+            let key = Pubkey::default();
+            let mut lamports = 0;
+            let mut data = vec![0; header_size + AccountSet::required_bytes(2)];
+            let account =
+                AccountInfo::new(&key, false, true, &mut lamports, &mut data, &key, false, 0);
+            lido.save_legacy(&account).unwrap();
+            let restored = Lido::deserialize_legacy(&account.data.borrow()).unwrap();
+            assert_eq!(restored, lido);
+
+            let header_data = lido.header.try_to_vec().unwrap();
+            assert_eq!(
+                &account.data.borrow()[LEGACY_REWARD_DISTRIBUTION_END..header_size],
+                &header_data[LEGACY_REWARD_DISTRIBUTION_END + 4..header_size + 4]
+            );
+        }
+    }
+
+    #[test]
+    fn test_lido_v9_upgrade() {
+        use solana_sdk::borsh::try_from_slice_unchecked;
+
+        let maintainer_v9 = get_instance_packed_len(&MaintainerV9::default()).unwrap();
+        assert_eq!(maintainer_v9, MaintainerV9::SIZE);
+        let maintainer = get_instance_packed_len(&Maintainer::default()).unwrap();
+        assert_eq!(maintainer, Maintainer::SIZE);
+        // Version 10 added the heartbeat slot.
+        assert_eq!(Maintainer::SIZE - MaintainerV9::SIZE, 8);
+
+        let maintainer = Pubkey::new_unique();
+        let mut maintainers = MaintainersV9::new(3);
+        maintainers
+            .add(
+                maintainer,
+                MaintainerV9 {
+                    fee_credit: StLamports(19),
+                    operations: 23,
+                },
+            )
+            .unwrap();
+        let lido_v9 = LidoV9 {
+            header: LidoHeader {
+                lido_version: 9,
+                manager: Pubkey::new_unique(),
+                maintainer_fee_credit: StLamports(19),
+                ..LidoHeader::default()
+            },
+            maintainers,
+        };
+
+        // Read it from an account in the version 9 layout, which is larger
+        // than the data in it.
+        let mut data = vec![0; LIDO_CONSTANT_SIZE + MaintainersV9::required_bytes(3)];
+        BorshSerialize::serialize(&lido_v9, &mut &mut data[..]).unwrap();
+        let maintainers_data = &data[LIDO_CONSTANT_SIZE..];
+        assert_eq!(&maintainers_data[..4], &1_u32.to_le_bytes());
+        assert_eq!(&maintainers_data[4..36], maintainer.as_ref());
+        assert_eq!(&maintainers_data[36..44], &19_u64.to_le_bytes());
+        assert_eq!(&maintainers_data[44..52], &23_u64.to_le_bytes());
+        assert_eq!(&maintainers_data[52..56], &3_u32.to_le_bytes());
+        let restored: LidoV9 = try_from_slice_unchecked(&data[..]).unwrap();
+        assert_eq!(restored, lido_v9);
+
+        let lido = restored.upgrade();
+        assert_eq!(lido.header, lido_v9.header);
+        assert_eq!(lido.maintainers.maximum_entries, 3);
         assert_eq!(
-            data.len() - size_validators - size_maintainers,
-            LIDO_CONSTANT_SIZE
+            lido.maintainers.get(&maintainer).unwrap().entry,
+            Maintainer {
+                fee_credit: StLamports(19),
+                operations: 23,
+                last_heartbeat_slot: 0,
+            }
         );
     }
 
@@ -977,36 +2094,48 @@ mod test_lido {
     fn test_lido_serialization_roundtrips() {
         use solana_sdk::borsh::try_from_slice_unchecked;
 
-        let mut validators = Validators::new(10_000);
-        validators
-            .add(Pubkey::new_unique(), Validator::new(Pubkey::new_unique()))
+        let mut maintainers = Maintainers::new(1);
+        maintainers
+            .add(
+                Pubkey::new_unique(),
+                Maintainer {
+                    fee_credit: StLamports(19),
+                    operations: 23,
+                    last_heartbeat_slot: 29,
+                },
+            )
             .unwrap();
-        let maintainers = Maintainers::new(1);
         let lido = Lido {
-            lido_version: 0,
-            manager: Pubkey::new_unique(),
-            st_sol_mint: Pubkey::new_unique(),
-            exchange_rate: ExchangeRate {
-                computed_in_epoch: 11,
-                sol_balance: Lamports(13),
-                st_sol_supply: StLamports(17),
-            },
-            sol_reserve_account_bump_seed: 1,
-            stake_authority_bump_seed: 2,
-            mint_authority_bump_seed: 3,
-            rewards_withdraw_authority_bump_seed: 4,
-            reward_distribution: RewardDistribution {
-                treasury_fee: 2,
-                validation_fee: 3,
-                developer_fee: 4,
-                st_sol_appreciation: 7,
+            header: LidoHeader {
+                lido_version: 0,
+                manager: Pubkey::new_unique(),
+                st_sol_mint: Pubkey::new_unique(),
+                validator_list: Pubkey::new_unique(),
+                exchange_rate: ExchangeRate {
+                    computed_in_epoch: 11,
+                    sol_balance: Lamports(13),
+                    st_sol_supply: StLamports(17),
+                },
+                sol_reserve_account_bump_seed: 1,
+                stake_authority_bump_seed: 2,
+                mint_authority_bump_seed: 3,
+                rewards_withdraw_authority_bump_seed: 4,
+                reward_distribution: RewardDistribution {
+                    treasury_fee: 2,
+                    validation_fee: 3,
+                    developer_fee: 4,
+                    st_sol_appreciation: 7,
+                    maintainer_fee: 5,
+                },
+                fee_recipients: FeeRecipients {
+                    treasury_account: Pubkey::new_unique(),
+                    developer_account: Pubkey::new_unique(),
+                },
+                fee_payout_mode: FeePayoutMode::Direct,
+                metrics: Metrics::new(),
+                deposit_cutoff_slots: 100,
+                maintainer_fee_credit: StLamports(29),
             },
-            fee_recipients: FeeRecipients {
-                treasury_account: Pubkey::new_unique(),
-                developer_account: Pubkey::new_unique(),
-            },
-            metrics: Metrics::new(),
-            validators: validators,
             maintainers: maintainers,
         };
         let mut data = Vec::new();
@@ -1016,6 +2145,164 @@ mod test_lido {
         assert_eq!(lido, lido_restored);
     }
 
+    #[test]
+    fn test_validators_serialization_roundtrips() {
+        use solana_sdk::borsh::try_from_slice_unchecked;
+
+        let mut validators = Validators::new(10_000);
+        validators
+            .add(Pubkey::new_unique(), Validator::new(Pubkey::new_unique()))
+            .unwrap();
+        let mut data = Vec::new();
+        BorshSerialize::serialize(&validators, &mut data).unwrap();
+        assert!(data.len() <= Validators::calculate_size(10_000));
+
+        let validators_restored = try_from_slice_unchecked(&data[..]).unwrap();
+        assert_eq!(validators, validators_restored);
+    }
+
+    #[test]
+    fn test_lido_v1_migrate() {
+        use solana_sdk::borsh::try_from_slice_unchecked;
+
+        let mut legacy_validators = LegacyValidators::new(3);
+        for _ in 0..2 {
+            let validator = LegacyValidator {
+                fee_address: Pubkey::new_unique(),
+                active: true,
+                ..LegacyValidator::default()
+            };
+            legacy_validators
+                .add(Pubkey::new_unique(), validator)
+                .unwrap();
+        }
+        let validators = legacy_validators.clone().upgrade().upgrade().upgrade();
+        let mut maintainers = AccountSet::new(2);
+        maintainers.add(Pubkey::new_unique(), ()).unwrap();
+        maintainers.add(Pubkey::new_unique(), ()).unwrap();
+
+        // Version 1 did not keep the entries sorted.
+        let mut unsorted_validators = legacy_validators.clone();
+        unsorted_validators.entries.reverse();
+        let mut unsorted_maintainers = maintainers.clone();
+        unsorted_maintainers.entries.reverse();
+
+        let lido_v1 = LidoV1 {
+            lido_version: 0,
+            manager: Pubkey::new_unique(),
+            st_sol_mint: Pubkey::new_unique(),
+            stake_authority_bump_seed: 2,
+            fee_payout_mode: FeePayoutMode::Direct,
+            validators: unsorted_validators,
+            maintainers: unsorted_maintainers,
+            ..LidoV1::default()
+        };
+
+        let validator_list = Pubkey::new_unique();
+        let (lido, migrated_validators) = lido_v1.clone().migrate(validator_list);
+        assert_eq!(lido.lido_version, LIDO_VERSION);
+        assert_eq!(lido.manager, lido_v1.manager);
+        assert_eq!(lido.st_sol_mint, lido_v1.st_sol_mint);
+        assert_eq!(lido.validator_list, validator_list);
+        assert_eq!(lido.stake_authority_bump_seed, 2);
+        assert_eq!(lido.fee_payout_mode, FeePayoutMode::Direct);
+        assert_eq!(lido.maintainers, upgrade_maintainers(maintainers));
+        assert_eq!(migrated_validators, validators);
+
+        // A version 1 account starts with the same bytes as a version 1
+        // `LidoV1`, so the legacy struct can read it.
+        let data = lido_v1.try_to_vec().unwrap();
+        let restored: LidoV1 = try_from_slice_unchecked(&data[..]).unwrap();
+        assert_eq!(restored, lido_v1);
+    }
+
+    #[test]
+    fn test_lido_v1_deserialize_v0_reads_version_0_account() {
+        let manager = Pubkey::new_unique();
+        let st_sol_mint = Pubkey::new_unique();
+        let treasury_account = Pubkey::new_unique();
+        let developer_account = Pubkey::new_unique();
+        let vote_account = Pubkey::new_unique();
+        let fee_address = Pubkey::new_unique();
+        let maintainer = Pubkey::new_unique();
+        let push_u32 = |data: &mut Vec<u8>, x: u32| data.extend_from_slice(&x.to_le_bytes());
+        let push_u64 = |data: &mut Vec<u8>, x: u64| data.extend_from_slice(&x.to_le_bytes());
+
+        // Write a version 0 account field by field, in the layout of the
+        // version 0 program, rather than serializing a `Lido`, so this test
+        // catches fields that the version 0 program did not have.
+        let mut data = vec![0];
+        data.extend_from_slice(manager.as_ref());
+        data.extend_from_slice(st_sol_mint.as_ref());
+        // Exchange rate: computed in epoch, stSOL supply, SOL balance.
+        push_u64(&mut data, 11);
+        push_u64(&mut data, 17);
+        push_u64(&mut data, 13);
+        // Bump seeds: reserve, stake authority, mint authority, rewards
+        // withdraw authority.
+        data.extend_from_slice(&[1, 2, 3, 4]);
+        // Reward distribution: treasury, validation, developer, appreciation.
+        push_u32(&mut data, 2);
+        push_u32(&mut data, 3);
+        push_u32(&mut data, 4);
+        push_u32(&mut data, 7);
+        data.extend_from_slice(treasury_account.as_ref());
+        data.extend_from_slice(developer_account.as_ref());
+        // Metrics: seven fee totals, the deposit histogram (12 buckets and a
+        // total), and the withdraw totals and count.
+        push_u64(&mut data, 19);
+        data.extend_from_slice(&[0; 8 * (6 + 13 + 3)]);
+        // The header of version 0 was one byte shorter than that of version 1.
+        assert_eq!(data.len(), LIDO_V1_CONSTANT_SIZE - 1);
+
+        // Validators: one entry, room for two.
+        push_u32(&mut data, 1);
+        data.extend_from_slice(vote_account.as_ref());
+        push_u64(&mut data, 23); // Fee credit.
+        data.extend_from_slice(fee_address.as_ref());
+        push_u64(&mut data, 1); // Stake seeds.
+        push_u64(&mut data, 3);
+        push_u64(&mut data, 2); // Unstake seeds.
+        push_u64(&mut data, 2);
+        push_u64(&mut data, 5_000_000_000); // Stake accounts balance.
+        push_u64(&mut data, 0); // Unstake accounts balance.
+        data.push(1); // Active.
+        push_u32(&mut data, 2);
+
+        // Maintainers: one entry, room for three.
+        push_u32(&mut data, 1);
+        data.extend_from_slice(maintainer.as_ref());
+        push_u32(&mut data, 3);
+
+        // The account is larger than the data in it.
+        data.resize(data.len() + 100, 0);
+
+        let lido_v1 = LidoV1::deserialize_v0(&data).unwrap();
+        assert_eq!(lido_v1.lido_version, 0);
+        assert_eq!(lido_v1.manager, manager);
+        assert_eq!(lido_v1.st_sol_mint, st_sol_mint);
+        assert_eq!(lido_v1.exchange_rate.sol_balance, Lamports(13));
+        assert_eq!(lido_v1.rewards_withdraw_authority_bump_seed, 4);
+        assert_eq!(lido_v1.reward_distribution.st_sol_appreciation, 7);
+        assert_eq!(lido_v1.fee_recipients.developer_account, developer_account);
+        assert_eq!(lido_v1.fee_payout_mode, FeePayoutMode::Credit);
+        assert_eq!(lido_v1.metrics.fee_treasury_sol_total, Lamports(19));
+        assert_eq!(lido_v1.validators.maximum_entries, 2);
+        assert_eq!(lido_v1.maintainers.maximum_entries, 3);
+
+        let validator_list = Pubkey::new_unique();
+        let (lido, validators) = lido_v1.migrate(validator_list);
+        assert_eq!(lido.fee_payout_mode, FeePayoutMode::Credit);
+        assert_eq!(lido.fee_recipients.treasury_account, treasury_account);
+        assert!(lido.maintainers.get(&maintainer).is_ok());
+        let validator = &validators.get(&vote_account).unwrap().entry;
+        assert_eq!(validator.fee_credit, StLamports(23));
+        assert_eq!(validator.fee_address, fee_address);
+        assert_eq!(validator.stake_seeds, SeedRange { begin: 1, end: 3 });
+        assert_eq!(validator.stake_accounts_balance, Lamports(5_000_000_000));
+        assert!(validator.active);
+    }
+
     #[test]
     fn test_exchange_when_balance_and_supply_are_zero() {
         let rate = ExchangeRate {
@@ -1130,14 +2417,14 @@ mod test_lido {
         use std::rc::Rc;
 
         let rent = &Rent::default();
-        let mut lido = Lido::default();
+        let mut validators = Validators::new(1);
         let key = Pubkey::default();
         let mut amount = rent.minimum_balance(0);
         let mut reserve_account =
             AccountInfo::new(&key, true, true, &mut amount, &mut [], &key, false, 0);
 
         assert_eq!(
-            lido.get_sol_balance(&rent, &reserve_account),
+            validators.get_sol_balance(&rent, &reserve_account),
             Ok(Lamports(0))
         );
 
@@ -1145,24 +2432,23 @@ mod test_lido {
         reserve_account.lamports = Rc::new(RefCell::new(&mut new_amount));
 
         assert_eq!(
-            lido.get_sol_balance(&rent, &reserve_account),
+            validators.get_sol_balance(&rent, &reserve_account),
             Ok(Lamports(10))
         );
 
-        lido.validators.maximum_entries = 1;
-        lido.validators
+        validators
             .add(Pubkey::new_unique(), Validator::new(Pubkey::new_unique()))
             .unwrap();
-        lido.validators.entries[0].entry.stake_accounts_balance = Lamports(37);
+        validators.entries[0].entry.stake_accounts_balance = Lamports(37);
         assert_eq!(
-            lido.get_sol_balance(&rent, &reserve_account),
+            validators.get_sol_balance(&rent, &reserve_account),
             Ok(Lamports(10 + 37))
         );
 
-        lido.validators.entries[0].entry.stake_accounts_balance = Lamports(u64::MAX);
+        validators.entries[0].entry.stake_accounts_balance = Lamports(u64::MAX);
 
         assert_eq!(
-            lido.get_sol_balance(&rent, &reserve_account),
+            validators.get_sol_balance(&rent, &reserve_account),
             Err(LidoError::CalculationFailure)
         );
 
@@ -1170,10 +2456,10 @@ mod test_lido {
         reserve_account.lamports = Rc::new(RefCell::new(&mut new_amount));
         // The amount here is more than the rent exemption that gets discounted
         // from the reserve, causing an overflow.
-        lido.validators.entries[0].entry.stake_accounts_balance = Lamports(5_000_000);
+        validators.entries[0].entry.stake_accounts_balance = Lamports(5_000_000);
 
         assert_eq!(
-            lido.get_sol_balance(&rent, &reserve_account),
+            validators.get_sol_balance(&rent, &reserve_account),
             Err(LidoError::CalculationFailure)
         );
     }
@@ -1193,6 +2479,7 @@ mod test_lido {
         mint.pack_into_slice(&mut data);
 
         let mut lido = Lido::default();
+        let mut validators = Validators::new(1);
         let mint_address = Pubkey::default();
         let mut amount = 0;
         let is_signer = false;
@@ -1213,24 +2500,23 @@ mod test_lido {
         lido.st_sol_mint = mint_address;
 
         assert_eq!(
-            lido.get_st_sol_supply(&st_sol_mint),
+            validators.get_st_sol_supply(&lido, &st_sol_mint),
             Ok(StLamports(200_000)),
         );
 
-        lido.validators.maximum_entries = 1;
-        lido.validators
+        validators
             .add(Pubkey::new_unique(), Validator::new(Pubkey::new_unique()))
             .unwrap();
-        lido.validators.entries[0].entry.fee_credit = StLamports(37);
+        validators.entries[0].entry.fee_credit = StLamports(37);
         assert_eq!(
-            lido.get_st_sol_supply(&st_sol_mint),
+            validators.get_st_sol_supply(&lido, &st_sol_mint),
             Ok(StLamports(200_000 + 37))
         );
 
         lido.st_sol_mint = Pubkey::new_unique();
 
         assert_eq!(
-            lido.get_st_sol_supply(&st_sol_mint),
+            validators.get_st_sol_supply(&lido, &st_sol_mint),
             Err(LidoError::InvalidStSolAccount.into())
         );
     }
@@ -1242,6 +2528,7 @@ mod test_lido {
             validation_fee: 2,
             developer_fee: 1,
             st_sol_appreciation: 0,
+            maintainer_fee: 0,
         };
 
         assert_eq!(
@@ -1252,6 +2539,7 @@ mod test_lido {
                 treasury_amount: Lamports(300),
                 reward_per_validator: Lamports(200),
                 developer_amount: Lamports(100),
+                maintainer_amount: Lamports(0),
                 st_sol_appreciation_amount: Lamports(0),
             },
         );
@@ -1264,6 +2552,7 @@ mod test_lido {
                 treasury_amount: Lamports(500),
                 reward_per_validator: Lamports(83),
                 developer_amount: Lamports(166),
+                maintainer_amount: Lamports(0),
                 st_sol_appreciation_amount: Lamports(2),
             },
         );
@@ -1277,6 +2566,7 @@ mod test_lido {
                 treasury_amount: Lamports(3),
                 reward_per_validator: Lamports(2),
                 developer_amount: Lamports(1),
+                maintainer_amount: Lamports(0),
                 st_sol_appreciation_amount: Lamports(94),
             },
         );
@@ -1286,6 +2576,7 @@ mod test_lido {
             validation_fee: 23,
             developer_fee: 19,
             st_sol_appreciation: 0,
+            maintainer_fee: 0,
         };
         assert_eq!(
             spec_coprime.split_reward(Lamports(1_000), 1).unwrap(),
@@ -1293,9 +2584,28 @@ mod test_lido {
                 treasury_amount: Lamports(288),
                 reward_per_validator: Lamports(389),
                 developer_amount: Lamports(322),
+                maintainer_amount: Lamports(0),
                 st_sol_appreciation_amount: Lamports(1),
             },
         );
+
+        let spec_with_maintainer = RewardDistribution {
+            treasury_fee: 3,
+            validation_fee: 2,
+            developer_fee: 1,
+            st_sol_appreciation: 90,
+            maintainer_fee: 4,
+        };
+        assert_eq!(
+            spec_with_maintainer.split_reward(Lamports(100), 1).unwrap(),
+            Fees {
+                treasury_amount: Lamports(3),
+                reward_per_validator: Lamports(2),
+                developer_amount: Lamports(1),
+                maintainer_amount: Lamports(4),
+                st_sol_appreciation_amount: Lamports(90),
+            },
+        );
     }
     #[test]
     fn test_n_val() {
@@ -1312,7 +2622,10 @@ mod test_lido {
 
         for i in 0..=255 {
             let lido = Lido {
-                lido_version: i,
+                header: LidoHeader {
+                    lido_version: i,
+                    ..LidoHeader::default()
+                },
                 ..Lido::default()
             };
             let mut res: Vec<u8> = Vec::new();
@@ -1351,4 +2664,117 @@ mod test_lido {
         }
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_distribute_maintainer_fee() {
+        let maintainer_a = Pubkey::new_unique();
+        let maintainer_b = Pubkey::new_unique();
+        let mut lido = Lido {
+            maintainers: Maintainers::new(2),
+            ..Lido::default()
+        };
+        lido.maintainers
+            .add(maintainer_a, Maintainer::default())
+            .unwrap();
+        lido.maintainers
+            .add(maintainer_b, Maintainer::default())
+            .unwrap();
+
+        // Without operations, the fee stays in the pool.
+        lido.maintainer_fee_credit = StLamports(100);
+        lido.distribute_maintainer_fee().unwrap();
+        assert_eq!(
+            lido.maintainers
+                .get(&maintainer_a)
+                .unwrap()
+                .entry
+                .fee_credit,
+            StLamports(0)
+        );
+
+        lido.observe_maintainer_operation(&maintainer_a).unwrap();
+        lido.observe_maintainer_operation(&maintainer_a).unwrap();
+        lido.observe_maintainer_operation(&maintainer_a).unwrap();
+        lido.observe_maintainer_operation(&maintainer_b).unwrap();
+        assert_eq!(
+            lido.observe_maintainer_operation(&Pubkey::new_unique()),
+            Err(LidoError::InvalidAccountMember.into())
+        );
+        lido.distribute_maintainer_fee().unwrap();
+        let a = &lido.maintainers.get(&maintainer_a).unwrap().entry;
+        let b = &lido.maintainers.get(&maintainer_b).unwrap().entry;
+        assert_eq!(a.fee_credit, StLamports(75));
+        assert_eq!(b.fee_credit, StLamports(25));
+        assert_eq!(a.operations, 0);
+        assert_eq!(b.operations, 0);
+
+        // Credit that was assigned already is not distributed again.
+        lido.maintainer_fee_credit = StLamports(110);
+        lido.observe_maintainer_operation(&maintainer_b).unwrap();
+        lido.distribute_maintainer_fee().unwrap();
+        assert_eq!(
+            lido.maintainers
+                .get(&maintainer_a)
+                .unwrap()
+                .entry
+                .fee_credit,
+            StLamports(75)
+        );
+        assert_eq!(
+            lido.maintainers
+                .get(&maintainer_b)
+                .unwrap()
+                .entry
+                .fee_credit,
+            StLamports(35)
+        );
+    }
+
+    #[test]
+    fn test_check_deposit_allowed() {
+        let epoch_schedule = EpochSchedule::without_warmup();
+        let first_slot_in_epoch_2 = epoch_schedule.get_first_slot_in_epoch(2);
+        let clock_at = |slot: u64| Clock {
+            slot,
+            epoch: epoch_schedule.get_epoch(slot),
+            ..Clock::default()
+        };
+        let mut lido = LidoHeader::default();
+        lido.exchange_rate.computed_in_epoch = 1;
+
+        // Without a cutoff, any slot is fine, even with an outdated exchange rate.
+        assert_eq!(
+            lido.check_deposit_allowed(&clock_at(first_slot_in_epoch_2 - 1), &epoch_schedule),
+            Ok(())
+        );
+        assert_eq!(
+            lido.check_deposit_allowed(&clock_at(first_slot_in_epoch_2), &epoch_schedule),
+            Ok(())
+        );
+
+        lido.deposit_cutoff_slots = 10;
+        assert_eq!(
+            lido.check_deposit_allowed(&clock_at(first_slot_in_epoch_2 - 11), &epoch_schedule),
+            Ok(())
+        );
+        assert_eq!(
+            lido.check_deposit_allowed(&clock_at(first_slot_in_epoch_2 - 10), &epoch_schedule),
+            Err(LidoError::DepositCutoff)
+        );
+        assert_eq!(
+            lido.check_deposit_allowed(&clock_at(first_slot_in_epoch_2 - 1), &epoch_schedule),
+            Err(LidoError::DepositCutoff)
+        );
+
+        // In the new epoch, we need the exchange rate update first.
+        assert_eq!(
+            lido.check_deposit_allowed(&clock_at(first_slot_in_epoch_2), &epoch_schedule),
+            Err(LidoError::ExchangeRateNotUpdatedInThisEpoch)
+        );
+        lido.exchange_rate.computed_in_epoch = 2;
+        assert_eq!(
+            lido.check_deposit_allowed(&clock_at(first_slot_in_epoch_2), &epoch_schedule),
+            Ok(())
+        );
+    }
 }
@@ -0,0 +1,210 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+//! Minimal bindings for the Metaplex token metadata program.
+//!
+//! Wallets and explorers look up the name, symbol, and logo of a token in a
+//! metadata account of this program. We only need to create and update the
+//! metadata of the stSOL mint, so rather than depending on the full Metaplex
+//! crate, this module builds those two instructions directly. The layout
+//! follows version 1 of the metadata program, where `CreateMetadataAccount` is
+//! instruction 0 and `UpdateMetadataAccount` is instruction 1.
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use serde::Serialize;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+
+/// Address of the Metaplex token metadata program.
+pub mod program {
+    solana_program::declare_id!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+}
+
+/// Maximum length of the name, in bytes, as enforced by the metadata program.
+pub const MAX_NAME_LENGTH: usize = 32;
+
+/// Maximum length of the symbol, in bytes, as enforced by the metadata program.
+pub const MAX_SYMBOL_LENGTH: usize = 10;
+
+/// Maximum length of the URI, in bytes, as enforced by the metadata program.
+pub const MAX_URI_LENGTH: usize = 200;
+
+/// Size of a metadata account, `MAX_METADATA_LEN` in the metadata program.
+pub const METADATA_ACCOUNT_SIZE: usize = 679;
+
+/// Seed prefix of metadata accounts.
+const METADATA_PREFIX: &[u8] = b"metadata";
+
+/// The metadata of a token, as Solido sets it.
+#[derive(
+    Clone, Debug, Default, BorshDeserialize, BorshSerialize, BorshSchema, Eq, PartialEq, Serialize,
+)]
+pub struct TokenMetadata {
+    pub name: String,
+    pub symbol: String,
+
+    /// Location of a JSON document with further details, such as the logo.
+    pub uri: String,
+}
+
+impl TokenMetadata {
+    /// Return whether the metadata program would accept these values.
+    pub fn is_valid(&self) -> bool {
+        self.name.len() <= MAX_NAME_LENGTH
+            && self.symbol.len() <= MAX_SYMBOL_LENGTH
+            && self.uri.len() <= MAX_URI_LENGTH
+    }
+}
+
+/// `Data` in the metadata program.
+#[derive(BorshSerialize)]
+struct Data {
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+    // Creators are only meaningful for NFTs, we always set `None`, so we don't
+    // need to define the `Creator` type.
+    creators: Option<()>,
+}
+
+impl From<&TokenMetadata> for Data {
+    fn from(metadata: &TokenMetadata) -> Data {
+        Data {
+            name: metadata.name.clone(),
+            symbol: metadata.symbol.clone(),
+            uri: metadata.uri.clone(),
+            seller_fee_basis_points: 0,
+            creators: None,
+        }
+    }
+}
+
+#[derive(BorshSerialize)]
+enum MetadataInstruction {
+    CreateMetadataAccount {
+        data: Data,
+        is_mutable: bool,
+    },
+    UpdateMetadataAccount {
+        data: Option<Data>,
+        update_authority: Option<Pubkey>,
+        primary_sale_happened: Option<bool>,
+    },
+}
+
+/// Return the address of the metadata account of a mint.
+pub fn find_metadata_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[METADATA_PREFIX, program::id().as_ref(), mint.as_ref()],
+        &program::id(),
+    )
+}
+
+/// Create the metadata account of a mint.
+///
+/// The mint authority must sign. The update authority does not need to sign,
+/// it is the account that can change the metadata later.
+pub fn create_metadata_account(
+    metadata: &Pubkey,
+    mint: &Pubkey,
+    mint_authority: &Pubkey,
+    payer: &Pubkey,
+    update_authority: &Pubkey,
+    token_metadata: &TokenMetadata,
+) -> Instruction {
+    let data = MetadataInstruction::CreateMetadataAccount {
+        data: token_metadata.into(),
+        is_mutable: true,
+    };
+    Instruction {
+        program_id: program::id(),
+        accounts: vec![
+            AccountMeta::new(*metadata, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*mint_authority, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*update_authority, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+        ],
+        data: data
+            .try_to_vec()
+            .expect("Serializing an Instruction to Vec<u8> does not fail."),
+    }
+}
+
+/// Replace the name, symbol, and URI of an existing metadata account.
+pub fn update_metadata_account(
+    metadata: &Pubkey,
+    update_authority: &Pubkey,
+    token_metadata: &TokenMetadata,
+) -> Instruction {
+    let data = MetadataInstruction::UpdateMetadataAccount {
+        data: Some(token_metadata.into()),
+        update_authority: None,
+        primary_sale_happened: None,
+    };
+    Instruction {
+        program_id: program::id(),
+        accounts: vec![
+            AccountMeta::new(*metadata, false),
+            AccountMeta::new_readonly(*update_authority, true),
+        ],
+        data: data
+            .try_to_vec()
+            .expect("Serializing an Instruction to Vec<u8> does not fail."),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn st_sol_metadata() -> TokenMetadata {
+        TokenMetadata {
+            name: "Lido Staked SOL".to_string(),
+            symbol: "stSOL".to_string(),
+            uri: "https://example.com/stsol.json".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_create_metadata_account_data() {
+        let key = Pubkey::new_unique();
+        let instruction = create_metadata_account(&key, &key, &key, &key, &key, &st_sol_metadata());
+
+        let mut expected = vec![0];
+        expected.extend_from_slice(&15_u32.to_le_bytes());
+        expected.extend_from_slice(b"Lido Staked SOL");
+        expected.extend_from_slice(&5_u32.to_le_bytes());
+        expected.extend_from_slice(b"stSOL");
+        expected.extend_from_slice(&30_u32.to_le_bytes());
+        expected.extend_from_slice(b"https://example.com/stsol.json");
+        // Seller fee basis points, no creators, is_mutable.
+        expected.extend_from_slice(&[0, 0, 0, 1]);
+        assert_eq!(instruction.data, expected);
+    }
+
+    #[test]
+    fn test_update_metadata_account_data() {
+        let key = Pubkey::new_unique();
+        let instruction = update_metadata_account(&key, &key, &st_sol_metadata());
+
+        // Instruction 1, and `Some` for the data.
+        assert_eq!(&instruction.data[..2], &[1, 1]);
+        // No new update authority, and no primary sale change.
+        assert_eq!(&instruction.data[instruction.data.len() - 2..], &[0, 0]);
+    }
+
+    #[test]
+    fn test_token_metadata_is_valid() {
+        assert!(st_sol_metadata().is_valid());
+        let mut metadata = st_sol_metadata();
+        metadata.symbol = "TOOLONGSYMBOL".to_string();
+        assert!(!metadata.is_valid());
+    }
+}
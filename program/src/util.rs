@@ -10,6 +10,17 @@ pub fn serialize_b58<S: Serializer>(x: &Pubkey, serializer: S) -> Result<S::Ok,
     serializer.serialize_str(&x.to_string())
 }
 
+/// Function to use when serializing an optional public key, to print it using base58.
+pub fn serialize_b58_option<S: Serializer>(
+    x: &Option<Pubkey>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match x {
+        Some(pubkey) => serializer.collect_str(pubkey),
+        None => serializer.serialize_none(),
+    }
+}
+
 /// Serializer that serializes a list of pubkeys as an array of base58 strings.
 pub fn serialize_b58_slice<T: AsRef<[Pubkey]>, S: Serializer>(
     pubkeys: T,
@@ -15,7 +15,7 @@ use solana_program::{
     hash::Hash,
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
-    vote,
+    sysvar, vote,
 };
 
 // FIXME: copied from the solana vote program.
@@ -126,3 +126,23 @@ pub fn withdraw(
         account_metas,
     )
 }
+
+// FIXME: copied from the solana vote program.
+pub fn authorize(
+    vote_pubkey: &Pubkey,
+    authorized_pubkey: &Pubkey, // currently authorized
+    new_authorized_pubkey: &Pubkey,
+    vote_authorize: VoteAuthorize,
+) -> Instruction {
+    let account_metas = vec![
+        AccountMeta::new(*vote_pubkey, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(*authorized_pubkey, true),
+    ];
+
+    Instruction::new_with_bincode(
+        vote::program::id(),
+        &VoteInstruction::Authorize(*new_authorized_pubkey, vote_authorize),
+        account_metas,
+    )
+}
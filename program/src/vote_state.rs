@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: GPL-3.0
 
 use crate::{error::LidoError, find_authority_program_address, REWARDS_WITHDRAW_AUTHORITY};
-use solana_program::{account_info::AccountInfo, msg, pubkey::Pubkey};
+use solana_program::{account_info::AccountInfo, clock::Epoch, msg, pubkey::Pubkey};
 use std::convert::TryInto;
 
 /// Structure used to read the first 4 fields of a Solana `VoteAccount`.
@@ -10,6 +10,22 @@ use std::convert::TryInto;
 /// program due to size constrains.
 
 const PARTIAL_VOTE_STATE_LEN: usize = 69;
+
+/// Size of a `Lockout` in the `votes` of a `VoteState`: a u64 slot and a u32
+/// confirmation count.
+const LOCKOUT_LEN: usize = 12;
+
+/// Size of an entry in the `authorized_voters` of a `VoteState`: a u64 epoch
+/// and a pubkey.
+const AUTHORIZED_VOTER_LEN: usize = 40;
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64, LidoError> {
+    data.get(offset..offset + 8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(LidoError::InvalidVoteAccount)
+}
+
 #[derive(Debug, PartialEq)]
 pub struct PartialVoteState {
     /// comes from an enum inside the `VoteState` structure
@@ -94,6 +110,52 @@ impl PartialVoteState {
             commission,
         })
     }
+
+    /// Read the authorized voter for `epoch` from a vote account.
+    ///
+    /// The authorized voters follow the variable-length `votes` and `root_slot`
+    /// fields of the vote state, so we have to skip over those. Like the vote
+    /// program, we take the voter of the latest epoch that is not after `epoch`.
+    /// The vote account should have been checked with [`Self::deserialize`]
+    /// before.
+    pub fn get_authorized_voter(
+        validator_vote_account: &AccountInfo,
+        epoch: Epoch,
+    ) -> Result<Pubkey, LidoError> {
+        let data = validator_vote_account.data.borrow();
+
+        let num_votes = read_u64(&data, PARTIAL_VOTE_STATE_LEN)?;
+        let mut offset = (num_votes as usize)
+            .checked_mul(LOCKOUT_LEN)
+            .and_then(|len| len.checked_add(PARTIAL_VOTE_STATE_LEN + 8))
+            .ok_or(LidoError::InvalidVoteAccount)?;
+
+        // `root_slot` is an `Option<u64>`.
+        offset += match data.get(offset) {
+            Some(0) => 1,
+            Some(1) => 9,
+            _ => return Err(LidoError::InvalidVoteAccount),
+        };
+
+        let num_voters = read_u64(&data, offset)?;
+        offset += 8;
+
+        // The authorized voters are a map ordered by epoch.
+        let mut authorized_voter = None;
+        for _ in 0..num_voters {
+            let voter_epoch = read_u64(&data, offset)?;
+            if voter_epoch > epoch {
+                break;
+            }
+            let pubkey_bytes = data
+                .get(offset + 8..offset + AUTHORIZED_VOTER_LEN)
+                .ok_or(LidoError::InvalidVoteAccount)?;
+            authorized_voter = Some(Pubkey::new(pubkey_bytes));
+            offset += AUTHORIZED_VOTER_LEN;
+        }
+
+        authorized_voter.ok_or(LidoError::InvalidVoteAccount)
+    }
 }
 
 #[cfg(test)]
@@ -142,6 +204,59 @@ mod test {
             commission: 100,
         };
         assert_eq!(expected_partial_result, partial_vote);
+
+        // The excerpt has no votes, no root slot, and the node as the only
+        // authorized voter, from epoch 0.
+        assert_eq!(
+            PartialVoteState::get_authorized_voter(&account, 5),
+            Ok(expected_partial_result.node_pubkey)
+        );
+    }
+
+    #[test]
+    fn test_get_authorized_voter_skips_votes_and_root_slot() {
+        let voter_a = Pubkey::new_unique();
+        let voter_b = Pubkey::new_unique();
+
+        let mut data = vec![1, 0, 0, 0];
+        data.extend_from_slice(&Pubkey::new_unique().to_bytes());
+        data.extend_from_slice(&Pubkey::new_unique().to_bytes());
+        data.push(100);
+        // Two lockouts.
+        data.extend_from_slice(&2_u64.to_le_bytes());
+        data.extend_from_slice(&[0xff; 2 * LOCKOUT_LEN]);
+        // Some root slot.
+        data.push(1);
+        data.extend_from_slice(&7_u64.to_le_bytes());
+        // Two authorized voters.
+        data.extend_from_slice(&2_u64.to_le_bytes());
+        data.extend_from_slice(&0_u64.to_le_bytes());
+        data.extend_from_slice(&voter_a.to_bytes());
+        data.extend_from_slice(&10_u64.to_le_bytes());
+        data.extend_from_slice(&voter_b.to_bytes());
+
+        let acc_key = Pubkey::new_unique();
+        let owner = solana_program::vote::program::id();
+        let mut lamports = 0;
+        let account = AccountInfo::new(
+            &acc_key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+
+        assert_eq!(
+            PartialVoteState::get_authorized_voter(&account, 9),
+            Ok(voter_a)
+        );
+        assert_eq!(
+            PartialVoteState::get_authorized_voter(&account, 10),
+            Ok(voter_b)
+        );
     }
 
     #[test]
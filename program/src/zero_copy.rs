@@ -0,0 +1,592 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+//! Zero-copy access to the data of the `Lido` and validator list accounts.
+//!
+//! Deserializing the full `Lido` struct or validator list with Borsh, and
+//! serializing it again at the end of an instruction, costs compute units
+//! proportional to the number of validators and maintainers. Most instructions
+//! need only the header and one or two validators, so [`LidoAccountView`] and
+//! [`ValidatorListView`] read and write those directly in the account data,
+//! without touching the rest.
+//!
+//! The Borsh layout of `Lido` is:
+//!
+//! ```text
+//! header                   LIDO_CONSTANT_SIZE bytes
+//! maintainers.entries.len  u32
+//! maintainers.entries      len * (32-byte pubkey + MAINTAINER_CONSTANT_SIZE bytes)
+//! maintainers.maximum      u32
+//! ```
+//!
+//! The Borsh layout of the validator list account, a `Validators`, is:
+//!
+//! ```text
+//! entries.len              u32
+//! entries                  len * (32-byte pubkey + VALIDATOR_CONSTANT_SIZE bytes)
+//! maximum                  u32
+//! ```
+//!
+//! Instructions that change the number of validators or maintainers must still
+//! deserialize the full list.
+
+use std::convert::TryInto;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
+    program_pack::Pack, pubkey::Pubkey, rent::Rent,
+};
+use spl_token::state::Mint;
+
+use crate::account_map::PubkeyAndEntry;
+use crate::error::LidoError;
+use crate::logic::get_reserve_available_balance;
+use crate::state::{
+    ExchangeRate, LidoHeader, Maintainer, Validator, LIDO_CONSTANT_SIZE, MAINTAINER_CONSTANT_SIZE,
+    VALIDATOR_CONSTANT_SIZE,
+};
+use crate::token::{Lamports, StLamports};
+
+const PUBKEY_SIZE: usize = 32;
+const VALIDATOR_ENTRY_SIZE: usize = PUBKEY_SIZE + VALIDATOR_CONSTANT_SIZE;
+const MAINTAINER_ENTRY_SIZE: usize = PUBKEY_SIZE + MAINTAINER_CONSTANT_SIZE;
+
+/// Offsets of individual `Validator` fields, relative to the start of the entry.
+const VALIDATOR_FEE_CREDIT_OFFSET: usize = PUBKEY_SIZE;
+const VALIDATOR_FEE_ADDRESS_OFFSET: usize = VALIDATOR_FEE_CREDIT_OFFSET + 8;
+const VALIDATOR_STAKE_ACCOUNTS_BALANCE_OFFSET: usize = VALIDATOR_FEE_ADDRESS_OFFSET + 32 + 16 + 16;
+const VALIDATOR_UNSTAKE_ACCOUNTS_BALANCE_OFFSET: usize =
+    VALIDATOR_STAKE_ACCOUNTS_BALANCE_OFFSET + 8;
+
+/// Offset of `LidoHeader::exchange_rate` in the `Lido` account data, after the
+/// version and three pubkeys.
+const LIDO_EXCHANGE_RATE_OFFSET: usize = 1 + 3 * PUBKEY_SIZE;
+
+/// Offset of the first maintainer in the `Lido` account data.
+const MAINTAINERS_OFFSET: usize = LIDO_CONSTANT_SIZE + 4;
+
+/// Offset of the first validator entry in the validator list account data.
+const VALIDATORS_OFFSET: usize = 4;
+
+/// Number of validators to deserialize at once when iterating the validator list.
+///
+/// The program heap is only 32 KiB, so we can't hold a large validator list in
+/// memory at once. Iterating in pages of this size bounds the memory we need.
+pub const VALIDATOR_LIST_PAGE_SIZE: usize = 32;
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, ProgramError> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(ProgramError::InvalidAccountData)
+}
+
+/// Binary search for `key` among `len` keys, sorted in ascending order.
+///
+/// `key_at(i)` returns the bytes of the `i`-th key. The byte-wise order of the
+/// keys is the same as the order of [`Pubkey`], which [`AccountMap`] uses.
+///
+/// [`AccountMap`]: crate::account_map::AccountMap
+fn binary_search_key<'a, F>(len: usize, key: &Pubkey, key_at: F) -> Option<usize>
+where
+    F: Fn(usize) -> &'a [u8],
+{
+    let (mut low, mut high) = (0, len);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        match key_at(mid).cmp(key.as_ref()) {
+            std::cmp::Ordering::Less => low = mid + 1,
+            std::cmp::Ordering::Greater => high = mid,
+            std::cmp::Ordering::Equal => return Some(mid),
+        }
+    }
+    None
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn check_data_len(name: &str, data: &[u8], end: usize) -> ProgramResult {
+    if data.len() < end {
+        msg!(
+            "{} account data is {} bytes, but it should be at least {} bytes.",
+            name,
+            data.len(),
+            end
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(())
+}
+
+/// A view over the serialized `Lido` account data.
+///
+/// `D` is `&[u8]` for read-only access, or `&mut [u8]` for read-write access.
+pub struct LidoAccountView<D> {
+    data: D,
+    num_maintainers: usize,
+}
+
+impl<D: AsRef<[u8]>> LidoAccountView<D> {
+    /// Confirm that the data is large enough to hold the header and the
+    /// maintainers whose length it contains, and build a view over it.
+    pub fn new(data: D) -> Result<Self, ProgramError> {
+        let bytes = data.as_ref();
+        let num_maintainers = read_u32(bytes, LIDO_CONSTANT_SIZE)? as usize;
+        let end = MAINTAINERS_OFFSET + num_maintainers * MAINTAINER_ENTRY_SIZE + 4;
+        check_data_len("Lido", bytes, end)?;
+        Ok(LidoAccountView {
+            data,
+            num_maintainers,
+        })
+    }
+
+    fn bytes(&self) -> &[u8] {
+        self.data.as_ref()
+    }
+
+    /// Deserialize the constant-size header of the account.
+    pub fn header(&self) -> Result<LidoHeader, ProgramError> {
+        let header = LidoHeader::try_from_slice(&self.bytes()[..LIDO_CONSTANT_SIZE])?;
+        Ok(header)
+    }
+
+    /// Read only the exchange rate from the header.
+    ///
+    /// See also [`crate::cpi::get_exchange_rate`].
+    pub fn exchange_rate(&self) -> ExchangeRate {
+        let bytes = self.bytes();
+        ExchangeRate {
+            computed_in_epoch: read_u64(bytes, LIDO_EXCHANGE_RATE_OFFSET),
+            st_sol_supply: StLamports(read_u64(bytes, LIDO_EXCHANGE_RATE_OFFSET + 8)),
+            sol_balance: Lamports(read_u64(bytes, LIDO_EXCHANGE_RATE_OFFSET + 16)),
+        }
+    }
+
+    pub fn num_maintainers(&self) -> usize {
+        self.num_maintainers
+    }
+
+    fn maintainer_offset(&self, index: usize) -> usize {
+        assert!(index < self.num_maintainers);
+        MAINTAINERS_OFFSET + index * MAINTAINER_ENTRY_SIZE
+    }
+
+    /// Return the index of the maintainer with the given key, if it is one.
+    pub fn find_maintainer(&self, maintainer: &Pubkey) -> Option<usize> {
+        let bytes = self.bytes();
+        let key_at = |i: usize| {
+            let offset = self.maintainer_offset(i);
+            &bytes[offset..offset + PUBKEY_SIZE]
+        };
+        binary_search_key(self.num_maintainers, maintainer, key_at)
+    }
+
+    /// Deserialize the maintainer at the given index.
+    pub fn maintainer(&self, index: usize) -> Result<Maintainer, ProgramError> {
+        let offset = self.maintainer_offset(index) + PUBKEY_SIZE;
+        let maintainer =
+            Maintainer::try_from_slice(&self.bytes()[offset..offset + MAINTAINER_CONSTANT_SIZE])?;
+        Ok(maintainer)
+    }
+
+    /// Checks if the passed maintainer belong to the list of maintainers.
+    ///
+    /// See also [`crate::state::Lido::check_maintainer`].
+    pub fn check_maintainer(&self, maintainer: &AccountInfo) -> ProgramResult {
+        let is_maintainer = self.find_maintainer(maintainer.key).is_some();
+        if !is_maintainer {
+            msg!(
+                "Invalid maintainer, account {} is not present in the maintainers list.",
+                maintainer.key
+            );
+            return Err(LidoError::InvalidMaintainer.into());
+        }
+        Ok(())
+    }
+}
+
+impl<D: AsRef<[u8]> + AsMut<[u8]>> LidoAccountView<D> {
+    /// Overwrite the header of the account.
+    pub fn set_header(&mut self, header: &LidoHeader) -> ProgramResult {
+        let mut header_data = &mut self.data.as_mut()[..LIDO_CONSTANT_SIZE];
+        header.serialize(&mut header_data)?;
+        Ok(())
+    }
+
+    /// Overwrite the maintainer at the given index.
+    pub fn set_maintainer(&mut self, index: usize, maintainer: &Maintainer) -> ProgramResult {
+        let offset = self.maintainer_offset(index) + PUBKEY_SIZE;
+        let mut maintainer_data =
+            &mut self.data.as_mut()[offset..offset + MAINTAINER_CONSTANT_SIZE];
+        maintainer.serialize(&mut maintainer_data)?;
+        Ok(())
+    }
+
+    /// Record a maintenance operation for `maintainer`, if it is one of the
+    /// maintainers. For other accounts, this does nothing.
+    ///
+    /// See also [`crate::state::Lido::observe_maintainer_operation`].
+    pub fn observe_operation_if_maintainer(&mut self, maintainer: &Pubkey) -> ProgramResult {
+        if let Some(index) = self.find_maintainer(maintainer) {
+            let mut entry = self.maintainer(index)?;
+            entry.operations = entry
+                .operations
+                .checked_add(1)
+                .ok_or(LidoError::CalculationFailure)?;
+            self.set_maintainer(index, &entry)?;
+        }
+        Ok(())
+    }
+
+    /// Divide the unassigned maintainer fees in `header` among the maintainers.
+    ///
+    /// See also [`crate::state::Lido::distribute_maintainer_fee`].
+    pub fn distribute_maintainer_fee(&mut self, header: &LidoHeader) -> ProgramResult {
+        let mut maintainers = (0..self.num_maintainers)
+            .map(|i| self.maintainer(i))
+            .collect::<Result<Vec<Maintainer>, ProgramError>>()?;
+        let mut maintainer_refs: Vec<&mut Maintainer> = maintainers.iter_mut().collect();
+        header.distribute_maintainer_fee_among(&mut maintainer_refs)?;
+        for (i, maintainer) in maintainers.iter().enumerate() {
+            self.set_maintainer(i, maintainer)?;
+        }
+        Ok(())
+    }
+}
+
+/// A view over the serialized validator list account data.
+///
+/// `D` is `&[u8]` for read-only access, or `&mut [u8]` for read-write access.
+pub struct ValidatorListView<D> {
+    data: D,
+    num_validators: usize,
+}
+
+impl<D: AsRef<[u8]>> ValidatorListView<D> {
+    /// Confirm that the data is large enough to hold the validators whose
+    /// length it contains, and build a view over it.
+    pub fn new(data: D) -> Result<Self, ProgramError> {
+        let bytes = data.as_ref();
+        let num_validators = read_u32(bytes, 0)? as usize;
+        let end = VALIDATORS_OFFSET + num_validators * VALIDATOR_ENTRY_SIZE + 4;
+        check_data_len("Validator list", bytes, end)?;
+        Ok(ValidatorListView {
+            data,
+            num_validators,
+        })
+    }
+
+    fn bytes(&self) -> &[u8] {
+        self.data.as_ref()
+    }
+
+    fn validator_offset(&self, index: usize) -> usize {
+        assert!(index < self.num_validators);
+        VALIDATORS_OFFSET + index * VALIDATOR_ENTRY_SIZE
+    }
+
+    pub fn num_validators(&self) -> usize {
+        self.num_validators
+    }
+
+    /// Return the vote account of the validator at the given index.
+    pub fn validator_pubkey(&self, index: usize) -> Pubkey {
+        let offset = self.validator_offset(index);
+        Pubkey::new(&self.bytes()[offset..offset + PUBKEY_SIZE])
+    }
+
+    /// Deserialize the validator at the given index.
+    pub fn validator(&self, index: usize) -> Result<PubkeyAndEntry<Validator>, ProgramError> {
+        let offset = self.validator_offset(index);
+        let entry = PubkeyAndEntry::<Validator>::try_from_slice(
+            &self.bytes()[offset..offset + VALIDATOR_ENTRY_SIZE],
+        )?;
+        Ok(entry)
+    }
+
+    /// Return the number of pages of `page_size` validators in the list.
+    pub fn num_pages(&self, page_size: usize) -> usize {
+        (self.num_validators + page_size - 1) / page_size
+    }
+
+    /// Deserialize the validators on the page with the given index.
+    ///
+    /// Page `i` holds the validators at indices `i * page_size` up to but not
+    /// including `(i + 1) * page_size`. The last page may be shorter.
+    pub fn page(
+        &self,
+        page_index: usize,
+        page_size: usize,
+    ) -> Result<Vec<PubkeyAndEntry<Validator>>, ProgramError> {
+        let begin = page_index * page_size;
+        let end = self.num_validators.min(begin + page_size);
+        (begin..end).map(|i| self.validator(i)).collect()
+    }
+
+    /// Iterate over the validators one page at a time.
+    pub fn pages(
+        &self,
+        page_size: usize,
+    ) -> impl Iterator<Item = Result<Vec<PubkeyAndEntry<Validator>>, ProgramError>> + '_ {
+        (0..self.num_pages(page_size)).map(move |i| self.page(i, page_size))
+    }
+
+    /// Return the index of the validator with the given vote account.
+    pub fn find_validator(&self, vote_account: &Pubkey) -> Result<usize, LidoError> {
+        let bytes = self.bytes();
+        let key_at = |i: usize| {
+            let offset = self.validator_offset(i);
+            &bytes[offset..offset + PUBKEY_SIZE]
+        };
+        binary_search_key(self.num_validators, vote_account, key_at)
+            .ok_or(LidoError::InvalidAccountMember)
+    }
+
+    /// Return the index of the validator with the given stSOL fee account.
+    ///
+    /// The list is sorted by vote account, not by fee account, so this is a
+    /// linear scan.
+    pub fn find_validator_by_fee_address(&self, fee_address: &Pubkey) -> Option<usize> {
+        (0..self.num_validators).find(|&i| {
+            let offset = self.validator_offset(i) + VALIDATOR_FEE_ADDRESS_OFFSET;
+            &self.bytes()[offset..offset + PUBKEY_SIZE] == fee_address.as_ref()
+        })
+    }
+
+    /// Return the `fee_credit` of the validator at the given index.
+    pub fn validator_fee_credit(&self, index: usize) -> StLamports {
+        let offset = self.validator_offset(index) + VALIDATOR_FEE_CREDIT_OFFSET;
+        StLamports(read_u64(self.bytes(), offset))
+    }
+
+    /// Return the effective stake balance of the validator at the given index.
+    ///
+    /// See also [`Validator::effective_stake_balance`]. Returns
+    /// [`LidoError::CalculationFailure`] if the unstake balance exceeds the
+    /// stake balance.
+    pub fn validator_effective_stake_balance(&self, index: usize) -> Result<Lamports, LidoError> {
+        let offset = self.validator_offset(index);
+        let stake = read_u64(
+            self.bytes(),
+            offset + VALIDATOR_STAKE_ACCOUNTS_BALANCE_OFFSET,
+        );
+        let unstake = read_u64(
+            self.bytes(),
+            offset + VALIDATOR_UNSTAKE_ACCOUNTS_BALANCE_OFFSET,
+        );
+        Ok((Lamports(stake) - Lamports(unstake))?)
+    }
+
+    /// Compute the total amount of SOL managed by this instance.
+    ///
+    /// See also [`crate::state::Validators::get_sol_balance`].
+    pub fn get_sol_balance(
+        &self,
+        rent: &Rent,
+        reserve: &AccountInfo,
+    ) -> Result<Lamports, LidoError> {
+        let mut result = get_reserve_available_balance(rent, reserve)?;
+        for i in 0..self.num_validators {
+            let offset = self.validator_offset(i) + VALIDATOR_STAKE_ACCOUNTS_BALANCE_OFFSET;
+            result = (result + Lamports(read_u64(self.bytes(), offset)))?;
+        }
+        Ok(result)
+    }
+
+    /// Return the total amount of stSOL in existence.
+    ///
+    /// See also [`crate::state::Validators::get_st_sol_supply`].
+    pub fn get_st_sol_supply(
+        &self,
+        header: &LidoHeader,
+        st_sol_mint: &AccountInfo,
+    ) -> Result<StLamports, ProgramError> {
+        header.check_mint_is_st_sol_mint(st_sol_mint)?;
+
+        let st_sol_mint = Mint::unpack_from_slice(&st_sol_mint.data.borrow())?;
+        let mut result = (StLamports(st_sol_mint.supply) + header.maintainer_fee_credit)?;
+        for i in 0..self.num_validators {
+            result = (result + self.validator_fee_credit(i))?;
+        }
+        Ok(result)
+    }
+}
+
+impl<D: AsRef<[u8]> + AsMut<[u8]>> ValidatorListView<D> {
+    /// Overwrite the validator at the given index.
+    pub fn set_validator(&mut self, index: usize, validator: &Validator) -> ProgramResult {
+        let offset = self.validator_offset(index) + PUBKEY_SIZE;
+        let mut validator_data = &mut self.data.as_mut()[offset..offset + VALIDATOR_CONSTANT_SIZE];
+        validator.serialize(&mut validator_data)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::state::{Lido, Maintainer, Maintainers, Validators};
+
+    fn new_lido_with_maintainers(num_maintainers: u32) -> Lido {
+        let mut lido = Lido {
+            maintainers: Maintainers::new(num_maintainers + 1),
+            ..Lido::default()
+        };
+        lido.exchange_rate.computed_in_epoch = 7;
+        lido.exchange_rate.st_sol_supply = StLamports(11);
+        lido.exchange_rate.sol_balance = Lamports(13);
+        for _ in 0..num_maintainers {
+            lido.maintainers
+                .add(Pubkey::new_unique(), Maintainer::default())
+                .unwrap();
+        }
+        lido
+    }
+
+    fn new_validators(num_validators: u32) -> Validators {
+        let mut validators = Validators::new(num_validators + 1);
+        for i in 0..num_validators {
+            let mut validator = Validator::new(Pubkey::new_unique());
+            validator.fee_credit = StLamports(i as u64 + 100);
+            validator.stake_accounts_balance = Lamports(i as u64 * 3 + 50);
+            validator.unstake_accounts_balance = Lamports(i as u64);
+            validators.add(Pubkey::new_unique(), validator).unwrap();
+        }
+        validators
+    }
+
+    #[test]
+    fn test_lido_view_reads_and_writes_the_same_as_borsh() {
+        let mut lido = new_lido_with_maintainers(2);
+        let mut data = lido.try_to_vec().unwrap();
+        // Accounts are usually larger than the data they hold.
+        data.extend_from_slice(&[0; 100]);
+
+        let view = LidoAccountView::new(&data[..]).unwrap();
+        assert_eq!(view.header().unwrap(), lido.header);
+        assert_eq!(view.exchange_rate(), lido.exchange_rate);
+        assert_eq!(view.num_maintainers(), 2);
+
+        lido.exchange_rate.computed_in_epoch = 8;
+        let mut view = LidoAccountView::new(&mut data[..]).unwrap();
+        view.set_header(&lido.header).unwrap();
+
+        let mut expected = lido.try_to_vec().unwrap();
+        expected.extend_from_slice(&[0; 100]);
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn test_lido_view_updates_maintainers_the_same_as_borsh() {
+        let mut lido = new_lido_with_maintainers(3);
+        lido.header.maintainer_fee_credit = StLamports(100);
+        let maintainer = lido.maintainers.entries[1].pubkey;
+        let mut data = lido.try_to_vec().unwrap();
+
+        let mut view = LidoAccountView::new(&mut data[..]).unwrap();
+        assert_eq!(view.find_maintainer(&maintainer), Some(1));
+        view.observe_operation_if_maintainer(&maintainer).unwrap();
+        // Other accounts can perform maintenance too, but they are not credited.
+        view.observe_operation_if_maintainer(&Pubkey::new_unique())
+            .unwrap();
+        view.distribute_maintainer_fee(&lido.header).unwrap();
+
+        lido.observe_maintainer_operation(&maintainer).unwrap();
+        lido.distribute_maintainer_fee().unwrap();
+        assert_eq!(data, lido.try_to_vec().unwrap());
+
+        let view = LidoAccountView::new(&data[..]).unwrap();
+        assert_eq!(view.maintainer(1).unwrap().fee_credit, StLamports(100));
+    }
+
+    #[test]
+    fn test_validator_list_view_reads_the_same_as_borsh() {
+        let validators = new_validators(3);
+        let data = validators.try_to_vec().unwrap();
+        let view = ValidatorListView::new(&data[..]).unwrap();
+
+        assert_eq!(view.num_validators(), 3);
+
+        for (i, pe) in validators.entries.iter().enumerate() {
+            assert_eq!(view.validator(i).unwrap(), *pe);
+            assert_eq!(view.validator_pubkey(i), pe.pubkey);
+            assert_eq!(view.find_validator(&pe.pubkey), Ok(i));
+            assert_eq!(
+                view.find_validator_by_fee_address(&pe.entry.fee_address),
+                Some(i)
+            );
+            assert_eq!(view.validator_fee_credit(i), pe.entry.fee_credit);
+            assert_eq!(
+                view.validator_effective_stake_balance(i),
+                Ok(pe.entry.effective_stake_balance())
+            );
+        }
+        assert_eq!(
+            view.find_validator(&Pubkey::new_unique()),
+            Err(LidoError::InvalidAccountMember)
+        );
+    }
+
+    #[test]
+    fn test_validator_list_view_effective_stake_balance_does_not_underflow() {
+        let mut validators = new_validators(1);
+        validators.entries[0].entry.stake_accounts_balance = Lamports(1);
+        validators.entries[0].entry.unstake_accounts_balance = Lamports(2);
+        let data = validators.try_to_vec().unwrap();
+        let view = ValidatorListView::new(&data[..]).unwrap();
+
+        assert_eq!(
+            view.validator_effective_stake_balance(0),
+            Err(LidoError::CalculationFailure)
+        );
+    }
+
+    #[test]
+    fn test_validator_list_view_writes_the_same_as_borsh() {
+        let mut validators = new_validators(3);
+        let mut data = validators.try_to_vec().unwrap();
+        data.extend_from_slice(&[0; 100]);
+
+        validators.entries[1].entry.fee_credit = StLamports(0);
+        validators.entries[1].entry.active = false;
+
+        let mut view = ValidatorListView::new(&mut data[..]).unwrap();
+        view.set_validator(1, &validators.entries[1].entry).unwrap();
+
+        let mut expected = validators.try_to_vec().unwrap();
+        expected.extend_from_slice(&[0; 100]);
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn test_validator_list_pages_cover_all_validators() {
+        let validators = new_validators(7);
+        let data = validators.try_to_vec().unwrap();
+        let view = ValidatorListView::new(&data[..]).unwrap();
+
+        assert_eq!(view.num_pages(3), 3);
+        assert_eq!(view.page(2, 3).unwrap().len(), 1);
+
+        let paged: Vec<_> = view.pages(3).map(|page| page.unwrap()).flatten().collect();
+        assert_eq!(paged, validators.entries);
+
+        let empty = Validators::new(1).try_to_vec().unwrap();
+        let view = ValidatorListView::new(&empty[..]).unwrap();
+        assert_eq!(view.pages(3).count(), 0);
+    }
+
+    #[test]
+    fn test_views_reject_truncated_data() {
+        let lido = new_lido_with_maintainers(2);
+        let data = lido.try_to_vec().unwrap();
+        assert!(LidoAccountView::new(&data[..data.len() - 1]).is_err());
+        assert!(LidoAccountView::new(&data[..LIDO_CONSTANT_SIZE]).is_err());
+
+        let validators = new_validators(3);
+        let data = validators.try_to_vec().unwrap();
+        assert!(ValidatorListView::new(&data[..data.len() - 1]).is_err());
+        assert!(ValidatorListView::new(&data[..0]).is_err());
+    }
+}
@@ -30,12 +30,14 @@ use solana_sdk::transport::TransportError;
 use solana_vote_program::vote_instruction;
 use solana_vote_program::vote_state::{VoteInit, VoteState};
 
+use lido::oracle::ExchangeRateOracle;
 use lido::token::{Lamports, StLamports};
+use lido::token_metadata::{find_metadata_address, TokenMetadata};
 use lido::{
     error::LidoError, instruction, RESERVE_ACCOUNT, REWARDS_WITHDRAW_AUTHORITY, STAKE_AUTHORITY,
 };
 use lido::{
-    state::{FeeRecipients, Lido, RewardDistribution, Validator},
+    state::{FeePayoutMode, FeeRecipients, Lido, RewardDistribution, Validator, Validators},
     MINT_AUTHORITY,
 };
 
@@ -80,6 +82,7 @@ pub struct Context {
 
     // Key pairs for the accounts in the Solido instance.
     pub solido: Keypair,
+    pub validator_list: Keypair,
     pub manager: Keypair,
     pub st_sol_mint: Pubkey,
     pub maintainer: Option<Keypair>,
@@ -93,12 +96,14 @@ pub struct Context {
     pub stake_authority: Pubkey,
     pub mint_authority: Pubkey,
     pub withdraw_authority: Pubkey,
+    pub exchange_rate_oracle: Pubkey,
 }
 
 pub struct ValidatorAccounts {
     pub node_account: Keypair,
     pub vote_account: Pubkey,
     pub fee_account: Pubkey,
+    pub vote_withdrawer: Pubkey,
 }
 
 /// Sign and send a transaction with a fresh block hash.
@@ -216,12 +221,14 @@ impl Context {
         let mut deterministic_keypair = DeterministicKeypairGen::new();
         let manager = deterministic_keypair.new_keypair();
         let solido = deterministic_keypair.new_keypair();
+        let validator_list = deterministic_keypair.new_keypair();
 
         let reward_distribution = RewardDistribution {
             validation_fee: 5,
             treasury_fee: 3,
             developer_fee: 2,
             st_sol_appreciation: 90,
+            maintainer_fee: 0,
         };
 
         let (reserve_address, _) = Pubkey::find_program_address(
@@ -241,21 +248,33 @@ impl Context {
             &id(),
         );
 
+        let (exchange_rate_oracle, _) = ExchangeRateOracle::find_address(&id(), &solido.pubkey());
+
         // Note: this name *must* match the name of the crate that contains the
         // program. If it does not, then it will still partially work, but we get
         // weird errors about resizing accounts.
         let program_crate_name = "lido";
-        let program_test = ProgramTest::new(
+        crate::log_capture::install();
+        let mut program_test = ProgramTest::new(
             program_crate_name,
             id(),
             processor!(lido::processor::process),
         );
 
+        // The stand-in for an integrator's program that calls into Solido.
+        // There is no BPF build of it, so it always runs as native code.
+        program_test.add_program(
+            "lido_cpi_test",
+            crate::cpi_test_program::id(),
+            processor!(crate::cpi_test_program::process_instruction),
+        );
+
         let mut result = Self {
             context: program_test.start_with_context().await,
             nonce: 0,
             manager,
             solido,
+            validator_list,
             st_sol_mint: Pubkey::default(),
             maintainer: None,
             validator: None,
@@ -266,6 +285,7 @@ impl Context {
             stake_authority,
             mint_authority,
             withdraw_authority,
+            exchange_rate_oracle,
             deterministic_keypair: deterministic_keypair,
         };
 
@@ -281,9 +301,11 @@ impl Context {
 
         let max_validators = 10_000;
         let max_maintainers = 1000;
-        let solido_size = Lido::calculate_size(max_validators, max_maintainers);
+        let solido_size = Lido::calculate_size(max_maintainers);
+        let validator_list_size = Validators::calculate_size(max_validators);
         let rent = result.context.banks_client.get_rent().await.unwrap();
         let rent_solido = rent.minimum_balance(solido_size);
+        let rent_validator_list = rent.minimum_balance(validator_list_size);
 
         let rent_reserve = rent.minimum_balance(0);
         result
@@ -302,6 +324,13 @@ impl Context {
                     solido_size as u64,
                     &id(),
                 ),
+                system_instruction::create_account(
+                    &payer,
+                    &result.validator_list.pubkey(),
+                    rent_validator_list,
+                    validator_list_size as u64,
+                    &id(),
+                ),
                 instruction::initialize(
                     &id(),
                     result.reward_distribution.clone(),
@@ -309,6 +338,7 @@ impl Context {
                     max_maintainers,
                     &instruction::InitializeAccountsMeta {
                         lido: result.solido.pubkey(),
+                        validator_list: result.validator_list.pubkey(),
                         manager: result.manager.pubkey(),
                         st_sol_mint: result.st_sol_mint,
                         treasury_account: result.treasury_st_sol_account,
@@ -317,7 +347,7 @@ impl Context {
                     },
                 ),
             ],
-            vec![&result.solido],
+            vec![&result.solido, &result.validator_list],
         )
         .await
         .expect("Failed to initialize Solido instance.");
@@ -709,9 +739,19 @@ impl Context {
         .await
     }
 
+    /// Add the validator, signed by its validator identity.
     pub async fn try_add_validator(
         &mut self,
         accounts: &ValidatorAccounts,
+    ) -> transport::Result<()> {
+        self.try_add_validator_with_authority(accounts, &accounts.node_account)
+            .await
+    }
+
+    pub async fn try_add_validator_with_authority(
+        &mut self,
+        accounts: &ValidatorAccounts,
+        validator_authority: &Keypair,
     ) -> transport::Result<()> {
         send_transaction(
             &mut self.context,
@@ -720,12 +760,15 @@ impl Context {
                 &id(),
                 &lido::instruction::AddValidatorMeta {
                     lido: self.solido.pubkey(),
+                    validator_list: self.validator_list.pubkey(),
                     manager: self.manager.pubkey(),
                     validator_vote_account: accounts.vote_account,
                     validator_fee_st_sol_account: accounts.fee_account,
+                    validator_vote_withdrawer: accounts.vote_withdrawer,
+                    validator_authority: validator_authority.pubkey(),
                 },
             )],
-            vec![&self.manager],
+            vec![&self.manager, validator_authority],
         )
         .await
     }
@@ -738,10 +781,13 @@ impl Context {
             .create_vote_account(&node_account, self.withdraw_authority, 100)
             .await;
 
+        let vote_withdrawer = self.deterministic_keypair.new_keypair().pubkey();
+
         let accounts = ValidatorAccounts {
             node_account,
             vote_account,
             fee_account,
+            vote_withdrawer,
         };
 
         self.try_add_validator(&accounts)
@@ -759,6 +805,7 @@ impl Context {
                 &id(),
                 &lido::instruction::DeactivateValidatorMeta {
                     lido: self.solido.pubkey(),
+                    validator_list: self.validator_list.pubkey(),
                     manager: self.manager.pubkey(),
                     validator_vote_account_to_deactivate: vote_account,
                 },
@@ -769,7 +816,76 @@ impl Context {
         .expect("Failed to deactivate validator.");
     }
 
+    pub async fn try_force_remove_validator(
+        &mut self,
+        vote_account: Pubkey,
+    ) -> transport::Result<()> {
+        let validators = self.get_validators().await;
+        let validator = validators.get(&vote_account).unwrap();
+        let stake_accounts = validator
+            .entry
+            .stake_seeds
+            .into_iter()
+            .map(|seed| {
+                validator
+                    .find_stake_account_address(
+                        &id(),
+                        &self.solido.pubkey(),
+                        seed,
+                        StakeType::Stake,
+                    )
+                    .0
+            })
+            .collect();
+
+        send_transaction(
+            &mut self.context,
+            &mut self.nonce,
+            &[lido::instruction::force_remove_validator(
+                &id(),
+                &lido::instruction::ForceRemoveValidatorMeta {
+                    lido: self.solido.pubkey(),
+                    manager: self.manager.pubkey(),
+                    validator_list: self.validator_list.pubkey(),
+                    validator_vote_account_to_remove: vote_account,
+                    st_sol_mint: self.st_sol_mint,
+                    mint_authority: self.mint_authority,
+                    validator_fee_st_sol_account: validator.entry.fee_address,
+                    stake_authority: self.stake_authority,
+                    stake_accounts,
+                },
+            )],
+            vec![&self.manager],
+        )
+        .await
+    }
+
+    /// Remove the validator, and hand the vote account back to the vote
+    /// withdrawer that the program expects.
     pub async fn try_remove_validator(&mut self, vote_account: Pubkey) -> transport::Result<()> {
+        let validators = self.get_validators().await;
+        let registered_vote_withdrawer = validators
+            .get(&vote_account)
+            .map(|validator| validator.entry.vote_withdrawer)
+            .unwrap_or_default();
+        let validator_vote_withdrawer = if registered_vote_withdrawer == Pubkey::default() {
+            self.get_vote_account(vote_account)
+                .await
+                .map(|vote_state| vote_state.node_pubkey)
+                .unwrap_or_default()
+        } else {
+            registered_vote_withdrawer
+        };
+
+        self.try_remove_validator_to(vote_account, validator_vote_withdrawer)
+            .await
+    }
+
+    pub async fn try_remove_validator_to(
+        &mut self,
+        vote_account: Pubkey,
+        validator_vote_withdrawer: Pubkey,
+    ) -> transport::Result<()> {
         send_transaction(
             &mut self.context,
             &mut self.nonce,
@@ -777,7 +893,10 @@ impl Context {
                 &id(),
                 &lido::instruction::RemoveValidatorMeta {
                     lido: self.solido.pubkey(),
+                    validator_list: self.validator_list.pubkey(),
                     validator_vote_account_to_remove: vote_account,
+                    validator_vote_withdrawer,
+                    rewards_withdraw_authority: self.withdraw_authority,
                 },
             )],
             vec![],
@@ -843,6 +962,7 @@ impl Context {
                 &id(),
                 &instruction::WithdrawAccountsMeta {
                     lido: self.solido.pubkey(),
+                    validator_list: self.validator_list.pubkey(),
                     st_sol_mint: self.st_sol_mint,
                     st_sol_account_owner: user.pubkey(),
                     st_sol_account,
@@ -886,10 +1006,9 @@ impl Context {
         approach: StakeDeposit,
         amount: Lamports,
     ) -> transport::Result<Pubkey> {
-        let solido = self.get_solido().await;
+        let validators = self.get_validators().await;
 
-        let validator_entry = solido
-            .validators
+        let validator_entry = validators
             .get(&validator_vote_account)
             .expect("Trying to stake with a non-member validator.");
 
@@ -925,6 +1044,7 @@ impl Context {
                 &id(),
                 &instruction::StakeDepositAccountsMeta {
                     lido: self.solido.pubkey(),
+                    validator_list: self.validator_list.pubkey(),
                     maintainer: maintainer.pubkey(),
                     validator_vote_account,
                     reserve: self.reserve_address,
@@ -960,8 +1080,8 @@ impl Context {
         amount: Lamports,
     ) -> transport::Result<()> {
         // Where the new stake will live.
-        let solido = self.get_solido().await;
-        let validator = solido.validators.get(&validator_vote_account).unwrap();
+        let validators = self.get_validators().await;
+        let validator = validators.get(&validator_vote_account).unwrap();
 
         let (source_stake_account, _) = validator.find_stake_account_address(
             &id(),
@@ -983,6 +1103,7 @@ impl Context {
                 &id(),
                 &instruction::UnstakeAccountsMeta {
                     lido: self.solido.pubkey(),
+                    validator_list: self.validator_list.pubkey(),
                     validator_vote_account,
                     source_stake_account,
                     destination_unstake_account,
@@ -1028,7 +1149,96 @@ impl Context {
         .await
     }
 
+    pub async fn try_change_fee_payout_mode(
+        &mut self,
+        new_fee_payout_mode: FeePayoutMode,
+    ) -> transport::Result<()> {
+        send_transaction(
+            &mut self.context,
+            &mut self.nonce,
+            &[instruction::change_fee_payout_mode(
+                &id(),
+                new_fee_payout_mode,
+                &instruction::ChangeFeePayoutModeMeta {
+                    lido: self.solido.pubkey(),
+                    manager: self.manager.pubkey(),
+                },
+            )],
+            vec![&self.manager],
+        )
+        .await
+    }
+
+    pub async fn change_fee_payout_mode(&mut self, new_fee_payout_mode: FeePayoutMode) {
+        self.try_change_fee_payout_mode(new_fee_payout_mode)
+            .await
+            .expect("Failed to change fee payout mode.");
+    }
+
+    pub async fn try_change_deposit_cutoff(
+        &mut self,
+        deposit_cutoff_slots: u64,
+    ) -> transport::Result<()> {
+        send_transaction(
+            &mut self.context,
+            &mut self.nonce,
+            &[instruction::change_deposit_cutoff(
+                &id(),
+                deposit_cutoff_slots,
+                &instruction::ChangeDepositCutoffMeta {
+                    lido: self.solido.pubkey(),
+                    manager: self.manager.pubkey(),
+                },
+            )],
+            vec![&self.manager],
+        )
+        .await
+    }
+
+    pub async fn change_deposit_cutoff(&mut self, deposit_cutoff_slots: u64) {
+        self.try_change_deposit_cutoff(deposit_cutoff_slots)
+            .await
+            .expect("Failed to change deposit cutoff.");
+    }
+
+    pub async fn try_set_token_metadata(
+        &mut self,
+        token_metadata: TokenMetadata,
+        mint_authority: Pubkey,
+    ) -> transport::Result<()> {
+        let (token_metadata_address, _) = find_metadata_address(&self.st_sol_mint);
+        send_transaction(
+            &mut self.context,
+            &mut self.nonce,
+            &[instruction::set_token_metadata(
+                &id(),
+                token_metadata,
+                &instruction::SetTokenMetadataMeta {
+                    lido: self.solido.pubkey(),
+                    manager: self.manager.pubkey(),
+                    st_sol_mint: self.st_sol_mint,
+                    mint_authority,
+                    token_metadata: token_metadata_address,
+                    payer: self.manager.pubkey(),
+                },
+            )],
+            vec![&self.manager],
+        )
+        .await
+    }
+
+    /// Return the maintainer to pass to the permissionless maintenance
+    /// instructions, if there is one.
+    ///
+    /// When the maintainer is set, it needs to sign those instructions.
+    fn get_maintenance_performer(&self) -> Option<Pubkey> {
+        self.maintainer
+            .as_ref()
+            .map(|maintainer| maintainer.pubkey())
+    }
+
     pub async fn try_update_exchange_rate(&mut self) -> transport::Result<()> {
+        let maintainer = self.get_maintenance_performer();
         send_transaction(
             &mut self.context,
             &mut self.nonce,
@@ -1036,11 +1246,14 @@ impl Context {
                 &id(),
                 &instruction::UpdateExchangeRateAccountsMeta {
                     lido: self.solido.pubkey(),
+                    validator_list: self.validator_list.pubkey(),
                     reserve: self.reserve_address,
                     st_sol_mint: self.st_sol_mint,
+                    exchange_rate_oracle: self.exchange_rate_oracle,
+                    maintainer,
                 },
             )],
-            vec![],
+            self.maintainer.iter().collect(),
         )
         .await
     }
@@ -1051,6 +1264,30 @@ impl Context {
             .expect("Failed to update exchange rate.");
     }
 
+    pub async fn try_initialize_exchange_rate_oracle(&mut self) -> transport::Result<()> {
+        let funder = self.context.payer.pubkey();
+        send_transaction(
+            &mut self.context,
+            &mut self.nonce,
+            &[instruction::initialize_exchange_rate_oracle(
+                &id(),
+                &instruction::InitializeExchangeRateOracleMeta {
+                    lido: self.solido.pubkey(),
+                    exchange_rate_oracle: self.exchange_rate_oracle,
+                    funder,
+                },
+            )],
+            vec![],
+        )
+        .await
+    }
+
+    pub async fn initialize_exchange_rate_oracle(&mut self) {
+        self.try_initialize_exchange_rate_oracle()
+            .await
+            .expect("Failed to initialize exchange rate oracle.");
+    }
+
     /// Merge two accounts of a given validator.
     ///
     /// Returns the address that stake was merged into.
@@ -1074,6 +1311,7 @@ impl Context {
             StakeType::Stake,
         );
 
+        let maintainer = self.get_maintenance_performer();
         send_transaction(
             &mut self.context,
             &mut self.nonce,
@@ -1081,13 +1319,15 @@ impl Context {
                 &id(),
                 &instruction::MergeStakeMeta {
                     lido: self.solido.pubkey(),
+                    validator_list: self.validator_list.pubkey(),
                     validator_vote_account: validator.pubkey,
                     stake_authority: self.stake_authority,
                     from_stake: from_stake_account,
                     to_stake: to_stake_account,
+                    maintainer,
                 },
             )],
-            vec![],
+            self.maintainer.iter().collect(),
         )
         .await?;
 
@@ -1112,8 +1352,8 @@ impl Context {
         &mut self,
         validator_vote_account: Pubkey,
     ) -> transport::Result<()> {
-        let solido = self.get_solido().await;
-        let validator = solido.validators.get(&validator_vote_account).unwrap();
+        let validators = self.get_validators().await;
+        let validator = validators.get(&validator_vote_account).unwrap();
 
         let mut stake_account_addrs: Vec<Pubkey> = Vec::new();
 
@@ -1128,6 +1368,7 @@ impl Context {
                 .0
         }));
 
+        let maintainer = self.get_maintenance_performer();
         send_transaction(
             &mut self.context,
             &mut self.nonce,
@@ -1135,13 +1376,15 @@ impl Context {
                 &id(),
                 &instruction::WithdrawInactiveStakeMeta {
                     lido: self.solido.pubkey(),
+                    validator_list: self.validator_list.pubkey(),
                     validator_vote_account,
                     stake_accounts: stake_account_addrs,
                     reserve: self.reserve_address,
                     stake_authority: self.stake_authority,
+                    maintainer,
                 },
             )],
-            vec![],
+            self.maintainer.iter().collect(),
         )
         .await
     }
@@ -1157,6 +1400,30 @@ impl Context {
     pub async fn try_collect_validator_fee(
         &mut self,
         validator_vote_account: Pubkey,
+    ) -> transport::Result<Lamports> {
+        let solido = self.get_solido().await;
+        let validators = self.get_validators().await;
+        let validator_fee_st_sol_accounts = match solido.fee_payout_mode {
+            FeePayoutMode::Credit => Vec::new(),
+            FeePayoutMode::Direct => validators
+                .iter_entries()
+                .map(|v| v.fee_address)
+                .take(lido::MAXIMUM_DIRECT_FEE_ACCOUNTS)
+                .collect(),
+        };
+        self.try_collect_validator_fee_with_fee_accounts(
+            validator_vote_account,
+            validator_fee_st_sol_accounts,
+        )
+        .await
+    }
+
+    /// Like `try_collect_validator_fee`, but pay out directly to the given
+    /// validator fee accounts.
+    pub async fn try_collect_validator_fee_with_fee_accounts(
+        &mut self,
+        validator_vote_account: Pubkey,
+        validator_fee_st_sol_accounts: Vec<Pubkey>,
     ) -> transport::Result<Lamports> {
         let solido = self.get_solido().await;
         let reserve_balance_before = self.get_sol_balance(self.reserve_address).await;
@@ -1168,6 +1435,7 @@ impl Context {
             .get_rent()
             .await
             .minimum_balance(vote_account.data.len());
+        let maintainer = self.get_maintenance_performer();
         send_transaction(
             &mut self.context,
             &mut self.nonce,
@@ -1175,6 +1443,7 @@ impl Context {
                 &id(),
                 &instruction::CollectValidatorFeeMeta {
                     lido: self.solido.pubkey(),
+                    validator_list: self.validator_list.pubkey(),
                     validator_vote_account: validator_vote_account,
                     st_sol_mint: self.st_sol_mint,
                     mint_authority: self.mint_authority,
@@ -1182,9 +1451,11 @@ impl Context {
                     developer_st_sol_account: self.developer_st_sol_account,
                     reserve: self.reserve_address,
                     rewards_withdraw_authority,
+                    validator_fee_st_sol_accounts,
+                    maintainer,
                 },
             )],
-            vec![],
+            self.maintainer.iter().collect(),
         )
         .await?;
         let reserve_balance_after = self.get_sol_balance(self.reserve_address).await;
@@ -1206,11 +1477,8 @@ impl Context {
         &mut self,
         validator_vote_account: Pubkey,
     ) -> transport::Result<StLamports> {
-        let solido_before = self.get_solido().await;
-        let validator_before = solido_before
-            .validators
-            .get(&validator_vote_account)
-            .unwrap();
+        let validators_before = self.get_validators().await;
+        let validator_before = validators_before.get(&validator_vote_account).unwrap();
 
         let validator_balance_before = self
             .get_st_sol_balance(validator_before.entry.fee_address)
@@ -1223,6 +1491,7 @@ impl Context {
                 &id(),
                 &instruction::ClaimValidatorFeeMeta {
                     lido: self.solido.pubkey(),
+                    validator_list: self.validator_list.pubkey(),
                     validator_fee_st_sol_account: validator_before.entry.fee_address,
                     st_sol_mint: self.st_sol_mint,
                     mint_authority: self.mint_authority,
@@ -1231,11 +1500,8 @@ impl Context {
             vec![],
         )
         .await?;
-        let solido_after = self.get_solido().await;
-        let validator_after = solido_after
-            .validators
-            .get(&validator_vote_account)
-            .unwrap();
+        let validators_after = self.get_validators().await;
+        let validator_after = validators_after.get(&validator_vote_account).unwrap();
         // Assert all the credits are taken from.
         assert_eq!(validator_after.entry.fee_credit, StLamports(0));
 
@@ -1258,6 +1524,96 @@ impl Context {
             .expect("Failed to claim validator fee.")
     }
 
+    /// Claim the fee credit of the validator into the given stSOL account,
+    /// signed by `validator_authority`.
+    pub async fn try_claim_validator_fee_to(
+        &mut self,
+        validator_vote_account: Pubkey,
+        validator_authority: &Keypair,
+        recipient_st_sol_account: Pubkey,
+    ) -> transport::Result<()> {
+        send_transaction(
+            &mut self.context,
+            &mut self.nonce,
+            &[instruction::claim_validator_fee_to(
+                &id(),
+                &instruction::ClaimValidatorFeeToMeta {
+                    lido: self.solido.pubkey(),
+                    validator_list: self.validator_list.pubkey(),
+                    validator_vote_account,
+                    validator_authority: validator_authority.pubkey(),
+                    st_sol_mint: self.st_sol_mint,
+                    mint_authority: self.mint_authority,
+                    recipient_st_sol_account,
+                },
+            )],
+            vec![validator_authority],
+        )
+        .await
+    }
+
+    /// Claim the maintainer fee of the context's maintainer into the given stSOL account.
+    pub async fn try_claim_maintainer_fee(
+        &mut self,
+        maintainer_st_sol_account: Pubkey,
+    ) -> transport::Result<()> {
+        let maintainer = self
+            .maintainer
+            .as_ref()
+            .expect("Must have maintainer to call ClaimMaintainerFee.");
+
+        send_transaction(
+            &mut self.context,
+            &mut self.nonce,
+            &[instruction::claim_maintainer_fee(
+                &id(),
+                &instruction::ClaimMaintainerFeeMeta {
+                    lido: self.solido.pubkey(),
+                    maintainer: maintainer.pubkey(),
+                    st_sol_mint: self.st_sol_mint,
+                    mint_authority: self.mint_authority,
+                    maintainer_st_sol_account,
+                },
+            )],
+            vec![maintainer],
+        )
+        .await
+    }
+
+    pub async fn claim_maintainer_fee(&mut self, maintainer_st_sol_account: Pubkey) {
+        self.try_claim_maintainer_fee(maintainer_st_sol_account)
+            .await
+            .expect("Failed to claim maintainer fee.");
+    }
+
+    /// Send a heartbeat for the context's maintainer.
+    pub async fn try_maintainer_heartbeat(&mut self) -> transport::Result<()> {
+        let maintainer = self
+            .maintainer
+            .as_ref()
+            .expect("Must have maintainer to call MaintainerHeartbeat.");
+
+        send_transaction(
+            &mut self.context,
+            &mut self.nonce,
+            &[instruction::maintainer_heartbeat(
+                &id(),
+                &instruction::MaintainerHeartbeatMeta {
+                    lido: self.solido.pubkey(),
+                    maintainer: maintainer.pubkey(),
+                },
+            )],
+            vec![maintainer],
+        )
+        .await
+    }
+
+    pub async fn maintainer_heartbeat(&mut self) {
+        self.try_maintainer_heartbeat()
+            .await
+            .expect("Failed to send maintainer heartbeat.");
+    }
+
     pub async fn try_get_account(&mut self, address: Pubkey) -> Option<Account> {
         self.context
             .banks_client
@@ -1305,6 +1661,16 @@ impl Context {
         try_from_slice_unchecked::<Lido>(lido_account.data.as_slice()).unwrap()
     }
 
+    pub async fn get_exchange_rate_oracle(&mut self) -> ExchangeRateOracle {
+        let oracle_account = self.get_account(self.exchange_rate_oracle).await;
+        try_from_slice_unchecked::<ExchangeRateOracle>(oracle_account.data.as_slice()).unwrap()
+    }
+
+    pub async fn get_validators(&mut self) -> Validators {
+        let validator_list_account = self.get_account(self.validator_list.pubkey()).await;
+        try_from_slice_unchecked::<Validators>(validator_list_account.data.as_slice()).unwrap()
+    }
+
     pub async fn get_rent(&mut self) -> Rent {
         self.context
             .banks_client
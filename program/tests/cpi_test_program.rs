@@ -0,0 +1,133 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+//! A minimal program that calls into Solido through [`lido::cpi`].
+//!
+//! It stands in for the on-chain programs of integrators in the tests. For
+//! every instruction it takes the accounts of the corresponding Solido
+//! instruction, followed by the Solido program account.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use lido::{
+    instruction::{
+        DepositAccountsInfo, DepositAccountsMeta, WithdrawAccountsInfo, WithdrawAccountsMeta,
+    },
+    token::{Lamports, StLamports},
+};
+
+solana_program::declare_id!("CpiTest111111111111111111111111111111111111");
+
+#[derive(Debug, BorshDeserialize, BorshSerialize)]
+pub enum CpiTestInstruction {
+    /// Deposit into Solido with [`lido::cpi::deposit`].
+    Deposit { amount: Lamports },
+
+    /// Withdraw from Solido with [`lido::cpi::withdraw`].
+    Withdraw { amount: StLamports },
+
+    /// Read the exchange rate with [`lido::cpi::get_exchange_rate`], and fail
+    /// unless converting `amount` yields `expected`.
+    ///
+    /// Accounts are the `Lido` account, followed by the Solido program account.
+    CheckExchangeRate {
+        amount: Lamports,
+        expected: StLamports,
+    },
+}
+
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let (solido_program, accounts) = accounts
+        .split_last()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    match CpiTestInstruction::try_from_slice(data)? {
+        CpiTestInstruction::Deposit { amount } => {
+            let accounts = DepositAccountsInfo::try_from_slice(accounts)?;
+            lido::cpi::deposit(solido_program, &accounts, amount, &[])
+        }
+        CpiTestInstruction::Withdraw { amount } => {
+            let accounts = WithdrawAccountsInfo::try_from_slice(accounts)?;
+            lido::cpi::withdraw(solido_program, &accounts, amount, &[])
+        }
+        CpiTestInstruction::CheckExchangeRate { amount, expected } => {
+            let lido = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+            let exchange_rate = lido::cpi::get_exchange_rate(solido_program.key, lido)?;
+            let st_sol_amount = exchange_rate.exchange_sol(amount)?;
+            if st_sol_amount != expected {
+                msg!(
+                    "Expected {} for {}, but got {}.",
+                    expected,
+                    amount,
+                    st_sol_amount
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+            Ok(())
+        }
+    }
+}
+
+fn with_solido_program(
+    mut accounts: Vec<AccountMeta>,
+    solido_program_id: &Pubkey,
+) -> Vec<AccountMeta> {
+    accounts.push(AccountMeta::new_readonly(*solido_program_id, false));
+    accounts
+}
+
+pub fn deposit(
+    solido_program_id: &Pubkey,
+    accounts: &DepositAccountsMeta,
+    amount: Lamports,
+) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: with_solido_program(accounts.to_vec(), solido_program_id),
+        data: CpiTestInstruction::Deposit { amount }.try_to_vec().unwrap(),
+    }
+}
+
+pub fn withdraw(
+    solido_program_id: &Pubkey,
+    accounts: &WithdrawAccountsMeta,
+    amount: StLamports,
+) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: with_solido_program(accounts.to_vec(), solido_program_id),
+        data: CpiTestInstruction::Withdraw { amount }
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+pub fn check_exchange_rate(
+    solido_program_id: &Pubkey,
+    lido: &Pubkey,
+    amount: Lamports,
+    expected: StLamports,
+) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: with_solido_program(
+            vec![AccountMeta::new_readonly(*lido, false)],
+            solido_program_id,
+        ),
+        data: CpiTestInstruction::CheckExchangeRate { amount, expected }
+            .try_to_vec()
+            .unwrap(),
+    }
+}
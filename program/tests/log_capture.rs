@@ -0,0 +1,117 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+//! Captures the program logs that the runtime emits, so tests can inspect them.
+//!
+//! `solana_program_test` prints the program logs, but it does not return them
+//! from `process_transaction`. To still get at them, we install a logger that
+//! keeps the runtime log lines in memory, and that passes every record on to a
+//! regular `env_logger`, so the test output stays the same.
+
+use std::sync::Mutex;
+use std::thread::ThreadId;
+
+use log::{LevelFilter, Log, Metadata, Record};
+use once_cell::sync::OnceCell;
+use solana_sdk::pubkey::Pubkey;
+
+/// The log target under which the runtime logs program output, including the
+/// "Program ... consumed X of Y compute units" lines.
+const RUNTIME_LOG_TARGET: &str = "solana_runtime::message_processor";
+
+/// The filter that `ProgramTest` configures when `RUST_LOG` is not set.
+const DEFAULT_FILTER: &str = "solana_rbpf::vm=debug,\
+    solana_runtime::message_processor=debug,\
+    solana_runtime::system_instruction_processor=trace,\
+    solana_program_test=info";
+
+struct CapturingLogger {
+    inner: env_logger::Logger,
+
+    /// Runtime log lines, with the thread that logged them.
+    ///
+    /// Tests run in parallel, and every test context processes its
+    /// transactions on its own thread, so the thread tells us which lines
+    /// belong together.
+    lines: Mutex<Vec<(ThreadId, String)>>,
+}
+
+impl Log for CapturingLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target() == RUNTIME_LOG_TARGET || self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if record.target() == RUNTIME_LOG_TARGET {
+            self.lines
+                .lock()
+                .unwrap()
+                .push((std::thread::current().id(), record.args().to_string()));
+        }
+        if self.inner.enabled(record.metadata()) {
+            self.inner.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// The installed logger, or `None` if another logger was installed first.
+static LOGGER: OnceCell<Option<&'static CapturingLogger>> = OnceCell::new();
+
+/// Install the capturing logger, if it was not installed yet, and return it.
+fn get_logger() -> Option<&'static CapturingLogger> {
+    *LOGGER.get_or_init(|| {
+        let logger: &'static CapturingLogger = Box::leak(Box::new(CapturingLogger {
+            inner: env_logger::Builder::from_env(
+                env_logger::Env::new().default_filter_or(DEFAULT_FILTER),
+            )
+            .format_timestamp_nanos()
+            .build(),
+            lines: Mutex::new(Vec::new()),
+        }));
+        log::set_logger(logger).ok().map(|()| logger)
+    })
+}
+
+/// Install the capturing logger, if it was not installed yet.
+///
+/// This needs to happen before the first `ProgramTest::new`, because that
+/// installs a logger of its own, and a process can only have one.
+pub fn install() {
+    get_logger();
+
+    // `ProgramTest` sets the maximum level according to `RUST_LOG`, make sure
+    // the runtime logs still reach us if that is more restrictive.
+    if log::max_level() < LevelFilter::Debug {
+        log::set_max_level(LevelFilter::Debug);
+    }
+}
+
+/// Return the compute units that `program_id` consumed, in the first
+/// instruction that it executed after the memo with the given text.
+///
+/// The memo text must be unique across all tests in this process, because
+/// tests run in parallel.
+pub fn get_compute_units_after_memo(memo: &str, program_id: &Pubkey) -> Option<u64> {
+    install();
+    let logger = get_logger()?;
+    let lines = logger.lines.lock().unwrap();
+
+    // The memo program logs the memo text as a quoted string.
+    let memo_quoted = format!("{:?}", memo);
+    let (memo_index, (memo_thread, _)) = lines
+        .iter()
+        .enumerate()
+        .find(|(_, (_, line))| line.contains(&memo_quoted))?;
+
+    let prefix = format!("Program {} consumed ", program_id);
+    lines[memo_index + 1..]
+        .iter()
+        .filter(|(thread, _)| thread == memo_thread)
+        .find_map(|(_, line)| line.strip_prefix(&prefix[..]))
+        .and_then(|rest| rest.split(' ').next())
+        .and_then(|consumed| consumed.parse().ok())
+}
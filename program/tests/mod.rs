@@ -11,4 +11,6 @@
 //
 // By putting everything in a single module, we sidestep this problem.
 pub mod context;
+pub mod cpi_test_program;
+pub mod log_capture;
 pub mod tests;
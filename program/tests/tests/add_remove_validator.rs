@@ -4,6 +4,7 @@
 #![cfg(feature = "test-bpf")]
 
 use solana_program_test::tokio;
+use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signer::Signer;
 
 use crate::assert_solido_error;
@@ -19,18 +20,22 @@ pub const TEST_STAKE_DEPOSIT_AMOUNT: Lamports = Lamports(10_000_000_000);
 async fn test_successful_add_validator() {
     let mut context = Context::new_with_maintainer().await;
 
-    let solido = context.get_solido().await;
-    assert_eq!(solido.validators.len(), 0);
+    let validators = context.get_validators().await;
+    assert_eq!(validators.len(), 0);
 
     let validator = context.add_validator().await;
 
-    let solido = context.get_solido().await;
-    assert_eq!(solido.validators.len(), 1);
-    assert_eq!(solido.validators.entries[0].pubkey, validator.vote_account);
+    let validators = context.get_validators().await;
+    assert_eq!(validators.len(), 1);
+    assert_eq!(validators.entries[0].pubkey, validator.vote_account);
     assert_eq!(
-        solido.validators.entries[0].entry.fee_address,
+        validators.entries[0].entry.fee_address,
         validator.fee_account
     );
+    assert_eq!(
+        validators.entries[0].entry.vote_withdrawer,
+        validator.vote_withdrawer
+    );
 
     // Adding the validator a second time should fail.
     let result = context.try_add_validator(&validator).await;
@@ -41,8 +46,8 @@ async fn test_successful_add_validator() {
 async fn test_add_validator_with_invalid_owner() {
     let mut context = Context::new_with_maintainer().await;
 
-    let solido = context.get_solido().await;
-    assert_eq!(solido.validators.len(), 0);
+    let validators = context.get_validators().await;
+    assert_eq!(validators.len(), 0);
     let node_key = context.deterministic_keypair.new_keypair();
     let real_vote_account = context
         .create_vote_account(&node_key, context.withdraw_authority, 100)
@@ -62,23 +67,103 @@ async fn test_add_validator_with_invalid_owner() {
             node_account: node_account,
             vote_account: invalid_vote_account,
             fee_account: fee_account,
+            vote_withdrawer: context.deterministic_keypair.new_keypair().pubkey(),
         })
         .await;
     assert_solido_error!(result, LidoError::ValidatorVoteAccountHasDifferentOwner);
 }
 
+#[tokio::test]
+async fn test_add_validator_requires_validator_authority() {
+    let mut context = Context::new_with_maintainer().await;
+
+    let node_account = context.deterministic_keypair.new_keypair();
+    let fee_account = context.create_st_sol_account(node_account.pubkey()).await;
+    let vote_account = context
+        .create_vote_account(&node_account, context.withdraw_authority, 100)
+        .await;
+    let vote_withdrawer = context.deterministic_keypair.new_keypair();
+    let accounts = ValidatorAccounts {
+        node_account,
+        vote_account,
+        fee_account,
+        vote_withdrawer: vote_withdrawer.pubkey(),
+    };
+
+    // The manager cannot pick a withdrawer on behalf of the validator.
+    let other = context.deterministic_keypair.new_keypair();
+    let result = context
+        .try_add_validator_with_authority(&accounts, &other)
+        .await;
+    assert_solido_error!(result, LidoError::InvalidValidatorAuthority);
+
+    // The vote withdrawer itself can sign.
+    context
+        .try_add_validator_with_authority(&accounts, &vote_withdrawer)
+        .await
+        .unwrap();
+    assert_eq!(context.get_validators().await.len(), 1);
+}
+
 #[tokio::test]
 async fn test_successful_remove_validator() {
     let mut context = Context::new_with_maintainer_and_validator().await;
-    let validator = &context.get_solido().await.validators.entries[0];
+    let validator = &context.get_validators().await.entries[0];
     context.deactivate_validator(validator.pubkey).await;
     context
         .try_remove_validator(validator.pubkey)
         .await
         .unwrap();
 
-    let solido = context.get_solido().await;
-    assert_eq!(solido.validators.len(), 0);
+    let validators = context.get_validators().await;
+    assert_eq!(validators.len(), 0);
+
+    // The vote account is handed back to the withdrawer that the validator
+    // registered.
+    let vote_account = context.get_vote_account(validator.pubkey).await.unwrap();
+    assert_eq!(
+        vote_account.authorized_withdrawer,
+        context.validator.as_ref().unwrap().vote_withdrawer
+    );
+}
+
+#[tokio::test]
+async fn test_remove_validator_without_vote_withdrawer() {
+    let mut context = Context::new_with_maintainer().await;
+
+    // Validators that were added before the vote withdrawer existed, or that
+    // were upgraded from an older layout, did not register one.
+    let node_account = context.deterministic_keypair.new_keypair();
+    let node_pubkey = node_account.pubkey();
+    let fee_account = context.create_st_sol_account(node_pubkey).await;
+    let vote_account = context
+        .create_vote_account(&node_account, context.withdraw_authority, 100)
+        .await;
+    context
+        .try_add_validator(&ValidatorAccounts {
+            node_account,
+            vote_account,
+            fee_account,
+            vote_withdrawer: Pubkey::default(),
+        })
+        .await
+        .unwrap();
+    context.deactivate_validator(vote_account).await;
+
+    // The vote account can only be handed to the validator identity.
+    let other_withdrawer = context.deterministic_keypair.new_keypair().pubkey();
+    let result = context
+        .try_remove_validator_to(vote_account, other_withdrawer)
+        .await;
+    assert_solido_error!(result, LidoError::InvalidVoteWithdrawer);
+
+    context
+        .try_remove_validator_to(vote_account, node_pubkey)
+        .await
+        .unwrap();
+    assert_eq!(context.get_validators().await.len(), 0);
+    let vote_state = context.get_vote_account(vote_account).await.unwrap();
+    assert_eq!(vote_state.authorized_withdrawer, node_pubkey);
 }
 
 #[tokio::test]
@@ -106,16 +191,16 @@ async fn test_remove_validator_with_unclaimed_credits() {
     context.update_exchange_rate().await;
     context.collect_validator_fee(validator.vote_account).await;
 
-    let solido = context.get_solido().await;
-    let vote_account = solido.validators.entries[0].pubkey;
+    let validators = context.get_validators().await;
+    let vote_account = validators.entries[0].pubkey;
     assert_eq!(
-        solido.validators.entries[0].entry.fee_credit,
+        validators.entries[0].entry.fee_credit,
         StLamports(62_301_530_769)
     );
 
     context.deactivate_validator(vote_account).await;
-    // let solido = context.get_solido().await;
-    // let vote_account = solido.validators.entries[0].pubkey;
+    // let validators = context.get_validators().await;
+    // let vote_account = validators.entries[0].pubkey;
     let result = context.try_remove_validator(vote_account).await;
     assert_solido_error!(result, LidoError::ValidatorHasUnclaimedCredit);
 }
@@ -123,7 +208,7 @@ async fn test_remove_validator_with_unclaimed_credits() {
 #[tokio::test]
 async fn test_removing_validator_with_stake_accounts_should_fail() {
     let (mut context, _) = Context::new_with_two_stake_accounts().await;
-    let validator = &context.get_solido().await.validators.entries[0];
+    let validator = &context.get_validators().await.entries[0];
     let result = context.try_remove_validator(validator.pubkey).await;
 
     // The validator should not be able to be removed if it is still active
@@ -138,19 +223,101 @@ async fn test_deactivate_validator() {
     let validator = context.add_validator().await;
 
     // Initially, the validator should be active.
-    let solido = context.get_solido().await;
-    assert_eq!(solido.validators.len(), 1);
-    assert!(solido.validators.entries[0].entry.active);
+    let validators = context.get_validators().await;
+    assert_eq!(validators.len(), 1);
+    assert!(validators.entries[0].entry.active);
 
     context.deactivate_validator(validator.vote_account).await;
 
     // After deactivation, it should be inactive.
     let solido = context.get_solido().await;
-    assert_eq!(solido.validators.len(), 1);
-    assert!(!solido.validators.entries[0].entry.active);
+    let validators = context.get_validators().await;
+    assert_eq!(validators.len(), 1);
+    assert!(!validators.entries[0].entry.active);
 
     // Deactivation is idempotent.
     context.deactivate_validator(validator.vote_account).await;
     let solido_after_second_deactivation = context.get_solido().await;
     assert_eq!(solido, solido_after_second_deactivation);
 }
+
+#[tokio::test]
+async fn test_force_remove_validator() {
+    let mut context = Context::new_with_maintainer().await;
+    let validator = context.add_validator().await;
+    let initial_amount = Lamports(10_000_000_000);
+    context.deposit(initial_amount).await;
+    context
+        .stake_deposit(validator.vote_account, StakeDeposit::Append, initial_amount)
+        .await;
+
+    // Earn some fees, and unstake part of the stake, so the validator has a
+    // fee credit, a stake account, and an unstake account.
+    context
+        .context
+        .increment_vote_account_credits(&validator.vote_account, 1);
+    context.advance_to_normal_epoch(0);
+    context.update_exchange_rate().await;
+    context.collect_validator_fee(validator.vote_account).await;
+    context
+        .unstake(validator.vote_account, Lamports(1_000_000_000))
+        .await;
+
+    let validators = context.get_validators().await;
+    let fee_credit = validators.entries[0].entry.fee_credit;
+    assert!(fee_credit > StLamports(0));
+
+    context
+        .try_force_remove_validator(validator.vote_account)
+        .await
+        .expect("Failed to force remove validator.");
+
+    // The fee is paid out, and the validator is now an inactive tombstone.
+    assert_eq!(
+        context.get_st_sol_balance(validator.fee_account).await,
+        fee_credit
+    );
+    let validators = context.get_validators().await;
+    let entry = &validators.entries[0].entry;
+    assert!(!entry.active);
+    assert!(entry.tombstone);
+    assert_eq!(entry.fee_credit, StLamports(0));
+
+    // All of its stake is deactivating.
+    let stake_account = context
+        .get_stake_account_from_seed(&validators.entries[0], 0)
+        .await;
+    assert_eq!(stake_account.balance.active, Lamports(0));
+    assert_eq!(
+        stake_account.balance.deactivating,
+        (stake_account.balance.total() - stake_account.balance.inactive).unwrap()
+    );
+
+    // It can't be force removed twice, and we can't remove it yet while it
+    // still has stake.
+    let result = context
+        .try_force_remove_validator(validator.vote_account)
+        .await;
+    assert_solido_error!(result, LidoError::ValidatorIsTombstone);
+    let result = context.try_remove_validator(validator.vote_account).await;
+    assert_solido_error!(result, LidoError::ValidatorShouldHaveNoStakeAccounts);
+
+    // Once the stake is inactive, we can withdraw it all, and remove the validator.
+    context.advance_to_normal_epoch(1);
+    context.update_exchange_rate().await;
+    context
+        .withdraw_inactive_stake(validator.vote_account)
+        .await;
+    let validators = context.get_validators().await;
+    let entry = &validators.entries[0].entry;
+    assert_eq!(entry.stake_seeds.begin, entry.stake_seeds.end);
+    assert_eq!(entry.unstake_seeds.begin, entry.unstake_seeds.end);
+    assert_eq!(entry.stake_accounts_balance, Lamports(0));
+
+    context
+        .try_remove_validator(validator.vote_account)
+        .await
+        .expect("Failed to remove validator.");
+    let validators = context.get_validators().await;
+    assert_eq!(validators.len(), 0);
+}
@@ -32,6 +32,7 @@ async fn test_successful_change_reward_distribution() {
         validation_fee: 44,
         developer_fee: 54,
         st_sol_appreciation: 122,
+        maintainer_fee: 13,
     };
 
     let new_treasury_owner = context.deterministic_keypair.new_keypair();
@@ -7,9 +7,11 @@ use crate::assert_solido_error;
 use crate::context::{Context, StakeDeposit};
 
 use lido::error::LidoError;
+use lido::state::FeePayoutMode;
 use lido::token::{Lamports, StLamports};
 
 use solana_program_test::tokio;
+use solana_sdk::signer::Signer;
 
 #[tokio::test]
 async fn test_collect_validator_fee() {
@@ -18,9 +20,12 @@ async fn test_collect_validator_fee() {
 
     // If we try to collect fee initially, that should work, but there is nothing to update.
     let solido_before = context.get_solido().await;
+    let validators_before = context.get_validators().await;
     context.collect_validator_fee(validator.vote_account).await;
     let solido_after = context.get_solido().await;
+    let validators_after = context.get_validators().await;
     assert_eq!(solido_before, solido_after);
+    assert_eq!(validators_before, validators_after);
 
     // Deposit and stake the deposit with the validator. This creates one stake account.
     let initial_amount = Lamports(1_000_000_000);
@@ -67,8 +72,8 @@ async fn test_collect_validator_fee() {
     let developer_before = context
         .get_st_sol_balance(context.developer_st_sol_account)
         .await;
-    let solido_before = context.get_solido().await;
-    let validator_before = solido_before.validators.entries[0].entry.fee_credit;
+    let validators_before = context.get_validators().await;
+    let validator_before = validators_before.entries[0].entry.fee_credit;
 
     context.advance_to_normal_epoch(1);
     let account = context.get_account(validator.vote_account).await;
@@ -88,7 +93,8 @@ async fn test_collect_validator_fee() {
         .get_st_sol_balance(context.developer_st_sol_account)
         .await;
     let solido_after = context.get_solido().await;
-    let validator_after = solido_after.validators.entries[0].entry.fee_credit;
+    let validators_after = context.get_validators().await;
+    let validator_after = validators_after.entries[0].entry.fee_credit;
 
     // The treasury balance increase, when converted back to SOL, should be equal
     // to 3% of the rewards. Two lamports differ due to rounding errors.
@@ -119,6 +125,21 @@ async fn test_collect_validator_fee() {
     // Four lamports differ due to rounding errors.
     assert_eq!(validator_fee_sol, Lamports(rewards.0 / 100 * 5 + 4));
 
+    // The per-validator metrics should have recorded the rewards and the fee.
+    let epoch = context.get_clock().await.epoch;
+    let metrics_before = &validators_before.entries[0].entry.metrics;
+    let metrics_after = &validators_after.entries[0].entry.metrics;
+    assert_eq!(
+        (metrics_after.rewards_total - metrics_before.rewards_total).unwrap(),
+        rewards
+    );
+    assert_eq!(
+        (metrics_after.fee_st_sol_total - metrics_before.fee_st_sol_total).unwrap(),
+        validator_fee
+    );
+    assert_eq!(metrics_after.last_rewards_epoch, epoch);
+    assert_eq!(metrics_after.last_active_epoch, epoch);
+
     // Claim validator fee
     let claimed_fee = context.claim_validator_fee(validator.vote_account).await;
     assert_eq!(
@@ -149,7 +170,7 @@ async fn test_collect_validator_fee_withdraws_donations_to_the_reserve() {
     let developer_before = context
         .get_st_sol_balance(context.developer_st_sol_account)
         .await;
-    let solido_before = context.get_solido().await;
+    let validators_before = context.get_validators().await;
 
     let vote_donation = context.collect_validator_fee(validator.vote_account).await;
     let reserve_after = context.get_sol_balance(context.reserve_address).await;
@@ -158,7 +179,7 @@ async fn test_collect_validator_fee_withdraws_donations_to_the_reserve() {
     assert_eq!(reserve_after, (reserve_before + vote_donation).unwrap());
     assert_eq!(vote_donation, donation);
 
-    let solido_after = context.get_solido().await;
+    let validators_after = context.get_validators().await;
     let treasury_after = context
         .get_st_sol_balance(context.treasury_st_sol_account)
         .await;
@@ -172,14 +193,11 @@ async fn test_collect_validator_fee_withdraws_donations_to_the_reserve() {
     // and 2% developer.
     assert_eq!(treasury_before, StLamports(0));
     assert_eq!(developer_before, StLamports(0));
-    assert_eq!(
-        solido_before.validators.entries[0].entry.fee_credit,
-        StLamports(0)
-    );
+    assert_eq!(validators_before.entries[0].entry.fee_credit, StLamports(0));
     assert_eq!(treasury_after, StLamports(3_000));
     assert_eq!(developer_after, StLamports(2_000));
     assert_eq!(
-        solido_after.validators.entries[0].entry.fee_credit,
+        validators_after.entries[0].entry.fee_credit,
         StLamports(5_000)
     );
 }
@@ -200,3 +218,179 @@ async fn test_collect_validator_fee_rejects_foreign_validators() {
     let result = context.try_collect_validator_fee(vote_account).await;
     assert_solido_error!(result, LidoError::InvalidAccountMember);
 }
+
+#[tokio::test]
+async fn test_collect_validator_fee_pays_directly_in_direct_mode() {
+    let mut context = Context::new_with_maintainer().await;
+    let validator = context.add_validator().await;
+    context.change_fee_payout_mode(FeePayoutMode::Direct).await;
+
+    let initial_amount = Lamports(2_000_000_000);
+    context.deposit(initial_amount).await;
+
+    // Donate to the vote account, so there is something to collect.
+    let donation = Lamports(100_000);
+    context.fund(validator.vote_account, donation).await;
+
+    let fee_account_before = context.get_st_sol_balance(validator.fee_account).await;
+    context.collect_validator_fee(validator.vote_account).await;
+    let fee_account_after = context.get_st_sol_balance(validator.fee_account).await;
+
+    // The validation fee is 5% of the donation. Instead of accruing it as
+    // credit, it should have been minted to the validator's fee account.
+    let validators = context.get_validators().await;
+    assert_eq!(fee_account_before, StLamports(0));
+    assert_eq!(fee_account_after, StLamports(5_000));
+    assert_eq!(validators.entries[0].entry.fee_credit, StLamports(0));
+}
+
+#[tokio::test]
+async fn test_collect_validator_fee_credits_validators_outside_the_page() {
+    let mut context = Context::new_with_maintainer().await;
+    let v1 = context.add_validator().await;
+    let v2 = context.add_validator().await;
+    context.change_fee_payout_mode(FeePayoutMode::Direct).await;
+
+    let initial_amount = Lamports(2_000_000_000);
+    context.deposit(initial_amount).await;
+
+    let donation = Lamports(100_000);
+    context.fund(v1.vote_account, donation).await;
+
+    // Pass only the fee account of the second validator, the first one should
+    // get its share as credit instead.
+    context
+        .try_collect_validator_fee_with_fee_accounts(v1.vote_account, vec![v2.fee_account])
+        .await
+        .unwrap();
+
+    // The validation fee is 5% of the donation, split over two validators.
+    let validators = context.get_validators().await;
+    assert_eq!(
+        context.get_st_sol_balance(v1.fee_account).await,
+        StLamports(0)
+    );
+    assert_eq!(
+        context.get_st_sol_balance(v2.fee_account).await,
+        StLamports(2_500)
+    );
+    assert_eq!(
+        validators.get(&v1.vote_account).unwrap().entry.fee_credit,
+        StLamports(2_500)
+    );
+    assert_eq!(
+        validators.get(&v2.vote_account).unwrap().entry.fee_credit,
+        StLamports(0)
+    );
+
+    // The credit can still be claimed in direct mode.
+    let claimed = context.claim_validator_fee(v1.vote_account).await;
+    assert_eq!(claimed, StLamports(2_500));
+}
+
+#[tokio::test]
+async fn test_collect_validator_fee_pays_out_credit_in_the_page() {
+    let mut context = Context::new_with_maintainer().await;
+    let v1 = context.add_validator().await;
+    let v2 = context.add_validator().await;
+    context.change_fee_payout_mode(FeePayoutMode::Direct).await;
+
+    let initial_amount = Lamports(2_000_000_000);
+    context.deposit(initial_amount).await;
+
+    // The first validator is not in the page, so it gets its share as credit.
+    let donation = Lamports(100_000);
+    context.fund(v1.vote_account, donation).await;
+    context
+        .try_collect_validator_fee_with_fee_accounts(v1.vote_account, vec![v2.fee_account])
+        .await
+        .unwrap();
+
+    // When it is in the page of the next collection, it gets both its credit
+    // and its new share, so it has nothing left to claim.
+    context.fund(v2.vote_account, donation).await;
+    context
+        .try_collect_validator_fee_with_fee_accounts(
+            v2.vote_account,
+            vec![v1.fee_account, v2.fee_account],
+        )
+        .await
+        .unwrap();
+
+    let validators = context.get_validators().await;
+    assert_eq!(
+        context.get_st_sol_balance(v1.fee_account).await,
+        StLamports(5_000)
+    );
+    assert_eq!(
+        context.get_st_sol_balance(v2.fee_account).await,
+        StLamports(5_000)
+    );
+    assert_eq!(
+        validators.get(&v1.vote_account).unwrap().entry.fee_credit,
+        StLamports(0)
+    );
+    assert_eq!(
+        validators.get(&v2.vote_account).unwrap().entry.fee_credit,
+        StLamports(0)
+    );
+}
+
+#[tokio::test]
+async fn test_collect_validator_fee_rejects_too_many_fee_accounts() {
+    let mut context = Context::new_with_maintainer().await;
+    let validator = context.add_validator().await;
+    context.change_fee_payout_mode(FeePayoutMode::Direct).await;
+
+    let donation = Lamports(100_000);
+    context.fund(validator.vote_account, donation).await;
+
+    let fee_accounts = vec![validator.fee_account; lido::MAXIMUM_DIRECT_FEE_ACCOUNTS + 1];
+    let result = context
+        .try_collect_validator_fee_with_fee_accounts(validator.vote_account, fee_accounts)
+        .await;
+    assert_solido_error!(result, LidoError::TooManyFeeAccounts);
+}
+
+#[tokio::test]
+async fn test_validator_claims_fee_to_recipient_of_its_choice() {
+    let mut context = Context::new_with_maintainer().await;
+    let validator = context.add_validator().await;
+    let initial_amount = Lamports(1_000_000_000);
+    context.deposit(initial_amount).await;
+    context
+        .stake_deposit(validator.vote_account, StakeDeposit::Append, initial_amount)
+        .await;
+
+    // Earn some rewards, so the validator has a fee credit.
+    context
+        .context
+        .increment_vote_account_credits(&validator.vote_account, 1);
+    context.advance_to_normal_epoch(0);
+    context.update_exchange_rate().await;
+    context.collect_validator_fee(validator.vote_account).await;
+    let fee_credit = context.get_validators().await.entries[0].entry.fee_credit;
+    assert!(fee_credit > StLamports(0));
+
+    let owner = context.deterministic_keypair.new_keypair();
+    let recipient = context.create_st_sol_account(owner.pubkey()).await;
+
+    // Somebody who is not the validator cannot claim its fees.
+    let result = context
+        .try_claim_validator_fee_to(validator.vote_account, &owner, recipient)
+        .await;
+    assert_solido_error!(result, LidoError::InvalidValidatorAuthority);
+
+    // But the validator identity can, to any account.
+    context
+        .try_claim_validator_fee_to(validator.vote_account, &validator.node_account, recipient)
+        .await
+        .expect("Failed to claim validator fee.");
+    assert_eq!(context.get_st_sol_balance(recipient).await, fee_credit);
+    assert_eq!(
+        context.get_st_sol_balance(validator.fee_account).await,
+        StLamports(0)
+    );
+    let validators = context.get_validators().await;
+    assert_eq!(validators.entries[0].entry.fee_credit, StLamports(0));
+}
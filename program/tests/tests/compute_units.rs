@@ -0,0 +1,192 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+#![cfg(feature = "test-bpf")]
+
+//! Benchmarks of the compute units consumed per instruction, for a growing
+//! number of validators.
+//!
+//! The runtime logs "Program ... consumed X of Y compute units" after every
+//! instruction. Before every instruction that we measure, we send a memo, so the
+//! numbers in the logs can be attributed to the instruction and validator count.
+//! We read them back from the logs, and confirm that every instruction stays
+//! within its budget. Run with `--nocapture` to see the numbers.
+
+use crate::context::{id, Context, StakeDeposit};
+use crate::log_capture::get_compute_units_after_memo;
+
+use lido::token::{Lamports, StLamports};
+
+use solana_program_test::tokio;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+
+/// Upper bound on the compute units that an instruction may consume, as a
+/// constant part plus a part per validator.
+///
+/// When an instruction exceeds its budget, check whether the increase is
+/// expected, and if so, raise the budget here. The maintenance daemon uses the
+/// same numbers to decide how many instructions fit in a transaction, see
+/// `cli/src/maintenance.rs`, so update them there too.
+struct Budget {
+    base: u64,
+    per_validator: u64,
+}
+
+const DEPOSIT_BUDGET: Budget = Budget {
+    base: 40_000,
+    per_validator: 0,
+};
+const UPDATE_EXCHANGE_RATE_BUDGET: Budget = Budget {
+    base: 40_000,
+    per_validator: 200,
+};
+const WITHDRAW_BUDGET: Budget = Budget {
+    base: 60_000,
+    per_validator: 250,
+};
+const CLAIM_VALIDATOR_FEE_BUDGET: Budget = Budget {
+    base: 40_000,
+    per_validator: 20,
+};
+const COLLECT_VALIDATOR_FEE_BUDGET: Budget = Budget {
+    base: 50_000,
+    per_validator: 200,
+};
+const WITHDRAW_INACTIVE_STAKE_BUDGET: Budget = Budget {
+    base: 50_000,
+    per_validator: 200,
+};
+const MERGE_STAKE_BUDGET: Budget = Budget {
+    base: 60_000,
+    per_validator: 200,
+};
+
+/// Confirm that the instruction executed after the memo succeeded, and that it
+/// stayed within its budget.
+fn assert_within_budget<T, E: std::fmt::Debug>(
+    result: Result<T, E>,
+    memo: &str,
+    num_validators: usize,
+    budget: Budget,
+) {
+    if let Err(err) = result {
+        panic!("{} failed: {:?}", memo, err);
+    }
+    let consumed = get_compute_units_after_memo(memo, &id())
+        .unwrap_or_else(|| panic!("{}: no compute units found in the logs.", memo));
+    let limit = budget.base + budget.per_validator * num_validators as u64;
+    println!(
+        "{}: consumed {} compute units, budget is {}.",
+        memo, consumed, limit
+    );
+    assert!(
+        consumed <= limit,
+        "{} consumed {} compute units, but the budget is {}.",
+        memo,
+        consumed,
+        limit,
+    );
+}
+
+async fn bench_instructions(num_validators: usize) {
+    let mut context = Context::new_with_maintainer().await;
+
+    // Stake with the first validator before we add the others. StakeDeposit
+    // needs to find the validator with the least stake, and we only want to
+    // measure the instructions below, not the setup.
+    let first_validator = context.add_validator().await;
+    // Two stake accounts that activate in the same epoch, so we can measure
+    // merging them at the end.
+    let stake_amount = Lamports(50 * LAMPORTS_PER_SOL);
+    let (user, st_sol_account) = context.deposit(Lamports(100 * LAMPORTS_PER_SOL)).await;
+    let stake_account = context
+        .stake_deposit(
+            first_validator.vote_account,
+            StakeDeposit::Append,
+            stake_amount,
+        )
+        .await;
+    context
+        .stake_deposit(
+            first_validator.vote_account,
+            StakeDeposit::Append,
+            stake_amount,
+        )
+        .await;
+
+    // The last validator is the most expensive one to find with a linear scan.
+    let mut last_vote_account = first_validator.vote_account;
+    for _ in 1..num_validators {
+        last_vote_account = context.add_validator().await.vote_account;
+    }
+
+    let memo = format!("{} validators: Deposit", num_validators);
+    context.memo(&memo).await;
+    let result = context.try_deposit(Lamports(LAMPORTS_PER_SOL)).await;
+    assert_within_budget(result, &memo, num_validators, DEPOSIT_BUDGET);
+
+    context.advance_to_normal_epoch(0);
+    let memo = format!("{} validators: UpdateExchangeRate", num_validators);
+    context.memo(&memo).await;
+    let result = context.try_update_exchange_rate().await;
+    assert_within_budget(result, &memo, num_validators, UPDATE_EXCHANGE_RATE_BUDGET);
+
+    let memo = format!("{} validators: WithdrawInactiveStake", num_validators);
+    context.memo(&memo).await;
+    let result = context
+        .try_withdraw_inactive_stake(first_validator.vote_account)
+        .await;
+    assert_within_budget(
+        result,
+        &memo,
+        num_validators,
+        WITHDRAW_INACTIVE_STAKE_BUDGET,
+    );
+
+    let memo = format!("{} validators: CollectValidatorFee", num_validators);
+    context.memo(&memo).await;
+    let result = context.try_collect_validator_fee(last_vote_account).await;
+    assert_within_budget(result, &memo, num_validators, COLLECT_VALIDATOR_FEE_BUDGET);
+
+    let memo = format!("{} validators: Withdraw", num_validators);
+    context.memo(&memo).await;
+    let result = context
+        .try_withdraw(
+            &user,
+            st_sol_account,
+            StLamports(LAMPORTS_PER_SOL),
+            first_validator.vote_account,
+            stake_account,
+        )
+        .await;
+    assert_within_budget(result, &memo, num_validators, WITHDRAW_BUDGET);
+
+    let memo = format!("{} validators: ClaimValidatorFee", num_validators);
+    context.memo(&memo).await;
+    let result = context.try_claim_validator_fee(last_vote_account).await;
+    assert_within_budget(result, &memo, num_validators, CLAIM_VALIDATOR_FEE_BUDGET);
+
+    // The withdrawal left the first stake account, at the begin of the seed
+    // range, so we can merge it into the second one.
+    let validators = context.get_validators().await;
+    let validator = validators.get(&first_validator.vote_account).unwrap();
+    let memo = format!("{} validators: MergeStake", num_validators);
+    context.memo(&memo).await;
+    let result = context.try_merge_stake(validator, 0, 1).await;
+    assert_within_budget(result, &memo, num_validators, MERGE_STAKE_BUDGET);
+}
+
+#[tokio::test]
+async fn test_compute_units_10_validators() {
+    bench_instructions(10).await;
+}
+
+#[tokio::test]
+async fn test_compute_units_100_validators() {
+    bench_instructions(100).await;
+}
+
+#[tokio::test]
+async fn test_compute_units_500_validators() {
+    bench_instructions(500).await;
+}
@@ -0,0 +1,143 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+#![cfg(feature = "test-bpf")]
+
+use solana_program::stake::state::StakeState;
+use solana_program_test::tokio;
+use solana_sdk::signer::Signer;
+
+use crate::context::{id, send_transaction, Context, StakeDeposit};
+use crate::cpi_test_program;
+use lido::instruction::{DepositAccountsMeta, WithdrawAccountsMeta};
+use lido::token::{Lamports, StLamports};
+
+const TEST_DEPOSIT_AMOUNT: Lamports = Lamports(100_000_000_000);
+
+#[tokio::test]
+async fn test_deposit_via_cpi() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+
+    let user = context.deterministic_keypair.new_keypair();
+    let recipient = context.create_st_sol_account(user.pubkey()).await;
+    context.fund(user.pubkey(), TEST_DEPOSIT_AMOUNT).await;
+
+    send_transaction(
+        &mut context.context,
+        &mut context.nonce,
+        &[cpi_test_program::deposit(
+            &id(),
+            &DepositAccountsMeta {
+                lido: context.solido.pubkey(),
+                user: user.pubkey(),
+                recipient,
+                st_sol_mint: context.st_sol_mint,
+                reserve_account: context.reserve_address,
+                mint_authority: context.mint_authority,
+            },
+            TEST_DEPOSIT_AMOUNT,
+        )],
+        vec![&user],
+    )
+    .await
+    .expect("Failed to deposit through the CPI test program.");
+
+    // Initially the exchange rate is 1:1.
+    let st_sol_balance = context.get_st_sol_balance(recipient).await;
+    assert_eq!(st_sol_balance, StLamports(TEST_DEPOSIT_AMOUNT.0));
+
+    let solido = context.get_solido().await;
+    assert_eq!(solido.metrics.deposit_amount.total, TEST_DEPOSIT_AMOUNT);
+}
+
+#[tokio::test]
+async fn test_withdraw_via_cpi() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let (user, st_sol_account) = context.deposit(TEST_DEPOSIT_AMOUNT).await;
+    let vote_account = context.validator.as_ref().unwrap().vote_account;
+    let stake_account = context
+        .stake_deposit(vote_account, StakeDeposit::Append, TEST_DEPOSIT_AMOUNT)
+        .await;
+    context.advance_to_normal_epoch(0);
+    context.update_exchange_rate().await;
+
+    // The stake program needs at least the rent-exempt balance in the new account.
+    let rent = context.get_rent().await;
+    let amount = StLamports(rent.minimum_balance(std::mem::size_of::<StakeState>()) + 1);
+    let new_stake = context.deterministic_keypair.new_keypair();
+
+    send_transaction(
+        &mut context.context,
+        &mut context.nonce,
+        &[cpi_test_program::withdraw(
+            &id(),
+            &WithdrawAccountsMeta {
+                lido: context.solido.pubkey(),
+                validator_list: context.validator_list.pubkey(),
+                st_sol_account_owner: user.pubkey(),
+                st_sol_account,
+                st_sol_mint: context.st_sol_mint,
+                validator_vote_account: vote_account,
+                source_stake_account: stake_account,
+                destination_stake_account: new_stake.pubkey(),
+                stake_authority: context.stake_authority,
+            },
+            amount,
+        )],
+        vec![&user, &new_stake],
+    )
+    .await
+    .expect("Failed to withdraw through the CPI test program.");
+
+    let st_sol_balance = context.get_st_sol_balance(st_sol_account).await;
+    assert_eq!(st_sol_balance, StLamports(TEST_DEPOSIT_AMOUNT.0 - amount.0));
+    let new_stake_balance = context.get_sol_balance(new_stake.pubkey()).await;
+    assert_eq!(new_stake_balance, Lamports(amount.0));
+}
+
+#[tokio::test]
+async fn test_get_exchange_rate_via_cpi() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    context.deposit(TEST_DEPOSIT_AMOUNT).await;
+
+    // Donate to the reserve, so the exchange rate is no longer 1:1 after the update.
+    context
+        .fund(context.reserve_address, Lamports(TEST_DEPOSIT_AMOUNT.0 / 2))
+        .await;
+    context.advance_to_normal_epoch(0);
+    context.update_exchange_rate().await;
+
+    let amount = Lamports(1_000_000_000);
+    let solido = context.get_solido().await;
+    let expected = solido.exchange_rate.exchange_sol(amount).unwrap();
+    assert!(expected < StLamports(amount.0));
+
+    send_transaction(
+        &mut context.context,
+        &mut context.nonce,
+        &[cpi_test_program::check_exchange_rate(
+            &id(),
+            &context.solido.pubkey(),
+            amount,
+            expected,
+        )],
+        vec![],
+    )
+    .await
+    .expect("The CPI test program should read the same exchange rate.");
+
+    // Confirm that the test program does check the value.
+    let result = send_transaction(
+        &mut context.context,
+        &mut context.nonce,
+        &[cpi_test_program::check_exchange_rate(
+            &id(),
+            &context.solido.pubkey(),
+            amount,
+            StLamports(amount.0),
+        )],
+        vec![],
+    )
+    .await;
+    assert!(result.is_err());
+}
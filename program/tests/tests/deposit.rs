@@ -52,3 +52,41 @@ async fn test_deposit_fails_with_wrong_reserve() {
 
     assert_solido_error!(result, LidoError::InvalidReserveAccount);
 }
+
+/// Without a deposit cutoff, a user could deposit just before the epoch ends,
+/// or after it ended but before the exchange rate is updated, and withdraw
+/// after the update, capturing rewards that the deposit did not earn.
+#[tokio::test]
+async fn test_deposit_cutoff_prevents_capturing_rewards_at_epoch_boundary() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    context.change_deposit_cutoff(10).await;
+
+    context.advance_to_normal_epoch(0);
+    context.update_exchange_rate().await;
+    context.deposit(TEST_DEPOSIT_AMOUNT).await;
+
+    // Close to the end of the epoch, deposits are refused.
+    let epoch_schedule = context.context.genesis_config().epoch_schedule;
+    let epoch_end = epoch_schedule.first_normal_slot + epoch_schedule.slots_per_epoch;
+    context
+        .context
+        .warp_to_slot(epoch_end - 5)
+        .expect("Failed to warp to slot.");
+    let result = context.try_deposit(TEST_DEPOSIT_AMOUNT).await;
+    assert_solido_error!(result, LidoError::DepositCutoff);
+
+    // Rewards arrive, and the epoch ends. Before the exchange rate reflects
+    // the rewards, deposits are still refused.
+    context
+        .fund(context.reserve_address, TEST_DEPOSIT_AMOUNT)
+        .await;
+    context.advance_to_normal_epoch(1);
+    let result = context.try_deposit(TEST_DEPOSIT_AMOUNT).await;
+    assert_solido_error!(result, LidoError::ExchangeRateNotUpdatedInThisEpoch);
+
+    // After the update, the deposit is priced including the rewards.
+    context.update_exchange_rate().await;
+    let (_, recipient) = context.deposit(TEST_DEPOSIT_AMOUNT).await;
+    let st_sol_balance = context.get_st_sol_balance(recipient).await;
+    assert!(st_sol_balance.0 < TEST_DEPOSIT_AMOUNT.0);
+}
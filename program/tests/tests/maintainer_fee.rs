@@ -0,0 +1,151 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+#![cfg(feature = "test-bpf")]
+
+use solana_program_test::tokio;
+use solana_sdk::signature::Signer;
+
+use lido::error::LidoError;
+use lido::state::RewardDistribution;
+use lido::token::{Lamports, StLamports};
+
+use crate::assert_solido_error;
+use crate::context::{Context, StakeDeposit};
+
+#[tokio::test]
+async fn test_maintainer_fee_is_accrued_per_operation_and_claimable() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let vote_account = context.validator.as_ref().unwrap().vote_account;
+    let maintainer = context.maintainer.as_ref().unwrap().pubkey();
+
+    // Take 1% of the rewards from the stSOL appreciation for the maintainers.
+    let solido = context.get_solido().await;
+    let reward_distribution = RewardDistribution {
+        st_sol_appreciation: 89,
+        maintainer_fee: 1,
+        ..context.reward_distribution.clone()
+    };
+    context
+        .try_change_reward_distribution(&reward_distribution, &solido.fee_recipients)
+        .await
+        .expect("Failed to change reward distribution.");
+
+    context.deposit(Lamports(2_000_000_000)).await;
+    context
+        .stake_deposit(vote_account, StakeDeposit::Append, Lamports(1_000_000_000))
+        .await;
+    let solido = context.get_solido().await;
+    let entry = &solido.maintainers.get(&maintainer).unwrap().entry;
+    assert_eq!(entry.operations, 1);
+
+    // Donate to the vote account, so there are rewards to pay fees on. This is
+    // still the initial epoch, so the exchange rate is 1:1.
+    context.fund(vote_account, Lamports(100_000)).await;
+    context.collect_validator_fee(vote_account).await;
+    let solido = context.get_solido().await;
+    assert_eq!(solido.maintainer_fee_credit, StLamports(1_000));
+    let entry = &solido.maintainers.get(&maintainer).unwrap().entry;
+    assert_eq!(entry.fee_credit, StLamports(0));
+
+    // The exchange rate update assigns the fee to the only maintainer that
+    // did any work.
+    context.advance_to_normal_epoch(0);
+    context.update_exchange_rate().await;
+    let solido = context.get_solido().await;
+    let entry = &solido.maintainers.get(&maintainer).unwrap().entry;
+    assert_eq!(entry.fee_credit, StLamports(1_000));
+    assert_eq!(entry.operations, 0);
+
+    let st_sol_account = context.create_st_sol_account(maintainer).await;
+    context.claim_maintainer_fee(st_sol_account).await;
+    assert_eq!(
+        context.get_st_sol_balance(st_sol_account).await,
+        StLamports(1_000)
+    );
+    let solido = context.get_solido().await;
+    assert_eq!(solido.maintainer_fee_credit, StLamports(0));
+    let entry = &solido.maintainers.get(&maintainer).unwrap().entry;
+    assert_eq!(entry.fee_credit, StLamports(0));
+}
+
+#[tokio::test]
+async fn test_claim_maintainer_fee_requires_maintainer() {
+    let mut context = Context::new_with_maintainer().await;
+    let maintainer = context.maintainer.as_ref().unwrap().pubkey();
+    let st_sol_account = context.create_st_sol_account(maintainer).await;
+
+    // A keypair that is not a maintainer should not be able to claim.
+    context.maintainer = Some(context.deterministic_keypair.new_keypair());
+    let result = context.try_claim_maintainer_fee(st_sol_account).await;
+    assert_solido_error!(result, LidoError::InvalidMaintainer);
+}
+
+#[tokio::test]
+async fn test_permissionless_maintenance_counts_for_maintainers_only() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let vote_account = context.validator.as_ref().unwrap().vote_account;
+    let maintainer = context.maintainer.as_ref().unwrap().pubkey();
+
+    // The context performs the permissionless maintenance as its maintainer.
+    // Donate to the vote account, so there are rewards to collect.
+    context.fund(vote_account, Lamports(100_000)).await;
+    context.collect_validator_fee(vote_account).await;
+    let solido = context.get_solido().await;
+    let entry = &solido.maintainers.get(&maintainer).unwrap().entry;
+    assert_eq!(entry.operations, 1);
+
+    // Without a maintainer, anybody can still perform it, but it does not
+    // count for anybody.
+    let maintainer_keypair = context.maintainer.take();
+    context.fund(vote_account, Lamports(100_000)).await;
+    context.collect_validator_fee(vote_account).await;
+    let solido = context.get_solido().await;
+    let entry = &solido.maintainers.get(&maintainer).unwrap().entry;
+    assert_eq!(entry.operations, 1);
+
+    // Dividing the fee in the exchange rate update resets the operations.
+    context.maintainer = maintainer_keypair;
+    context.advance_to_normal_epoch(0);
+    context.update_exchange_rate().await;
+    let solido = context.get_solido().await;
+    let entry = &solido.maintainers.get(&maintainer).unwrap().entry;
+    assert_eq!(entry.operations, 0);
+}
+
+#[tokio::test]
+async fn test_noop_maintenance_does_not_count() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let vote_account = context.validator.as_ref().unwrap().vote_account;
+    let maintainer = context.maintainer.as_ref().unwrap().pubkey();
+
+    // There is no stake and there are no rewards yet, so these succeed, but
+    // they do nothing, and the maintainer should not be credited for them.
+    context.withdraw_inactive_stake(vote_account).await;
+    context.collect_validator_fee(vote_account).await;
+    let solido = context.get_solido().await;
+    let entry = &solido.maintainers.get(&maintainer).unwrap().entry;
+    assert_eq!(entry.operations, 0);
+
+    // Once there is stake, withdrawing still does nothing as long as nobody
+    // donated to the stake account.
+    context.deposit(Lamports(2_000_000_000)).await;
+    let stake_account = context
+        .stake_deposit(vote_account, StakeDeposit::Append, Lamports(1_000_000_000))
+        .await;
+    let solido = context.get_solido().await;
+    let entry = &solido.maintainers.get(&maintainer).unwrap().entry;
+    assert_eq!(entry.operations, 1);
+
+    context.withdraw_inactive_stake(vote_account).await;
+    let solido = context.get_solido().await;
+    let entry = &solido.maintainers.get(&maintainer).unwrap().entry;
+    assert_eq!(entry.operations, 1);
+
+    // A donation gives it something to withdraw, so then it does count.
+    context.fund(stake_account, Lamports(100)).await;
+    context.withdraw_inactive_stake(vote_account).await;
+    let solido = context.get_solido().await;
+    let entry = &solido.maintainers.get(&maintainer).unwrap().entry;
+    assert_eq!(entry.operations, 2);
+}
@@ -46,3 +46,25 @@ async fn test_successful_add_remove_maintainer() {
     assert!(!has_maintainer);
     assert_eq!(solido.maintainers.len(), 0);
 }
+
+#[tokio::test]
+async fn test_maintainer_heartbeat_records_slot() {
+    let mut context = Context::new_with_maintainer().await;
+    let maintainer = context.maintainer.as_ref().unwrap().pubkey();
+
+    let solido = context.get_solido().await;
+    let entry = &solido.maintainers.get(&maintainer).unwrap().entry;
+    assert_eq!(entry.last_heartbeat_slot, 0);
+
+    context.advance_to_normal_epoch(0);
+    context.maintainer_heartbeat().await;
+    let clock = context.get_clock().await;
+    let solido = context.get_solido().await;
+    let entry = &solido.maintainers.get(&maintainer).unwrap().entry;
+    assert_eq!(entry.last_heartbeat_slot, clock.slot);
+
+    // A keypair that is not a maintainer should not be able to send a heartbeat.
+    context.maintainer = Some(context.deterministic_keypair.new_keypair());
+    let result = context.try_maintainer_heartbeat().await;
+    assert_solido_error!(result, LidoError::InvalidMaintainer);
+}
@@ -4,12 +4,16 @@
 pub mod add_remove_validator;
 pub mod change_reward_distribution;
 pub mod collect_validator_fee;
+pub mod compute_units;
+pub mod cpi;
 pub mod deposit;
 pub mod limits;
+pub mod maintainer_fee;
 pub mod maintainers;
 pub mod merge_stake;
 pub mod solana_assumptions;
 pub mod stake_deposit;
+pub mod token_metadata;
 pub mod unstake;
 pub mod update_exchange_rate;
 pub mod withdraw_inactive_stake;
@@ -0,0 +1,47 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+#![cfg(feature = "test-bpf")]
+
+use crate::assert_solido_error;
+use crate::context::Context;
+
+use lido::error::LidoError;
+use lido::token_metadata::TokenMetadata;
+use solana_program_test::tokio;
+use solana_sdk::signature::Signer;
+
+// The metadata program is not loaded in the test context, so these tests only
+// cover the checks that happen before we call into it.
+
+fn st_sol_metadata() -> TokenMetadata {
+    TokenMetadata {
+        name: "Lido Staked SOL".to_string(),
+        symbol: "stSOL".to_string(),
+        uri: "https://example.com/stsol.json".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_set_token_metadata_rejects_too_long_symbol() {
+    let mut context = Context::new_empty().await;
+    let mut token_metadata = st_sol_metadata();
+    token_metadata.symbol = "TOOLONGSYMBOL".to_string();
+
+    let mint_authority = context.mint_authority;
+    let result = context
+        .try_set_token_metadata(token_metadata, mint_authority)
+        .await;
+    assert_solido_error!(result, LidoError::InvalidTokenMetadata);
+}
+
+#[tokio::test]
+async fn test_set_token_metadata_rejects_wrong_mint_authority() {
+    let mut context = Context::new_empty().await;
+    let not_mint_authority = context.deterministic_keypair.new_keypair().pubkey();
+
+    let result = context
+        .try_set_token_metadata(st_sol_metadata(), not_mint_authority)
+        .await;
+    assert_solido_error!(result, LidoError::InvalidMint);
+}
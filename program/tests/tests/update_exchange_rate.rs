@@ -4,11 +4,13 @@
 #![cfg(feature = "test-bpf")]
 
 use solana_program_test::tokio;
+use solana_sdk::signer::Signer;
 
 use crate::assert_solido_error;
 use crate::context::Context;
 
 use lido::error::LidoError;
+use lido::oracle::EXCHANGE_RATE_ORACLE_VERSION;
 use lido::state::ExchangeRate;
 use lido::token::{Lamports, StLamports};
 
@@ -101,3 +103,56 @@ async fn test_update_exchange_rate() {
     let received_st_sol = context.get_st_sol_balance(recipient).await;
     assert_eq!(received_st_sol, StLamports(DEPOSIT_AMOUNT / 2));
 }
+
+#[tokio::test]
+async fn test_update_exchange_rate_writes_oracle() {
+    let mut context = Context::new_with_maintainer().await;
+
+    // Without an oracle, updating the exchange rate still works.
+    context.advance_to_normal_epoch(0);
+    context.update_exchange_rate().await;
+
+    // When the oracle is created, it starts out with the current exchange rate.
+    context.initialize_exchange_rate_oracle().await;
+    let solido = context.get_solido().await;
+    let oracle = context.get_exchange_rate_oracle().await;
+    assert_eq!(oracle.version, EXCHANGE_RATE_ORACLE_VERSION);
+    assert_eq!(oracle.solido, context.solido.pubkey());
+    assert_eq!(oracle.exchange_rate(), solido.exchange_rate);
+
+    // It can only be created once.
+    let result = context.try_initialize_exchange_rate_oracle().await;
+    assert_solido_error!(result, LidoError::AlreadyInUse);
+
+    const DEPOSIT_AMOUNT: u64 = 100_000_000;
+    context.deposit(Lamports(DEPOSIT_AMOUNT)).await;
+    context
+        .fund(context.reserve_address, Lamports(DEPOSIT_AMOUNT))
+        .await;
+
+    // After the next update, the oracle should hold the new exchange rate.
+    context.advance_to_normal_epoch(1);
+    let clock = context.get_clock().await;
+    context.update_exchange_rate().await;
+    let oracle = context.get_exchange_rate_oracle().await;
+    assert_eq!(
+        oracle.exchange_rate(),
+        ExchangeRate {
+            computed_in_epoch: clock.epoch,
+            st_sol_supply: StLamports(DEPOSIT_AMOUNT),
+            sol_balance: Lamports(2 * DEPOSIT_AMOUNT),
+        }
+    );
+    assert!(oracle.updated_at_slot >= clock.slot);
+}
+
+#[tokio::test]
+async fn test_update_exchange_rate_rejects_foreign_oracle() {
+    let mut context = Context::new_with_maintainer().await;
+    context.advance_to_normal_epoch(0);
+
+    // Pass an account that is not the oracle of this instance.
+    context.exchange_rate_oracle = context.reserve_address;
+    let result = context.try_update_exchange_rate().await;
+    assert_solido_error!(result, LidoError::InvalidExchangeRateOracle);
+}
@@ -181,6 +181,21 @@ async fn test_withdrawal_result() {
         Lamports(test_withdraw_amount.0)
     );
     assert_eq!(solido_after.metrics.withdraw_amount.count, 1);
+    assert_eq!(
+        solido_after.metrics.withdraw_amount_histogram.total,
+        Lamports(test_withdraw_amount.0)
+    );
+    assert_eq!(
+        solido_after
+            .metrics
+            .withdraw_amount_histogram
+            .num_observations(),
+        1
+    );
+    assert_eq!(
+        solido_after.metrics.flow_current_epoch.withdraw_total,
+        Lamports(test_withdraw_amount.0)
+    );
 
     // Check that the staker/withdrawer authorities are set to the user.
     let stake_data = context.context.get_account(split_stake_account).await;
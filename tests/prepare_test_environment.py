@@ -13,11 +13,13 @@ import os
 from typing import Optional, Dict, Any
 
 from util import (
+    TestAccount,
     create_test_account,
     solana_program_deploy,
     create_spl_token_account,
     create_vote_account,
     get_network,
+    get_websocket_url,
     solana,
     solido,
     multisig,
@@ -88,7 +90,11 @@ st_sol_mint_account = result['st_sol_mint_address']
 print(f'> Created instance at {solido_address}')
 
 
-def approve_and_execute(transaction_address: str) -> None:
+def approve_and_execute(
+    transaction_address: str, executor: Optional[TestAccount] = None
+) -> None:
+    if executor is None:
+        executor = maintainer
     multisig(
         'approve',
         '--multisig-program-id',
@@ -107,7 +113,7 @@ def approve_and_execute(transaction_address: str) -> None:
         multisig_instance,
         '--transaction-address',
         transaction_address,
-        keypair_path=maintainer.keypair_path,
+        keypair_path=executor.keypair_path,
     )
 
 
@@ -159,11 +165,19 @@ def add_validator(index: int, vote_account: Optional[str]) -> str:
         vote_account,
         '--validator-fee-account',
         validator_fee_st_sol_account,
+        '--validator-vote-withdrawer',
+        validator_fee_st_sol_account_owner.pubkey,
+        '--validator-authority',
+        validator_fee_st_sol_account_owner.pubkey,
         '--multisig-address',
         multisig_instance,
         keypair_path=maintainer.keypair_path,
     )
-    approve_and_execute(transaction_result['transaction_address'])
+    # The vote withdrawer has to sign, so it executes the approved transaction.
+    approve_and_execute(
+        transaction_result['transaction_address'],
+        executor=validator_fee_st_sol_account_owner,
+    )
     return vote_account
 
 
@@ -289,6 +303,8 @@ print(
             solido_address,
             '--max-poll-interval-seconds',
             '10',
+            '--websocket-url',
+            get_websocket_url(),
         ]
     ),
 )
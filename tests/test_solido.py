@@ -30,7 +30,7 @@ from util import (
     spl_token,
 )
 
-from typing import Any, Dict, NamedTuple, Tuple
+from typing import Any, Dict, NamedTuple, Optional, Tuple
 
 # We start by generating an account that we will need later. We put the tests
 # keys in a directory where we can .gitignore them, so they don't litter the
@@ -84,10 +84,15 @@ print(f'> Created instance at {multisig_instance}.')
 def approve_and_execute(
     transaction_to_approve: str,
     signer: TestAccount,
+    executor: Optional[TestAccount] = None,
 ) -> None:
     """
-    Helper to approve and execute a transaction with a single key.
+    Helper to approve and execute a transaction with a single key. The executor
+    defaults to the approver, but instructions that need an additional signer
+    have to be executed by that signer.
     """
+    if executor is None:
+        executor = signer
     multisig(
         'approve',
         '--multisig-program-id',
@@ -106,10 +111,18 @@ def approve_and_execute(
         multisig_instance,
         '--transaction-address',
         transaction_to_approve,
-        keypair_path=signer.keypair_path,
+        keypair_path=executor.keypair_path,
     )
 
 
+print('\nExporting IDL ...')
+idl = solido('export-idl')
+deposit_instruction = next(i for i in idl['instructions'] if i['name'] == 'Deposit')
+assert deposit_instruction['accounts'][0]['name'] == 'lido'
+assert idl['accounts'][0]['declaration'] == 'Lido'
+print(f'> IDL describes {len(idl["instructions"])} instructions.')
+
+
 # Test creating a solido instance with a known minter.
 solido_test_account = create_test_account('tests/.keys/solido_address.json', fund=False)
 authorities = solido(
@@ -211,8 +224,28 @@ assert solido_instance['solido']['reward_distribution'] == {
     'validation_fee': 3,
     'developer_fee': 2,
     'st_sol_appreciation': 90,
+    'maintainer_fee': 0,
 }
 
+print('\nCreating exchange rate oracle ...')
+result = solido(
+    'create-exchange-rate-oracle',
+    '--solido-program-id',
+    solido_program_id,
+    '--solido-address',
+    solido_address,
+    keypair_path=test_addrs[0].keypair_path,
+)
+authorities = solido(
+    'show-authorities',
+    '--solido-address',
+    solido_address,
+    '--solido-program-id',
+    solido_program_id,
+)
+assert result['exchange_rate_oracle'] == authorities['exchange_rate_oracle']
+print(f'> Created exchange rate oracle at {result["exchange_rate_oracle"]}.')
+
 validator_fee_account_owner = create_test_account(
     'tests/.keys/validator-token-account-key.json'
 )
@@ -263,6 +296,10 @@ def add_validator(
         vote_account.pubkey,
         '--validator-fee-account',
         fee_account,
+        '--validator-vote-withdrawer',
+        account.pubkey,
+        '--validator-authority',
+        account.pubkey,
         '--multisig-address',
         multisig_instance,
         keypair_path=test_addrs[1].keypair_path,
@@ -294,7 +331,8 @@ assert (
 )
 
 
-approve_and_execute(transaction_address, test_addrs[0])
+# The validator has to sign, so it executes the approved transaction.
+approve_and_execute(transaction_address, test_addrs[0], validator.account)
 transaction_status = multisig(
     'show-transaction',
     '--multisig-program-id',
@@ -321,7 +359,7 @@ solido_instance = solido(
     solido_address,
 )
 
-assert solido_instance['solido']['validators']['entries'][0] == {
+assert solido_instance['validators']['entries'][0] == {
     'pubkey': validator.vote_account.pubkey,
     'entry': {
         'fee_credit': 0,
@@ -337,6 +375,15 @@ assert solido_instance['solido']['validators']['entries'][0] == {
         'stake_accounts_balance': 0,
         'unstake_accounts_balance': 0,
         'active': True,
+        'metrics': {
+            'rewards_total_lamports': 0,
+            'fee_total_st_lamports': 0,
+            'epochs_active': 0,
+            'last_active_epoch': 0,
+            'last_rewards_epoch': 0,
+        },
+        'tombstone': False,
+        'vote_withdrawer': validator.account.pubkey,
     },
 }, f'Unexpected validator entry, in {json.dumps(solido_instance, indent=True)}'
 
@@ -371,7 +418,7 @@ solido_instance = solido(
 )
 assert solido_instance['solido']['maintainers']['entries'][0] == {
     'pubkey': maintainer.pubkey,
-    'entry': None,
+    'entry': {'fee_credit': 0, 'operations': 0, 'last_heartbeat_slot': 0},
 }
 
 print(f'> Removing maintainer {maintainer}')
@@ -422,7 +469,8 @@ approve_and_execute(transaction_address, test_addrs[0])
 current_epoch = int(solana('epoch'))
 
 
-def perform_maintenance() -> Any:
+def perform_maintenance(dry_run: bool = False) -> Any:
+    extra_args = ['--dry-run'] if dry_run else []
     return solido(
         'perform-maintenance',
         '--solido-address',
@@ -431,6 +479,7 @@ def perform_maintenance() -> Any:
         solido_program_id,
         '--stake-time',
         'anytime',
+        *extra_args,
         keypair_path=maintainer.keypair_path,
     )
 
@@ -475,6 +524,12 @@ def deposit(lamports: int, expect_created_token_account: bool = False) -> None:
 
 deposit(lamports=3_000_000_000, expect_created_token_account=True)
 
+print('\nSimulating maintenance ...')
+result = perform_maintenance(dry_run=True)
+assert result['error'] is None, f'Simulation failed: {result}'
+assert 'StakeDeposit' in result['maintenance'], f'Expected StakeDeposit, got {result}'
+print('> Simulated a StakeDeposit, without sending the transaction.')
+
 print('\nRunning maintenance ...')
 result = perform_maintenance()
 expected_result = {
@@ -518,7 +573,7 @@ transaction_status = multisig(
     '--transaction-address',
     transaction_address,
 )
-approve_and_execute(transaction_address, test_addrs[0])
+approve_and_execute(transaction_address, test_addrs[0], validator_1.account)
 
 # Should unstake 1/2 (1.5 - 0.0005/2 Sol) of the validator's balance.
 result = perform_maintenance()
@@ -552,6 +607,7 @@ expected_result = {
         'validator_vote_account': validator.vote_account.pubkey,
         'expected_difference_stake_lamports': 100_000_000,  # We donated 0.1 SOL.
         'unstake_withdrawn_to_reserve_lamports': 1_499_750_000,  # Amount that was unstaked for the newcomming validator.
+        'stake_withdrawn_to_reserve_lamports': 0,
     }
 }
 
@@ -632,7 +688,7 @@ solido_instance = solido(
     '--solido-address',
     solido_address,
 )
-assert not solido_instance['solido']['validators']['entries'][0]['entry'][
+assert not solido_instance['validators']['entries'][0]['entry'][
     'active'
 ], 'Validator should be inactive after deactivation.'
 print('> Validator is inactive as expected.')
@@ -660,7 +716,7 @@ solido_instance = solido(
     solido_address,
 )
 # Should have bumped the validator's `stake_seeds` and `unstake_seeds`.
-val = solido_instance['solido']['validators']['entries'][0]['entry']
+val = solido_instance['validators']['entries'][0]['entry']
 assert val['stake_seeds'] == {'begin': 1, 'end': 1}
 assert val['unstake_seeds'] == {'begin': 1, 'end': 2}
 
@@ -672,6 +728,7 @@ expected_result = {
         'validator_vote_account': validator.vote_account.pubkey,
         'expected_difference_stake_lamports': 0,
         'unstake_withdrawn_to_reserve_lamports': 1_500_250_000,
+        'stake_withdrawn_to_reserve_lamports': 0,
     }
 }
 assert result == expected_result, f'\nExpected: {expected_result}\nActual:   {result}'
@@ -702,7 +759,16 @@ solido_instance = solido(
     '--solido-address',
     solido_address,
 )
-number_validators = len(solido_instance['solido']['validators']['entries'])
+number_validators = len(solido_instance['validators']['entries'])
 assert (
     number_validators == 1
 ), f'\nExpected no validators\nGot: {number_validators} validators'
+
+# The vote account is handed back to the withdrawer that the validator registered.
+vote_account_info = json.loads(
+    solana('vote-account', validator.vote_account.pubkey, '--output', 'json')
+)
+assert (
+    vote_account_info['authorizedWithdrawer'] == validator.account.pubkey
+), f'\nExpected withdrawer {validator.account.pubkey}\nGot: {vote_account_info}'
+print('> Vote account was handed back to the validator.')
@@ -67,6 +67,20 @@ def get_network() -> str:
         return network
 
 
+def get_websocket_url() -> str:
+    """
+    Return the websocket url for the network, the same way the Solana CLI
+    derives it: http becomes ws, and if there is a port, it is one higher.
+    """
+    scheme, rest = get_network().split('://', 1)
+    host, _, path = rest.partition('/')
+    if ':' in host:
+        hostname, port = host.rsplit(':', 1)
+        host = f'{hostname}:{int(port) + 1}'
+    ws_scheme = {'http': 'ws', 'https': 'wss'}[scheme]
+    return f'{ws_scheme}://{host}/{path}'.rstrip('/')
+
+
 def solido(*args: str, keypair_path: Optional[str] = None) -> Any:
     """
     Run 'solido' against network, return its parsed json output.